@@ -0,0 +1,59 @@
+//! Measures the combinators that restore their input on failure, on plain string input and
+//! on state carriers, where restoring means saving and rewinding a checkpoint.
+//!
+//! ```text
+//! cargo bench --bench combinators
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use friss::parsers::{Position, WithState};
+use friss::reader::ReaderInput;
+use friss::*;
+
+fn text() -> String {
+    "item,".repeat(2_000) + "item;"
+}
+
+fn restoring_combinators(c: &mut Criterion) {
+    let text = text();
+    let source = text.as_str();
+
+    let mut group = c.benchmark_group("str");
+    let item = || "item".make_literal_matcher("Expected item");
+    let comma = || ",".make_literal_matcher("Expected comma");
+    let sep_by = item().sep_by(comma());
+    let validate = item().validate(|_| true, "Rejected").sep_by(comma());
+    let backtrack = item().seq(comma()).backtrack().many();
+    let or = item().or(comma()).map(|_| ());
+    let recover = item().validate(|_| false, "Rejected").recover_with(|_| item());
+    group.bench_function("sep_by", |b| b.iter(|| sep_by.parse(black_box(source))));
+    group.bench_function("validate", |b| b.iter(|| validate.parse(black_box(source))));
+    group.bench_function("backtrack", |b| b.iter(|| backtrack.parse(black_box(source))));
+    group.bench_function("or", |b| b.iter(|| or.parse(black_box(source))));
+    group.bench_function("recover_with", |b| b.iter(|| recover.parse(black_box(source))));
+    group.finish();
+
+    let mut group = c.benchmark_group("position");
+    let start = source.with_state(Position::default());
+    let item = || "item".with_state(Position::default()).make_literal_matcher("Expected item");
+    let comma = || ",".with_state(Position::default()).make_literal_matcher("Expected comma");
+    let sep_by = item().sep_by(comma());
+    let validate = item().validate(|_| true, "Rejected").sep_by(comma());
+    let backtrack = item().seq(comma()).backtrack().many();
+    group.bench_function("sep_by", |b| b.iter(|| sep_by.parse(black_box(start))));
+    group.bench_function("validate", |b| b.iter(|| validate.parse(black_box(start))));
+    group.bench_function("backtrack", |b| b.iter(|| backtrack.parse(black_box(start))));
+    group.finish();
+
+    let mut group = c.benchmark_group("reader");
+    let item = || ReaderInput::from_bytes(b"item").make_literal_matcher("Expected item");
+    let comma = || b','.make_character_matcher("Expected comma");
+    let sep_by = item().map(|_| ()).sep_by(comma().map(|_| ()));
+    group.bench_function("sep_by", |b| {
+        b.iter(|| sep_by.parse(ReaderInput::new(black_box(source.as_bytes()))))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, restoring_combinators);
+criterion_main!(benches);
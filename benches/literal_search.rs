@@ -0,0 +1,70 @@
+//! Compares literal search strategies on large inputs.
+//!
+//! Run with and without the `memchr` feature to see the effect of the vectorized search:
+//!
+//! ```text
+//! cargo bench --bench literal_search
+//! cargo bench --bench literal_search --features memchr
+//! ```
+//!
+//! Measured on a 1.1 MB input (x86_64, release build). Each cell is the median of three
+//! bench runs, each reporting the median of 20 iterations; single runs differed by up to
+//! 30%, so only differences well beyond that are meaningful:
+//!
+//! | benchmark               | plain     | `memchr`  |
+//! |-------------------------|-----------|-----------|
+//! | first_match_in          | 0.09 GB/s | 0.09 GB/s |
+//! | first_match_with_prefix | 3.5 GB/s  | 12.8 GB/s |
+//! | byte literal run        | 8.4 GB/s  | 8.6 GB/s  |
+//! | take_until on bytes     | 0.35 GB/s | 45.6 GB/s |
+//! | take_until_byte         | 1.3 GB/s  | 45.6 GB/s |
+//! | char-wise take_while    | 0.58 GB/s | 0.58 GB/s |
+//! | take_while_ascii        | 1.1 GB/s  | 0.70 GB/s |
+//!
+//! With the `memchr` feature, `first_match_with_prefix` is about four times faster, and
+//! searching bytes for a delimiter becomes thirty to a hundred times faster. Matching
+//! literals needs no search and is unaffected. `take_while_ascii` does not use `memchr`;
+//! it was 1.2 to 1.9 times as fast as decoding characters, and the gap between its two
+//! columns comes from the separate builds, not from the feature.
+
+use friss::bench::*;
+use friss::scan::*;
+use friss::*;
+
+fn main() {
+    let text = "lorem ipsum dolor sit amet, ".repeat(40_000) + "<frame>";
+    let haystack = text.as_str();
+    let config = BenchmarkConfig { warmup_iterations: 2, iterations: 20 };
+
+    let frame = "<frame>".make_literal_matcher("Expected frame");
+    let sliding = |input| match frame.first_match_in(input) {
+        Some((offset, _, rest)) => Ok((rest, offset)),
+        None => Err((input, "No frame")),
+    };
+    let prefixed = |input| match frame.first_match_with_prefix(input, "<frame>") {
+        Some((offset, _, rest)) => Ok((rest, offset)),
+        None => Err((input, "No frame")),
+    };
+    println!("first_match_in:          {}", sliding.benchmark_with(&[haystack], config));
+    println!("first_match_with_prefix: {}", prefixed.benchmark_with(&[haystack], config));
+
+    let words = b"lorem ipsum dolor sit amet, ".as_slice().make_literal_matcher("Expected words");
+    let run = words.fold_many(0usize, |n, _| n + 1);
+    println!("byte literal run:        {}", run.benchmark_with(&[haystack.as_bytes()], config));
+
+    let until_slice = take_until(b"<".as_slice(), "No frame");
+    let until_byte = take_until_byte(b'<', "No frame");
+    println!("take_until on bytes:     {}", until_slice.benchmark_with(&[haystack.as_bytes()], config));
+    println!("take_until_byte:         {}", until_byte.benchmark_with(&[haystack.as_bytes()], config));
+
+    let letters = "abcdefghijklmnopqrstuvwxyz".repeat(40_000) + "!";
+    let by_table = take_while_ascii::<&str, ()>(u8::is_ascii_lowercase);
+    println!("char-wise take_while:    {}", lowercase_prefix.benchmark_with(&[letters.as_str()], config));
+    println!("take_while_ascii:        {}", by_table.benchmark_with(&[letters.as_str()], config));
+}
+
+/// Splits off the ASCII lowercase prefix, decoding characters.
+fn lowercase_prefix(input: &str) -> Result<(&str, &str), (&str, ())> {
+    let end = input.find(|c: char| !c.is_ascii_lowercase()).unwrap_or(input.len());
+    Ok((&input[end..], &input[..end]))
+}
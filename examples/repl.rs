@@ -0,0 +1,94 @@
+//! An interactive calculator built on `friss::repl`.
+//!
+//! Run with `cargo run --example repl`. Expressions may span several lines: an entry that
+//! ends in the middle of an expression, such as `1 +`, is continued on the next line.
+
+use friss::grammar::*;
+use friss::repl::*;
+use friss::*;
+
+const FUNCTIONS: [&str; 4] = ["abs", "max", "min", "sqrt"];
+
+struct Calculator;
+
+impl Calculator {
+    fn expression<'a>(g: &Grammar) -> impl Parser<&'a str, f64, Expected> {
+        let g = g.clone();
+        move |input: &'a str| {
+            let term = Self::term(&g);
+            let operator = |i| g.symbol("+").parse(i).or_else(|_| g.symbol("-").parse(i));
+            let (mut rest, mut value) = term.parse(input)?;
+            while let Ok((after, op)) = operator.parse(rest) {
+                let (after, rhs) = term.parse(after)?;
+                value = if op == "+" { value + rhs } else { value - rhs };
+                rest = after;
+            }
+            Ok((rest, value))
+        }
+    }
+
+    fn term<'a>(g: &Grammar) -> impl Parser<&'a str, f64, Expected> {
+        let g = g.clone();
+        move |input: &'a str| {
+            let factor = Self::factor(&g);
+            let operator = |i| g.symbol("*").parse(i).or_else(|_| g.symbol("/").parse(i));
+            let (mut rest, mut value) = factor.parse(input)?;
+            while let Ok((after, op)) = operator.parse(rest) {
+                let (after, rhs) = factor.parse(after)?;
+                value = if op == "*" { value * rhs } else { value / rhs };
+                rest = after;
+            }
+            Ok((rest, value))
+        }
+    }
+
+    fn factor<'a>(g: &Grammar) -> impl Parser<&'a str, f64, Expected> {
+        let g = g.clone();
+        move |input: &'a str| {
+            if let Ok(ok) = g.number().parse(input) {
+                return Ok(ok);
+            }
+            if let Ok((rest, _)) = g.symbol("(").parse(input) {
+                let (rest, value) = Self::expression(&g).parse(rest)?;
+                let (rest, _) = g.symbol(")").parse(rest)?;
+                return Ok((rest, value));
+            }
+            let (rest, name) = g.identifier().parse(input)?;
+            let (rest, _) = g.symbol("(").parse(rest)?;
+            let (rest, args) = Self::expression(&g).sep_by1(g.symbol(",").map(|_| 0.0), Expected::Number).parse(rest)?;
+            let (rest, _) = g.symbol(")").parse(rest)?;
+            let value = match (name, args.as_slice()) {
+                ("abs", [x]) => x.abs(),
+                ("sqrt", [x]) => x.sqrt(),
+                ("max", [first, others @ ..]) => others.iter().fold(*first, |a, b| a.max(*b)),
+                ("min", [first, others @ ..]) => others.iter().fold(*first, |a, b| a.min(*b)),
+                _ => return Err((input, Expected::Custom(format!("one of {}", FUNCTIONS.join(", "))))),
+            };
+            Ok((rest, value))
+        }
+    }
+}
+
+impl ReplGrammar for Calculator {
+    type Output = f64;
+
+    fn parse(&self, source: &str) -> Result<f64, GrammarError> {
+        GrammarBuilder::new()
+            .line_comment("#")
+            .build(Self::expression)
+            .parse_source(source)
+    }
+
+    fn completions(&self, prefix: &str) -> Vec<String> {
+        FUNCTIONS
+            .iter()
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| format!("{}(", name))
+            .collect()
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    println!("friss calculator; :history, :complete <prefix> and :quit are available");
+    Repl::new(Calculator).run(std::io::stdin().lock(), std::io::stdout())
+}
@@ -0,0 +1,14 @@
+#![no_main]
+
+use friss::formats::csv::{csv_record, CsvConfig};
+use friss::Parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let record = csv_record(CsvConfig::new().delimiter(';').quote('\''));
+        for fields in record.iter(input) {
+            assert!(!fields.is_empty());
+        }
+    }
+});
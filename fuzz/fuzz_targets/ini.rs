@@ -0,0 +1,11 @@
+#![no_main]
+
+use friss::formats::ini::ini_document;
+use friss::Parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = ini_document().parse_complete(input);
+    }
+});
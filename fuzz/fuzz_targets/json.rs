@@ -0,0 +1,13 @@
+#![no_main]
+
+use friss::formats::json::json_node;
+use friss::Parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        if let Ok((rest, node)) = json_node().parse(input) {
+            assert!(node.span.end <= input.len() - rest.len());
+        }
+    }
+});
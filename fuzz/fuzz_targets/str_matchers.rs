@@ -0,0 +1,16 @@
+#![no_main]
+
+use friss::*;
+use libfuzzer_sys::fuzz_target;
+
+// Runs the `&str` matchers and the core combinators on arbitrary text
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let anything = || <&str as Parsable<&str>>::make_anything_matcher("Expected anything");
+        let _ = anything().many().parse(input);
+        let _ = 'a'.make_character_matcher("Expected a").sep_by(','.make_character_matcher("Expected a")).parse(input);
+        let _ = "ab".make_literal_matcher("Expected ab").alt(anything()).many().parse(input);
+        let _ = anything().validate(|c| c.is_alphabetic(), "Expected letter").at_most_n::<4>().parse(input);
+        let _ = <&str as Parsable<&str>>::make_empty_matcher("Expected end").parse(input);
+    }
+});
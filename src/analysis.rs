@@ -0,0 +1,802 @@
+//! # Grammar Analysis
+//!
+//! Combinators are closures, so a grammar built from them cannot be inspected. This module
+//! analyses a description of the grammar instead, written with the same shapes as the
+//! combinators: terminals, rule references, `seq`, `alt`, `many`, `many1` and `optional`.
+//! The analysis catches bugs that otherwise show up as hangs or unreachable alternatives:
+//!
+//! - left-recursive cycles, which recurse forever in a recursive descent parser unless the
+//!   rule uses `recursive_left` or `packrat`
+//! - repetitions of something nullable, which make `many` loop without consuming input
+//! - references to undefined rules
+//!
+//! It also computes which rules are nullable and the FIRST and FOLLOW sets of every rule.
+//! A `testgen::Syntax` converts into an `Expr`, so generator descriptions can be checked too.
+//!
+//! `GrammarSpec::to_dot` exports the rules and their `seq`/`alt`/repetition structure as a
+//! Graphviz DOT graph. To avoid writing the description next to the parser, wrap parsers
+//! with `described`: a `Described` parser records its `Expr` as it is combined.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::analysis::*;
+//!
+//! // expr := expr "+" term | term ; term := digit | "(" expr ")"
+//! let grammar = GrammarSpec::new()
+//!     .rule("expr", alt([seq([rule("expr"), term("+"), rule("term")]), rule("term")]))
+//!     .rule("term", alt([term("digit"), seq([term("("), rule("expr"), term(")")])]))
+//!     .rule("list", many(optional(rule("term"))));
+//!
+//! let analysis = grammar.analyze();
+//! assert_eq!(analysis.first("term"), Some(&Terminal::set(["digit", "("])));
+//! assert!(analysis.follow("term").unwrap().contains(&Terminal::EndOfInput));
+//! assert!(analysis.is_nullable("list"));
+//!
+//! let problems = analysis.problems();
+//! assert_eq!(problems, vec![
+//!     GrammarProblem::LeftRecursion(vec!["expr".to_string()]),
+//!     GrammarProblem::NullableRepetition("list".to_string()),
+//! ]);
+//! assert_eq!(problems[0].to_string(), "left recursion: expr -> expr");
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use crate::core::{Parsable, Parser};
+use crate::testgen::Syntax;
+use crate::types::Either;
+
+/// A terminal symbol of FIRST and FOLLOW sets.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Terminal {
+    /// A terminal of the grammar, by its name or literal text
+    Symbol(String),
+    /// The end of the input, which follows the start rule
+    EndOfInput,
+}
+
+impl Terminal {
+    /// Builds a set of `Terminal::Symbol`s.
+    pub fn set<'a>(symbols: impl IntoIterator<Item = &'a str>) -> BTreeSet<Terminal> {
+        symbols.into_iter().map(|s| Terminal::Symbol(s.to_string())).collect()
+    }
+}
+
+impl Display for Terminal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Terminal::Symbol(s) => write!(f, "{:?}", s),
+            Terminal::EndOfInput => write!(f, "end of input"),
+        }
+    }
+}
+
+/// The description of a rule's right-hand side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// Matches the empty string
+    Empty,
+    Terminal(String),
+    /// A reference to a rule
+    Rule(String),
+    Seq(Vec<Expr>),
+    Alt(Vec<Expr>),
+    /// Zero or more repetitions
+    Many(Box<Expr>),
+    /// One or more repetitions
+    Many1(Box<Expr>),
+    Optional(Box<Expr>),
+}
+
+/// Matches the empty string.
+pub fn empty() -> Expr {
+    Expr::Empty
+}
+
+/// Matches the terminal `name`, a token kind or literal text.
+pub fn term(name: &str) -> Expr {
+    Expr::Terminal(String::from(name))
+}
+
+/// References the rule `name`.
+pub fn rule(name: &str) -> Expr {
+    Expr::Rule(String::from(name))
+}
+
+/// Matches all `parts` in order.
+pub fn seq(parts: impl IntoIterator<Item = Expr>) -> Expr {
+    Expr::Seq(parts.into_iter().collect())
+}
+
+/// Matches one of `choices`.
+pub fn alt(choices: impl IntoIterator<Item = Expr>) -> Expr {
+    Expr::Alt(choices.into_iter().collect())
+}
+
+/// Matches `expr` zero or more times.
+pub fn many(expr: Expr) -> Expr {
+    Expr::Many(Box::new(expr))
+}
+
+/// Matches `expr` one or more times.
+pub fn many1(expr: Expr) -> Expr {
+    Expr::Many1(Box::new(expr))
+}
+
+/// Matches `expr` or nothing.
+pub fn optional(expr: Expr) -> Expr {
+    Expr::Optional(Box::new(expr))
+}
+
+impl From<&Syntax> for Expr {
+    fn from(syntax: &Syntax) -> Self {
+        match syntax {
+            Syntax::Literal(text) if text.is_empty() => Expr::Empty,
+            Syntax::Literal(text) => Expr::Terminal(text.clone()),
+            Syntax::Item(ranges) => {
+                let ranges: Vec<String> = ranges.iter().map(|(lo, hi)| format!("{}-{}", lo, hi)).collect();
+                Expr::Terminal(format!("[{}]", ranges.join(",")))
+            }
+            Syntax::Seq(parts) => Expr::Seq(parts.iter().map(Expr::from).collect()),
+            Syntax::Alt(choices) => Expr::Alt(choices.iter().map(Expr::from).collect()),
+            Syntax::Many { syntax, min: 0, .. } => many(Expr::from(&**syntax)),
+            Syntax::Many { syntax, .. } => many1(Expr::from(&**syntax)),
+        }
+    }
+}
+
+/// A grammar bug found by `Analysis::problems`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarProblem {
+    /// Rules that reach each other without consuming input, in definition order
+    LeftRecursion(Vec<String>),
+    /// A rule repeats something that can match the empty string
+    NullableRepetition(String),
+    /// A rule is referenced but not defined
+    UndefinedRule(String),
+}
+
+impl Display for GrammarProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GrammarProblem::LeftRecursion(cycle) => {
+                write!(f, "left recursion: {}", cycle.join(" -> "))?;
+                match cycle.first() {
+                    Some(first) => write!(f, " -> {}", first),
+                    None => Ok(()),
+                }
+            }
+            GrammarProblem::NullableRepetition(rule) => {
+                write!(f, "rule `{}` repeats something that matches the empty string", rule)
+            }
+            GrammarProblem::UndefinedRule(rule) => write!(f, "undefined rule `{}`", rule),
+        }
+    }
+}
+
+/// A grammar description: named rules, the first of which is the start rule.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GrammarSpec {
+    rules: Vec<(String, Expr)>,
+}
+
+impl GrammarSpec {
+    /// Creates an empty grammar.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines the rule `name`. A rule defined twice matches either definition.
+    pub fn rule(mut self, name: &str, expr: Expr) -> Self {
+        match self.rules.iter_mut().find(|(defined, _)| defined == name) {
+            Some((_, existing)) => {
+                let previous = core::mem::replace(existing, Expr::Empty);
+                *existing = alt([previous, expr]);
+            }
+            None => self.rules.push((name.to_string(), expr)),
+        }
+        self
+    }
+
+    /// Returns the rules in definition order.
+    pub fn rules(&self) -> impl Iterator<Item = (&str, &Expr)> {
+        self.rules.iter().map(|(name, expr)| (name.as_str(), expr))
+    }
+
+    /// Computes nullability, FIRST and FOLLOW sets and the grammar's problems.
+    pub fn analyze(&self) -> Analysis {
+        let mut analysis = Analysis {
+            order: self.rules.iter().map(|(name, _)| name.clone()).collect(),
+            ..Analysis::default()
+        };
+        analysis.compute_nullable(self);
+        analysis.compute_first(self);
+        analysis.compute_follow(self);
+        analysis.find_problems(self);
+        analysis
+    }
+
+    /// Returns the grammar as a Graphviz DOT graph.
+    ///
+    /// Each rule is a box pointing at the tree of its right-hand side: `seq`, `alt` and the
+    /// repetitions `*`, `+` and `?` are inner nodes, terminals are leaves, and rule references
+    /// are dashed edges back to the referenced rule. Undefined rules are drawn in red.
+    pub fn to_dot(&self) -> String {
+        Dot { grammar: self }.to_string()
+    }
+}
+
+/// Writes a `GrammarSpec` as a DOT graph, see `GrammarSpec::to_dot`.
+struct Dot<'a> {
+    grammar: &'a GrammarSpec,
+}
+
+impl<'a> Dot<'a> {
+    fn write_expr(
+        f: &mut Formatter<'_>,
+        expr: &'a Expr,
+        parent: &str,
+        next: &mut usize,
+        referenced: &mut BTreeSet<&'a str>,
+    ) -> fmt::Result {
+        let (label, shape, children) = match expr {
+            Expr::Rule(name) => {
+                referenced.insert(name);
+                return writeln!(f, "    {} -> {} [style=dashed];", parent, dot_rule_id(name));
+            }
+            Expr::Empty => (String::from("ε"), "plaintext", &[][..]),
+            Expr::Terminal(name) => (format!("{:?}", name), "plaintext", &[][..]),
+            Expr::Seq(parts) => (String::from("seq"), "ellipse", &parts[..]),
+            Expr::Alt(choices) => (String::from("alt"), "diamond", &choices[..]),
+            Expr::Many(inner) => (String::from("*"), "circle", core::slice::from_ref(&**inner)),
+            Expr::Many1(inner) => (String::from("+"), "circle", core::slice::from_ref(&**inner)),
+            Expr::Optional(inner) => (String::from("?"), "circle", core::slice::from_ref(&**inner)),
+        };
+        let id = format!("n{}", next);
+        *next += 1;
+        writeln!(f, "    {} [label=\"{}\", shape={}];", id, dot_escape(&label), shape)?;
+        writeln!(f, "    {} -> {};", parent, id)?;
+        children
+            .iter()
+            .try_for_each(|child| Self::write_expr(f, child, &id, next, referenced))
+    }
+}
+
+impl Display for Dot<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "digraph grammar {{")?;
+        writeln!(f, "    ordering=out;")?;
+        let mut next = 0;
+        let mut referenced = BTreeSet::new();
+        for (name, expr) in &self.grammar.rules {
+            let id = dot_rule_id(name);
+            writeln!(f, "    {} [label=\"{}\", shape=box];", id, dot_escape(name))?;
+            Self::write_expr(f, expr, &id, &mut next, &mut referenced)?;
+        }
+        for name in referenced {
+            if !self.grammar.rules.iter().any(|(defined, _)| defined == name) {
+                writeln!(f, "    {} [label=\"{}\", shape=box, color=red];", dot_rule_id(name), dot_escape(name))?;
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+fn dot_rule_id(name: &str) -> String {
+    format!("\"rule:{}\"", dot_escape(name))
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// A parser together with the `Expr` describing it, built with `described`.
+///
+/// Combining described parsers combines both halves: the parsers with the combinators of
+/// `Parser`, the descriptions with the matching `Expr`s. A grammar built this way can be
+/// analysed or exported with `to_dot` without writing its description separately. Name a
+/// rule with `as_rule` before using it in other rules, so that they reference it instead
+/// of repeating its description.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::analysis::*;
+///
+/// let digit = described(term("digit"), make_char_predicate_matcher(|c| c.is_ascii_digit(), "Expected digit"));
+/// let number = digit.many();
+/// let grammar = GrammarSpec::new().rule("number", number.expr().clone());
+///
+/// let number = number.as_rule("number");
+/// let sign = described(term("-"), '-'.make_character_matcher("Expected -"));
+/// let signed = sign.optional().seq(number).map(|(sign, digits)| (sign.is_some(), digits.len()));
+/// let grammar = grammar.rule("signed", signed.expr().clone());
+///
+/// assert_eq!(signed.parse("-12"), Ok(("", (true, 2))));
+/// assert_eq!(signed.expr(), &seq([optional(term("-")), rule("number")]));
+/// assert!(grammar.to_dot().contains("\"rule:signed\" [label=\"signed\", shape=box];"));
+/// assert!(grammar.analyze().is_nullable("signed"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Described<P> {
+    parser: P,
+    expr: Expr,
+}
+
+/// Pairs `parser` with its description `expr`.
+pub fn described<P>(expr: Expr, parser: P) -> Described<P> {
+    Described { parser, expr }
+}
+
+impl<P> Described<P> {
+    /// Returns the description.
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Returns the parser and its description.
+    pub fn into_parts(self) -> (P, Expr) {
+        (self.parser, self.expr)
+    }
+
+    /// Describes the parser as a reference to the rule `name`.
+    pub fn as_rule(self, name: &str) -> Self {
+        described(rule(name), self.parser)
+    }
+
+    /// Sequences with `other`, like `Parser::seq`.
+    pub fn seq<I, O, E, O2, E2>(
+        self,
+        other: Described<impl Parser<I, O2, E2>>,
+    ) -> Described<impl Parser<I, (O, O2), Either<E, E2>>>
+    where
+        P: Parser<I, O, E>,
+        I: Parsable<E> + Parsable<E2> + Parsable<Either<E, E2>>,
+        E: Clone,
+        E2: Clone,
+    {
+        let parts = match (self.expr, other.expr) {
+            (Expr::Seq(mut parts), Expr::Seq(more)) => {
+                parts.extend(more);
+                parts
+            }
+            (Expr::Seq(mut parts), expr) => {
+                parts.push(expr);
+                parts
+            }
+            (expr, other) => alloc::vec![expr, other],
+        };
+        described(Expr::Seq(parts), self.parser.seq(other.parser))
+    }
+
+    /// Tries `other` if this parser fails, like `Parser::alt`.
+    pub fn alt<I, O, E, O2, E2>(
+        self,
+        other: Described<impl Parser<I, O2, E2>>,
+    ) -> Described<impl Parser<I, Either<O, O2>, (E, E2)>>
+    where
+        P: Parser<I, O, E>,
+        I: Parsable<E> + Parsable<E2> + Parsable<(E, E2)>,
+        E: Clone,
+        E2: Clone,
+    {
+        let choices = match self.expr {
+            Expr::Alt(mut choices) => {
+                choices.push(other.expr);
+                choices
+            }
+            expr => alloc::vec![expr, other.expr],
+        };
+        described(Expr::Alt(choices), self.parser.alt(other.parser))
+    }
+
+    /// Repeats the parser zero or more times, like `Parser::many`.
+    pub fn many<I, O, E>(self) -> Described<impl Parser<I, Vec<O>, E>>
+    where
+        P: Parser<I, O, E>,
+        I: Parsable<E> + PartialEq,
+        E: Clone,
+    {
+        described(many(self.expr), self.parser.many())
+    }
+
+    /// Makes the parser optional, like `Parser::maybe`.
+    pub fn optional<I, O, E>(self) -> Described<impl Parser<I, Option<O>, E>>
+    where
+        P: Parser<I, O, E>,
+        I: Parsable<E>,
+        E: Clone,
+    {
+        described(optional(self.expr), self.parser.maybe())
+    }
+
+    /// Maps the output, like `Parser::map`; the description is unchanged.
+    pub fn map<I, O, E, O2>(self, f: impl Fn(O) -> O2) -> Described<impl Parser<I, O2, E>>
+    where
+        P: Parser<I, O, E>,
+        I: Parsable<E>,
+        E: Clone,
+    {
+        described(self.expr, self.parser.map(f))
+    }
+}
+
+impl<I, O, E, P> Parser<I, O, E> for Described<P>
+where
+    P: Parser<I, O, E>,
+    I: Parsable<E>,
+    E: Clone,
+{
+    fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
+        self.parser.parse(input)
+    }
+}
+
+/// The results of `GrammarSpec::analyze`.
+#[derive(Debug, Clone, Default)]
+pub struct Analysis {
+    order: Vec<String>,
+    nullable: BTreeSet<String>,
+    first: BTreeMap<String, BTreeSet<Terminal>>,
+    follow: BTreeMap<String, BTreeSet<Terminal>>,
+    problems: Vec<GrammarProblem>,
+}
+
+impl Analysis {
+    /// Returns true if the rule `name` can match the empty string.
+    pub fn is_nullable(&self, name: &str) -> bool {
+        self.nullable.contains(name)
+    }
+
+    /// Returns the terminals a match of the rule `name` can start with.
+    pub fn first(&self, name: &str) -> Option<&BTreeSet<Terminal>> {
+        self.first.get(name)
+    }
+
+    /// Returns the terminals that can follow a match of the rule `name`.
+    pub fn follow(&self, name: &str) -> Option<&BTreeSet<Terminal>> {
+        self.follow.get(name)
+    }
+
+    /// Returns the problems found: left-recursive cycles, nullable repetitions and
+    /// undefined rules, each in the order of the rules' definitions.
+    pub fn problems(&self) -> Vec<GrammarProblem> {
+        self.problems.clone()
+    }
+
+    fn expr_nullable(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Empty | Expr::Many(_) | Expr::Optional(_) => true,
+            Expr::Terminal(_) => false,
+            Expr::Rule(name) => self.nullable.contains(name),
+            Expr::Seq(parts) => parts.iter().all(|part| self.expr_nullable(part)),
+            Expr::Alt(choices) => choices.iter().any(|choice| self.expr_nullable(choice)),
+            Expr::Many1(inner) => self.expr_nullable(inner),
+        }
+    }
+
+    fn expr_first(&self, expr: &Expr, out: &mut BTreeSet<Terminal>) {
+        match expr {
+            Expr::Empty => {}
+            Expr::Terminal(name) => {
+                out.insert(Terminal::Symbol(name.clone()));
+            }
+            Expr::Rule(name) => out.extend(self.first.get(name).into_iter().flatten().cloned()),
+            Expr::Seq(parts) => {
+                for part in parts {
+                    self.expr_first(part, out);
+                    if !self.expr_nullable(part) {
+                        break;
+                    }
+                }
+            }
+            Expr::Alt(choices) => choices.iter().for_each(|choice| self.expr_first(choice, out)),
+            Expr::Many(inner) | Expr::Many1(inner) | Expr::Optional(inner) => self.expr_first(inner, out),
+        }
+    }
+
+    fn compute_nullable(&mut self, grammar: &GrammarSpec) {
+        loop {
+            let before = self.nullable.len();
+            for (name, expr) in &grammar.rules {
+                if self.expr_nullable(expr) {
+                    self.nullable.insert(name.clone());
+                }
+            }
+            if self.nullable.len() == before {
+                return;
+            }
+        }
+    }
+
+    fn compute_first(&mut self, grammar: &GrammarSpec) {
+        loop {
+            let mut changed = false;
+            for (name, expr) in &grammar.rules {
+                let mut first = BTreeSet::new();
+                self.expr_first(expr, &mut first);
+                if self.first.get(name) != Some(&first) {
+                    self.first.insert(name.clone(), first);
+                    changed = true;
+                }
+            }
+            if !changed {
+                return;
+            }
+        }
+    }
+
+    /// Adds to the FOLLOW sets of the rules in `expr`, which is followed by `after`.
+    fn expr_follow(&self, expr: &Expr, after: &BTreeSet<Terminal>, follow: &mut BTreeMap<String, BTreeSet<Terminal>>) {
+        match expr {
+            Expr::Empty | Expr::Terminal(_) => {}
+            Expr::Rule(name) => follow.entry(name.clone()).or_default().extend(after.iter().cloned()),
+            Expr::Seq(parts) => {
+                let mut after = after.clone();
+                for part in parts.iter().rev() {
+                    self.expr_follow(part, &after, follow);
+                    let mut first = BTreeSet::new();
+                    self.expr_first(part, &mut first);
+                    if !self.expr_nullable(part) {
+                        after.clear();
+                    }
+                    after.extend(first);
+                }
+            }
+            Expr::Alt(choices) => choices.iter().for_each(|choice| self.expr_follow(choice, after, follow)),
+            Expr::Optional(inner) => self.expr_follow(inner, after, follow),
+            Expr::Many(inner) | Expr::Many1(inner) => {
+                // A repetition may be followed by another repetition
+                let mut after = after.clone();
+                self.expr_first(inner, &mut after);
+                self.expr_follow(inner, &after, follow);
+            }
+        }
+    }
+
+    fn compute_follow(&mut self, grammar: &GrammarSpec) {
+        let mut follow: BTreeMap<String, BTreeSet<Terminal>> =
+            grammar.rules.iter().map(|(name, _)| (name.clone(), BTreeSet::new())).collect();
+        if let Some((start, _)) = grammar.rules.first() {
+            follow.entry(start.clone()).or_default().insert(Terminal::EndOfInput);
+        }
+        loop {
+            let mut next = follow.clone();
+            for (name, expr) in &grammar.rules {
+                let after = follow.get(name).cloned().unwrap_or_default();
+                self.expr_follow(expr, &after, &mut next);
+            }
+            if next == follow {
+                break;
+            }
+            follow = next;
+        }
+        self.follow = follow;
+    }
+
+    /// Collects the rules `expr` can reach before consuming input.
+    fn left_references(&self, expr: &Expr, out: &mut BTreeSet<String>) {
+        match expr {
+            Expr::Empty | Expr::Terminal(_) => {}
+            Expr::Rule(name) => {
+                out.insert(name.clone());
+            }
+            Expr::Seq(parts) => {
+                for part in parts {
+                    self.left_references(part, out);
+                    if !self.expr_nullable(part) {
+                        break;
+                    }
+                }
+            }
+            Expr::Alt(choices) => choices.iter().for_each(|choice| self.left_references(choice, out)),
+            Expr::Many(inner) | Expr::Many1(inner) | Expr::Optional(inner) => self.left_references(inner, out),
+        }
+    }
+
+    /// Finds repetitions of nullable expressions and undefined references.
+    fn check_expr(&self, expr: &Expr, defined: &BTreeSet<&str>, nullable_repetition: &mut bool, undefined: &mut Vec<String>) {
+        match expr {
+            Expr::Empty | Expr::Terminal(_) => {}
+            Expr::Rule(name) => {
+                if !defined.contains(name.as_str()) && !undefined.contains(name) {
+                    undefined.push(name.clone());
+                }
+            }
+            Expr::Seq(exprs) | Expr::Alt(exprs) => exprs
+                .iter()
+                .for_each(|expr| self.check_expr(expr, defined, nullable_repetition, undefined)),
+            Expr::Many(inner) | Expr::Many1(inner) => {
+                *nullable_repetition |= self.expr_nullable(inner);
+                self.check_expr(inner, defined, nullable_repetition, undefined);
+            }
+            Expr::Optional(inner) => self.check_expr(inner, defined, nullable_repetition, undefined),
+        }
+    }
+
+    fn find_problems(&mut self, grammar: &GrammarSpec) {
+        let edges: Vec<BTreeSet<usize>> = grammar
+            .rules
+            .iter()
+            .map(|(_, expr)| {
+                let mut names = BTreeSet::new();
+                self.left_references(expr, &mut names);
+                names.iter().filter_map(|name| self.order.iter().position(|n| n == name)).collect()
+            })
+            .collect();
+        for component in strongly_connected(&edges) {
+            let Some(&first) = component.first() else { continue };
+            if component.len() > 1 || edges.get(first).is_some_and(|targets| targets.contains(&first)) {
+                let cycle = component.iter().filter_map(|&i| self.order.get(i).cloned()).collect();
+                self.problems.push(GrammarProblem::LeftRecursion(cycle));
+            }
+        }
+
+        let defined: BTreeSet<&str> = self.order.iter().map(String::as_str).collect();
+        let mut undefined = Vec::new();
+        for (name, expr) in &grammar.rules {
+            let mut nullable_repetition = false;
+            self.check_expr(expr, &defined, &mut nullable_repetition, &mut undefined);
+            if nullable_repetition {
+                self.problems.push(GrammarProblem::NullableRepetition(name.clone()));
+            }
+        }
+        self.problems.extend(undefined.into_iter().map(GrammarProblem::UndefinedRule));
+    }
+}
+
+/// Returns the strongly connected components of a graph, each sorted, in the order of
+/// their smallest node (Tarjan's algorithm).
+#[allow(clippy::indexing_slicing)] // Every node is below `edges.len()`, the length of each table
+fn strongly_connected(edges: &[BTreeSet<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        edges: &'a [BTreeSet<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        next: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next);
+            self.low[node] = self.next;
+            self.next += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+
+            for &target in self.edges[node].iter() {
+                match self.index[target] {
+                    None => {
+                        self.visit(target);
+                        self.low[node] = self.low[node].min(self.low[target]);
+                    }
+                    Some(index) if self.on_stack[target] => self.low[node] = self.low[node].min(index),
+                    Some(_) => {}
+                }
+            }
+
+            if Some(self.low[node]) == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        edges,
+        index: alloc::vec![None; edges.len()],
+        low: alloc::vec![0; edges.len()],
+        stack: Vec::new(),
+        on_stack: alloc::vec![false; edges.len()],
+        next: 0,
+        components: Vec::new(),
+    };
+    for node in 0..edges.len() {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+    let mut components = tarjan.components;
+    components.sort_unstable_by_key(|component| component[0]);
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indirect_left_recursion_and_follow_sets() {
+        // a := b "x" | "y" ; b := opt c a ; c := "z"? ; d := many(e) ; e := missing
+        let grammar = GrammarSpec::new()
+            .rule("a", alt([seq([rule("b"), term("x")]), term("y")]))
+            .rule("b", seq([rule("c"), rule("a")]))
+            .rule("c", optional(term("z")))
+            .rule("d", many(rule("e")))
+            .rule("e", rule("missing"));
+        let analysis = grammar.analyze();
+
+        assert!(analysis.is_nullable("c") && !analysis.is_nullable("a"));
+        assert_eq!(analysis.first("b"), Some(&Terminal::set(["y", "z"])));
+        assert_eq!(analysis.follow("a"), Some(&[Terminal::Symbol("x".to_string()), Terminal::EndOfInput].into()));
+        assert_eq!(analysis.follow("c"), Some(&Terminal::set(["y", "z"])));
+        assert_eq!(
+            analysis.problems(),
+            vec![
+                GrammarProblem::LeftRecursion(vec!["a".to_string(), "b".to_string()]),
+                GrammarProblem::UndefinedRule("missing".to_string()),
+            ]
+        );
+        assert_eq!(analysis.problems()[0].to_string(), "left recursion: a -> b -> a");
+
+        // A second definition is an alternative
+        let grammar = GrammarSpec::new().rule("s", term("a")).rule("s", empty());
+        assert!(grammar.analyze().is_nullable("s"));
+        assert_eq!(grammar.rules().count(), 1);
+
+        // Generator descriptions convert, and repeating an empty literal is reported
+        let syntax = crate::testgen::many(crate::testgen::literal(""));
+        let grammar = GrammarSpec::new().rule("s", Expr::from(&syntax));
+        assert_eq!(grammar.analyze().problems(), vec![GrammarProblem::NullableRepetition("s".to_string())]);
+    }
+
+    #[test]
+    fn test_dot_export_of_described_parsers() {
+        use crate::core::ParsableItem;
+
+        let x = described(term("x"), ParsableItem::<&str, _>::make_character_matcher('x', "Expected x"));
+        let quote = described(term("\""), ParsableItem::<&str, _>::make_character_matcher('"', "Expected \""));
+        let item = x.alt(quote).map(|_| ()).as_rule("item");
+        let list = item.many().seq(described(empty(), "".make_literal_matcher("")).optional());
+        assert_eq!(list.parse("x\"x!"), Ok(("!", (vec![(), (), ()], Some("")))));
+        assert_eq!(list.expr(), &seq([many(rule("item")), optional(empty())]));
+
+        let grammar = GrammarSpec::new()
+            .rule("list", list.expr().clone())
+            .rule("item", alt([term("x"), term("\\\""), rule("missing")]));
+        let expected = [
+            "digraph grammar {",
+            "    ordering=out;",
+            "    \"rule:list\" [label=\"list\", shape=box];",
+            "    n0 [label=\"seq\", shape=ellipse];",
+            "    \"rule:list\" -> n0;",
+            "    n1 [label=\"*\", shape=circle];",
+            "    n0 -> n1;",
+            "    n1 -> \"rule:item\" [style=dashed];",
+            "    n2 [label=\"?\", shape=circle];",
+            "    n0 -> n2;",
+            "    n3 [label=\"ε\", shape=plaintext];",
+            "    n2 -> n3;",
+            "    \"rule:item\" [label=\"item\", shape=box];",
+            "    n4 [label=\"alt\", shape=diamond];",
+            "    \"rule:item\" -> n4;",
+            "    n5 [label=\"\\\"x\\\"\", shape=plaintext];",
+            "    n4 -> n5;",
+            "    n6 [label=\"\\\"\\\\\\\\\\\\\\\"\\\"\", shape=plaintext];",
+            "    n4 -> n6;",
+            "    n4 -> \"rule:missing\" [style=dashed];",
+            "    \"rule:missing\" [label=\"missing\", shape=box, color=red];",
+            "}",
+            "",
+        ];
+        assert_eq!(grammar.to_dot(), expected.join("\n"));
+    }
+}
@@ -0,0 +1,318 @@
+//! # Parsing From Async Readers
+//!
+//! This module is the async counterpart of `reader`: an `AsyncParseDriver` reads chunks
+//! from a `tokio::io::AsyncRead` source and parses consecutive records from them, without
+//! blocking while the source has no data. It is available with the `async` feature.
+//!
+//! Parsers run over `ReaderInput<AsyncFeed>`, the bytes received so far. When a parser
+//! needs bytes that have not arrived yet, its result is discarded, the driver awaits more
+//! data and parses the record again from its start. Records therefore never see a
+//! truncated input. To bound the cost of re-parsing, the driver keeps reading whatever
+//! the source has ready until the bytes of the record have doubled, and only re-parses
+//! early when it would otherwise have to wait. A record arriving in many ready chunks is
+//! then parsed a logarithmic number of times; only a source that keeps stalling mid-record
+//! causes a re-parse per stall.
+//!
+//! The driver owns its buffer and hands each attempt a fresh `ReaderInput`, so no
+//! reference-counted state is held across an `await`: `next` and `collect` return `Send`
+//! futures whenever the reader, parser, outputs and errors are `Send`.
+//!
+//! Sources are `AsyncRead`. A `Stream` of `Bytes` chunks, such as an HTTP body, is adapted
+//! with `tokio_util::io::StreamReader`, which keeps the `futures` and `bytes` crates out of
+//! this crate's dependencies.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::async_reader::*;
+//! use friss::reader::ReaderInput;
+//! # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+//! #     let mut future = std::pin::pin!(future);
+//! #     struct Noop;
+//! #     impl std::task::Wake for Noop {
+//! #         fn wake(self: std::sync::Arc<Self>) {}
+//! #     }
+//! #     let waker = std::task::Waker::from(std::sync::Arc::new(Noop));
+//! #     let mut cx = std::task::Context::from_waker(&waker);
+//! #     loop {
+//! #         if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+//! #             return output;
+//! #         }
+//! #     }
+//! # }
+//!
+//! let digit = <ReaderInput<AsyncFeed> as Parsable<&str>>::make_anything_matcher("Expected digit")
+//!     .validate(|b: &u8| b.is_ascii_digit(), "Expected digit");
+//! let number = digit.many().skip(b';'.make_character_matcher("Expected ;"));
+//!
+//! let mut driver = AsyncParseDriver::new(number, "12;3;".as_bytes());
+//! block_on(async {
+//!     assert_eq!(driver.next().await.unwrap().unwrap(), b"12");
+//!     assert_eq!(driver.next().await.unwrap().unwrap(), b"3");
+//!     assert!(driver.next().await.is_none());
+//! });
+//! ```
+
+use std::future::poll_fn;
+use std::io::{self, ErrorKind, Read};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::Poll;
+
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::core::Parser;
+use crate::reader::{ReadError, ReaderInput, READ_CHUNK_SIZE};
+
+/// The source of the `ReaderInput` an `AsyncParseDriver` hands to its parser.
+///
+/// The input starts out with the bytes received so far; reading past them reports
+/// `ErrorKind::WouldBlock` until the stream has ended.
+pub struct AsyncFeed {
+    finished: bool,
+}
+
+impl Read for AsyncFeed {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        if self.finished {
+            Ok(0)
+        } else {
+            Err(ErrorKind::WouldBlock.into())
+        }
+    }
+}
+
+/// Parses consecutive records from an async reader.
+pub struct AsyncParseDriver<R, P, O, E> {
+    reader: R,
+    parser: P,
+    /// Bytes received from the start of the next record on
+    buffered: Vec<u8>,
+    /// Stream offset of `buffered[0]`
+    base: usize,
+    finished: bool,
+    done: bool,
+    _marker: PhantomData<(O, E)>,
+}
+
+impl<R, P, O, E> AsyncParseDriver<R, P, O, E>
+where
+    R: AsyncRead + Unpin,
+    P: Parser<ReaderInput<AsyncFeed>, O, E>,
+    E: Clone,
+{
+    /// Creates a driver parsing records from `reader` with `parser`.
+    pub fn new(parser: P, reader: R) -> Self {
+        AsyncParseDriver {
+            reader,
+            parser,
+            buffered: Vec::new(),
+            base: 0,
+            finished: false,
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Parses the next record, awaiting more data as needed. Returns `None` once the
+    /// reader is exhausted.
+    ///
+    /// After an error, or a record that consumes no input, no further records are parsed.
+    pub async fn next(&mut self) -> Option<Result<O, ReadError<E>>> {
+        loop {
+            if self.done {
+                return None;
+            }
+            if let Poll::Ready(record) = self.parse_buffered() {
+                return record;
+            }
+            if let Err(e) = self.fill().await {
+                self.done = true;
+                return Some(Err(ReadError::Io(e)));
+            }
+        }
+    }
+
+    /// Parses all remaining records.
+    pub async fn collect(mut self) -> Result<Vec<O>, ReadError<E>> {
+        let mut records = Vec::new();
+        while let Some(record) = self.next().await {
+            records.push(record?);
+        }
+        Ok(records)
+    }
+
+    /// Parses a record from the buffered bytes, or returns `Poll::Pending` if the parser
+    /// needed bytes that have not arrived.
+    fn parse_buffered(&mut self) -> Poll<Option<Result<O, ReadError<E>>>> {
+        let feed = AsyncFeed { finished: self.finished };
+        let input = ReaderInput::with_buffered(feed, self.base, self.buffered.clone());
+        let result = if input.is_empty() { None } else { Some(self.parser.parse(input.clone())) };
+        if input.take_would_block() {
+            return Poll::Pending;
+        }
+        Poll::Ready(match result {
+            None => {
+                self.done = true;
+                None
+            }
+            Some(Ok((rest, output))) => {
+                let consumed = rest.offset() - self.base;
+                if consumed == 0 {
+                    self.done = true;
+                }
+                self.buffered.drain(..consumed);
+                self.base += consumed;
+                Some(Ok(output))
+            }
+            Some(Err((rest, error))) => {
+                self.done = true;
+                Some(Err(ReadError::Parse {
+                    offset: rest.offset(),
+                    error,
+                }))
+            }
+        })
+    }
+
+    /// Waits for at least one chunk, then keeps reading the chunks that are ready until
+    /// the buffered bytes have doubled.
+    async fn fill(&mut self) -> io::Result<()> {
+        let target = self.buffered.len() * 2;
+        let mut received = false;
+        let mut chunk = vec![0; READ_CHUNK_SIZE];
+        poll_fn(|cx| loop {
+            if self.finished || (received && self.buffered.len() >= target) {
+                return Poll::Ready(Ok(()));
+            }
+            let mut buf = ReadBuf::new(&mut chunk);
+            match Pin::new(&mut self.reader).poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(())) => {
+                    self.finished = buf.filled().is_empty();
+                    self.buffered.extend_from_slice(buf.filled());
+                    received = true;
+                }
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                // Parse what has arrived rather than wait, the record may be complete
+                Poll::Pending if received => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::cell::Cell;
+    use std::future::Future;
+    use std::pin::{pin, Pin};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use tokio::io::ReadBuf;
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Hands out one byte per read, alternating with `Pending`.
+    struct Trickle {
+        data: &'static [u8],
+        ready: bool,
+    }
+
+    impl AsyncRead for Trickle {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            if let Some((&byte, rest)) = self.data.split_first() {
+                buf.put_slice(&[byte]);
+                self.data = rest;
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Hands out its data in chunks of `size` bytes, always ready.
+    struct Chunks {
+        data: &'static [u8],
+        size: usize,
+    }
+
+    impl AsyncRead for Chunks {
+        fn poll_read(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            let (chunk, rest) = self.data.split_at(self.size.min(self.data.len()));
+            buf.put_slice(chunk);
+            self.data = rest;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn test_ready_chunks_are_gathered_before_parsing() {
+        let attempts = Cell::new(0);
+        let byte = <ReaderInput<AsyncFeed> as Parsable<&str>>::make_anything_matcher("Expected byte");
+        let line = byte.validate(|b: &u8| *b != b'\n', "Expected byte").many().skip(b'\n'.make_character_matcher("Expected newline"));
+        let counted = |input| {
+            attempts.set(attempts.get() + 1);
+            line.parse(input)
+        };
+
+        let data = ("x".repeat(4000) + "\n").into_bytes().leak();
+        let mut driver = AsyncParseDriver::new(counted, Chunks { data, size: 1 });
+        assert_eq!(block_on(driver.next()).unwrap().unwrap().len(), 4000);
+        // One byte per read, yet the record is parsed about log2(4000) times, not 4000
+        assert!(attempts.get() < 20);
+    }
+
+    #[test]
+    fn test_driver_futures_are_send() {
+        fn assert_send<T: Send>(_: T) {}
+        let digit = <ReaderInput<AsyncFeed> as Parsable<&str>>::make_anything_matcher("Expected digit");
+        let mut driver = AsyncParseDriver::new(digit, Trickle { data: b"1", ready: false });
+        assert_send(driver.next());
+        assert_send(driver.collect());
+    }
+
+    #[test]
+    fn test_records_split_across_chunks() {
+        let word = || {
+            let letter = <ReaderInput<AsyncFeed> as Parsable<&str>>::make_anything_matcher("Expected letter")
+                .validate(|b: &u8| b.is_ascii_alphabetic(), "Expected letter");
+            let keyword = ReaderInput::from_bytes(b"say ").make_literal_matcher("Expected say");
+            letter.many().preceded_by(keyword).map_err(Foldable::fold).skip(b'\n'.make_character_matcher("Expected newline"))
+        };
+
+        let source = Trickle { data: b"say hello\nsay hi\n", ready: false };
+        let records = block_on(AsyncParseDriver::new(word(), source).collect()).unwrap();
+        assert_eq!(records, vec![b"hello".to_vec(), b"hi".to_vec()]);
+
+        let source = Trickle { data: b"say hello\nshout\n", ready: false };
+        let err = block_on(AsyncParseDriver::new(word(), source).collect()).unwrap_err();
+        assert!(matches!(err, ReadError::Parse { offset: 10, error: "Expected say" }));
+
+        // A record cut off by the end of the stream is an error, not a partial record
+        let source = Trickle { data: b"say hel", ready: false };
+        let err = block_on(AsyncParseDriver::new(word(), source).collect()).unwrap_err();
+        assert!(matches!(err, ReadError::Parse { offset: 7, error: "Expected newline" }));
+    }
+}
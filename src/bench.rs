@@ -0,0 +1,192 @@
+//! # Benchmark Harness
+//!
+//! This module provides a small, dependency-free harness for measuring parser throughput
+//! over a corpus of inputs. It is meant for quick feedback while tuning a grammar from
+//! examples or tests; use a full benchmarking framework for rigorous measurements.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::bench::*;
+//!
+//! let parser = 'a'.make_character_matcher("Expected a").many();
+//! let corpus = ["aaaa", "aaaaaaaa", "aa"];
+//!
+//! let report = parser.benchmark_with(&corpus, BenchmarkConfig { warmup_iterations: 1, iterations: 5 });
+//!
+//! assert_eq!(report.bytes_per_iteration, 14);
+//! assert_eq!(report.samples.len(), 5);
+//! assert_eq!(report.failures, 0);
+//! assert!(report.min <= report.median && report.median <= report.max);
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::core::{Parsable, Parser, ParserOutput};
+use crate::state::StateCarrier;
+
+/// Inputs whose size in bytes is known, used to compute throughput.
+pub trait ByteLen {
+    /// Returns the size of the input in bytes.
+    fn byte_len(&self) -> usize;
+}
+
+impl ByteLen for &str {
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> ByteLen for &[T] {
+    fn byte_len(&self) -> usize {
+        std::mem::size_of_val(*self)
+    }
+}
+
+impl<S, I: ByteLen> ByteLen for StateCarrier<S, I> {
+    fn byte_len(&self) -> usize {
+        self.input.byte_len()
+    }
+}
+
+/// Settings for a benchmark run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchmarkConfig {
+    /// Untimed passes over the corpus run before measuring
+    pub warmup_iterations: usize,
+    /// Timed passes over the corpus
+    pub iterations: usize,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        BenchmarkConfig {
+            warmup_iterations: 3,
+            iterations: 30,
+        }
+    }
+}
+
+/// Timing summary of a benchmark run.
+///
+/// Every sample is the time of one full pass over the corpus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkReport {
+    /// Total input size of one pass over the corpus
+    pub bytes_per_iteration: usize,
+    /// Number of inputs that failed to parse in one pass
+    pub failures: usize,
+    /// Sorted duration of every timed pass
+    pub samples: Vec<Duration>,
+    /// Fastest pass
+    pub min: Duration,
+    /// Median pass
+    pub median: Duration,
+    /// Slowest pass
+    pub max: Duration,
+}
+
+impl BenchmarkReport {
+    /// Returns the duration below which `p` percent of the passes finished.
+    pub fn percentile(&self, p: f64) -> Duration {
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * self.samples.len().saturating_sub(1) as f64).round();
+        self.samples.get(rank as usize).copied().unwrap_or_default()
+    }
+
+    /// Returns the throughput of the median pass in megabytes per second.
+    pub fn throughput_mb_s(&self) -> f64 {
+        let secs = self.median.as_secs_f64();
+        if secs == 0.0 {
+            f64::INFINITY
+        } else {
+            self.bytes_per_iteration as f64 / secs / 1_000_000.0
+        }
+    }
+}
+
+impl Display for BenchmarkReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} MB/s (median {:?}, p90 {:?}, min {:?}, max {:?}, {} bytes, {} failures)",
+            self.throughput_mb_s(),
+            self.median,
+            self.percentile(90.0),
+            self.min,
+            self.max,
+            self.bytes_per_iteration,
+            self.failures
+        )
+    }
+}
+
+/// Extension trait adding throughput measurement to parsers.
+pub trait BenchmarkParser<Input, Output, Error>: Parser<Input, Output, Error>
+where
+    Input: Parsable<Error> + Clone + ByteLen,
+    Output: ParserOutput,
+    Error: Clone,
+{
+    /// Measures the parser over `corpus` with the default `BenchmarkConfig`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::bench::*;
+    ///
+    /// let parser = "ab".make_literal_matcher("Expected ab").many();
+    /// let report = parser.benchmark_against(&["abab", "ab"]);
+    /// println!("{}", report);
+    /// assert_eq!(report.bytes_per_iteration, 6);
+    /// ```
+    fn benchmark_against(&self, corpus: &[Input]) -> BenchmarkReport {
+        self.benchmark_with(corpus, BenchmarkConfig::default())
+    }
+
+    /// Measures the parser over `corpus` with the given settings.
+    fn benchmark_with(&self, corpus: &[Input], config: BenchmarkConfig) -> BenchmarkReport {
+        let pass = || {
+            corpus
+                .iter()
+                .filter(|input| black_box(self.parse(black_box((*input).clone()))).is_err())
+                .count()
+        };
+
+        for _ in 0..config.warmup_iterations {
+            pass();
+        }
+
+        let mut failures = 0;
+        let mut samples: Vec<Duration> = (0..config.iterations.max(1))
+            .map(|_| {
+                let start = Instant::now();
+                failures = pass();
+                start.elapsed()
+            })
+            .collect();
+        samples.sort();
+
+        BenchmarkReport {
+            bytes_per_iteration: corpus.iter().map(ByteLen::byte_len).sum(),
+            failures,
+            // A configuration without iterations reports zero durations
+            min: samples.first().copied().unwrap_or_default(),
+            median: samples.get(samples.len() / 2).copied().unwrap_or_default(),
+            max: samples.last().copied().unwrap_or_default(),
+            samples,
+        }
+    }
+}
+
+impl<Input, Output, Error, P> BenchmarkParser<Input, Output, Error> for P
+where
+    Input: Parsable<Error> + Clone + ByteLen,
+    Output: ParserOutput,
+    Error: Clone,
+    P: Parser<Input, Output, Error>,
+{
+}
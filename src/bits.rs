@@ -0,0 +1,234 @@
+//! # Bit-Level Parsing
+//!
+//! This module parses fields narrower than a byte, such as the flags of a DNS header or the
+//! variable-width codes of a video bitstream. `BitInput` walks a byte slice one bit at a
+//! time, most significant bit first, and implements `Parsable` with single bits (`bool`) as
+//! items. `take_bits(n)` reads an `n`-bit unsigned integer.
+//!
+//! `bytes_to_bits` runs a bit parser inside a byte parser, and `bits_to_bytes` runs a byte
+//! parser inside a bit parser, so bit fields can be interleaved with the parsers of
+//! `parsers::binary`.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::bits::*;
+//! use friss::parsers::binary::*;
+//!
+//! // Start of a DNS header: 16-bit id, QR (1 bit), OPCODE (4 bits), then AA, TC and RD
+//! let flags = bytes_to_bits(take_bits(1).seq(take_bits(4)).seq(take_bits(3)));
+//! let header = be_u16().seq(flags);
+//! let bytes: &[u8] = &[0x12, 0x34, 0b1_0010_101, 0x80];
+//!
+//! assert_eq!(header.parse(bytes), Ok((&[0x80u8][..], (0x1234, ((1, 2), 5)))));
+//! ```
+
+use crate::core::{Checkpoint, Parsable, Parser};
+use crate::parsers::binary::Incomplete;
+
+/// A position inside a byte slice, counted in bits.
+///
+/// Bits are read from the most significant bit of each byte down to the least significant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitInput<'a> {
+    bytes: &'a [u8],
+    bit: usize,
+}
+
+impl<'a> BitInput<'a> {
+    /// Starts reading at the first bit of `bytes`.
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        BitInput { bytes, bit: 0 }
+    }
+
+    /// Returns the number of bits left.
+    pub fn remaining_bits(&self) -> usize {
+        self.bytes.len() * 8 - self.bit
+    }
+
+    /// Returns true if the position is at a byte boundary.
+    pub fn is_aligned(&self) -> bool {
+        self.bit == 0
+    }
+
+    /// Returns the bytes from the current one on; a partially read byte is included.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the bytes after the current position, skipping the rest of a partially read
+    /// byte.
+    pub fn aligned_bytes(&self) -> &'a [u8] {
+        if self.is_aligned() {
+            self.bytes
+        } else {
+            self.bytes.get(1..).unwrap_or_default()
+        }
+    }
+
+    /// Returns the next bit without advancing.
+    fn peek_bit(&self) -> Option<bool> {
+        self.bytes.first().map(|byte| byte & (0x80 >> self.bit) != 0)
+    }
+
+    /// Advances by `n` bits, which must be available.
+    fn advance(&self, n: usize) -> Self {
+        let bit = self.bit + n;
+        BitInput {
+            bytes: self.bytes.get(bit / 8..).unwrap_or_default(),
+            bit: bit % 8,
+        }
+    }
+}
+
+impl Checkpoint for BitInput<'_> {
+    type Checkpoint = Self;
+
+    fn checkpoint(&self) -> Self {
+        *self
+    }
+
+    fn rewind(checkpoint: Self) -> Self {
+        checkpoint
+    }
+}
+
+impl<Error: Clone> Parsable<Error> for BitInput<'_> {
+    type Item = bool;
+
+    fn make_literal_matcher(self, err: Error) -> impl Parser<Self, Self, Error> {
+        move |input: Self| {
+            let len = self.remaining_bits();
+            if input.remaining_bits() < len {
+                return Err((input, err.clone()));
+            }
+            let mut expected = self;
+            let mut actual = input;
+            while let Some(bit) = expected.peek_bit() {
+                if actual.peek_bit() != Some(bit) {
+                    return Err((input, err.clone()));
+                }
+                expected = expected.advance(1);
+                actual = actual.advance(1);
+            }
+            Ok((actual, self))
+        }
+    }
+
+    fn make_anything_matcher(err: Error) -> impl Parser<Self, Self::Item, Error> {
+        move |input: Self| match input.peek_bit() {
+            Some(bit) => Ok((input.advance(1), bit)),
+            None => Err((input, err.clone())),
+        }
+    }
+
+    fn make_item_matcher(character: Self::Item, err: Error) -> impl Parser<Self, Self::Item, Error> {
+        move |input: Self| match input.peek_bit() {
+            Some(bit) if bit == character => Ok((input.advance(1), bit)),
+            _ => Err((input, err.clone())),
+        }
+    }
+
+    fn make_empty_matcher(err: Error) -> impl Parser<Self, (), Error> {
+        move |input: Self| {
+            if input.remaining_bits() == 0 {
+                Ok((input, ()))
+            } else {
+                Err((input, err.clone()))
+            }
+        }
+    }
+}
+
+/// Reads an `n`-bit unsigned integer, most significant bit first. The error counts the
+/// missing bits.
+///
+/// # Panics
+///
+/// Panics if `n` is larger than 64.
+pub fn take_bits<'a>(n: usize) -> impl Parser<BitInput<'a>, u64, Incomplete> {
+    assert!(n <= 64, "take_bits reads at most 64 bits, got {}", n);
+    move |input: BitInput<'a>| {
+        if input.remaining_bits() < n {
+            return Err((input, Incomplete { needed: n - input.remaining_bits() }));
+        }
+        let mut value = 0u64;
+        let mut rest = input;
+        for _ in 0..n {
+            value = (value << 1) | rest.peek_bit().map_or(0, u64::from);
+            rest = rest.advance(1);
+        }
+        Ok((rest, value))
+    }
+}
+
+/// Runs the bit parser `parser` on byte input. Parsing continues at the byte after the
+/// last bit read, so a partially read byte is skipped.
+pub fn bytes_to_bits<'a, Output, Error>(
+    parser: impl Parser<BitInput<'a>, Output, Error>,
+) -> impl Parser<&'a [u8], Output, Error>
+where
+    Error: Clone,
+{
+    move |input: &'a [u8]| match parser.parse(BitInput::new(input)) {
+        Ok((rest, output)) => Ok((rest.aligned_bytes(), output)),
+        Err((rest, err)) => Err((rest.bytes(), err)),
+    }
+}
+
+/// Runs the byte parser `parser` on bit input, starting at the next byte boundary.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::bits::*;
+/// use friss::parsers::binary::*;
+///
+/// // A 1-bit flag padded to a byte, then a big endian u16
+/// let field = take_bits(1).seq(bits_to_bytes(be_u16()));
+/// let bytes: &[u8] = &[0x80, 0x01, 0x02];
+///
+/// let (rest, (flag, value)) = field.parse(BitInput::new(bytes)).unwrap();
+/// assert_eq!((flag, value), (1, 0x0102));
+/// assert_eq!(rest.remaining_bits(), 0);
+/// ```
+pub fn bits_to_bytes<'a, Output, Error>(
+    parser: impl Parser<&'a [u8], Output, Error>,
+) -> impl Parser<BitInput<'a>, Output, Error>
+where
+    Error: Clone,
+{
+    move |input: BitInput<'a>| match parser.parse(input.aligned_bytes()) {
+        Ok((rest, output)) => Ok((BitInput::new(rest), output)),
+        Err((rest, err)) => Err((BitInput::new(rest), err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_bit_input_matchers() {
+        let bytes: &[u8] = &[0b1010_0000, 0xff];
+        let input = BitInput::new(bytes);
+
+        let one = true.make_character_matcher("Expected 1");
+        let (rest, _) = one.parse(input).unwrap();
+        assert_eq!(rest.remaining_bits(), 15);
+        assert!(one.parse(rest).is_err());
+
+        let pattern = BitInput::new(&[0b1010_0000]).make_literal_matcher("Expected pattern");
+        let (rest, _) = pattern.parse(input).unwrap();
+        assert!(rest.is_aligned());
+        assert_eq!(rest.bytes(), &[0xff]);
+
+        let (rest, value) = take_bits(12).parse(BitInput::new(bytes).advance(4)).unwrap();
+        assert_eq!(value, 0x0ff);
+        assert_eq!(take_bits(1).parse(rest), Err((rest, Incomplete { needed: 1 })));
+        assert!(<BitInput as Parsable<()>>::make_empty_matcher(()).parse(rest).is_ok());
+    }
+}
@@ -0,0 +1,500 @@
+//! # Grammar Composition
+//!
+//! This module lets large grammars be split into independently defined sub-grammars, for
+//! example expressions, statements and types living in separate modules or crates. Each
+//! `GrammarModule` owns a namespace of named rules and may reference rules of other
+//! modules by qualified name (`"namespace.rule"`). A `GrammarComposer` combines the modules,
+//! resolving every reference at composition time and reporting missing or duplicate rules.
+//!
+//! Sub-grammars can keep their own error enums: rules are converted into the composed
+//! error type with `Into`. A context shared by all sub-grammars is carried in the input,
+//! for example with `StateCarrier`.
+//!
+//! When rules are only known at runtime, for example statements contributed by plugins, a
+//! `RuleSet` registers parsers under names that are looked up each time a rule parses, so
+//! rules can be added, extended with further alternatives or replaced at any point.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::compose::*;
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! enum ExprError { ExpectedNumber }
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! enum Error { Expr(ExprError), Stmt(&'static str) }
+//!
+//! impl From<ExprError> for Error {
+//!     fn from(e: ExprError) -> Self { Error::Expr(e) }
+//! }
+//!
+//! let mut expr = GrammarModule::new("expr");
+//! expr.rule("number", "1".make_literal_matcher(ExprError::ExpectedNumber).map(String::from));
+//!
+//! let mut stmt = GrammarModule::<&str, String, Error>::new("stmt");
+//! let number = stmt.reference("expr.number");
+//! stmt.rule("print", number
+//!     .preceded_by("print ".make_literal_matcher(Error::Stmt("Expected print")))
+//!     .map_err(Foldable::fold));
+//!
+//! let grammar = GrammarComposer::new().include(expr).include(stmt).compose().unwrap();
+//! let print = grammar.rule("stmt.print").unwrap();
+//!
+//! assert_eq!(print.parse("print 1"), Ok(("", "1".to_string())));
+//! assert_eq!(print.parse("print x"), Err(("x", Error::Expr(ExprError::ExpectedNumber))));
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::rc::Rc;
+
+use crate::core::{Parsable, Parser};
+
+type Rule<I, O, E> = Rc<dyn Parser<I, O, E>>;
+type Slot<I, O, E> = Rc<RefCell<Option<Rule<I, O, E>>>>;
+
+/// A reference to a named rule, resolved when the grammar is composed.
+pub struct RuleRef<I, O, E> {
+    name: String,
+    slot: Slot<I, O, E>,
+}
+
+impl<I, O, E> Clone for RuleRef<I, O, E> {
+    fn clone(&self) -> Self {
+        RuleRef {
+            name: self.name.clone(),
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<I, O, E> RuleRef<I, O, E> {
+    /// Returns the qualified name of the referenced rule.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<I, O, E> Parser<I, O, E> for RuleRef<I, O, E>
+where
+    I: Parsable<E>,
+    E: Clone,
+{
+    #[allow(clippy::panic)] // A grammar that is never composed, never input
+    fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
+        let rule = self.slot.borrow().clone();
+        match rule {
+            Some(rule) => rule.parse(input),
+            None => panic!("Rule reference `{}` used before the grammar was composed", self.name),
+        }
+    }
+}
+
+/// A sub-grammar with its own namespace of rules.
+pub struct GrammarModule<I, O, E> {
+    namespace: String,
+    rules: Vec<(String, Rule<I, O, E>)>,
+    references: Vec<RuleRef<I, O, E>>,
+}
+
+impl<I, O, E> GrammarModule<I, O, E>
+where
+    I: Parsable<E> + 'static,
+    O: 'static,
+    E: Clone + 'static,
+{
+    /// Creates an empty module whose rules are named `namespace.rule`.
+    pub fn new(namespace: impl Into<String>) -> Self {
+        GrammarModule {
+            namespace: namespace.into(),
+            rules: Vec::new(),
+            references: Vec::new(),
+        }
+    }
+
+    /// Returns the namespace of the module.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Defines the rule `name`, converting its errors into the module's error type.
+    pub fn rule<E2>(&mut self, name: impl Into<String>, parser: impl Parser<I, O, E2> + 'static) -> &mut Self
+    where
+        I: Parsable<E2>,
+        E2: Clone + Into<E>,
+    {
+        let rule = move |input: I| parser.parse(input).map_err(|(rest, e)| (rest, e.into()));
+        self.rules.push((name.into(), Rc::new(rule)));
+        self
+    }
+
+    /// References a rule by name. Names containing a `.` are qualified with the namespace
+    /// of another module; plain names refer to rules of this module. The reference can be
+    /// used before the rule is defined and is resolved by `GrammarComposer::compose`.
+    pub fn reference(&mut self, name: &str) -> RuleRef<I, O, E> {
+        let name = if name.contains('.') {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.namespace, name)
+        };
+        let reference = RuleRef {
+            name,
+            slot: Rc::new(RefCell::new(None)),
+        };
+        self.references.push(reference.clone());
+        reference
+    }
+}
+
+/// Problems found while composing a grammar.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompositionError {
+    /// References to rules that no module defines
+    pub unresolved: Vec<String>,
+    /// Rules defined more than once
+    pub duplicates: Vec<String>,
+}
+
+impl Display for CompositionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut problems = Vec::new();
+        if !self.unresolved.is_empty() {
+            problems.push(format!("unresolved rules: {}", self.unresolved.join(", ")));
+        }
+        if !self.duplicates.is_empty() {
+            problems.push(format!("duplicate rules: {}", self.duplicates.join(", ")));
+        }
+        write!(f, "{}", problems.join("; "))
+    }
+}
+
+impl std::error::Error for CompositionError {}
+
+/// Combines grammar modules into one grammar.
+pub struct GrammarComposer<I, O, E> {
+    modules: Vec<GrammarModule<I, O, E>>,
+}
+
+impl<I, O, E> Default for GrammarComposer<I, O, E> {
+    fn default() -> Self {
+        GrammarComposer { modules: Vec::new() }
+    }
+}
+
+impl<I, O, E> GrammarComposer<I, O, E> {
+    /// Creates an empty composer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a module to the grammar.
+    pub fn include(mut self, module: GrammarModule<I, O, E>) -> Self {
+        self.modules.push(module);
+        self
+    }
+
+    /// Resolves all rule references and returns the composed grammar.
+    pub fn compose(self) -> Result<ComposedGrammar<I, O, E>, CompositionError> {
+        let mut rules = HashMap::new();
+        let mut error = CompositionError::default();
+        let mut references = Vec::new();
+
+        for module in self.modules {
+            for (name, rule) in module.rules {
+                let qualified = format!("{}.{}", module.namespace, name);
+                if rules.insert(qualified.clone(), rule).is_some() && !error.duplicates.contains(&qualified) {
+                    error.duplicates.push(qualified);
+                }
+            }
+            references.extend(module.references);
+        }
+
+        for reference in references {
+            match rules.get(&reference.name) {
+                Some(rule) => *reference.slot.borrow_mut() = Some(rule.clone()),
+                None => {
+                    if !error.unresolved.contains(&reference.name) {
+                        error.unresolved.push(reference.name);
+                    }
+                }
+            }
+        }
+
+        if error.unresolved.is_empty() && error.duplicates.is_empty() {
+            Ok(ComposedGrammar { rules })
+        } else {
+            Err(error)
+        }
+    }
+}
+
+/// A grammar whose rules are all resolved.
+pub struct ComposedGrammar<I, O, E> {
+    rules: HashMap<String, Rule<I, O, E>>,
+}
+
+impl<I, O, E> ComposedGrammar<I, O, E> {
+    /// Returns the rule with the qualified `name`.
+    pub fn rule(&self, name: &str) -> Option<RuleRef<I, O, E>> {
+        self.rules.get(name).map(|rule| RuleRef {
+            name: name.to_string(),
+            slot: Rc::new(RefCell::new(Some(rule.clone()))),
+        })
+    }
+
+    /// Returns the qualified names of all rules, sorted.
+    pub fn rule_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.rules.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+}
+
+type Alternatives<I, O, E> = Rc<Vec<Rule<I, O, E>>>;
+type Rules<I, O, E> = Rc<RefCell<HashMap<String, Alternatives<I, O, E>>>>;
+
+/// A registry of named rules that reference each other by name, resolved at parse time.
+///
+/// A name may have several alternatives, tried in the order they were added until one
+/// succeeds; if all fail, the error of the last one is returned. Parsing a name without
+/// alternatives fails with the error built by the `missing` function given to `new`.
+/// Clones of a rule set share its rules.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::compose::*;
+///
+/// let rules = RuleSet::<&str, String, String>::new(|name| format!("Unknown rule {}", name));
+/// let statement = rules.rule("statement");
+///
+/// // Statements are terminated by `;`, whichever rules define them
+/// let program = statement.skip(';'.make_character_matcher("Expected ;".to_string())).many();
+///
+/// rules.extend("statement", "print".make_literal_matcher("Expected print").map(String::from));
+/// assert_eq!(program.parse("print;print;"), Ok(("", vec!["print".to_string(); 2])));
+///
+/// // A plugin adds a statement later on
+/// rules.extend("statement", "exit".make_literal_matcher("Expected exit").map(String::from));
+/// assert_eq!(program.parse("print;exit;").unwrap().1, vec!["print", "exit"]);
+///
+/// assert_eq!(rules.rule("expr").parse("1"), Err(("1", "Unknown rule expr".to_string())));
+/// ```
+pub struct RuleSet<I, O, E> {
+    rules: Rules<I, O, E>,
+    missing: Rc<dyn Fn(&str) -> E>,
+}
+
+impl<I, O, E> Clone for RuleSet<I, O, E> {
+    fn clone(&self) -> Self {
+        RuleSet {
+            rules: self.rules.clone(),
+            missing: self.missing.clone(),
+        }
+    }
+}
+
+impl<I, O, E> RuleSet<I, O, E>
+where
+    I: Parsable<E> + 'static,
+    O: 'static,
+    E: Clone + 'static,
+{
+    /// Creates an empty rule set, reporting rules without definition with `missing`.
+    pub fn new(missing: impl Fn(&str) -> E + 'static) -> Self {
+        RuleSet {
+            rules: Rc::new(RefCell::new(HashMap::new())),
+            missing: Rc::new(missing),
+        }
+    }
+
+    /// Defines the rule `name`, replacing all its alternatives.
+    pub fn define<E2>(&self, name: impl Into<String>, parser: impl Parser<I, O, E2> + 'static) -> &Self
+    where
+        I: Parsable<E2>,
+        E2: Clone + Into<E>,
+    {
+        self.rules
+            .borrow_mut()
+            .insert(name.into(), Rc::new(vec![Self::convert(parser)]));
+        self
+    }
+
+    /// Adds `parser` as an alternative of the rule `name`, tried after the existing ones.
+    pub fn extend<E2>(&self, name: impl Into<String>, parser: impl Parser<I, O, E2> + 'static) -> &Self
+    where
+        I: Parsable<E2>,
+        E2: Clone + Into<E>,
+    {
+        let mut rules = self.rules.borrow_mut();
+        let alternatives = rules.entry(name.into()).or_default();
+        Rc::make_mut(alternatives).push(Self::convert(parser));
+        self
+    }
+
+    /// Removes the rule `name`, returning true if it was defined.
+    pub fn remove(&self, name: &str) -> bool {
+        self.rules.borrow_mut().remove(name).is_some()
+    }
+
+    /// Returns true if the rule `name` has at least one alternative.
+    pub fn contains(&self, name: &str) -> bool {
+        self.rules.borrow().get(name).is_some_and(|alternatives| !alternatives.is_empty())
+    }
+
+    /// Returns the names of all rules, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.rules.borrow().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Returns a parser for the rule `name`. The rule may be defined later; its current
+    /// alternatives are looked up each time the parser runs.
+    pub fn rule(&self, name: impl Into<String>) -> NamedRule<I, O, E> {
+        NamedRule {
+            name: name.into(),
+            set: self.clone(),
+        }
+    }
+
+    fn convert<E2>(parser: impl Parser<I, O, E2> + 'static) -> Rule<I, O, E>
+    where
+        I: Parsable<E2>,
+        E2: Clone + Into<E>,
+    {
+        Rc::new(move |input: I| parser.parse(input).map_err(|(rest, e)| (rest, e.into())))
+    }
+}
+
+/// A rule of a `RuleSet`, looked up by name at parse time.
+pub struct NamedRule<I, O, E> {
+    name: String,
+    set: RuleSet<I, O, E>,
+}
+
+impl<I, O, E> Clone for NamedRule<I, O, E> {
+    fn clone(&self) -> Self {
+        NamedRule {
+            name: self.name.clone(),
+            set: self.set.clone(),
+        }
+    }
+}
+
+impl<I, O, E> NamedRule<I, O, E> {
+    /// Returns the name of the rule.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<I, O, E> Parser<I, O, E> for NamedRule<I, O, E>
+where
+    I: Parsable<E>,
+    E: Clone,
+{
+    fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
+        // Release the borrow before parsing, since rules may look up other rules
+        let alternatives = self.set.rules.borrow().get(&self.name).cloned().unwrap_or_default();
+        let Some((last, first)) = alternatives.split_last() else {
+            return Err((input, (self.set.missing)(&self.name)));
+        };
+        let mut input = input;
+        for alternative in first {
+            let checkpoint = input.checkpoint();
+            match alternative.parse(input) {
+                Ok(result) => return Ok(result),
+                Err(_) => input = I::rewind(checkpoint),
+            }
+        }
+        last.parse(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_mutually_recursive_modules() {
+        // expr.value := "1" | stmt.block ; stmt.block := "{" expr.value "}"
+        let mut expr = GrammarModule::<&str, usize, &str>::new("expr");
+        let block = expr.reference("stmt.block");
+        expr.rule("value", move |input| {
+            "1".make_literal_matcher("Expected value")
+                .map(|_| 1)
+                .parse(input)
+                .or_else(|(_, _)| block.parse(input))
+        });
+
+        let mut stmt = GrammarModule::new("stmt");
+        let value = stmt.reference("expr.value");
+        stmt.rule("block", move |input| {
+            let (rest, _) = "{".make_literal_matcher("Expected {").parse(input)?;
+            let (rest, depth) = value.parse(rest)?;
+            let (rest, _) = "}".make_literal_matcher("Expected }").parse(rest)?;
+            Ok((rest, depth + 1))
+        });
+
+        let grammar = GrammarComposer::new().include(expr).include(stmt).compose().unwrap();
+        assert_eq!(grammar.rule_names(), vec!["expr.value", "stmt.block"]);
+        assert_eq!(grammar.rule("expr.value").unwrap().parse("{{1}}"), Ok(("", 3)));
+        assert!(grammar.rule("value").is_none());
+    }
+
+    #[test]
+    fn test_rule_set_lookup_at_parse_time() {
+        #[derive(Clone, Debug, PartialEq)]
+        enum Error { Unknown(String), Expected(&'static str) }
+
+        impl From<&'static str> for Error {
+            fn from(e: &'static str) -> Self { Error::Expected(e) }
+        }
+
+        // nested := "(" nested ")" | "x", referenced before it is defined
+        let rules = RuleSet::<&str, usize, Error>::new(|name| Error::Unknown(name.to_string()));
+        let nested = rules.rule("nested");
+        assert_eq!(nested.parse("x"), Err(("x", Error::Unknown("nested".to_string()))));
+
+        let inner = rules.rule("nested");
+        rules
+            .define("nested", move |input| {
+                let (rest, _) = "(".make_literal_matcher(Error::Expected("Expected (")).parse(input)?;
+                let (rest, depth) = inner.parse(rest)?;
+                let (rest, _) = ")".make_literal_matcher(Error::Expected("Expected )")).parse(rest)?;
+                Ok((rest, depth + 1))
+            })
+            .extend("nested", "x".make_literal_matcher("Expected x").map(|_| 0));
+        assert_eq!(nested.parse("((x))"), Ok(("", 2)));
+        assert_eq!(nested.parse("y"), Err(("y", Error::Expected("Expected x"))));
+        assert_eq!(rules.names(), vec!["nested"]);
+
+        // Replacing the rule drops both alternatives
+        rules.define("nested", "y".make_literal_matcher("Expected y").map(|_| 9));
+        assert_eq!(nested.parse("x"), Err(("x", Error::Expected("Expected y"))));
+        assert!(rules.remove("nested"));
+        assert!(!rules.contains("nested"));
+        assert_eq!(nested.name(), "nested");
+    }
+
+    #[test]
+    fn test_composition_errors() {
+        let mut a = GrammarModule::<&str, (), ()>::new("a");
+        a.rule("x", pure::<_, _, ()>(()));
+        a.reference("missing");
+        a.reference("b.y");
+        let mut b = GrammarModule::new("a");
+        b.rule("x", pure::<_, _, ()>(()));
+
+        let err = GrammarComposer::new().include(a).include(b).compose().err().unwrap();
+        assert_eq!(err.unresolved, vec!["a.missing", "b.y"]);
+        assert_eq!(err.duplicates, vec!["a.x"]);
+        assert_eq!(err.to_string(), "unresolved rules: a.missing, b.y; duplicate rules: a.x");
+    }
+}
@@ -0,0 +1,192 @@
+//! # Contextual Parsers
+//!
+//! Context-sensitive grammars need to remember what they parsed earlier, such as a length
+//! read from a header or the names declared so far. This module provides primitive
+//! stateful parsers that read and write the state of a `StateCarrier` without consuming
+//! input, like `get` and `put` of a state monad:
+//!
+//! - `get_state()` returns the current state
+//! - `put_state(s)` replaces the state with `s`
+//! - `modify_state(f)` replaces the state with `f` applied to it
+//!
+//! They work with any state. For user-defined state, wrap it in `Context`: a
+//! `StateCarrier<Context<T>, I>` parses like `I` and leaves the context unchanged, and plain
+//! parsers can be lifted into it with `Parser::lift`.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::context::*;
+//! use friss::parsers::WithState;
+//!
+//! // A digit giving the length of the following field
+//! let length = <&str as Parsable<&str>>::make_anything_matcher("Expected length")
+//!     .validate(|c: &char| c.is_ascii_digit(), "Expected length")
+//!     .lift::<Context<usize>>()
+//!     .bind_output(|digit| put_state(Context(digit.to_digit(10).unwrap() as usize)));
+//!
+//! let field = get_state().bind_output(|Context(n): Context<usize>| {
+//!     (move |input: &'static str| match input.get(..n) {
+//!         Some(field) => Ok((&input[n..], field)),
+//!         None => Err((input, "Field too short")),
+//!     })
+//!     .lift::<Context<usize>>()
+//! });
+//!
+//! let record = length.seq(field).map(|(_, field)| field).map_err(Foldable::fold);
+//! let (rest, field) = record.parse("3abcde".with_state(Context(0))).unwrap();
+//!
+//! assert_eq!(field, "abc");
+//! assert_eq!((rest.state, rest.input), (Context(3), "de"));
+//! ```
+
+use crate::core::{Parsable, Parser};
+use crate::state::{StateCarrier, StateUpdate, StatefulParser, StatefulResult};
+
+/// User-defined state for context-sensitive parsing.
+///
+/// Matchers on a `StateCarrier<Context<T>, I>` delegate to `I` and leave the context as it
+/// is; only the parsers of this module change it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Context<T>(pub T);
+
+/// Lifted parsers leave the context unchanged.
+impl<T, I> StateUpdate<I> for Context<T> {
+    fn update(&mut self, _before: &I, _after: &I) {}
+}
+
+/// Returns the current state without consuming input.
+pub fn get_state<State, Input, Error>() -> impl StatefulParser<State, Input, State, Error>
+where
+    State: Clone + Default,
+    Input: Parsable<Error> + Clone,
+    StateCarrier<State, Input>: Parsable<Error>,
+    Error: Clone,
+{
+    move |carrier: StateCarrier<State, Input>| {
+        let state = carrier.state.clone();
+        Ok((carrier, state))
+    }
+}
+
+/// Replaces the state with `state` without consuming input.
+pub fn put_state<State, Input, Error>(state: State) -> impl StatefulParser<State, Input, (), Error>
+where
+    State: Clone + Default,
+    Input: Parsable<Error> + Clone,
+    StateCarrier<State, Input>: Parsable<Error>,
+    Error: Clone,
+{
+    move |carrier: StateCarrier<State, Input>| Ok((StateCarrier::new(state.clone(), carrier.input), ()))
+}
+
+/// Replaces the state with `f` applied to it, without consuming input.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::context::*;
+/// use friss::parsers::WithState;
+///
+/// let declare = |name: &'static str| {
+///     name.make_literal_matcher("Expected name")
+///         .lift::<Context<Vec<&str>>>()
+///         .skip(modify_state(move |Context(mut names): Context<Vec<&str>>| {
+///             names.push(name);
+///             Context(names)
+///         }))
+/// };
+///
+/// let (rest, _) = declare("x").seq(declare("y")).parse("xy".with_state(Context(vec![]))).unwrap();
+/// assert_eq!(rest.state, Context(vec!["x", "y"]));
+/// ```
+pub fn modify_state<State, Input, Error, F>(f: F) -> impl StatefulParser<State, Input, (), Error>
+where
+    State: Default,
+    Input: Parsable<Error> + Clone,
+    StateCarrier<State, Input>: Parsable<Error>,
+    Error: Clone,
+    F: Fn(State) -> State,
+{
+    move |carrier: StateCarrier<State, Input>| Ok((StateCarrier::new(f(carrier.state), carrier.input), ()))
+}
+
+/// The context stays unchanged while parsing, so all matchers delegate to the inner input.
+impl<T, S, Error> Parsable<Error> for StateCarrier<Context<T>, S>
+where
+    T: Clone,
+    S: Parsable<Error>,
+    Error: Clone,
+{
+    type Item = S::Item;
+
+    fn make_literal_matcher(self, err: Error) -> impl Parser<Self, Self, Error> {
+        let literal = self.input.make_literal_matcher(err);
+        move |input: StateCarrier<Context<T>, S>| {
+            let state = input.state;
+            match literal.parse(input.input) {
+                Ok((rest, ret)) => Ok((StateCarrier::new(state.clone(), rest), StateCarrier::new(state, ret))),
+                Err((rest, err)) => Err((StateCarrier::new(state, rest), err)),
+            }
+        }
+    }
+
+    fn make_anything_matcher(err: Error) -> impl Parser<Self, Self::Item, Error> {
+        let anything = S::make_anything_matcher(err);
+        move |input: StateCarrier<Context<T>, S>| delegate(&anything, input)
+    }
+
+    fn make_item_matcher(character: Self::Item, err: Error) -> impl Parser<Self, Self::Item, Error> {
+        let item = S::make_item_matcher(character, err);
+        move |input: StateCarrier<Context<T>, S>| delegate(&item, input)
+    }
+
+    fn make_empty_matcher(err: Error) -> impl Parser<Self, (), Error> {
+        let empty = S::make_empty_matcher(err);
+        move |input: StateCarrier<Context<T>, S>| delegate(&empty, input)
+    }
+}
+
+fn delegate<T, S, O, E>(
+    parser: &impl Parser<S, O, E>,
+    input: StateCarrier<Context<T>, S>,
+) -> StatefulResult<Context<T>, S, O, E>
+where
+    S: Parsable<E>,
+    E: Clone,
+{
+    let state = input.state;
+    match parser.parse(input.input) {
+        Ok((rest, ret)) => Ok((StateCarrier::new(state, rest), ret)),
+        Err((rest, err)) => Err((StateCarrier::new(state, rest), err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{Offset, WithState};
+    use crate::*;
+
+    #[test]
+    fn test_state_primitives_do_not_consume() {
+        let start = "ab".with_state(Offset(5));
+
+        let (rest, state) = get_state::<_, _, &str>().parse(start).unwrap();
+        assert_eq!((rest, state), (start, Offset(5)));
+
+        let reset = put_state::<_, _, &str>(Offset(0)).seq_stateful("a".with_state(Offset(0)).make_literal_matcher("Expected a"));
+        let (rest, _) = reset.parse(start).unwrap();
+        assert_eq!((rest.state, rest.input), (Offset(1), "b"));
+
+        let double = modify_state::<_, _, &str, _>(|Offset(n)| Offset(n * 2));
+        assert_eq!(double.parse(start).unwrap().0.state, Offset(10));
+
+        // Matchers on a context carrier leave the context alone
+        let letter = <StateCarrier<Context<u8>, &str> as Parsable<&str>>::make_anything_matcher("Expected letter");
+        let (rest, c) = letter.parse("xy".with_state(Context(7))).unwrap();
+        assert_eq!((c, rest.state, rest.input), ('x', Context(7), "y"));
+    }
+}
@@ -6,6 +6,7 @@
 //! - `Parser`: Core trait for all parsers
 //! - `Parsable`: Trait for types that can be parsed
 //! - `ParsableItem`: Trait for individual items within parsable types
+//! - `Checkpoint`: Trait for saving and restoring positions of parsable types
 //!
 //! ## Example Usage
 //!
@@ -28,10 +29,12 @@
 //! assert_eq!(combined.parse("123"), Ok(("3", "12".to_string())));
 //! ```
 use crate::{
-    state::{ParserWithStateTransition, StateCarrier, StatefulParser},
+    scan::Scannable,
+    state::{ParserWithStateTransition, StateCarrier, StateUpdate, StatefulParser},
     types::*,
 };
-use std::{borrow::Borrow, cell::{Cell, RefCell}, marker::PhantomData, rc::Rc};
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{cell::RefCell, marker::PhantomData};
 
 /// Trait for items within a `Parsable` type.
 ///
@@ -56,7 +59,9 @@ pub trait ParsableItem<Parent: Parsable<Error>, Error: Clone>: Sized {
 
 /// Trait for types that can be parsed.
 ///
-/// This trait defines methods for creating basic parsers for a type.
+/// This trait defines methods for creating basic parsers for a type. Every parsable type is
+/// also a `Checkpoint`, so combinators can save a position with `checkpoint` and return to
+/// it with `rewind` when they backtrack.
 ///
 /// ## Example
 ///
@@ -75,7 +80,7 @@ pub trait ParsableItem<Parent: Parsable<Error>, Error: Clone>: Sized {
 /// assert_eq!(any_char.parse("abc"), Ok(("bc", 'a')));
 /// assert_eq!(any_char.parse(""), Err(("", "Expected any character")));
 /// ```
-pub trait Parsable<Error: Clone>: Sized + Clone {
+pub trait Parsable<Error: Clone>: Sized + Clone + Checkpoint {
     /// The type of individual items within this parsable type.
     type Item;
 
@@ -91,6 +96,85 @@ pub trait Parsable<Error: Clone>: Sized + Clone {
 
     /// Creates a parser that matches empty input.
     fn make_empty_matcher(err: Error) -> impl Parser<Self, (), Error>;
+
+    /// Creates a parser that matches one item satisfying `predicate`.
+    ///
+    /// Unlike `make_anything_matcher` followed by `validate`, a rejected item fails at its
+    /// own position with the input, and any state, as it was before the item. Negate the
+    /// predicate to match any item but some.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::parsers::{Position, WithState};
+    /// use friss::state::StateCarrier;
+    ///
+    /// let not_quote = <&str as Parsable<&str>>::make_predicate_matcher(|c| *c != '"', "Unexpected quote");
+    /// assert_eq!(not_quote.many().parse("ab\"c"), Ok(("\"c", vec!['a', 'b'])));
+    ///
+    /// let high = <&[u8] as Parsable<()>>::make_predicate_matcher(|b| **b >= 0x80, ());
+    /// assert_eq!(high.parse(&[0xff, 0x01]), Ok((&[0x01][..], &0xff)));
+    ///
+    /// let digit = <StateCarrier<Position, &str> as Parsable<&str>>::make_predicate_matcher(
+    ///     |c| c.is_ascii_digit(),
+    ///     "Expected digit",
+    /// );
+    /// let input = "x1".with_state(Position::default());
+    /// let (rest, err) = digit.parse(input).unwrap_err();
+    /// assert_eq!((rest, err), (input, "Expected digit"));
+    /// ```
+    fn make_predicate_matcher(
+        predicate: impl Fn(&Self::Item) -> bool,
+        err: Error,
+    ) -> impl Parser<Self, Self::Item, Error> {
+        let anything = Self::make_anything_matcher(err.clone());
+        move |input: Self| {
+            let checkpoint = input.checkpoint();
+            match anything.parse(input) {
+                Ok((rest, item)) if predicate(&item) => Ok((rest, item)),
+                Ok(_) => Err((Self::rewind(checkpoint), err.clone())),
+                Err(failure) => Err(failure),
+            }
+        }
+    }
+}
+
+/// Trait for saving a position of an input and returning to it later.
+///
+/// Combinators that may have to undo a failed parse, such as `backtrack`, `validate` or
+/// `sep_by`, take a checkpoint before running their parser instead of cloning the whole
+/// input. A checkpoint holds just enough to rebuild the input: the input itself for slices,
+/// the position for inputs sharing a buffer, and the state along with the position for
+/// `StateCarrier`s.
+///
+/// Failed attempts are transactional: `alt` and `or` run every branch from the same
+/// checkpoint, and `maybe`, `many` and its variants rewind an attempt that failed. State
+/// changes made by a failed branch are therefore never seen by the next branch or the
+/// following parsers.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::{Position, WithState};
+///
+/// let input = "ab".with_state(Position::default());
+/// let checkpoint = input.checkpoint();
+/// let (rest, _) = 'a'.make_character_matcher("Expected a").parse(input).unwrap();
+///
+/// assert_eq!(rest.state, Position::new(0, 1));
+/// assert_eq!(StateCarrier::rewind(checkpoint), input);
+/// ```
+pub trait Checkpoint: Sized {
+    /// The saved position.
+    type Checkpoint: Clone;
+
+    /// Saves the current position.
+    fn checkpoint(&self) -> Self::Checkpoint;
+
+    /// Returns to a saved position.
+    fn rewind(checkpoint: Self::Checkpoint) -> Self;
 }
 
 /// Marker trait for parser outputs.
@@ -134,6 +218,117 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
     /// ```
     fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)>;
 
+    /// Parses the whole input and returns only the output.
+    ///
+    /// Leftover input is reported as `FrissErrorKind::TrailingInput`. Errors carry the offset
+    /// of the failure and, for text input, its line and column.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::parsers::Position;
+    ///
+    /// let parser = "ab".make_literal_matcher("Expected ab").many();
+    ///
+    /// assert_eq!(parser.parse_complete("abab"), Ok(vec!["ab", "ab"]));
+    ///
+    /// let err = parser.parse_complete("abab x").unwrap_err();
+    /// assert_eq!(err.kind, FrissErrorKind::TrailingInput);
+    /// assert_eq!(err.offset, 4);
+    /// assert_eq!(err.position, Some(Position::new(0, 4)));
+    /// assert_eq!(err.to_string(), "0:4: unexpected trailing input");
+    /// ```
+    fn parse_complete(&self, input: Input) -> Result<Output, FrissError<Error>>
+    where
+        Input: Locate,
+    {
+        let original = input.clone();
+        let (rest, kind) = match self.parse(input) {
+            Ok((rest, output)) if rest.remaining() == 0 => return Ok(output),
+            Ok((rest, _)) => (rest, FrissErrorKind::TrailingInput),
+            Err((rest, err)) => (rest, FrissErrorKind::Parse(err)),
+        };
+        Err(FrissError {
+            kind,
+            offset: original.remaining().saturating_sub(rest.remaining()),
+            position: rest.position_in(&original),
+        })
+    }
+
+    /// Runs the parser, turning a panic inside it into a `Panicked` error at the start of the
+    /// input. Parser errors are returned as `Either::Left`, panics as `Either::Right`.
+    ///
+    /// This is a stopgap for parsers that are not panic-free yet, such as user closures that
+    /// index into the input. The panic hook still runs, so the message is also printed.
+    /// Requires the `std` feature.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let first_byte = |input: &'static str| -> Result<(&str, u8), (&str, &str)> {
+    ///     Ok((&input[1..], input.as_bytes()[0]))
+    /// };
+    ///
+    /// assert_eq!(first_byte.parse_catch("ab"), Ok(("b", b'a')));
+    /// let (rest, err) = first_byte.parse_catch("").unwrap_err();
+    /// assert_eq!(rest, "");
+    /// assert!(matches!(err, Either::Right(Panicked { message: Some(_) })));
+    /// ```
+    #[cfg(feature = "std")]
+    fn parse_catch(&self, input: Input) -> Result<(Input, Output), (Input, Either<Error, Panicked>)> {
+        let original = input.clone();
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.parse(input))) {
+            Ok(Ok(success)) => Ok(success),
+            Ok(Err((rest, err))) => Err((rest, Either::Left(err))),
+            Err(payload) => {
+                let message = match payload.downcast::<String>() {
+                    Ok(message) => Some(*message),
+                    Err(payload) => payload.downcast_ref::<&str>().map(|message| message.to_string()),
+                };
+                Err((original, Either::Right(Panicked { message })))
+            }
+        }
+    }
+
+    /// Applies the parser repeatedly and lazily, yielding one output at a time.
+    ///
+    /// Unlike `many`, no `Vec` is built, so large inputs of records can be processed one
+    /// record at a time. Iteration stops at the first failure, which is rewound like in
+    /// `many`, or after a match that consumed no input. `ParseIter::finish` returns the
+    /// remaining input and the error that stopped the iteration.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let digit = <&str as Parsable<&str>>::make_anything_matcher("Expected digit")
+    ///     .validate(|c: &char| c.is_ascii_digit(), "Expected digit");
+    /// let record = digit.skip(";".make_literal_matcher("Expected ;"));
+    ///
+    /// let mut records = record.iter("1;2;3;x");
+    /// let sum: u32 = records.by_ref().map(|d| d.to_digit(10).unwrap()).sum();
+    ///
+    /// assert_eq!(sum, 6);
+    /// assert_eq!(records.finish(), ("x", Some("Expected digit")));
+    /// ```
+    fn iter(&self, input: Input) -> ParseIter<'_, Self, Input, Output, Error>
+    where
+        Self: Sized,
+        Input: PartialEq,
+    {
+        ParseIter {
+            parser: self,
+            rest: input,
+            error: None,
+            done: false,
+            _phantom: PhantomData,
+        }
+    }
+
     
     /// Creates a stateful parser by adding state transition handling.
     ///
@@ -190,6 +385,44 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         ParserWithStateTransition::new_with_success_and_fail(self, succes, fail)
     }
 
+    /// Runs this plain parser inside a `StateCarrier`, updating the state from the input it
+    /// consumed through `StateUpdate`. The state is also updated when the parser fails, up to
+    /// the position of the failure.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::parsers::*;
+    ///
+    /// let word = "hello\nworld".make_literal_matcher("Expected greeting").lift::<Position>();
+    /// let (rest, _) = word.parse("hello\nworld!".with_state(Position::default())).unwrap();
+    ///
+    /// assert_eq!(rest.state, Position::new(1, 5));
+    /// assert_eq!(rest.input, "!");
+    /// ```
+    fn lift<State>(self) -> impl StatefulParser<State, Input, Output, Error>
+    where
+        State: Default + StateUpdate<Input>,
+        Input: Clone,
+        StateCarrier<State, Input>: Parsable<Error>,
+        Self: Sized,
+    {
+        move |carrier: StateCarrier<State, Input>| {
+            let StateCarrier { mut state, input } = carrier;
+            match self.parse(input.clone()) {
+                Ok((rest, output)) => {
+                    state.update(&input, &rest);
+                    Ok((StateCarrier { state, input: rest }, output))
+                }
+                Err((rest, error)) => {
+                    state.update(&input, &rest);
+                    Err((StateCarrier { state, input: rest }, error))
+                }
+            }
+        }
+    }
+
     /*fn with_state_transition2<State,StateF>(self,succes:StateF)-> impl StatefulParser<State,Input,Output,Error>
     where
         Input : Clone,
@@ -224,90 +457,479 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
     for<'a> FailF: FnMut(State, Input, Error, Input) -> (State, Input, Error) + 'a,
     StateCarrier<State, Input>: Parsable<Error>,
     {
-
-        TransitionParser::new_with_success_and_fail(self,|state,rest,error,_orig| (state,rest,error),fail)
+
+        TransitionParser::new_with_success_and_fail(self,|state,rest,error,_orig| (state,rest,error),fail)
+    }
+    */
+
+    /// Validates the output of the parser with a predicate.
+    ///
+    /// Returns an error if the predicate returns false. The error is reported at a
+    /// checkpoint taken before the parser ran, so a stateful input is rewound to its
+    /// starting state instead of being cloned up front.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// // Parse a digit and validate it's even
+    /// let digit = <&str as Parsable<&str>>::make_anything_matcher("Expected digit")
+    ///     .validate(|c| c.is_digit(10) && c.to_digit(10).unwrap() % 2 == 0, "Expected even digit");
+    ///
+    /// assert_eq!(digit.parse("2"), Ok(("", '2')));
+    /// assert_eq!(digit.parse("3"), Err(("3", "Expected even digit")));
+    /// ```
+    fn validate<Pred>(self, predicate: Pred, err: Error) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Error: Clone,
+        Pred: Fn(&Output) -> bool,
+    {
+        move |input: Input| {
+            let checkpoint = input.checkpoint();
+            let (rest, result) = self.parse(input)?;
+            if predicate(&result) {
+                Ok((rest, result))
+            } else {
+                Err((Input::rewind(checkpoint), err.clone()))
+            }
+        }
+    }
+
+    /// Validates and transforms the output of the parser in one step.
+    ///
+    /// Returns an error, rewound like `validate`, if `f` returns `None`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let digit = <&str as Parsable<&str>>::make_anything_matcher("Expected digit")
+    ///     .validate_map(|c| c.to_digit(10), "Expected digit");
+    ///
+    /// assert_eq!(digit.parse("7a"), Ok(("a", 7)));
+    /// assert_eq!(digit.parse("a7"), Err(("a7", "Expected digit")));
+    /// ```
+    fn validate_map<Out2, Fun>(self, f: Fun, err: Error) -> impl Parser<Input, Out2, Error>
+    where
+        Self: Sized,
+        Error: Clone,
+        Fun: Fn(Output) -> Option<Out2>,
+    {
+        move |input: Input| {
+            let checkpoint = input.checkpoint();
+            let (rest, result) = self.parse(input)?;
+            match f(result) {
+                Some(out) => Ok((rest, out)),
+                None => Err((Input::rewind(checkpoint), err.clone())),
+            }
+        }
+    }
+
+    /// Maps the output of the parser with a fallible function.
+    ///
+    /// If `f` fails, its error is converted with `err_fn` and reported at the input the
+    /// parser started on, rewound like `validate`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let digits = '9'.make_character_matcher("Expected digit").many();
+    /// let byte = digits.try_map(
+    ///     |ds| ds.into_iter().collect::<String>().parse::<u8>(),
+    ///     |_| "Number out of range",
+    /// );
+    ///
+    /// assert_eq!(byte.parse("99;"), Ok((";", 99)));
+    /// assert_eq!(byte.parse("999;"), Err(("999;", "Number out of range")));
+    /// ```
+    fn try_map<Out2, Err2, Fun, ErrFun>(self, f: Fun, err_fn: ErrFun) -> impl Parser<Input, Out2, Error>
+    where
+        Self: Sized,
+        Fun: Fn(Output) -> Result<Out2, Err2>,
+        ErrFun: Fn(Err2) -> Error,
+    {
+        move |input: Input| {
+            let checkpoint = input.checkpoint();
+            let (rest, result) = self.parse(input)?;
+            match f(result) {
+                Ok(out) => Ok((rest, out)),
+                Err(e) => Err((Input::rewind(checkpoint), err_fn(e))),
+            }
+        }
+    }
+
+    /// Maps the output of the parser with a function.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let parser = "123".make_literal_matcher("Expected 123")
+    ///     .map(|s| s.parse::<i32>().unwrap());
+    ///
+    /// assert_eq!(parser.parse("123"), Ok(("", 123)));
+    /// ```
+    fn map<Out2, Fun>(self, f: Fun) -> impl Parser<Input, Out2, Error>
+    where
+        Fun: Fn(Output) -> Out2,
+        Self: Sized,
+    {
+        move |input: Input| match self.parse(input) {
+            Ok((rest, ret)) => Ok((rest, f(ret))),
+            Err((rest, ret)) => Err((rest, ret)),
+        }
+    }
+
+    /// Replaces the output of the parser with a fixed value.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq)]
+    /// enum Keyword { If, Else }
+    ///
+    /// let keyword = "if".make_literal_matcher("Expected keyword").value(Keyword::If);
+    ///
+    /// assert_eq!(keyword.parse("if x"), Ok((" x", Keyword::If)));
+    /// ```
+    fn value<Out2>(self, value: Out2) -> impl Parser<Input, Out2, Error>
+    where
+        Out2: Clone,
+        Self: Sized,
+    {
+        move |input: Input| self.parse(input).map(|(rest, _)| (rest, value.clone()))
+    }
+
+    /// Discards the output of the parser.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let comma = ",".make_literal_matcher("Expected comma").ignore();
+    ///
+    /// assert_eq!(comma.parse(", x"), Ok((" x", ())));
+    /// ```
+    fn ignore(self) -> impl Parser<Input, (), Error>
+    where
+        Self: Sized,
+    {
+        move |input: Input| self.parse(input).map(|(rest, _)| (rest, ()))
+    }
+
+    /// Calls `f` with a reference to the output of each successful parse, leaving the
+    /// result unchanged. Useful for logging and metrics.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use std::cell::RefCell;
+    ///
+    /// let seen = RefCell::new(Vec::new());
+    /// let word = "hello".make_literal_matcher("Expected hello")
+    ///     .inspect(|word| seen.borrow_mut().push(word.len()));
+    ///
+    /// assert_eq!(word.parse("hello!"), Ok(("!", "hello")));
+    /// assert_eq!(word.parse("bye"), Err(("bye", "Expected hello")));
+    /// assert_eq!(*seen.borrow(), vec![5]);
+    /// ```
+    fn inspect<Fun>(self, f: Fun) -> impl Parser<Input, Output, Error>
+    where
+        Fun: Fn(&Output),
+        Self: Sized,
+    {
+        move |input: Input| {
+            let result = self.parse(input);
+            if let Ok((_, ret)) = &result {
+                f(ret);
+            }
+            result
+        }
+    }
+
+    /// Boxes the parser, erasing its type.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut rules: HashMap<&str, BoxedParser<&str, &str, &str>> = HashMap::new();
+    /// rules.insert("a", "a".make_literal_matcher("Expected a").boxed());
+    /// rules.insert("ab", "a".make_literal_matcher("Expected a").skip("b".make_literal_matcher("Expected b")).boxed());
+    ///
+    /// assert_eq!(rules["ab"].parse("abc"), Ok(("c", "a")));
+    /// ```
+    fn boxed<'a>(self) -> BoxedParser<'a, Input, Output, Error>
+    where
+        Self: Sized + 'a,
+    {
+        BoxedParser::new(self)
+    }
+
+    /// Borrows the parser, so that combinators can use it without taking ownership.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let x = 'x'.make_character_matcher("Expected x");
+    /// let pair = x.by_ref().seq(x.by_ref());
+    /// let run = x.by_ref().many();
+    ///
+    /// assert_eq!(pair.parse("xxy"), Ok(("y", ('x', 'x'))));
+    /// assert_eq!(run.parse("xxxy"), Ok(("y", vec!['x', 'x', 'x'])));
+    /// assert_eq!(x.parse("x"), Ok(("", 'x')));
+    /// ```
+    fn by_ref(&self) -> impl Parser<Input, Output, Error> + '_
+    where
+        Self: Sized,
+    {
+        move |input: Input| self.parse(input)
+    }
+
+    /// Moves the parser behind a reference-counted handle that is cheap to clone.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// // sum := term ('+' term)* ; term := digit | '(' sum ')'
+    /// let sum: Box<dyn Parser<&'static str, u32, &'static str>> = recursive(|sum| {
+    ///     let digit = make_char_predicate_matcher(|c| c.is_ascii_digit(), "Expected digit")
+    ///         .map(|c| c.to_digit(10).unwrap_or(0));
+    ///     let term = (move |input: &'static str| match '('.make_character_matcher("Expected (").parse(input) {
+    ///         Ok((rest, _)) => {
+    ///             let (rest, value) = sum.parse(rest)?;
+    ///             let (rest, _) = ')'.make_character_matcher("Expected )").parse(rest)?;
+    ///             Ok((rest, value))
+    ///         }
+    ///         Err(_) => digit.parse(input),
+    ///     })
+    ///     .shared();
+    ///     let first = term.clone();
+    ///     let more = term.preceded_by('+'.make_character_matcher("Expected +")).map_err(|err| err.fold()).many();
+    ///     Box::new(first.seq(more).map(|(value, rest)| value + rest.iter().sum::<u32>()).map_err(|err| err.fold()))
+    /// });
+    ///
+    /// assert_eq!(sum.parse("1+(2+3)+4"), Ok(("", 10)));
+    /// ```
+    fn shared(self) -> SharedParser<Self>
+    where
+        Self: Sized,
+    {
+        SharedParser {
+            parser: alloc::rc::Rc::new(self),
+        }
+    }
+
+    /// Moves the parser behind an atomically reference-counted handle that is cheap to clone
+    /// and can be sent to other threads if the parser is `Send + Sync`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let word = "go".make_literal_matcher("Expected go").shared_sync();
+    /// let worker = word.clone();
+    /// let words = std::thread::spawn(move || worker.many().parse("gogo!")).join().unwrap();
+    ///
+    /// assert_eq!(words, Ok(("!", vec!["go", "go"])));
+    /// assert_eq!(word.parse("no"), Err(("no", "Expected go")));
+    /// ```
+    fn shared_sync(self) -> SyncSharedParser<Self>
+    where
+        Self: Sized,
+    {
+        SyncSharedParser {
+            parser: alloc::sync::Arc::new(self),
+        }
+    }
+
+    /// Maps the error of the parser with a function.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let parser = "hello".make_literal_matcher("simple error")
+    ///     .map_err(|_| "detailed error message");
+    ///
+    /// assert_eq!(parser.parse("world"), Err(("world", "detailed error message")));
+    /// ```
+    fn map_err<Err2, Fun>(self, f: Fun) -> impl Parser<Input, Output, Err2>
+    where
+        Fun: Fn(Error) -> Err2,
+        Input: Parsable<Err2>,
+        Err2: Clone,
+        Self: Sized,
+    {
+        move |input: Input| match self.parse(input) {
+            Ok((rest, ret)) => Ok((rest, ret)),
+            Err((rest, ret)) => Err((rest, f(ret))),
+        }
+    }
+
+    /// Calls `f` with a reference to the error of each failed parse, leaving the result
+    /// unchanged. Useful for logging and metrics.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use std::cell::Cell;
+    ///
+    /// let failures = Cell::new(0);
+    /// let word = "hello".make_literal_matcher("Expected hello")
+    ///     .inspect_err(|_| failures.set(failures.get() + 1));
+    ///
+    /// assert_eq!(word.parse("hello"), Ok(("", "hello")));
+    /// assert_eq!(word.parse("bye"), Err(("bye", "Expected hello")));
+    /// assert_eq!(failures.get(), 1);
+    /// ```
+    fn inspect_err<Fun>(self, f: Fun) -> impl Parser<Input, Output, Error>
+    where
+        Fun: Fn(&Error),
+        Self: Sized,
+    {
+        move |input: Input| {
+            let result = self.parse(input);
+            if let Err((_, err)) = &result {
+                f(err);
+            }
+            result
+        }
+    }
+
+    /// Attaches to each error where it was reported, counted from where the parser started,
+    /// see `ErrorAt`. The remaining input of a failure only says how much is left; this says
+    /// how far the parser got, e.g. into which element of a `seq`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::parsers::Position;
+    ///
+    /// let pair = ("let".make_literal_matcher("Expected let"), " x".make_literal_matcher("Expected name"))
+    ///     .seq()
+    ///     .map_err(|err| err.fold())
+    ///     .located();
+    ///
+    /// let (rest, err) = pair.parse("let y").unwrap_err();
+    /// assert_eq!(rest, " y");
+    /// assert_eq!((err.error, err.offset, err.position), ("Expected name", 3, Some(Position::new(0, 3))));
+    /// assert_eq!(err.to_string(), "0:3: Expected name");
+    /// ```
+    fn located(self) -> impl Parser<Input, Output, ErrorAt<Error>>
+    where
+        Input: Parsable<ErrorAt<Error>> + Locate,
+        Self: Sized,
+    {
+        move |input: Input| {
+            let original = input.clone();
+            self.parse(input).map_err(|(rest, error)| {
+                let located = ErrorAt {
+                    error,
+                    offset: original.remaining().saturating_sub(rest.remaining()),
+                    position: rest.position_in(&original),
+                };
+                (rest, located)
+            })
+        }
     }
-    */
 
-    /// Validates the output of the parser with a predicate.
-    ///
-    /// Returns an error if the predicate returns false.
+    /// Flattens the nested error of chained `seq` or `alt` calls into one flat type, see
+    /// `ErrorFold`. `N` is the number of errors in the flat type.
     ///
     /// ## Example
     ///
     /// ```rust
     /// use friss::*;
     ///
-    /// // Parse a digit and validate it's even
-    /// let digit = <&str as Parsable<&str>>::make_anything_matcher("Expected digit")
-    ///     .validate(|c| c.is_digit(10) && c.to_digit(10).unwrap() % 2 == 0, "Expected even digit");
+    /// let lit = |s: &'static str| s.make_literal_matcher(s);
+    /// let parser = lit("a").seq(lit("b")).seq(lit("c")).normalize_err::<3>();
     ///
-    /// assert_eq!(digit.parse("2"), Ok(("", '2')));
-    /// assert_eq!(digit.parse("3"), Err(("3", "Expected even digit")));
+    /// assert_eq!(parser.parse("abx"), Err(("x", Either3::Right("c"))));
+    ///
+    /// let parser = lit("a").alt(lit("b")).alt(lit("c")).normalize_err::<3>();
+    /// assert_eq!(parser.parse("x"), Err(("x", ("a", "b", "c"))));
     /// ```
-    fn validate<Pred>(self, predicate: Pred, err: Error) -> impl Parser<Input, Output, Error>
+    fn normalize_err<const N: usize>(self) -> impl Parser<Input, Output, <Error as ErrorFold<N>>::Flat>
     where
+        Error: ErrorFold<N>,
+        <Error as ErrorFold<N>>::Flat: Clone,
+        Input: Parsable<<Error as ErrorFold<N>>::Flat>,
         Self: Sized,
-        Input: Clone,
-        Error: Clone,
-        Pred: Fn(&Output) -> bool,
     {
-        move |input: Input| {
-            let ipt = input.clone();
-            let (rest, result) = self.parse(ipt)?;
-            if predicate(&result) {
-                Ok((rest, result))
-            } else {
-                Err((input, err.clone()))
-            }
-        }
+        self.map_err(ErrorFold::<N>::normalize)
     }
 
-    /// Maps the output of the parser with a function.
+    /// Names the parser, wrapping its error into a `Labelled` error carrying the name.
     ///
     /// ## Example
     ///
     /// ```rust
     /// use friss::*;
     ///
-    /// let parser = "123".make_literal_matcher("Expected 123")
-    ///     .map(|s| s.parse::<i32>().unwrap());
+    /// let parser = "true".make_literal_matcher("Expected true").label("boolean");
     ///
-    /// assert_eq!(parser.parse("123"), Ok(("", 123)));
+    /// assert_eq!(
+    ///     parser.parse("yes"),
+    ///     Err(("yes", Labelled { error: "Expected true", trace: vec!["boolean"] }))
+    /// );
     /// ```
-    fn map<Out2, Fun>(self, f: Fun) -> impl Parser<Input, Out2, Error>
+    fn label(self, name: &'static str) -> impl Parser<Input, Output, Labelled<Error>>
     where
-        Fun: Fn(Output) -> Out2,
         Self: Sized,
+        Input: Parsable<Labelled<Error>>,
     {
         move |input: Input| match self.parse(input) {
-            Ok((rest, ret)) => Ok((rest, f(ret))),
-            Err((rest, ret)) => Err((rest, ret)),
+            Ok((rest, ret)) => Ok((rest, ret)),
+            Err((rest, err)) => Err((rest, Labelled::new(err, name))),
         }
     }
 
-    /// Maps the error of the parser with a function.
+    /// Adds an enclosing rule name to the trace of an already labelled error.
     ///
     /// ## Example
     ///
     /// ```rust
     /// use friss::*;
     ///
-    /// let parser = "hello".make_literal_matcher("simple error")
-    ///     .map_err(|_| "detailed error message");
+    /// let element = "1".make_literal_matcher("Expected 1").label("array element");
+    /// let value = element.context("array").context("value");
     ///
-    /// assert_eq!(parser.parse("world"), Err(("world", "detailed error message")));
+    /// let (_, err) = value.parse("2").unwrap_err();
+    /// assert_eq!(err.trace, vec!["array element", "array", "value"]);
+    /// assert_eq!(err.to_string(), "Expected 1\n  while parsing array element\n  while parsing array\n  while parsing value");
     /// ```
-    fn map_err<Err2, Fun>(self, f: Fun) -> impl Parser<Input, Output, Err2>
+    fn context(self, name: &'static str) -> impl Parser<Input, Output, Error>
     where
-        Fun: Fn(Error) -> Err2,
-        Input: Parsable<Err2>,
-        Err2: Clone,
         Self: Sized,
+        Error: LabelStack,
     {
         move |input: Input| match self.parse(input) {
             Ok((rest, ret)) => Ok((rest, ret)),
-            Err((rest, ret)) => Err((rest, f(ret))),
+            Err((rest, mut err)) => {
+                err.push_label(name);
+                Err((rest, err))
+            }
         }
     }
 
@@ -325,7 +947,8 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
     /// let count_parser = digit.bind_output(|c| {
     ///     let count = c.to_digit(10).unwrap() as usize;
     ///     "a".make_literal_matcher("Expected 'a'")
-    ///         .exactly_n::<3>("Need exactly 3 'a's")
+    ///         .exactly_n::<3>()
+    ///         .map_err(|err| err.error)
     /// });
     ///
     /// // assert_eq!(count_parser.parse("3aaa"), Ok(("", Box::new(["a", "a", "a"]))));
@@ -497,7 +1120,8 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
                 Either::Right(l) => l,
             })
     }
-    /// Tries this parser, and if it fails, tries the alternative parser.
+    /// Tries this parser, and if it fails, tries the alternative parser on the original
+    /// input, rewound to a checkpoint taken before this parser ran.
     ///
     /// ## Example
     ///
@@ -520,16 +1144,159 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         Error2: Clone,
         Input: Parsable<Error2> + Parsable<(Error, Error2)>,
     {
-        move |input: Input| match self.parse(input) {
+        move |input: Input| {
+            let checkpoint = input.checkpoint();
+            match self.parse(input) {
+                Ok((rest, ret)) => Ok((rest, Either::Left(ret))),
+                Err((_, e1)) => match p.parse(Input::rewind(checkpoint)) {
+                    Ok((rest, ret)) => Ok((rest, Either::Right(ret))),
+                    Err((rest, e2)) => Err((rest, (e1, e2))),
+                },
+            }
+        }
+    }
+
+    /// Ordered choice preferring this parser, the same as `alt`. Spelled out next to
+    /// `alt_last` and `alt_longest` when the policy matters to the reader.
+    fn alt_first<Output2, Error2>(
+        self,
+        p: impl Parser<Input, Output2, Error2>,
+    ) -> impl AltParser<Input, Output, Output2, Error, Error2>
+    where
+        Self: Sized,
+        Error2: Clone,
+        Input: Parsable<Error2> + Parsable<(Error, Error2)>,
+    {
+        self.alt(p)
+    }
+
+    /// Ordered choice preferring the alternative: tries it first, and this parser only if
+    /// it fails, so the alternative wins when both would succeed. Useful when later rules
+    /// override earlier ones, such as user configuration over defaults.
+    ///
+    /// If both fail, the errors are reported at the start of the input, in the same order
+    /// as for `alt`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let builtin = "tab".make_literal_matcher("Expected tab").value("\t");
+    /// let user = "tab".make_literal_matcher("Expected tab").value("    ");
+    /// let indent = builtin.alt_last(user).map(|either| either.fold());
+    ///
+    /// assert_eq!(indent.parse("tab"), Ok(("", "    ")));
+    /// assert_eq!(indent.parse("space"), Err(("space", ("Expected tab", "Expected tab"))));
+    /// ```
+    fn alt_last<Output2, Error2>(
+        self,
+        p: impl Parser<Input, Output2, Error2>,
+    ) -> impl AltParser<Input, Output, Output2, Error, Error2>
+    where
+        Self: Sized,
+        Error2: Clone,
+        Input: Parsable<Error2> + Parsable<(Error, Error2)>,
+    {
+        move |input: Input| {
+            let checkpoint = input.checkpoint();
+            match p.parse(input) {
+                Ok((rest, ret)) => Ok((rest, Either::Right(ret))),
+                Err((_, e2)) => match self.parse(Input::rewind(checkpoint.clone())) {
+                    Ok((rest, ret)) => Ok((rest, Either::Left(ret))),
+                    Err((_, e1)) => Err((Input::rewind(checkpoint), (e1, e2))),
+                },
+            }
+        }
+    }
+
+    /// Tries this parser and the alternative on the same input and commits to the one that
+    /// consumes more. On a tie this parser wins.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let less = "<".make_literal_matcher("Expected <");
+    /// let less_equal = "<=".make_literal_matcher("Expected <=");
+    /// let operator = less.alt_longest(less_equal);
+    ///
+    /// assert_eq!(operator.parse("<= 1"), Ok((" 1", Either::Right("<="))));
+    /// assert_eq!(operator.parse("< 1"), Ok((" 1", Either::Left("<"))));
+    /// assert_eq!(operator.parse("> 1"), Err(("> 1", ("Expected <", "Expected <="))));
+    /// ```
+    fn alt_longest<Output2, Error2>(
+        self,
+        p: impl Parser<Input, Output2, Error2>,
+    ) -> impl Parser<Input, Either<Output, Output2>, (Error, Error2)>
+    where
+        Self: Sized,
+        Error2: Clone,
+        Input: Parsable<Error2> + Parsable<(Error, Error2)> + Locate,
+    {
+        move |input: Input| match (self.parse(input.clone()), p.parse(input.clone())) {
+            (Ok((rest1, ret1)), Ok((rest2, ret2))) => {
+                if rest2.remaining() < rest1.remaining() {
+                    Ok((rest2, Either::Right(ret2)))
+                } else {
+                    Ok((rest1, Either::Left(ret1)))
+                }
+            }
+            (Ok((rest, ret)), Err(_)) => Ok((rest, Either::Left(ret))),
+            (Err(_), Ok((rest, ret))) => Ok((rest, Either::Right(ret))),
+            (Err((_, e1)), Err((_, e2))) => Err((input, (e1, e2))),
+        }
+    }
+
+    /// Tries this parser and the alternative on the same input, like `alt`. If both fail,
+    /// reports the error of the one that got further into the input, at the position where
+    /// it failed; the other error is kept in the notes. On a tie this parser's error wins.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let lit = |s: &'static str| s.make_literal_matcher(s);
+    /// let call = lit("f").seq(lit("(")).seq(lit(")"));
+    /// let index = lit("f").seq(lit("[")).seq(lit("]"));
+    /// let parser = call.alt_farthest(index);
+    ///
+    /// let (rest, err) = parser.parse("f[x").unwrap_err();
+    /// assert_eq!(rest, "x");
+    /// assert_eq!(err.error, Either::Right(Either::Right("]")));
+    /// assert_eq!(err.notes.0, (Either::Left(Either::Right("(")), 2));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    fn alt_farthest<Output2, Error2>(
+        self,
+        p: impl Parser<Input, Output2, Error2>,
+    ) -> impl Parser<Input, Either<Output, Output2>, Farthest<Either<Error, Error2>, ((Error, usize), (Error2, usize))>>
+    where
+        Self: Sized,
+        Error2: Clone,
+        Input: Parsable<Error2> + Parsable<Farthest<Either<Error, Error2>, ((Error, usize), (Error2, usize))>> + Locate,
+    {
+        move |input: Input| match self.parse(input.clone()) {
             Ok((rest, ret)) => Ok((rest, Either::Left(ret))),
-            Err((rest, e1)) => match p.parse(rest) {
+            Err((rest1, e1)) => match p.parse(input) {
                 Ok((rest, ret)) => Ok((rest, Either::Right(ret))),
-                Err((rest, e2)) => Err((rest, (e1, e2))),
+                Err((rest2, e2)) => {
+                    let (remaining1, remaining2) = (rest1.remaining(), rest2.remaining());
+                    let notes = ((e1.clone(), remaining1), (e2.clone(), remaining2));
+                    if remaining2 < remaining1 {
+                        Err((rest2, Farthest { error: Either::Right(e2), remaining: remaining2, notes }))
+                    } else {
+                        Err((rest1, Farthest { error: Either::Left(e1), remaining: remaining1, notes }))
+                    }
+                }
             },
         }
     }
 
-    /// Makes the parser optional, always succeeding with None if the parser fails.
+    /// Makes the parser optional, always succeeding with None if the parser fails. On
+    /// failure the input is rewound to where the parser started.
     ///
     /// ## Example
     ///
@@ -545,55 +1312,259 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
     where
         Self: Sized,
     {
-        move |input: Input| match self.parse(input) {
-            Ok((rest, ret)) => Ok((rest, Some(ret))),
-            Err((rest, _e1)) => Ok((rest, None)),
+        move |input: Input| {
+            let checkpoint = input.checkpoint();
+            match self.parse(input) {
+                Ok((rest, ret)) => Ok((rest, Some(ret))),
+                Err(_) => Ok((Input::rewind(checkpoint), None)),
+            }
+        }
+    }
+
+    /// Makes the parser optional like `maybe`, yielding `Output::default()` when it fails.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let digits = '0'.make_character_matcher("Expected 0").many().map(|zeros| zeros.len());
+    /// let padding = "pad".make_literal_matcher("Expected pad").seq(digits).map(|(_, n)| n).or_default();
+    ///
+    /// assert_eq!(padding.parse("pad00;"), Ok((";", 2)));
+    /// assert_eq!(padding.parse("pa;"), Ok(("pa;", 0)));
+    /// ```
+    fn or_default(self) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Output: Default,
+    {
+        self.maybe().map(Option::unwrap_or_default)
+    }
+
+    /// Makes the parser optional like `maybe`, yielding a clone of `value` when it fails.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let sign = '-'.make_character_matcher("Expected -").value(-1).or_value(1);
+    ///
+    /// assert_eq!(sign.parse("-5"), Ok(("5", -1)));
+    /// assert_eq!(sign.parse("5"), Ok(("5", 1)));
+    /// ```
+    fn or_value(self, value: Output) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Output: Clone,
+    {
+        self.maybe().map(move |out| out.unwrap_or_else(|| value.clone()))
+    }
+
+    /// Fails with `err` if the parser succeeds without consuming any input.
+    ///
+    /// Useful to guard user-supplied parsers placed inside `many` or `sep_by` against
+    /// accidental zero-length matches.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let parser = "a".make_literal_matcher("Expected a")
+    ///     .maybe()
+    ///     .non_empty("Matched nothing");
+    ///
+    /// assert_eq!(parser.parse("ab"), Ok(("b", Some("a"))));
+    /// assert_eq!(parser.parse("b"), Err(("b", "Matched nothing")));
+    /// ```
+    fn non_empty(self, err: Error) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Input: PartialEq,
+        Error: Clone,
+    {
+        move |input: Input| {
+            let (rest, result) = self.parse(input.clone())?;
+            if rest == input {
+                Err((input, err.clone()))
+            } else {
+                Ok((rest, result))
+            }
+        }
+    }
+
+    /// Fails with `err` instead of running the parser when it is already running `limit`
+    /// times, i.e. when it is nested `limit` deep within itself.
+    ///
+    /// Each level of nesting in a recursive grammar takes stack space, so deeply nested
+    /// input, such as 100000 opening parentheses, overflows the stack and aborts the
+    /// program. Wrapping the recursive rule turns excessive nesting into a parse error. The
+    /// count belongs to this parser; clones of it made before wrapping are not counted.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// // nested := "(" nested ")" | ""
+    /// let nested = recursive(|nested| {
+    ///     let open = '('.make_character_matcher("Expected (");
+    ///     let close = ')'.make_character_matcher("Expected )");
+    ///     let level = move |input: &'static str| match open.parse(input) {
+    ///         Ok((rest, _)) => {
+    ///             let (rest, depth) = nested.parse(rest)?;
+    ///             let (rest, _) = close.parse(rest)?;
+    ///             Ok((rest, depth + 1))
+    ///         }
+    ///         Err(_) => Ok((input, 0)),
+    ///     };
+    ///     Box::new(level.with_depth_limit(100, "Too deep"))
+    /// });
+    ///
+    /// assert_eq!(nested.parse("((()))"), Ok(("", 3)));
+    /// let deep: &'static str = "(".repeat(100_000).leak();
+    /// assert_eq!(nested.parse(deep), Err((&deep[100..], "Too deep")));
+    /// ```
+    fn with_depth_limit(self, limit: usize, err: Error) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Error: Clone,
+    {
+        let depth = core::cell::Cell::new(0);
+        move |input: Input| {
+            if depth.get() >= limit {
+                return Err((input, err.clone()));
+            }
+            let _guard = DepthGuard::enter(&depth);
+            self.parse(input)
+        }
+    }
+
+    /// Applies the parser zero or more times, collecting all results.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let parser = "a".make_literal_matcher("Expected a").many();
+    ///
+    /// assert_eq!(parser.parse(""), Ok(("", vec![])));
+    /// assert_eq!(parser.parse("a"), Ok(("", vec!["a"])));
+    /// assert_eq!(parser.parse("aaa"), Ok(("", vec!["a", "a", "a"])));
+    /// assert_eq!(parser.parse("aaab"), Ok(("b", vec!["a", "a", "a"])));
+    ///
+    /// // A parser matching without consuming input stops after its first match
+    /// let empty = "".make_literal_matcher("Expected nothing").many();
+    /// assert_eq!(empty.parse("ab"), Ok(("ab", vec![""])));
+    /// ```
+    fn many(self) -> impl ManyParser<Input, Output, Error>
+    where
+        Self: Sized,
+        Input: PartialEq,
+    {
+        move |input: Input| {
+            let mut result = Vec::new();
+            let mut rest = input;
+
+            // A failed attempt is undone, along with its state changes
+            while let Ok((new_rest, ret)) = self.parse(rest.clone()) {
+                result.push(ret);
+                // A match that consumed nothing would repeat forever
+                if new_rest == rest {
+                    break;
+                }
+                rest = new_rest;
+            }
+
+            Ok((rest, result))
+        }
+    }
+
+    /// Applies the parser zero or more times, folding the results into an accumulator
+    /// instead of collecting them.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let two = '2'.make_character_matcher("Expected 2")
+    ///     .map(|c| c.to_digit(10).unwrap());
+    /// let parser = two.fold_many(0, |sum, d| sum + d);
+    ///
+    /// assert_eq!(parser.parse(""), Ok(("", 0)));
+    /// assert_eq!(parser.parse("222x"), Ok(("x", 6)));
+    /// ```
+    fn fold_many<Acc>(
+        self,
+        init: Acc,
+        f: impl Fn(Acc, Output) -> Acc,
+    ) -> impl Parser<Input, Acc, Error>
+    where
+        Self: Sized,
+        Input: PartialEq,
+        Acc: Clone,
+    {
+        move |input: Input| {
+            let mut acc = init.clone();
+            let mut rest = input;
+
+            while let Ok((new_rest, ret)) = self.parse(rest.clone()) {
+                acc = f(acc, ret);
+                if new_rest == rest {
+                    break;
+                }
+                rest = new_rest;
+            }
+
+            Ok((rest, acc))
         }
     }
 
-    /// Applies the parser zero or more times, collecting all results.
+    /// Applies the parser one or more times, folding the results into an accumulator.
+    /// Returns `err` if the first application fails.
     ///
     /// ## Example
     ///
     /// ```rust
     /// use friss::*;
     ///
-    /// let parser = "a".make_literal_matcher("Expected a").many();
+    /// let parser = "a".make_literal_matcher("Expected a")
+    ///     .fold_many1(0, |count, _| count + 1, "Need at least one a");
     ///
-    /// assert_eq!(parser.parse(""), Ok(("", vec![])));
-    /// assert_eq!(parser.parse("a"), Ok(("", vec!["a"])));
-    /// assert_eq!(parser.parse("aaa"), Ok(("", vec!["a", "a", "a"])));
-    /// assert_eq!(parser.parse("aaab"), Ok(("b", vec!["a", "a", "a"])));
+    /// assert_eq!(parser.parse("aaab"), Ok(("b", 3)));
+    /// assert_eq!(parser.parse("b"), Err(("b", "Need at least one a")));
     /// ```
-    fn many(self) -> impl ManyParser<Input, Output, Error>
+    fn fold_many1<Acc>(
+        self,
+        init: Acc,
+        f: impl Fn(Acc, Output) -> Acc,
+        err: Error,
+    ) -> impl Parser<Input, Acc, Error>
     where
         Self: Sized,
         Input: PartialEq,
+        Acc: Clone,
     {
         move |input: Input| {
-            let mut result = Vec::new();
-            let mut rest = input;
-
-            loop {
-                //let rest_ref = &rest;
-                match self.parse(rest) {
-                    Ok((new_rest, ret)) => {
-                        /*
-                        //we probably dont need this
-                        if &new_rest == rest_ref {
-                            break;
-                        }*/
-                        rest = new_rest;
-                        result.push(ret);
-                    }
-                    Err((new_rest, _err)) => {
-                        rest = new_rest;
-                        break;
-                    }
+            let (mut rest, first) = match self.parse(input) {
+                Ok(ok) => ok,
+                Err((rest, _)) => return Err((rest, err.clone())),
+            };
+            let mut acc = f(init.clone(), first);
+
+            while let Ok((new_rest, ret)) = self.parse(rest.clone()) {
+                acc = f(acc, ret);
+                if new_rest == rest {
+                    break;
                 }
+                rest = new_rest;
             }
 
-            Ok((rest, result))
+            Ok((rest, acc))
         }
     }
 
@@ -660,25 +1631,23 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         Self: Sized,
         Error: Clone,
         Input: PartialEq,
-        Output: Copy,
     {
         move |input: Input| {
-            let mut result = [None; N];
+            let mut result: [Option<Output>; N] = core::array::from_fn(|_| None);
             let mut rest = input;
-            let mut remaining = N;
 
-            while remaining > 0 {
+            for slot in result.iter_mut() {
+                let checkpoint = rest.checkpoint();
                 match self.parse(rest) {
                     Ok((new_rest, ret)) => {
                         /*if new_rest == rest {
                             break;
                         }*/
                         rest = new_rest;
-                        result[N - remaining] = Some(ret);
-                        remaining -= 1;
+                        *slot = Some(ret);
                     }
-                    Err((new_rest, _)) => {
-                        rest = new_rest;
+                    Err(_) => {
+                        rest = Input::rewind(checkpoint);
                         break;
                     }
                 }
@@ -688,71 +1657,135 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
     }
 
     /// Applies the parser exactly N times, returning an error if fewer than N matches are found.
+    /// The output type needs no `Copy` or `Clone` bound.
+    ///
+    /// A failed attempt is reported where it failed, as a `RepetitionError` holding the
+    /// error of the attempt and how many matches were found before it.
     ///
     /// ## Example
     ///
     /// ```rust
     /// use friss::*;
     ///
-    /// let parser = "a".make_literal_matcher("Expected a")
-    ///     .exactly_n::<2>("Need exactly 2 'a's");
+    /// let parser = "a".make_literal_matcher("Expected a").exactly_n::<2>();
     ///
     /// assert_eq!(parser.parse("aa"), Ok(("", Box::new(["a", "a"]))));
-    /// assert_eq!(parser.parse("a"), Err(("", "Need exactly 2 'a's")));
     /// assert_eq!(parser.parse("aaa"), Ok(("a", Box::new(["a", "a"]))));
+    ///
+    /// let (rest, err) = parser.parse("ab").unwrap_err();
+    /// assert_eq!(rest, "b");
+    /// assert_eq!(err, RepetitionError { expected: 2, found: 1, error: "Expected a" });
+    /// assert_eq!(err.to_string(), "expected 2 repetitions, found 1: Expected a");
     /// ```
-    fn exactly_n<const N: usize>(self, err: Error) -> impl ExactlyNParser<N, Input, Output, Error>
+    fn exactly_n<const N: usize>(self) -> impl ExactlyNParser<N, Input, Output, RepetitionError<Error>>
     where
         Self: Sized,
-        Error: Clone,
-        Input: PartialEq,
-        Output: Copy,
+        Input: Parsable<RepetitionError<Error>>,
     {
         move |input: Input| {
-            let end_p = Input::make_empty_matcher(err.clone());
             let mut result = Vec::with_capacity(N);
             let mut rest = input;
-            let mut remaining = N;
-            let mut maybe_err = None;
-            while remaining > 0 {
+            while result.len() < N {
                 match self.parse(rest) {
                     Ok((new_rest, ret)) => {
-                        let is_end = end_p.parse(new_rest);
-                        match is_end {
-                            Ok((new_new_rest, _)) => {
-                                rest = new_new_rest;
-                                result.push(ret);
-                                break;
-                            }
-                            Err((new_new_rest, _)) => {
-                                rest = new_new_rest;
-                                result.push(ret);
-                                remaining -= 1;
-                            }
-                        }
-                    }
-                    Err((new_rest, err)) => {
                         rest = new_rest;
-                        maybe_err = Some(err);
-                        break;
+                        result.push(ret);
+                    }
+                    Err((new_rest, error)) => {
+                        let found = result.len();
+                        return Err((new_rest, RepetitionError { expected: N, found, error }));
                     }
                 }
             }
+            #[allow(clippy::expect_used)] // The loop stops at exactly N items, never reached by input
+            let array: Box<[Output; N]> = result.into_boxed_slice().try_into().ok().expect("N items were collected");
+            Ok((rest, array))
+        }
+    }
 
-            if let Some(err) = maybe_err {
-                return Err((rest, err.clone()));
+    /// Like `exactly_n`, but a failure keeps the items parsed before it, see
+    /// `PartialRepetition`. The error holds the items, so they must be `Clone`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let hex = make_char_predicate_matcher(|c| c.is_ascii_hexdigit(), "Expected hex digit");
+    /// let color = hex.exactly_n_partial::<6>();
+    ///
+    /// assert_eq!(color.parse("ffa0c1;"), Ok((";", Box::new(['f', 'f', 'a', '0', 'c', '1']))));
+    ///
+    /// let (rest, err) = color.parse("ffa0z").unwrap_err();
+    /// assert_eq!(rest, "z");
+    /// assert_eq!((err.items, err.expected, err.error), (vec!['f', 'f', 'a', '0'], 6, "Expected hex digit"));
+    /// ```
+    fn exactly_n_partial<const N: usize>(
+        self,
+    ) -> impl ExactlyNParser<N, Input, Output, PartialRepetition<Output, Error>>
+    where
+        Self: Sized,
+        Output: Clone,
+        Input: Parsable<PartialRepetition<Output, Error>>,
+    {
+        move |input: Input| {
+            let mut items = Vec::with_capacity(N);
+            let mut rest = input;
+            while items.len() < N {
+                match self.parse(rest) {
+                    Ok((new_rest, ret)) => {
+                        rest = new_rest;
+                        items.push(ret);
+                    }
+                    Err((new_rest, error)) => {
+                        return Err((new_rest, PartialRepetition { expected: N, items, error }));
+                    }
+                }
             }
+            #[allow(clippy::expect_used)] // The loop stops at exactly N items, never reached by input
+            let array: Box<[Output; N]> = items.into_boxed_slice().try_into().ok().expect("N items were collected");
+            Ok((rest, array))
+        }
+    }
 
-            if result.len() == N {
-                result.shrink_to_fit();
-                if let Some((r, _)) = result.split_first_chunk_mut::<N>() {
-                    Ok((rest, Box::new(r.to_owned())))
-                } else {
-                    unreachable!("wtf")
+    /// Like `at_least_n`, which stops after `n` matches, but a failure keeps the items
+    /// parsed before it, see `PartialRepetition`. The error holds the items, so they must
+    /// be `Clone`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let parser = "ab".make_literal_matcher("Expected ab").at_least_n_partial(2);
+    ///
+    /// assert_eq!(parser.parse("abababa"), Ok(("aba", vec!["ab", "ab"])));
+    ///
+    /// let (rest, err) = parser.parse("aba").unwrap_err();
+    /// assert_eq!(rest, "a");
+    /// assert_eq!((err.index(), err.items, err.error), (1, vec!["ab"], "Expected ab"));
+    /// ```
+    fn at_least_n_partial(self, n: usize) -> impl AtLeastNParser<Input, Output, PartialRepetition<Output, Error>>
+    where
+        Self: Sized,
+        Output: Clone,
+        Input: PartialEq + Parsable<PartialRepetition<Output, Error>>,
+    {
+        move |input: Input| {
+            let mut items = Vec::with_capacity(n);
+            let mut rest = input;
+            while items.len() < n {
+                match self.parse(rest) {
+                    Ok((new_rest, ret)) => {
+                        rest = new_rest;
+                        items.push(ret);
+                    }
+                    Err((new_rest, error)) => {
+                        return Err((new_rest, PartialRepetition { expected: n, items, error }));
+                    }
                 }
-            } else {
-                Err((rest, err.clone()))
             }
+            Ok((rest, items))
         }
     }
 
@@ -778,8 +1811,9 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         Input: Clone,
     {
         move |input: Input| {
-            self.parse(input.clone())
-                .or_else(|(_, err)| recovery(err).parse(input))
+            let checkpoint = input.checkpoint();
+            self.parse(input)
+                .or_else(|(_, err)| recovery(err).parse(Input::rewind(checkpoint)))
         }
     }
     /// Succeeds if the given parser fails, returning the original input.
@@ -806,6 +1840,30 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         }
     }
 
+    /// Runs the parser and fails with `err` if any input is left over.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let parser = "abc".make_literal_matcher("Expected abc").all_consuming("Unexpected input");
+    ///
+    /// assert_eq!(parser.parse("abc"), Ok(("", "abc")));
+    /// assert_eq!(parser.parse("abcd"), Err(("d", "Unexpected input")));
+    /// ```
+    fn all_consuming(self, err: Error) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+    {
+        let end = Input::make_empty_matcher(err);
+        move |input: Input| {
+            let (rest, output) = self.parse(input)?;
+            let (rest, ()) = end.parse(rest)?;
+            Ok((rest, output))
+        }
+    }
+
     /// Looks ahead in the input stream without consuming it.
     ///
     /// ## Example
@@ -855,6 +1913,72 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         }
     }
 
+    /// Runs the parser and requires `next` to match right after it, without consuming what
+    /// `next` matched.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let key = "key".make_literal_matcher("Expected key");
+    /// let parser = key.followed_by(":".make_literal_matcher("Expected :"));
+    ///
+    /// assert_eq!(parser.parse("key: 1"), Ok((": 1", "key")));
+    /// assert_eq!(parser.parse("key 1"), Err((" 1", Either::Right("Expected :"))));
+    /// ```
+    fn followed_by<Output2, Error2>(
+        self,
+        next: impl Parser<Input, Output2, Error2>,
+    ) -> impl Parser<Input, Output, Either<Error, Error2>>
+    where
+        Self: Sized,
+        Error2: Clone,
+        Input: Parsable<Error2> + Parsable<Either<Error, Error2>>,
+    {
+        move |input: Input| {
+            let (rest, output) = self.parse(input).map_err(|(rest, err)| (rest, Either::Left(err)))?;
+            match next.parse(rest.clone()) {
+                Ok(_) => Ok((rest, output)),
+                Err((rest, err)) => Err((rest, Either::Right(err))),
+            }
+        }
+    }
+
+    /// Runs the parser and fails with `err` if `next` would match right after it. The error
+    /// is reported where `next` would start.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let letter = <&str as Parsable<&str>>::make_anything_matcher("Expected letter")
+    ///     .validate(|c: &char| c.is_alphanumeric(), "Expected letter");
+    /// let keyword = "let".make_literal_matcher("Expected let").not_followed_by(letter, "Expected let");
+    ///
+    /// assert_eq!(keyword.parse("let x"), Ok((" x", "let")));
+    /// assert_eq!(keyword.parse("letter"), Err(("ter", "Expected let")));
+    /// ```
+    fn not_followed_by<Output2, Error2>(
+        self,
+        next: impl Parser<Input, Output2, Error2>,
+        err: Error,
+    ) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Error2: Clone,
+        Input: Parsable<Error2>,
+    {
+        move |input: Input| {
+            let (rest, output) = self.parse(input)?;
+            match next.parse(rest.clone()) {
+                Ok(_) => Err((rest, err.clone())),
+                Err(_) => Ok((rest, output)),
+            }
+        }
+    }
+
     /// Parses content between two delimiters, returning only the content.
     ///
     /// ## Example
@@ -923,28 +2047,29 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         move |mut input: Input| {
             let mut results = Vec::new();
 
-            match self.parse(input.clone()) {
+            let checkpoint = input.checkpoint();
+            match self.parse(input) {
                 Ok((rest, item)) => {
                     results.push(item);
                     input = rest;
                 }
-                Err(_) => return Ok((input, results)),
+                Err(_) => return Ok((Input::rewind(checkpoint), results)),
             }
 
-            while let Ok((rest1, _)) = sep.parse(input.clone()) {
-                if let Ok((rest2, item)) = self.parse(rest1) {
-                    results.push(item);
-                    input = rest2;
-                } else {
-                    break;
+            loop {
+                let checkpoint = input.checkpoint();
+                match sep.parse(input).and_then(|(rest1, _)| self.parse(rest1)) {
+                    Ok((rest2, item)) => {
+                        results.push(item);
+                        input = rest2;
+                    }
+                    Err(_) => return Ok((Input::rewind(checkpoint), results)),
                 }
             }
-
-            Ok((input, results))
         }
     }
 
-    /// Lifts a parser to `StatefulParser`
+    // Lifts a parser to `StatefulParser`
     /*fn state_lift<State>(&self)-> StatefulParser<State,Input,Output,Error>{
         move |carrier: StateCarrier<State, Input>| {
             let state = carrier.state;
@@ -982,24 +2107,26 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         Input: Clone,
         Error: Clone,
     {
-        move |input: Input| match self.parse(input.clone()) {
-            Ok((rest, first)) => {
-                let mut results = vec![first];
-                let mut current_input = rest;
-
-                while let Ok((rest1, _)) = sep.parse(current_input.clone()) {
-                    match self.parse(rest1) {
-                        Ok((rest2, item)) => {
-                            results.push(item);
-                            current_input = rest2;
+        move |input: Input| {
+            let checkpoint = input.checkpoint();
+            match self.parse(input) {
+                Ok((rest, first)) => {
+                    let mut results = vec![first];
+                    let mut current_input = rest;
+
+                    loop {
+                        let checkpoint = current_input.checkpoint();
+                        match sep.parse(current_input).and_then(|(rest1, _)| self.parse(rest1)) {
+                            Ok((rest2, item)) => {
+                                results.push(item);
+                                current_input = rest2;
+                            }
+                            Err(_) => return Ok((Input::rewind(checkpoint), results)),
                         }
-                        Err(_) => break,
                     }
                 }
-
-                Ok((current_input, results))
+                Err(_) => Err((Input::rewind(checkpoint), err.clone())),
             }
-            Err(_) => Err((input, err.clone())),
         }
     }
 
@@ -1042,7 +2169,8 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         }
     }
 
-    /// Similar to `chainl1` but produces right-associative operations.
+    /// Similar to `chainl1` but produces right-associative operations. Chains of any length
+    /// are parsed without recursion.
     ///
     /// ## Example
     ///
@@ -1065,46 +2193,139 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         Input: Clone,
         Output: Clone,
     {
-        // Helper recursive function to handle the right-associative parsing
-        fn parse_right<I, O, E, P, OP>(
-            term_parser: &P,
-            op_parser: &OP,
-            input: I,
-        ) -> Result<(I, O), (I, E)>
-        where
-            P: Parser<I, O, E>,
-            OP: Parser<I, Box<dyn Fn(O, O) -> O>, E>,
-            I: Parsable<E> + Clone,
-            O: Clone,
-            E: Clone,
-        {
-            // Parse the leftmost term
-            let (rest, left_term) = term_parser.parse(input)?;
-
-            // Try to parse an operator
-            match op_parser.parse(rest.clone()) {
-                Ok((rest_after_op, op_func)) => {
-                    // Recursively parse the right side
-                    match parse_right(term_parser, op_parser, rest_after_op) {
-                        Ok((final_rest, right_term)) => {
-                            // Apply the operator with right associativity
-                            Ok((final_rest, op_func(left_term, right_term)))
-                        }
-                        Err(_) => {
-                            // If right side fails, just return the left term
-                            Ok((rest, left_term))
-                        }
-                    }
-                }
-                Err(_) => {
-                    // No operator found, just return the term
-                    Ok((rest, left_term))
-                }
+        move |input: Input| {
+            // Collect the terms with the operator following each, then combine them from
+            // the right, so long chains do not recurse
+            let (mut rest, mut last) = self.parse(input)?;
+            let mut lefts = Vec::new();
+            while let Ok((rest_after_op, op_func)) = op.parse(rest.clone()) {
+                // If the right side fails, the chain ends before the operator
+                let Ok((next_rest, term)) = self.parse(rest_after_op) else {
+                    break;
+                };
+                lefts.push((core::mem::replace(&mut last, term), op_func));
+                rest = next_rest;
+            }
+            let value = lefts
+                .into_iter()
+                .rev()
+                .fold(last, |right, (left, op_func)| op_func(left, right));
+            Ok((rest, value))
+        }
+    }
+
+    /// Parses one or more terms separated by operators, combining them left-associatively
+    /// with `combine(left, operator, right)`.
+    ///
+    /// Unlike `chainl1`, the operator parser returns its own value, such as an operator
+    /// enum, and has its own error type. An operator error only ends the chain, as does a
+    /// term failing after an operator, which leaves the operator unconsumed; the only error
+    /// is that of the first term.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq)]
+    /// enum Op { Sub, Div }
+    ///
+    /// let num = make_char_predicate_matcher(|c| c.is_ascii_digit(), "Expected digit")
+    ///     .map(|c| c.to_digit(10).unwrap() as i32);
+    /// let op = '-'.make_character_matcher(()).value(Op::Sub)
+    ///     .alt('/'.make_character_matcher(()).value(Op::Div))
+    ///     .map(|op| op.fold());
+    ///
+    /// let parser = num.infixl(op, |left, op, right| match op {
+    ///     Op::Sub => left - right,
+    ///     Op::Div => left / right,
+    /// });
+    /// assert_eq!(parser.parse("8-4/2"), Ok(("", 2))); // (8-4)/2
+    /// assert_eq!(parser.parse("8-x"), Ok(("-x", 8)));
+    /// ```
+    fn infixl<Op, OpError, Combine>(
+        self,
+        op: impl Parser<Input, Op, OpError>,
+        combine: Combine,
+    ) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Input: Parsable<OpError>,
+        OpError: Clone,
+        Combine: Fn(Output, Op, Output) -> Output,
+    {
+        move |input: Input| {
+            let (mut rest, mut acc) = self.parse(input)?;
+            loop {
+                let checkpoint = rest.checkpoint();
+                let Ok((rest_after_op, op_value)) = op.parse(rest) else {
+                    return Ok((Input::rewind(checkpoint), acc));
+                };
+                let Ok((next_rest, term)) = self.parse(rest_after_op) else {
+                    return Ok((Input::rewind(checkpoint), acc));
+                };
+                acc = combine(acc, op_value, term);
+                rest = next_rest;
             }
         }
+    }
 
-        // The actual parser implementation
-        move |input: Input| parse_right(&self, &op, input)
+    /// Parses one or more terms separated by operators, combining them right-associatively
+    /// with `combine(left, operator, right)`. Like `infixl`, the operator returns its own
+    /// value and error, and chains of any length are parsed without recursion.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// enum Type { Int, Fn(Box<Type>, Box<Type>) }
+    ///
+    /// let int = "int".make_literal_matcher("Expected int").value(Type::Int);
+    /// let arrow = "->".make_literal_matcher("Expected ->").ignore();
+    ///
+    /// let parser = int.infixr(arrow, |from, (), to| Type::Fn(Box::new(from), Box::new(to)));
+    /// assert_eq!(
+    ///     parser.parse("int->int->int"),
+    ///     Ok(("", Type::Fn(Box::new(Type::Int), Box::new(Type::Fn(Box::new(Type::Int), Box::new(Type::Int))))))
+    /// );
+    /// ```
+    fn infixr<Op, OpError, Combine>(
+        self,
+        op: impl Parser<Input, Op, OpError>,
+        combine: Combine,
+    ) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Input: Parsable<OpError>,
+        OpError: Clone,
+        Combine: Fn(Output, Op, Output) -> Output,
+    {
+        move |input: Input| {
+            // Collect the terms with the operator following each, then combine them from
+            // the right
+            let (mut rest, mut last) = self.parse(input)?;
+            let mut lefts = Vec::new();
+            loop {
+                let checkpoint = rest.checkpoint();
+                let Ok((rest_after_op, op_value)) = op.parse(rest) else {
+                    rest = Input::rewind(checkpoint);
+                    break;
+                };
+                let Ok((next_rest, term)) = self.parse(rest_after_op) else {
+                    rest = Input::rewind(checkpoint);
+                    break;
+                };
+                lefts.push((core::mem::replace(&mut last, term), op_value));
+                rest = next_rest;
+            }
+            let value = lefts
+                .into_iter()
+                .rev()
+                .fold(last, |right, (left, op_value)| combine(left, op_value, right));
+            Ok((rest, value))
+        }
     }
 
     /// Applies a function to the parser's output. Output is flattened .
@@ -1198,9 +2419,9 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         Self: Sized,
         Input: Clone,
     {
-        move |input: Input| match self.parse(input.clone()) {
-            Ok(result) => Ok(result),
-            Err((_, err)) => Err((input, err)),
+        move |input: Input| {
+            let checkpoint = input.checkpoint();
+            self.parse(input).map_err(|(_, err)| (Input::rewind(checkpoint), err))
         }
     }
 
@@ -1220,6 +2441,7 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
     /// assert_eq!(parser.parse("ba"), Ok(("ba", (None, Some(("a","b"))))));
     /// assert_eq!(parser.parse("c"), Err(("c", ("Not a", "Not b"))));
     /// ```
+    #[allow(clippy::type_complexity)]
     fn or<Output2, Error2>(
         self,
         other: impl Parser<Input, Output2, Error2>,
@@ -1231,8 +2453,10 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         Error2: Clone,
     {
         move |input: Input| {
-            let first = self.parse(input.clone());
-            let second = other.parse(input.clone());
+            let checkpoint = input.checkpoint();
+            let first = self.parse(input);
+            let second = other.parse(Input::rewind(checkpoint.clone()));
+            let input = Input::rewind(checkpoint);
 
             match (first, second) {
                 (Ok(a), Ok(b)) => Ok((input, (Some(a), Some(b)))),
@@ -1300,23 +2524,18 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
     ///
     /// ## Example
     ///
-    /// let parser = (
-    ///        [1].make_literal_matcher("1 failed"),
-    ///        [2].make_literal_matcher("2 failed"),
-    ///    )
-    ///        .seq()
-    ///        .rev_apply(move |input: &'a [i32]| Ok((input, move |a: &[i32], b: &[i32]| b[0] + a[0])));
-    ///    match parser.parse(&[1, 2]) {
-    ///        Ok((_, x)) => assert_eq!(x, 3),
-    ///        _ => assert!(false, "Parser failed"),
-    ///    }
-    ///
-    ///
-    ///
-    ///
+    /// ```rust
+    /// use friss::*;
     ///
+    /// let input: &'static [i32] = &[1, 2];
+    /// let parser = (
+    ///     [1].make_literal_matcher("1 failed"),
+    ///     [2].make_literal_matcher("2 failed"),
+    /// )
+    ///     .seq()
+    ///     .rev_apply(|input: &'static [i32]| Ok((input, |a: &[i32], b: &[i32]| b[0] + a[0])));
     ///
-
+    /// assert_eq!(parser.parse(input).map(|(_, x)| x), Ok(3));
     /// ```
     fn rev_apply<Args, Out, T, P>(self, fun_supplier: P) -> impl Parser<Input, Out, Error>
     where
@@ -1332,49 +2551,106 @@ pub trait Parser<Input: Parsable<Error>, Output: ParserOutput, Error: Clone> {
         }
     }
 }
-/// Creates a parser that always returns the given output without consuming input.
+/// Creates a parser that always returns the given output without consuming input.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::core::Parser;
+///
+/// let value : i32 = 42;
+/// let pure_parser : Box<dyn Parser<&str,i32,&str>> = Box::new(pure(value));
+///
+/// assert_eq!(pure_parser.parse("hello"), Ok(("hello", 42)));
+/// ```
+pub fn pure<Input, Output, Error>(out: Output) -> impl Parser<Input, Output, Error>
+where
+    Input: Parsable<Error> + Clone,
+    Output: Clone,
+    Error: Clone,
+{
+    move |input: Input| Ok((input, out.clone()))
+}
+
+/// Creates a parser that always fails with the given error.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::core::Parser;
+///
+/// let fail_parser : Box<dyn Parser<&str,(),&str>>= Box::new(fail("Always fails"));
+///
+/// assert_eq!(fail_parser.parse("anything"), Err(("anything", "Always fails")));
+/// ```
+pub fn fail<Input, Output, Error>(err: Error) -> impl Parser<Input, Output, Error>
+where
+    Input: Parsable<Error> + Clone,
+    Error: Clone,
+{
+    move |input: Input| Err((input, err.clone()))
+}
+
+/// Creates a parser that succeeds only on empty input.
 ///
 /// ## Example
 ///
 /// ```rust
 /// use friss::*;
-/// use friss::core::Parser;
 ///
-/// let value : i32 = 42;
-/// let pure_parser : Box<dyn Parser<&str,i32,&str>> = Box::new(pure(value));
+/// let eof = end_of_input("Expected end of input");
 ///
-/// assert_eq!(pure_parser.parse("hello"), Ok(("hello", 42)));
+/// assert_eq!(eof.parse(""), Ok(("", ())));
+/// assert_eq!(eof.parse("x"), Err(("x", "Expected end of input")));
 /// ```
-pub fn pure<Input, Output, Error>(out: Output) -> impl Parser<Input, Output, Error>
+pub fn end_of_input<Input, Error>(err: Error) -> impl Parser<Input, (), Error>
 where
-    Input: Parsable<Error> + Clone,
-    Output: Clone,
+    Input: Parsable<Error>,
     Error: Clone,
 {
-    move |input: Input| Ok((input, out.clone()))
+    Input::make_empty_matcher(err)
 }
 
-/// Creates a parser that always fails with the given error.
+/// Creates a parser returning the whole remaining input and leaving the input empty.
 ///
 /// ## Example
 ///
 /// ```rust
 /// use friss::*;
-/// use friss::core::Parser;
 ///
-/// let fail_parser : Box<dyn Parser<&str,(),&str>>= Box::new(fail("Always fails"));
+/// let value = "key=".make_literal_matcher("Expected key").seq(rest::<_, &str>());
 ///
-/// assert_eq!(fail_parser.parse("anything"), Err(("anything", "Always fails")));
+/// assert_eq!(value.parse("key=any value"), Ok(("", ("key=", "any value"))));
 /// ```
-pub fn fail<Input, Output, Error>(err: Error) -> impl Parser<Input, Output, Error>
+pub fn rest<Input, Error>() -> impl Parser<Input, Input, Error>
 where
-    Input: Parsable<Error> + Clone,
+    Input: Parsable<Error> + Scannable,
     Error: Clone,
 {
-    move |input: Input| Err((input, err.clone()))
+    move |input: Input| Ok((input.suffix(input.scan_len()), input))
+}
+
+/// Counts a level of nesting in `Parser::with_depth_limit` until dropped, even when the
+/// parser panics.
+struct DepthGuard<'a>(&'a core::cell::Cell<usize>);
+
+impl<'a> DepthGuard<'a> {
+    fn enter(depth: &'a core::cell::Cell<usize>) -> Self {
+        depth.set(depth.get() + 1);
+        DepthGuard(depth)
+    }
+}
+
+impl Drop for DepthGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
 }
 
 /// Creates a recursive parser that can reference itself.
+#[allow(clippy::panic)] // Only reachable by calling the placeholder inside `f`, never by input
 pub fn recursive<Input, Output, Error, F>(f: F) -> Box<dyn Parser<Input, Output, Error>>
 where
     Input: Parsable<Error> + Clone + 'static,
@@ -1383,8 +2659,7 @@ where
     F: FnOnce(Box<dyn Parser<Input, Output, Error>>) -> Box<dyn Parser<Input, Output, Error>>
         + 'static,
 {
-    let cell: std::rc::Rc<RefCell<Option<Box<dyn Parser<Input, Output, Error>>>>> =
-        std::rc::Rc::new(RefCell::new(None));
+    let cell = alloc::rc::Rc::new(RefCell::new(None::<Box<dyn Parser<Input, Output, Error>>>));
 
     let cell_for_placeholder = cell.clone();
 
@@ -1412,6 +2687,533 @@ where
     })
 }
 
+/// Iterator returned by `Parser::iter`, yielding the outputs of a repeated parser.
+pub struct ParseIter<'p, P, Input, Output, Error> {
+    parser: &'p P,
+    rest: Input,
+    error: Option<Error>,
+    done: bool,
+    _phantom: PhantomData<fn() -> Output>,
+}
+
+impl<P, Input, Output, Error> ParseIter<'_, P, Input, Output, Error> {
+    /// Returns the input not consumed so far.
+    pub fn rest(&self) -> &Input {
+        &self.rest
+    }
+
+    /// Returns the error that stopped the iteration, if any.
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// Consumes the iterator, returning the remaining input and the error that stopped the
+    /// iteration. The error is `None` if the iteration has not stopped, or stopped after a
+    /// match that consumed no input.
+    pub fn finish(self) -> (Input, Option<Error>) {
+        (self.rest, self.error)
+    }
+}
+
+impl<P, Input, Output, Error> Iterator for ParseIter<'_, P, Input, Output, Error>
+where
+    P: Parser<Input, Output, Error>,
+    Input: Parsable<Error> + PartialEq,
+    Error: Clone,
+{
+    type Item = Output;
+
+    fn next(&mut self) -> Option<Output> {
+        if self.done {
+            return None;
+        }
+        let checkpoint = self.rest.checkpoint();
+        match self.parser.parse(self.rest.clone()) {
+            Ok((rest, output)) => {
+                // A match that consumed nothing would repeat forever
+                self.done = rest == Input::rewind(checkpoint);
+                self.rest = rest;
+                Some(output)
+            }
+            Err((_, err)) => {
+                self.rest = Input::rewind(checkpoint);
+                self.error = Some(err);
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
+/// A parser with its type erased, for storing grammars in structs, maps and recursion cells.
+pub struct BoxedParser<'a, Input, Output, Error> {
+    parser: Box<dyn Parser<Input, Output, Error> + 'a>,
+}
+
+impl<'a, Input, Output, Error> BoxedParser<'a, Input, Output, Error> {
+    /// Boxes `parser`.
+    pub fn new(parser: impl Parser<Input, Output, Error> + 'a) -> Self
+    where
+        Input: Parsable<Error>,
+        Error: Clone,
+    {
+        BoxedParser {
+            parser: Box::new(parser),
+        }
+    }
+}
+
+impl<Input, Output, Error> Parser<Input, Output, Error> for BoxedParser<'_, Input, Output, Error>
+where
+    Input: Parsable<Error>,
+    Error: Clone,
+{
+    fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+        self.parser.parse(input)
+    }
+}
+
+/// A cheaply clonable handle to a parser, created with `Parser::shared`.
+///
+/// Clones share one parser behind an `Rc`, so a sub-parser built once can be captured by
+/// several branches and closures of a grammar.
+pub struct SharedParser<P: ?Sized> {
+    parser: alloc::rc::Rc<P>,
+}
+
+impl<P: ?Sized> Clone for SharedParser<P> {
+    fn clone(&self) -> Self {
+        SharedParser {
+            parser: self.parser.clone(),
+        }
+    }
+}
+
+impl<Input, Output, Error, P> Parser<Input, Output, Error> for SharedParser<P>
+where
+    P: Parser<Input, Output, Error> + ?Sized,
+    Input: Parsable<Error>,
+    Error: Clone,
+{
+    fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+        self.parser.parse(input)
+    }
+}
+
+/// A cheaply clonable handle to a parser that can be shared between threads, created with
+/// `Parser::shared_sync`.
+///
+/// Works like `SharedParser`, but clones share the parser behind an `Arc`.
+pub struct SyncSharedParser<P: ?Sized> {
+    parser: alloc::sync::Arc<P>,
+}
+
+impl<P: ?Sized> Clone for SyncSharedParser<P> {
+    fn clone(&self) -> Self {
+        SyncSharedParser {
+            parser: self.parser.clone(),
+        }
+    }
+}
+
+impl<Input, Output, Error, P> Parser<Input, Output, Error> for SyncSharedParser<P>
+where
+    P: Parser<Input, Output, Error> + ?Sized,
+    Input: Parsable<Error>,
+    Error: Clone,
+{
+    fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+        self.parser.parse(input)
+    }
+}
+
+// Shared and borrowed parsers delegate to the parser they point to. References and boxes
+// of closures are closures themselves and already parse through the `Fn` impl below, which
+// rules out blanket impls for `&P` and `Box<P>`; they are provided for trait objects, and
+// `Parser::by_ref` borrows any other parser.
+
+impl<Input, Output, Error, P> Parser<Input, Output, Error> for alloc::rc::Rc<P>
+where
+    P: Parser<Input, Output, Error> + ?Sized,
+    Input: Parsable<Error>,
+    Error: Clone,
+{
+    fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+        (**self).parse(input)
+    }
+}
+
+impl<Input, Output, Error, P> Parser<Input, Output, Error> for alloc::sync::Arc<P>
+where
+    P: Parser<Input, Output, Error> + ?Sized,
+    Input: Parsable<Error>,
+    Error: Clone,
+{
+    fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+        (**self).parse(input)
+    }
+}
+
+macro_rules! impl_parser_for_trait_objects {
+    ($($pointer:ty),* $(,)?) => {
+        $(
+            impl<'a, Input, Output, Error> Parser<Input, Output, Error> for $pointer
+            where
+                Input: Parsable<Error>,
+                Error: Clone,
+            {
+                fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+                    (**self).parse(input)
+                }
+            }
+        )*
+    };
+}
+
+impl_parser_for_trait_objects!(
+    Box<dyn Parser<Input, Output, Error> + 'a>,
+    Box<dyn Parser<Input, Output, Error> + Send + Sync + 'a>,
+    &'a dyn Parser<Input, Output, Error>,
+    &'a (dyn Parser<Input, Output, Error> + Send + Sync),
+);
+
+/// A forward-declared parser, defined after it has been referenced.
+///
+/// Clones of a declaration share the definition, so mutually recursive rules can each hold
+/// a clone of the others before any of them is defined. Parsing with a declaration that was
+/// never defined panics.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+///
+/// // expr := term ('+' term)? ; term := '1' | '(' expr ')'
+/// let expr: Declared<&str, i32, &str> = Declared::declare();
+/// let term: Declared<&str, i32, &str> = Declared::declare();
+///
+/// let (e, t) = (expr.clone(), term.clone());
+/// expr.define(move |input| {
+///     let (rest, lhs) = t.parse(input)?;
+///     match '+'.make_character_matcher("Expected +").parse(rest) {
+///         Ok((rest, _)) => t.parse(rest).map(|(rest, rhs)| (rest, lhs + rhs)),
+///         Err(_) => Ok((rest, lhs)),
+///     }
+/// });
+/// term.define(move |input| {
+///     if let Ok((rest, _)) = '1'.make_character_matcher("Expected term").parse(input) {
+///         return Ok((rest, 1));
+///     }
+///     let (rest, _) = '('.make_character_matcher("Expected term").parse(input)?;
+///     let (rest, value) = e.parse(rest)?;
+///     let (rest, _) = ')'.make_character_matcher("Expected )").parse(rest)?;
+///     Ok((rest, value))
+/// });
+///
+/// assert_eq!(expr.parse("1+(1+1)"), Ok(("", 3)));
+/// ```
+pub struct Declared<Input, Output, Error> {
+    definition: Definition<Input, Output, Error>,
+}
+
+/// The parser a `Declared` handle stands for, set once by `define`.
+type Definition<Input, Output, Error> =
+    alloc::rc::Rc<core::cell::OnceCell<Box<dyn Parser<Input, Output, Error>>>>;
+
+impl<Input, Output, Error> Clone for Declared<Input, Output, Error> {
+    fn clone(&self) -> Self {
+        Declared {
+            definition: self.definition.clone(),
+        }
+    }
+}
+
+impl<Input, Output, Error> Default for Declared<Input, Output, Error> {
+    fn default() -> Self {
+        Self::declare()
+    }
+}
+
+impl<Input, Output, Error> Declared<Input, Output, Error> {
+    /// Declares a parser without defining it.
+    pub fn declare() -> Self {
+        Declared {
+            definition: Default::default(),
+        }
+    }
+
+    /// Returns true if the parser has been defined.
+    pub fn is_defined(&self) -> bool {
+        self.definition.get().is_some()
+    }
+
+    /// Defines the declared parser.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parser was already defined.
+    #[allow(clippy::panic)]
+    pub fn define(&self, parser: impl Parser<Input, Output, Error> + 'static)
+    where
+        Input: Parsable<Error>,
+        Error: Clone,
+    {
+        if self.definition.set(Box::new(parser)).is_err() {
+            panic!("Declared parser defined twice");
+        }
+    }
+}
+
+impl<Input, Output, Error> Parser<Input, Output, Error> for Declared<Input, Output, Error>
+where
+    Input: Parsable<Error>,
+    Error: Clone,
+{
+    #[allow(clippy::panic)] // A grammar that never defines the parser, never input
+    fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+        match self.definition.get() {
+            Some(parser) => parser.parse(input),
+            None => panic!("Declared parser used before being defined"),
+        }
+    }
+}
+
+/// A parser trait object that can be shared between threads.
+pub type SyncParser<Input, Output, Error> = Box<dyn Parser<Input, Output, Error> + Send + Sync>;
+
+/// A forward-declared parser that can be shared between threads.
+///
+/// Works like `Declared`, but the definition is held in an `Arc<OnceLock<...>>` and must be
+/// `Send + Sync`. Requires the `std` feature.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+///
+/// // list := '[' list* ']'
+/// let list: SyncDeclared<&'static str, usize, &'static str> = SyncDeclared::declare();
+/// let inner = list.clone();
+/// list.define(move |input: &'static str| {
+///     let (mut rest, _) = '['.make_character_matcher("Expected [").parse(input)?;
+///     let mut count = 0;
+///     while let Ok((next, _)) = inner.parse(rest) {
+///         (rest, count) = (next, count + 1);
+///     }
+///     let (rest, _) = ']'.make_character_matcher("Expected ]").parse(rest)?;
+///     Ok((rest, count))
+/// });
+///
+/// let count = std::thread::spawn(move || list.parse("[[][[]]]")).join().unwrap();
+/// assert_eq!(count, Ok(("", 2)));
+/// ```
+#[cfg(feature = "std")]
+pub struct SyncDeclared<Input, Output, Error> {
+    definition: std::sync::Arc<std::sync::OnceLock<SyncParser<Input, Output, Error>>>,
+}
+
+#[cfg(feature = "std")]
+impl<Input, Output, Error> Clone for SyncDeclared<Input, Output, Error> {
+    fn clone(&self) -> Self {
+        SyncDeclared {
+            definition: self.definition.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Input, Output, Error> Default for SyncDeclared<Input, Output, Error> {
+    fn default() -> Self {
+        Self::declare()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Input, Output, Error> SyncDeclared<Input, Output, Error> {
+    /// Declares a parser without defining it.
+    pub fn declare() -> Self {
+        SyncDeclared {
+            definition: Default::default(),
+        }
+    }
+
+    /// Returns true if the parser has been defined.
+    pub fn is_defined(&self) -> bool {
+        self.definition.get().is_some()
+    }
+
+    /// Defines the declared parser.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parser was already defined.
+    #[allow(clippy::panic)]
+    pub fn define(&self, parser: impl Parser<Input, Output, Error> + Send + Sync + 'static)
+    where
+        Input: Parsable<Error>,
+        Error: Clone,
+    {
+        if self.definition.set(Box::new(parser)).is_err() {
+            panic!("Declared parser defined twice");
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Input, Output, Error> Parser<Input, Output, Error> for SyncDeclared<Input, Output, Error>
+where
+    Input: Parsable<Error>,
+    Error: Clone,
+{
+    #[allow(clippy::panic)] // A grammar that never defines the parser, never input
+    fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+        match self.definition.get() {
+            Some(parser) => parser.parse(input),
+            None => panic!("Declared parser used before being defined"),
+        }
+    }
+}
+
+/// Creates a recursive parser that can reference itself and is `Send + Sync`.
+///
+/// Works like `recursive`, but the self-reference is resolved through an `Arc<OnceLock<...>>`,
+/// so the grammar can be stored in a static or shared between threads. Requires the `std`
+/// feature.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use std::sync::LazyLock;
+///
+/// // P -> (P) | empty
+/// static PARENS: LazyLock<SyncParser<&'static str, usize, &'static str>> = LazyLock::new(|| {
+///     recursive_sync(|parens| {
+///         Box::new(move |input| {
+///             match '('.make_character_matcher("Expected (").parse(input) {
+///                 Ok((rest, _)) => {
+///                     let (rest, depth) = parens.parse(rest)?;
+///                     let (rest, _) = ')'.make_character_matcher("Expected )").parse(rest)?;
+///                     Ok((rest, depth + 1))
+///                 }
+///                 Err(_) => Ok((input, 0)),
+///             }
+///         })
+///     })
+/// });
+///
+/// let depth = std::thread::spawn(|| PARENS.parse("(())")).join().unwrap();
+/// assert_eq!(depth, Ok(("", 2)));
+/// ```
+#[cfg(feature = "std")]
+#[allow(clippy::panic, clippy::unreachable)] // Only reachable by calling the placeholder inside `f`
+pub fn recursive_sync<Input, Output, Error, F>(f: F) -> SyncParser<Input, Output, Error>
+where
+    Input: Parsable<Error> + 'static,
+    Output: 'static,
+    Error: Clone + 'static,
+    F: FnOnce(SyncParser<Input, Output, Error>) -> SyncParser<Input, Output, Error>,
+{
+    let cell: std::sync::Arc<std::sync::OnceLock<SyncParser<Input, Output, Error>>> = Default::default();
+
+    let cell_for_placeholder = cell.clone();
+    let placeholder: SyncParser<Input, Output, Error> = Box::new(move |input: Input| {
+        match cell_for_placeholder.get() {
+            Some(parser) => parser.parse(input),
+            None => panic!("Recursive parser used before being initialized"),
+        }
+    });
+
+    if cell.set(f(placeholder)).is_err() {
+        unreachable!("Recursive parser initialized twice");
+    }
+
+    Box::new(move |input: Input| match cell.get() {
+        Some(parser) => parser.parse(input),
+        None => panic!("Recursive parser not initialized"),
+    })
+}
+
+/// Creates a recursive parser whose definition may be directly left-recursive.
+///
+/// A self-reference at the start of the input position currently being parsed first
+/// fails with `err`, then returns the longest result found so far, growing it until no
+/// more input is consumed (Warth-style seed growing). Results are memoized by input position
+/// only while the growth loop runs.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+///
+/// // sum := sum '+' '1' | '1'
+/// let sum = recursive_left("Expected sum", |sum| {
+///     Box::new(move |input| {
+///         let plus = |i| sum.parse(i).and_then(|(rest, n)| {
+///             let (rest, _) = "+1".make_literal_matcher("Expected +1").parse(rest)?;
+///             Ok((rest, n + 1))
+///         });
+///         plus(input).or_else(|_| '1'.make_character_matcher("Expected 1").map(|_| 1).parse(input))
+///     })
+/// });
+///
+/// assert_eq!(sum.parse("1+1+1;"), Ok((";", 3)));
+/// assert_eq!(sum.parse("x"), Err(("x", "Expected 1")));
+/// ```
+#[allow(clippy::expect_used)] // Invariants of the growing loop, not reachable by input
+pub fn recursive_left<Input, Output, Error, F>(err: Error, f: F) -> Box<dyn Parser<Input, Output, Error>>
+where
+    Input: Parsable<Error> + Scannable + 'static,
+    Output: Clone + 'static,
+    Error: Clone + 'static,
+    F: FnOnce(Box<dyn Parser<Input, Output, Error>>) -> Box<dyn Parser<Input, Output, Error>>,
+{
+    type Seeds<I, O, E> = alloc::collections::BTreeMap<usize, Result<(I, O), (I, E)>>;
+
+    let cell = alloc::rc::Rc::new(RefCell::new(None::<Box<dyn Parser<Input, Output, Error>>>));
+    let seeds: alloc::rc::Rc<RefCell<Seeds<Input, Output, Error>>> = Default::default();
+
+    let grow = {
+        let cell = cell.clone();
+        alloc::rc::Rc::new(move |input: Input| {
+            let position = input.scan_len();
+            if let Some(seed) = seeds.borrow().get(&position) {
+                return seed.clone();
+            }
+            seeds.borrow_mut().insert(position, Err((input.clone(), err.clone())));
+
+            let borrowed = cell.as_ref().borrow();
+            let parser = borrowed
+                .as_ref()
+                .expect("Recursive parser used before being initialized");
+            let failure = loop {
+                let result = parser.parse(input.clone());
+                let grew = match (&result, seeds.borrow().get(&position)) {
+                    (Ok(_), Some(Err(_)) | None) => true,
+                    (Ok((rest, _)), Some(Ok((seed, _)))) => rest.scan_len() < seed.scan_len(),
+                    (Err(_), _) => false,
+                };
+                if !grew {
+                    break result.err();
+                }
+                seeds.borrow_mut().insert(position, result);
+            };
+            // Without any successful growth the failure of the first attempt is reported
+            let grown = seeds.borrow_mut().remove(&position);
+            match grown {
+                Some(Ok(success)) => Ok(success),
+                _ => Err(failure.expect("First attempt succeeded without growing")),
+            }
+        })
+    };
+
+    let grow_for_placeholder = grow.clone();
+    let actual = f(Box::new(move |input: Input| grow_for_placeholder(input)));
+    *cell.as_ref().borrow_mut() = Some(actual);
+
+    Box::new(move |input: Input| grow(input))
+}
+
 /// Marker trait for arguments to applicative functions.
 pub trait ApplicativeFuncArgs {}
 
@@ -1620,4 +3422,3 @@ where
         Parent::make_item_matcher(self, err)
     }
 }
-
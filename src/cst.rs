@@ -0,0 +1,140 @@
+//! # Concrete Syntax Trees
+//!
+//! Formatters and linters must not lose the whitespace and comments a parser skips. In CST
+//! mode a node keeps the trivia around it: `with_trivia(trivia)` runs the trivia parser as
+//! often as it matches before and after a parser, and returns the output wrapped in
+//! `Trivia` together with the skipped source text. Nodes built this way can be printed back
+//! exactly as they were read.
+//!
+//! Trivia between two nodes belongs to the first one as trailing trivia, unless it was
+//! parsed with `with_leading_trivia`, which only captures the trivia in front.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::cst::*;
+//!
+//! let space = ' '.make_character_matcher("Expected space");
+//! let comment = "/*".make_literal_matcher("Expected comment")
+//!     .seq(<&str as Parsable<&str>>::make_anything_matcher("Expected comment").validate(|c| *c != '/', "").many())
+//!     .seq("/".make_literal_matcher("Expected comment end"));
+//! let trivia = space.alt(comment);
+//!
+//! let word = <&str as Parsable<&str>>::make_anything_matcher("Expected word")
+//!     .validate(|c: &char| c.is_alphabetic(), "Expected word")
+//!     .many()
+//!     .with_trivia(trivia);
+//!
+//! let (rest, node) = word.parse("  /* hi */ab c").unwrap();
+//! assert_eq!(rest, "c");
+//! assert_eq!(node.leading, "  /* hi */");
+//! assert_eq!(node.value, vec!['a', 'b']);
+//! assert_eq!(node.trailing, " ");
+//! ```
+
+use crate::core::Parser;
+use crate::parsers::consumed;
+
+/// A parser output together with the trivia surrounding it in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Trivia<'a, Output> {
+    /// The trivia preceding the output
+    pub leading: &'a str,
+    /// The output of the wrapped parser
+    pub value: Output,
+    /// The trivia following the output
+    pub trailing: &'a str,
+}
+
+impl<'a, Output> Trivia<'a, Output> {
+    pub fn new(leading: &'a str, value: Output, trailing: &'a str) -> Self {
+        Trivia {
+            leading,
+            value,
+            trailing,
+        }
+    }
+
+    /// Applies `f` to the output, keeping the trivia.
+    pub fn map<Out2>(self, f: impl FnOnce(Output) -> Out2) -> Trivia<'a, Out2> {
+        Trivia::new(self.leading, f(self.value), self.trailing)
+    }
+}
+
+/// Runs `trivia` until it fails or stops consuming, returning the rest and the skipped text.
+fn skip_trivia<'a, T, E: Clone>(trivia: &impl Parser<&'a str, T, E>, input: &'a str) -> (&'a str, &'a str) {
+    let mut rest = input;
+    while let Ok((next, _)) = trivia.parse(rest) {
+        if next.len() == rest.len() {
+            break;
+        }
+        rest = next;
+    }
+    (rest, consumed(input, rest))
+}
+
+/// Extension trait capturing trivia around string parsers.
+pub trait TriviaExt<'a, Output, Error>: Parser<&'a str, Output, Error>
+where
+    Error: Clone,
+    Self: Sized,
+{
+    /// Skips `trivia` before and after this parser and keeps the skipped text in the output.
+    fn with_trivia<T, E2: Clone>(
+        self,
+        trivia: impl Parser<&'a str, T, E2>,
+    ) -> impl Parser<&'a str, Trivia<'a, Output>, Error> {
+        move |input: &'a str| {
+            let (rest, leading) = skip_trivia(&trivia, input);
+            let (rest, value) = self.parse(rest)?;
+            let (rest, trailing) = skip_trivia(&trivia, rest);
+            Ok((rest, Trivia::new(leading, value, trailing)))
+        }
+    }
+
+    /// Skips `trivia` before this parser only and keeps the skipped text in the output. The
+    /// trailing trivia of the result is empty.
+    fn with_leading_trivia<T, E2: Clone>(
+        self,
+        trivia: impl Parser<&'a str, T, E2>,
+    ) -> impl Parser<&'a str, Trivia<'a, Output>, Error> {
+        move |input: &'a str| {
+            let (rest, leading) = skip_trivia(&trivia, input);
+            let (rest, value) = self.parse(rest)?;
+            Ok((rest, Trivia::new(leading, value, "")))
+        }
+    }
+}
+
+impl<'a, P, Output, Error> TriviaExt<'a, Output, Error> for P
+where
+    P: Parser<&'a str, Output, Error>,
+    Error: Clone,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_trivia_is_attached_to_neighbours() {
+        let space = || ' '.make_character_matcher("Expected space");
+        let digit = || {
+            <&str as Parsable<&str>>::make_anything_matcher("Expected digit")
+                .validate(|c: &char| c.is_ascii_digit(), "Expected digit")
+        };
+
+        let (rest, nodes) = digit().with_trivia(space()).many().parse(" 1  2 3x").unwrap();
+        assert_eq!(rest, "x");
+        let pieces: Vec<_> = nodes.iter().map(|n| (n.leading, n.value, n.trailing)).collect();
+        assert_eq!(pieces, vec![(" ", '1', "  "), ("", '2', " "), ("", '3', "")]);
+
+        let (rest, node) = digit().with_leading_trivia(space()).parse("  4 ").unwrap();
+        assert_eq!((rest, node), (" ", Trivia::new("  ", '4', "")));
+
+        assert_eq!(digit().with_trivia(space()).parse("  x"), Err(("x", "Expected digit")));
+    }
+}
@@ -0,0 +1,272 @@
+//! # Serde Deserialization
+//!
+//! Deserializes Rust types from any format parsed with friss. A format only needs a parser
+//! whose output converts into the generic `Value` tree; `from_str` runs the parser on the
+//! whole input and deserializes the result. `Value` implements serde's `Deserializer`, so
+//! `from_value` also works on trees built by hand. It is available with the `serde`
+//! feature.
+//!
+//! `JsonValue` from `formats::json` converts into `Value`. Whole numbers become integers, so
+//! they deserialize into integer fields as well as float fields. Enums are read like in
+//! serde_json: a unit variant from a string, other variants from a map with a single entry.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::de::*;
+//! use friss::formats::json::json_value;
+//! use serde::Deserialize;
+//!
+//! #[derive(Debug, PartialEq, Deserialize)]
+//! enum Role { Admin, Guest { until: u32 } }
+//!
+//! #[derive(Debug, PartialEq, Deserialize)]
+//! struct User { name: String, age: u8, tags: Vec<String>, role: Role, email: Option<String> }
+//!
+//! let input = r#"{"name": "ada", "age": 36, "tags": ["x"], "role": {"Guest": {"until": 7}}, "email": null}"#;
+//! let user: User = from_str(&json_value(), input).unwrap();
+//! assert_eq!(user.role, Role::Guest { until: 7 });
+//! assert_eq!(user.email, None);
+//!
+//! let err = from_str::<User, _, _>(&json_value(), r#"{"name": "ada"}"#).unwrap_err();
+//! assert_eq!(err.to_string(), "missing field `age`");
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, VariantAccess, Visitor};
+
+use crate::core::Parser;
+use crate::formats::json::JsonValue;
+use crate::types::FrissError;
+
+/// A generic data tree that parsers produce for deserialization.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    String(String),
+    Seq(Vec<Value>),
+    /// Entries in source order
+    Map(Vec<(Value, Value)>),
+}
+
+impl From<JsonValue> for Value {
+    fn from(value: JsonValue) -> Self {
+        match value {
+            JsonValue::Null => Value::Unit,
+            JsonValue::Bool(b) => Value::Bool(b),
+            JsonValue::Number(n) if n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64 => {
+                Value::I64(n as i64)
+            }
+            JsonValue::Number(n) => Value::F64(n),
+            JsonValue::String(s) => Value::String(s),
+            JsonValue::Array(items) => Value::Seq(items.into_iter().map(Value::from).collect()),
+            JsonValue::Object(members) => Value::Map(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (Value::String(key), value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A value that does not fit the type being deserialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeError(String);
+
+impl Display for DeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+/// Errors of `from_str`: either the input does not parse, or its value does not deserialize.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FromStrError<E> {
+    Parse(FrissError<E>),
+    Deserialize(DeError),
+}
+
+impl<E: Display> Display for FromStrError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FromStrError::Parse(err) => write!(f, "{}", err),
+            FromStrError::Deserialize(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + Display> std::error::Error for FromStrError<E> {}
+
+/// Deserializes a `T` from a `Value`.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, DeError> {
+    T::deserialize(value)
+}
+
+/// Parses the whole of `input` with `parser` and deserializes a `T` from its output.
+pub fn from_str<'a, T, V, E>(parser: &impl Parser<&'a str, V, E>, input: &'a str) -> Result<T, FromStrError<E>>
+where
+    T: DeserializeOwned,
+    V: Into<Value>,
+    E: Clone,
+{
+    let value = parser.parse_complete(input).map_err(FromStrError::Parse)?;
+    from_value(value.into()).map_err(FromStrError::Deserialize)
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Value::Unit => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::I64(n) => visitor.visit_i64(n),
+            Value::U64(n) => visitor.visit_u64(n),
+            Value::F64(n) => visitor.visit_f64(n),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Seq(items) => {
+                let mut seq = SeqDeserializer::new(items.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Map(entries) => {
+                let mut map = MapDeserializer::new(entries.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Value::Unit => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let (variant, content) = match self {
+            Value::String(_) => (self, None),
+            Value::Map(mut entries) if entries.len() == 1 => {
+                let (variant, content) = entries.remove(0);
+                (variant, Some(content))
+            }
+            _ => return Err(de::Error::custom("expected a string or a map with a single entry for an enum")),
+        };
+        visitor.visit_enum(Enum { variant, content })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// The variant name and content of an enum value.
+struct Enum {
+    variant: Value,
+    content: Option<Value>,
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = DeError;
+    type Variant = Variant;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Variant), DeError> {
+        let variant = seed.deserialize(self.variant)?;
+        Ok((variant, Variant(self.content)))
+    }
+}
+
+/// The content of an enum variant, absent for unit variants written as strings.
+struct Variant(Option<Value>);
+
+impl<'de> VariantAccess<'de> for Variant {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        match self.0 {
+            None | Some(Value::Unit) => Ok(()),
+            Some(_) => Err(de::Error::custom("expected a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, DeError> {
+        seed.deserialize(self.0.unwrap_or(Value::Unit))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, DeError> {
+        de::Deserializer::deserialize_seq(self.0.unwrap_or(Value::Unit), visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, DeError> {
+        de::Deserializer::deserialize_map(self.0.unwrap_or(Value::Unit), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Shape {
+        Point,
+        Circle(f64),
+        Line(i32, i32),
+    }
+
+    #[test]
+    fn test_deserialize_values() {
+        let seq = |items: Vec<Value>| Value::Seq(items);
+        assert_eq!(from_value::<Vec<Shape>>(seq(vec![
+            Value::String("Point".into()),
+            Value::Map(vec![(Value::String("Circle".into()), Value::I64(2))]),
+            Value::Map(vec![(Value::String("Line".into()), seq(vec![Value::I64(1), Value::I64(-1)]))]),
+        ])), Ok(vec![Shape::Point, Shape::Circle(2.0), Shape::Line(1, -1)]));
+
+        let map = Value::Map(vec![(Value::String("a".into()), Value::U64(1))]);
+        assert_eq!(from_value::<BTreeMap<String, u8>>(map), Ok(BTreeMap::from([("a".to_string(), 1)])));
+        assert_eq!(from_value::<(bool, ())>(seq(vec![Value::Bool(true), Value::Unit])), Ok((true, ())));
+
+        assert!(from_value::<u8>(Value::I64(300)).is_err());
+        assert!(from_value::<(u8,)>(seq(vec![Value::I64(1), Value::I64(2)])).is_err());
+        assert!(from_value::<Shape>(Value::Map(vec![])).is_err());
+        assert_eq!(Value::from(JsonValue::Number(1.5)), Value::F64(1.5));
+    }
+}
@@ -0,0 +1,223 @@
+//! # Deterministic Dispatch
+//!
+//! An `alt` over many branches tries each branch in turn, so a 30-way choice of keywords
+//! runs up to 30 literal matchers. When the branches start with different items, the next
+//! item already tells which branches can match. `dispatch` peeks at that item and selects
+//! the branches registered for it from a lookup table, trying only those:
+//!
+//! - `on(item, parser)` registers a branch for inputs starting with `item`; several branches
+//!   for the same item are tried in registration order
+//! - `on_each(items, parser)` registers one branch for several items, e.g. all digits
+//! - `otherwise(parser)` is tried when no registered branch matches, like the last
+//!   alternative of an `alt`
+//!
+//! Items with a small index (ASCII characters, bytes) are looked up in a table in constant
+//! time, others in an ordered map. If every tried branch fails, the error of the last one is
+//! returned; if no branch applies, the dispatch fails with its own error without consuming
+//! input.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::dispatch::*;
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Tok<'a> { If, In, Else, Number(&'a str), Ident(&'a str) }
+//!
+//! let digits = |input: &'static str| {
+//!     let end = input.find(|c: char| !c.is_ascii_digit()).unwrap_or(input.len());
+//!     Ok((&input[end..], Tok::Number(&input[..end])))
+//! };
+//! let ident = |input: &'static str| {
+//!     let end = input.find(|c: char| !c.is_alphanumeric()).unwrap_or(input.len());
+//!     if end == 0 { return Err((input, "Expected token")); }
+//!     Ok((&input[end..], Tok::Ident(&input[..end])))
+//! };
+//!
+//! let token = dispatch("Expected token")
+//!     .on('i', "if".make_keyword_matcher("Expected if").map(|_| Tok::If))
+//!     .on('i', "in".make_keyword_matcher("Expected in").map(|_| Tok::In))
+//!     .on('e', "else".make_keyword_matcher("Expected else").map(|_| Tok::Else))
+//!     .on_each('0'..='9', digits)
+//!     .otherwise(ident);
+//!
+//! assert_eq!(token.parse("in x"), Ok((" x", Tok::In)));
+//! assert_eq!(token.parse("42;"), Ok((";", Tok::Number("42"))));
+//! assert_eq!(token.parse("iffy"), Ok(("", Tok::Ident("iffy"))));
+//! assert_eq!(token.parse("+"), Err(("+", "Expected token")));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+
+use crate::core::{Parsable, Parser};
+
+/// Items that `dispatch` can switch on.
+pub trait DispatchKey: Ord + Clone {
+    /// Returns the item's slot in the constant-time lookup table, if it has one.
+    fn table_index(&self) -> Option<u8>;
+}
+
+impl DispatchKey for char {
+    fn table_index(&self) -> Option<u8> {
+        u8::try_from(*self).ok().filter(u8::is_ascii)
+    }
+}
+
+impl DispatchKey for u8 {
+    fn table_index(&self) -> Option<u8> {
+        Some(*self)
+    }
+}
+
+impl DispatchKey for bool {
+    fn table_index(&self) -> Option<u8> {
+        Some(u8::from(*self))
+    }
+}
+
+impl<T: DispatchKey> DispatchKey for &T {
+    fn table_index(&self) -> Option<u8> {
+        (**self).table_index()
+    }
+}
+
+type Branch<'a, I, O, E> = Rc<dyn Parser<I, O, E> + 'a>;
+
+/// A choice selecting its branches by the next input item, built with `dispatch`.
+pub struct Dispatch<'a, I, O, E>
+where
+    I: Parsable<E>,
+    E: Clone,
+{
+    /// Bucket indices of the items with a table index
+    table: Box<[Option<usize>; 256]>,
+    /// Bucket indices of the other items
+    others: BTreeMap<I::Item, usize>,
+    /// The branches registered for each item
+    buckets: Vec<Vec<Branch<'a, I, O, E>>>,
+    fallback: Option<Branch<'a, I, O, E>>,
+    err: E,
+}
+
+/// Creates an empty dispatch, failing with `err` when no branch applies.
+pub fn dispatch<'a, I, O, E>(err: E) -> Dispatch<'a, I, O, E>
+where
+    I: Parsable<E>,
+    E: Clone,
+{
+    Dispatch {
+        table: Box::new([None; 256]),
+        others: BTreeMap::new(),
+        buckets: Vec::new(),
+        fallback: None,
+        err,
+    }
+}
+
+impl<'a, I, O, E> Dispatch<'a, I, O, E>
+where
+    I: Parsable<E>,
+    I::Item: DispatchKey,
+    E: Clone,
+{
+    /// Registers `parser` for inputs starting with `item`.
+    pub fn on(self, item: I::Item, parser: impl Parser<I, O, E> + 'a) -> Self {
+        self.on_each([item], parser)
+    }
+
+    /// Registers `parser` for inputs starting with any of `items`.
+    #[allow(clippy::indexing_slicing)] // A `u8` index is always within the 256 table entries
+    pub fn on_each(mut self, items: impl IntoIterator<Item = I::Item>, parser: impl Parser<I, O, E> + 'a) -> Self {
+        let parser: Branch<'a, I, O, E> = Rc::new(parser);
+        for item in items {
+            let bucket = self.bucket(&item).unwrap_or_else(|| {
+                self.buckets.push(Vec::new());
+                let bucket = self.buckets.len() - 1;
+                match item.table_index() {
+                    Some(index) => self.table[usize::from(index)] = Some(bucket),
+                    None => {
+                        self.others.insert(item, bucket);
+                    }
+                }
+                bucket
+            });
+            if let Some(branches) = self.buckets.get_mut(bucket) {
+                branches.push(parser.clone());
+            }
+        }
+        self
+    }
+
+    /// Sets the parser tried when no registered branch matches, including at the end of input.
+    pub fn otherwise(mut self, parser: impl Parser<I, O, E> + 'a) -> Self {
+        self.fallback = Some(Rc::new(parser));
+        self
+    }
+
+    #[allow(clippy::indexing_slicing)] // A `u8` index is always within the 256 table entries
+    fn bucket(&self, item: &I::Item) -> Option<usize> {
+        match item.table_index() {
+            Some(index) => self.table[usize::from(index)],
+            None => self.others.get(item).copied(),
+        }
+    }
+}
+
+impl<I, O, E> Parser<I, O, E> for Dispatch<'_, I, O, E>
+where
+    I: Parsable<E>,
+    I::Item: DispatchKey,
+    E: Clone,
+{
+    fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
+        let next = I::make_anything_matcher(self.err.clone()).parse(input.clone());
+        let branches = match next {
+            Ok((_, item)) => self.bucket(&item).and_then(|bucket| self.buckets.get(bucket)),
+            Err(_) => None,
+        };
+        let mut tried = branches.into_iter().flatten().chain(&self.fallback).peekable();
+
+        let mut input = input;
+        while let Some(branch) = tried.next() {
+            if tried.peek().is_none() {
+                return branch.parse(input);
+            }
+            let checkpoint = input.checkpoint();
+            match branch.parse(input) {
+                Ok(result) => return Ok(result),
+                Err(_) => input = I::rewind(checkpoint),
+            }
+        }
+        Err((input, self.err.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dispatch_on_bytes_and_wide_characters() {
+        let opcode = dispatch("Unknown opcode")
+            .on(&0x01, [0x01u8, 0x00].as_slice().make_literal_matcher("Expected nop").map(|_| "nop"))
+            .on(&0x01, [0x01u8].as_slice().make_literal_matcher("Expected jump").map(|_| "jump"))
+            .on(&0xff, [0xffu8].as_slice().make_literal_matcher("Expected halt").map(|_| "halt"));
+        let bytes: &[u8] = &[0x01, 0x02, 0xff];
+        assert_eq!(opcode.parse(bytes), Ok((&bytes[1..], "jump")));
+        assert_eq!(opcode.parse(&bytes[2..]), Ok((&bytes[3..], "halt")));
+        assert_eq!(opcode.parse(&bytes[1..]), Err((&bytes[1..], "Unknown opcode")));
+        assert_eq!(opcode.parse(&[]), Err((&[][..], "Unknown opcode")));
+
+        // Characters outside ASCII go to the ordered map; the last branch's error is kept
+        let greeting = dispatch("Expected greeting")
+            .on('こ', "こんにちは".make_literal_matcher("Expected こんにちは"))
+            .on('h', "hello".make_literal_matcher("Expected hello"));
+        assert_eq!(greeting.parse("こんにちは!"), Ok(("!", "こんにちは")));
+        assert_eq!(greeting.parse("こんばんは"), Err(("こんばんは", "Expected こんにちは")));
+        assert_eq!(greeting.parse(""), Err(("", "Expected greeting")));
+    }
+}
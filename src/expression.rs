@@ -0,0 +1,299 @@
+//! # Operator Tables
+//!
+//! `expression(term, levels, combine)` builds a whole precedence hierarchy at once from a
+//! table of operators, like Parsec's `buildExpressionParser`. Each `Level` holds operator
+//! parsers of equal precedence and how they associate; the levels are listed from the
+//! tightest binding to the loosest:
+//!
+//! - `Assoc::Left` and `Assoc::Right` levels hold infix operators, combined like `infixl`
+//!   and `infixr`
+//! - `Assoc::None` levels hold infix operators that do not chain, so `a < b < c` stops
+//!   after `a < b`
+//! - `Assoc::Prefix` and `Assoc::Postfix` levels hold unary operators, which may repeat,
+//!   as in `--x`
+//!
+//! The operators return a value of their own, such as an operator enum, and `combine`
+//! builds the result of each `Operation`. An operator that matches without a following
+//! operand is left unconsumed, and operator errors only end a chain: the expression fails
+//! only with an error of `term`.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::expression::*;
+//!
+//! #[derive(Debug, Clone, Copy)]
+//! enum Op { Neg, Fact, Pow, Mul, Add, Sub }
+//!
+//! let calculator = recursive(|expr| {
+//!     let symbol = |c: char, op: Op| c.make_character_matcher(()).value(op).boxed();
+//!     let digit = make_char_predicate_matcher(|c| c.is_ascii_digit(), "Expected term")
+//!         .map(|c| c.to_digit(10).unwrap() as i64);
+//!     let term = move |input: &'static str| match '('.make_character_matcher("Expected term").parse(input) {
+//!         Ok((rest, _)) => {
+//!             let (rest, value) = expr.parse(rest)?;
+//!             let (rest, _) = ')'.make_character_matcher("Expected )").parse(rest)?;
+//!             Ok((rest, value))
+//!         }
+//!         Err(_) => digit.parse(input),
+//!     };
+//!     let table = [
+//!         Level { assoc: Assoc::Postfix, operators: vec![symbol('!', Op::Fact)] },
+//!         Level { assoc: Assoc::Prefix, operators: vec![symbol('-', Op::Neg)] },
+//!         Level { assoc: Assoc::Right, operators: vec![symbol('^', Op::Pow)] },
+//!         Level { assoc: Assoc::Left, operators: vec![symbol('*', Op::Mul)] },
+//!         Level { assoc: Assoc::Left, operators: vec![symbol('+', Op::Add), symbol('-', Op::Sub)] },
+//!     ];
+//!     Box::new(expression(term, table, |operation| match operation {
+//!         Operation::Prefix(_, x) => -x,
+//!         Operation::Postfix(x, _) => (1..=x).product(),
+//!         Operation::Infix(x, Op::Pow, y) => x.pow(y as u32),
+//!         Operation::Infix(x, Op::Mul, y) => x * y,
+//!         Operation::Infix(x, Op::Add, y) => x + y,
+//!         Operation::Infix(x, _, y) => x - y,
+//!     }))
+//! });
+//!
+//! assert_eq!(calculator.parse("2*3!-(1+2)^2"), Ok(("", 3)));
+//! assert_eq!(calculator.parse("2^3^2"), Ok(("", 512)));
+//! assert_eq!(calculator.parse("--2^2-1"), Ok(("", 3)));
+//! assert_eq!(calculator.parse("1+*"), Ok(("+*", 1)));
+//! assert_eq!(calculator.parse("*1"), Err(("*1", "Expected term")));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::core::{BoxedParser, Parsable, Parser};
+
+/// How the operators of a `Level` apply to their operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    /// Infix operators grouping to the left, `a - b - c` is `(a - b) - c`
+    Left,
+    /// Infix operators grouping to the right, `a ^ b ^ c` is `a ^ (b ^ c)`
+    Right,
+    /// Infix operators that do not chain
+    None,
+    /// Unary operators before their operand
+    Prefix,
+    /// Unary operators after their operand
+    Postfix,
+}
+
+/// Operators of equal precedence in the table of `expression`.
+pub struct Level<'a, I, Op, E> {
+    pub assoc: Assoc,
+    /// The operators, tried in order
+    pub operators: Vec<BoxedParser<'a, I, Op, E>>,
+}
+
+/// An operator applied to its operands, passed to the `combine` function of `expression`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation<O, Op> {
+    Infix(O, Op, O),
+    Prefix(Op, O),
+    Postfix(O, Op),
+}
+
+/// A parser built from an operator table, see `expression`.
+pub struct Expression<'a, I, O, E, Op, OpError> {
+    term: Box<dyn Parser<I, O, E> + 'a>,
+    levels: Vec<Level<'a, I, Op, OpError>>,
+    combine: Box<dyn Fn(Operation<O, Op>) -> O + 'a>,
+}
+
+/// Creates a parser for expressions of `term`s and the operators of `levels`, listed from
+/// the tightest binding to the loosest, building results with `combine`.
+pub fn expression<'a, I, O, E, Op, OpError>(
+    term: impl Parser<I, O, E> + 'a,
+    levels: impl IntoIterator<Item = Level<'a, I, Op, OpError>>,
+    combine: impl Fn(Operation<O, Op>) -> O + 'a,
+) -> Expression<'a, I, O, E, Op, OpError>
+where
+    I: Parsable<E>,
+    E: Clone,
+{
+    Expression {
+        term: Box::new(term),
+        levels: levels.into_iter().collect(),
+        combine: Box::new(combine),
+    }
+}
+
+impl<I, O, E, Op, OpError> Expression<'_, I, O, E, Op, OpError>
+where
+    I: Parsable<E> + Parsable<OpError>,
+    E: Clone,
+    OpError: Clone,
+{
+    /// Parses an operand of the level `index`, i.e. an expression of the tighter levels.
+    fn operand(&self, index: usize, input: I) -> Result<(I, O), (I, E)> {
+        match index.checked_sub(1) {
+            Some(tighter) => self.level(tighter, input),
+            None => self.term.parse(input),
+        }
+    }
+
+    /// Parses one of the operators of `level`, rewinding if none matches.
+    fn operator(level: &Level<'_, I, Op, OpError>, input: I) -> Result<(I, Op), I> {
+        let mut input = input;
+        for operator in &level.operators {
+            let checkpoint = input.checkpoint();
+            match operator.parse(input) {
+                Ok(result) => return Ok(result),
+                Err(_) => input = I::rewind(checkpoint),
+            }
+        }
+        Err(input)
+    }
+
+    /// Parses an operator of `level` and the operand following it, rewinding if either
+    /// fails.
+    fn operator_and_operand(&self, index: usize, level: &Level<'_, I, Op, OpError>, input: I) -> Result<(I, Op, O), I> {
+        let checkpoint = input.checkpoint();
+        let (rest, op) = Self::operator(level, input)?;
+        match self.operand(index, rest) {
+            Ok((rest, operand)) => Ok((rest, op, operand)),
+            Err(_) => Err(I::rewind(checkpoint)),
+        }
+    }
+
+    /// Parses an expression of the levels up to `index`.
+    fn level(&self, index: usize, input: I) -> Result<(I, O), (I, E)> {
+        let Some(level) = self.levels.get(index) else {
+            return self.term.parse(input);
+        };
+        match level.assoc {
+            Assoc::Prefix => {
+                let mut ops = Vec::new();
+                let mut rest = input;
+                loop {
+                    match Self::operator(level, rest) {
+                        Ok((next, op)) => {
+                            ops.push(op);
+                            rest = next;
+                        }
+                        Err(next) => {
+                            rest = next;
+                            break;
+                        }
+                    }
+                }
+                let (rest, operand) = self.operand(index, rest)?;
+                let value = ops
+                    .into_iter()
+                    .rev()
+                    .fold(operand, |operand, op| (self.combine)(Operation::Prefix(op, operand)));
+                Ok((rest, value))
+            }
+            Assoc::Postfix => {
+                let (mut rest, mut value) = self.operand(index, input)?;
+                loop {
+                    match Self::operator(level, rest) {
+                        Ok((next, op)) => {
+                            value = (self.combine)(Operation::Postfix(value, op));
+                            rest = next;
+                        }
+                        Err(next) => return Ok((next, value)),
+                    }
+                }
+            }
+            Assoc::Left => {
+                let (mut rest, mut value) = self.operand(index, input)?;
+                loop {
+                    match self.operator_and_operand(index, level, rest) {
+                        Ok((next, op, right)) => {
+                            value = (self.combine)(Operation::Infix(value, op, right));
+                            rest = next;
+                        }
+                        Err(next) => return Ok((next, value)),
+                    }
+                }
+            }
+            Assoc::Right => {
+                // Collect the operands with the operator following each, then combine them
+                // from the right
+                let (mut rest, mut last) = self.operand(index, input)?;
+                let mut lefts = Vec::new();
+                loop {
+                    match self.operator_and_operand(index, level, rest) {
+                        Ok((next, op, right)) => {
+                            lefts.push((core::mem::replace(&mut last, right), op));
+                            rest = next;
+                        }
+                        Err(next) => {
+                            rest = next;
+                            break;
+                        }
+                    }
+                }
+                let value = lefts
+                    .into_iter()
+                    .rev()
+                    .fold(last, |right, (left, op)| (self.combine)(Operation::Infix(left, op, right)));
+                Ok((rest, value))
+            }
+            Assoc::None => {
+                let (rest, left) = self.operand(index, input)?;
+                match self.operator_and_operand(index, level, rest) {
+                    Ok((rest, op, right)) => Ok((rest, (self.combine)(Operation::Infix(left, op, right)))),
+                    Err(rest) => Ok((rest, left)),
+                }
+            }
+        }
+    }
+}
+
+impl<I, O, E, Op, OpError> Parser<I, O, E> for Expression<'_, I, O, E, Op, OpError>
+where
+    I: Parsable<E> + Parsable<OpError>,
+    E: Clone,
+    OpError: Clone,
+{
+    fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
+        match self.levels.len().checked_sub(1) {
+            Some(loosest) => self.level(loosest, input),
+            None => self.term.parse(input),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::string::String;
+    use crate::core::ParsableItem;
+
+    #[test]
+    fn test_operator_table_grouping() {
+        let symbol = |c: char| ParsableItem::<&str, _>::make_character_matcher(c, ()).boxed();
+        let atom = crate::parsers::make_char_predicate_matcher(|c| c.is_ascii_lowercase(), "Expected atom")
+            .map(String::from);
+        let table = [
+            Level { assoc: Assoc::Postfix, operators: alloc::vec![symbol('?')] },
+            Level { assoc: Assoc::Prefix, operators: alloc::vec![symbol('!'), symbol('~')] },
+            Level { assoc: Assoc::None, operators: alloc::vec![symbol('<')] },
+            Level { assoc: Assoc::Right, operators: alloc::vec![symbol('&')] },
+            Level { assoc: Assoc::Left, operators: alloc::vec![symbol('|')] },
+        ];
+        let parser = expression(atom, table, |operation| match operation {
+            Operation::Infix(x, op, y) => format!("({}{}{})", x, op, y),
+            Operation::Prefix(op, x) => format!("{}{}", op, x),
+            Operation::Postfix(x, op) => format!("{}{}", x, op),
+        });
+
+        assert_eq!(parser.parse("a|b|c&d&e"), Ok(("", String::from("((a|b)|(c&(d&e)))"))));
+        assert_eq!(parser.parse("!~a??<b|c"), Ok(("", String::from("((!~a??<b)|c)"))));
+        // A non-associative operator does not chain, and a dangling operator is left over
+        assert_eq!(parser.parse("a<b<c"), Ok(("<c", String::from("(a<b)"))));
+        assert_eq!(parser.parse("a&b|"), Ok(("|", String::from("(a&b)"))));
+        assert_eq!(parser.parse("!"), Err(("", "Expected atom")));
+
+        // Without levels, only terms are parsed
+        let plain = expression(crate::core::pure::<&str, _, ()>(1), [] as [Level<&str, (), ()>; 0], |_| 0);
+        assert_eq!(plain.parse("x"), Ok(("x", 1)));
+    }
+}
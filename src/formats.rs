@@ -0,0 +1,11 @@
+//! # Data Formats
+//!
+//! Reusable parsers for common data formats, built from the combinators of this crate:
+//!
+//! - `csv` parses RFC 4180 comma-separated values
+//! - `json` parses RFC 8259 JSON, optionally keeping the span of every value
+//! - `ini` parses INI configuration files with sections, comments and quoted values
+
+pub mod csv;
+pub mod ini;
+pub mod json;
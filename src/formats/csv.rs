@@ -0,0 +1,225 @@
+//! # CSV
+//!
+//! Parsers for comma-separated values as described by RFC 4180. Fields may be quoted, a
+//! doubled quote inside a quoted field stands for one quote, and quoted fields may span
+//! lines. Records end with `\r\n` or `\n`; the last record may also end at the end of input.
+//! The delimiter and quote characters are configurable with `CsvConfig`.
+//!
+//! Fields are returned as `Cow<str>`, borrowing from the input unless a quote had to be
+//! unescaped. `csv_record` parses one record, so `csv_record(config).iter(input)` processes a
+//! large file one record at a time; `csv_document` parses all records at once.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::formats::csv::*;
+//!
+//! let input = "name,quote\r\nada,\"said \"\"hi\"\"\"\r\nbob,\"two\nlines\"\r\n";
+//! let records = csv_document(CsvConfig::new()).parse_complete(input).unwrap();
+//!
+//! assert_eq!(records[0], vec!["name", "quote"]);
+//! assert_eq!(records[1], vec!["ada", "said \"hi\""]);
+//! assert_eq!(records[2], vec!["bob", "two\nlines"]);
+//!
+//! // Records one at a time, with a different delimiter
+//! let record = csv_record(CsvConfig::new().delimiter(';'));
+//! let mut rows = record.iter("a;b\nc;d");
+//! assert_eq!(rows.next(), Some(vec!["a".into(), "b".into()]));
+//! assert_eq!(rows.next(), Some(vec!["c".into(), "d".into()]));
+//! assert_eq!(rows.next(), None);
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use crate::core::Parser;
+
+/// The delimiter and quote characters of a CSV dialect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvConfig {
+    delimiter: char,
+    quote: char,
+}
+
+impl Default for CsvConfig {
+    fn default() -> Self {
+        CsvConfig {
+            delimiter: ',',
+            quote: '"',
+        }
+    }
+}
+
+impl CsvConfig {
+    /// Creates the RFC 4180 dialect, with `,` as delimiter and `"` as quote.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the character separating fields.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets the character quoting fields.
+    pub fn quote(mut self, quote: char) -> Self {
+        self.quote = quote;
+        self
+    }
+}
+
+/// Errors of the CSV parsers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvError {
+    /// There is no record left
+    EndOfInput,
+    /// A quoted field is not closed
+    UnterminatedQuote,
+    /// A quote appears inside an unquoted field
+    UnexpectedQuote,
+    /// A closing quote is not followed by a delimiter or the end of the record
+    ExpectedDelimiter,
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::EndOfInput => write!(f, "expected a record"),
+            CsvError::UnterminatedQuote => write!(f, "unterminated quoted field"),
+            CsvError::UnexpectedQuote => write!(f, "unexpected quote in unquoted field"),
+            CsvError::ExpectedDelimiter => write!(f, "expected delimiter or end of record after quoted field"),
+        }
+    }
+}
+
+impl core::error::Error for CsvError {}
+
+/// Returns the input after a record terminator, if it starts with one.
+fn strip_line_end(input: &str) -> Option<&str> {
+    input.strip_prefix("\r\n").or_else(|| input.strip_prefix('\n'))
+}
+
+fn is_field_end(config: CsvConfig, rest: &str) -> bool {
+    rest.is_empty() || rest.starts_with(config.delimiter) || strip_line_end(rest).is_some()
+}
+
+/// Parses a single field, leaving the delimiter or record terminator after it unconsumed.
+pub fn csv_field<'a>(config: CsvConfig) -> impl Parser<&'a str, Cow<'a, str>, CsvError> {
+    move |input: &'a str| {
+        let Some(body) = input.strip_prefix(config.quote) else {
+            let end = input
+                .find([config.delimiter, '\n', config.quote])
+                .unwrap_or(input.len());
+            let (field, rest) = input.split_at(end);
+            if rest.starts_with(config.quote) {
+                return Err((rest, CsvError::UnexpectedQuote));
+            }
+            // A `\r` belongs to the terminator only when it precedes `\n`
+            let field = if rest.starts_with('\n') {
+                field.strip_suffix('\r').unwrap_or(field)
+            } else {
+                field
+            };
+            let rest = input.get(field.len()..).unwrap_or(rest);
+            return Ok((rest, Cow::Borrowed(field)));
+        };
+
+        let mut field = Cow::Borrowed("");
+        let mut rest = body;
+        loop {
+            let Some((chunk, after)) = rest.split_once(config.quote) else {
+                return Err((input, CsvError::UnterminatedQuote));
+            };
+            match after.strip_prefix(config.quote) {
+                // A doubled quote stands for one quote
+                Some(next) => {
+                    let owned = field.to_mut();
+                    owned.push_str(chunk);
+                    owned.push(config.quote);
+                    rest = next;
+                }
+                None => {
+                    match &mut field {
+                        Cow::Borrowed(_) => field = Cow::Borrowed(chunk),
+                        Cow::Owned(owned) => owned.push_str(chunk),
+                    }
+                    if !is_field_end(config, after) {
+                        return Err((after, CsvError::ExpectedDelimiter));
+                    }
+                    return Ok((after, field));
+                }
+            }
+        }
+    }
+}
+
+/// Parses a record and its terminator. Fails with `CsvError::EndOfInput` on empty input.
+pub fn csv_record<'a>(config: CsvConfig) -> impl Parser<&'a str, Vec<Cow<'a, str>>, CsvError> {
+    let field = csv_field(config);
+    move |input: &'a str| {
+        if input.is_empty() {
+            return Err((input, CsvError::EndOfInput));
+        }
+        let mut fields = Vec::new();
+        let mut rest = input;
+        loop {
+            let (next, value) = field.parse(rest)?;
+            fields.push(value);
+            match next.strip_prefix(config.delimiter) {
+                Some(next) => rest = next,
+                None => return Ok((strip_line_end(next).unwrap_or(next), fields)),
+            }
+        }
+    }
+}
+
+/// Parses all records up to the end of input, stopping at the first malformed record.
+///
+/// A trailing empty line ends the document rather than adding an empty record.
+pub fn csv_document<'a>(config: CsvConfig) -> impl Parser<&'a str, Vec<Vec<Cow<'a, str>>>, CsvError> {
+    let record = csv_record(config);
+    move |input: &'a str| {
+        let mut records = Vec::new();
+        let mut rest = input;
+        while !rest.is_empty() {
+            if let Some("") = strip_line_end(rest) {
+                return Ok(("", records));
+            }
+            let (next, fields) = record.parse(rest)?;
+            records.push(fields);
+            rest = next;
+        }
+        Ok((rest, records))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn test_csv_quoting_and_errors() {
+        let field = csv_field(CsvConfig::new());
+        assert_eq!(field.parse("\"a,b\",c"), Ok((",c", Cow::Borrowed("a,b"))));
+        assert!(matches!(field.parse("\"a\"\"b\"\n").unwrap().1, Cow::Owned(ref s) if s == "a\"b"));
+        assert_eq!(field.parse("\"open"), Err(("\"open", CsvError::UnterminatedQuote)));
+        assert_eq!(field.parse("\"a\"b"), Err(("b", CsvError::ExpectedDelimiter)));
+        assert_eq!(field.parse("a\"b"), Err(("\"b", CsvError::UnexpectedQuote)));
+
+        let record = csv_record(CsvConfig::new().quote('\''));
+        assert_eq!(record.parse("a,,'x'\r\nnext"), Ok(("next", vec!["a".into(), "".into(), "x".into()])));
+        assert_eq!(record.parse("a\rb\n"), Ok(("", vec![String::from("a\rb").into()])));
+        assert_eq!(record.parse("\n"), Ok(("", vec!["".into()])));
+        assert_eq!(record.parse(""), Err(("", CsvError::EndOfInput)));
+
+        let document = csv_document(CsvConfig::new());
+        assert_eq!(document.parse("a\nb\"\n"), Err(("\"\n", CsvError::UnexpectedQuote)));
+        assert_eq!(document.parse("a,b\n\n"), Ok(("", vec![vec!["a".into(), "b".into()]])));
+        assert_eq!(document.parse("a\r\n\r\n"), Ok(("", vec![vec!["a".into()]])));
+        assert_eq!(document.parse("a\n\nb"), Ok(("", vec![vec!["a".into()], vec!["".into()], vec!["b".into()]])));
+    }
+}
@@ -0,0 +1,293 @@
+//! # INI
+//!
+//! Parsers for INI style configuration files, in the TOML-lite flavour most tools accept:
+//!
+//! - `[name]` starts a section; entries before the first header belong to the global section
+//! - `key = value` defines an entry; keys and unquoted values are trimmed
+//! - lines starting with `;` or `#` are comments, and so is the rest of a line after a `;`
+//!   or `#` that follows whitespace
+//! - `"..."` values decode escape sequences like Rust strings, `'...'` values are taken
+//!   literally with `''` standing for one quote
+//!
+//! Lines end with `\r\n` or `\n`. Keys, section names and values without escapes borrow from
+//! the input.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::formats::ini::*;
+//!
+//! let config = "\
+//! ; global settings
+//! name = friss
+//!
+//! [server]
+//! host = localhost   # inline comment
+//! motd = \"hello\\tworld\"
+//! path = 'C:\\temp'
+//! ";
+//! let ini = ini_document().parse_complete(config).unwrap();
+//!
+//! assert_eq!(ini.get("", "name"), Some("friss"));
+//! assert_eq!(ini.get("server", "host"), Some("localhost"));
+//! assert_eq!(ini.get("server", "motd"), Some("hello\tworld"));
+//! assert_eq!(ini.get("server", "path"), Some("C:\\temp"));
+//! assert_eq!(ini.get("server", "port"), None);
+//!
+//! let err = ini_document().parse_complete("[server\nhost = localhost").unwrap_err();
+//! assert_eq!(err.to_string(), "0:7: expected ']'");
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use crate::core::Parser;
+use crate::parsers::text::{escaped_str, EscapeTable};
+
+/// A `key = value` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IniEntry<'a> {
+    pub key: &'a str,
+    pub value: Cow<'a, str>,
+}
+
+/// A section and its entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IniSection<'a> {
+    pub name: &'a str,
+    pub entries: Vec<IniEntry<'a>>,
+}
+
+/// A parsed configuration file. Sections and entries keep their order and duplicates.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IniDocument<'a> {
+    /// Entries before the first section header
+    pub global: Vec<IniEntry<'a>>,
+    pub sections: Vec<IniSection<'a>>,
+}
+
+impl IniDocument<'_> {
+    /// Returns the value of `key` in `section`, or in the global section if `section` is
+    /// empty. When a key is defined several times, the last definition wins.
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        if section.is_empty() {
+            return lookup(&self.global, key);
+        }
+        self.sections
+            .iter()
+            .rev()
+            .filter(|s| s.name == section)
+            .find_map(|s| lookup(&s.entries, key))
+    }
+}
+
+/// Returns the value of the last entry named `key`.
+fn lookup<'e>(entries: &'e [IniEntry<'_>], key: &str) -> Option<&'e str> {
+    entries
+        .iter()
+        .rev()
+        .find(|entry| entry.key == key)
+        .map(|entry| entry.value.as_ref())
+}
+
+/// Errors of the INI parsers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IniError {
+    /// A section header is not closed
+    ExpectedBracket,
+    /// A section header has an empty name
+    EmptySectionName,
+    /// A line is neither a header, an entry nor a comment
+    ExpectedEquals,
+    /// An entry has an empty key
+    EmptyKey,
+    /// A quoted value is not closed
+    UnterminatedQuote,
+    /// An escape sequence in a `"..."` value is unknown
+    InvalidEscape,
+    /// Something other than a comment follows a header or quoted value
+    ExpectedLineEnd,
+}
+
+impl Display for IniError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IniError::ExpectedBracket => write!(f, "expected ']'"),
+            IniError::EmptySectionName => write!(f, "empty section name"),
+            IniError::ExpectedEquals => write!(f, "expected '='"),
+            IniError::EmptyKey => write!(f, "empty key"),
+            IniError::UnterminatedQuote => write!(f, "unterminated quoted value"),
+            IniError::InvalidEscape => write!(f, "invalid escape sequence"),
+            IniError::ExpectedLineEnd => write!(f, "expected end of line"),
+        }
+    }
+}
+
+impl core::error::Error for IniError {}
+
+fn skip_spaces(input: &str) -> &str {
+    input.trim_start_matches([' ', '\t'])
+}
+
+fn is_comment_start(c: char) -> bool {
+    c == ';' || c == '#'
+}
+
+/// Splits off the rest of the current line, excluding the line terminator.
+fn split_line(input: &str) -> (&str, &str) {
+    let end = input.find('\n').unwrap_or(input.len());
+    let (line, rest) = input.split_at_checked(end).unwrap_or((input, ""));
+    (line.strip_suffix('\r').unwrap_or(line), rest)
+}
+
+/// Consumes trailing spaces, an optional comment and the line terminator.
+fn line_end(input: &str) -> Result<&str, (&str, IniError)> {
+    let rest = skip_spaces(input);
+    let rest = if rest.starts_with(is_comment_start) { split_line(rest).1 } else { rest };
+    match rest.strip_prefix("\r\n").or_else(|| rest.strip_prefix('\n')) {
+        Some(rest) => Ok(rest),
+        None if rest.is_empty() => Ok(rest),
+        None => Err((rest, IniError::ExpectedLineEnd)),
+    }
+}
+
+/// Skips blank lines and comment lines.
+fn skip_blank_lines(input: &str) -> &str {
+    let mut rest = input;
+    loop {
+        let line = skip_spaces(rest);
+        match line_end(line) {
+            Ok(next) if next.len() < rest.len() => rest = next,
+            _ => return rest,
+        }
+    }
+}
+
+/// Parses a `[name]` header line, returning the trimmed name.
+pub fn ini_section_header<'a>() -> impl Parser<&'a str, &'a str, IniError> {
+    move |input: &'a str| {
+        let Some(body) = skip_spaces(input).strip_prefix('[') else {
+            return Err((input, IniError::ExpectedBracket));
+        };
+        let (line, _) = split_line(body);
+        let Some((name, _)) = line.split_once(']') else {
+            return Err((body.get(line.len()..).unwrap_or(body), IniError::ExpectedBracket));
+        };
+        let after = body.get(name.len() + 1..).unwrap_or_default();
+        let name = name.trim();
+        if name.is_empty() {
+            return Err((body, IniError::EmptySectionName));
+        }
+        Ok((line_end(after)?, name))
+    }
+}
+
+/// Parses a `"..."` or `'...'` value.
+fn quoted_value<'a>() -> impl Parser<&'a str, Cow<'a, str>, IniError> {
+    let double = escaped_str('"', '\\', EscapeTable::standard(), ());
+    let single = escaped_str('\'', '\'', EscapeTable::new().escape('\'', '\''), ());
+    move |input: &'a str| {
+        let result = if input.starts_with('"') { double.parse(input) } else { single.parse(input) };
+        // The string parsers fail at the opening quote if unterminated, else at the escape
+        result.map_err(|(rest, ())| {
+            let err = if rest.len() == input.len() { IniError::UnterminatedQuote } else { IniError::InvalidEscape };
+            (rest, err)
+        })
+    }
+}
+
+/// Returns the start of `line` up to a comment after whitespace, which ends an unquoted
+/// value.
+fn before_comment(line: &str) -> &str {
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        if is_comment_start(c) && previous.is_whitespace() {
+            return line.get(..i).unwrap_or(line);
+        }
+        previous = c;
+    }
+    line
+}
+
+/// Parses a `key = value` line.
+pub fn ini_entry<'a>() -> impl Parser<&'a str, IniEntry<'a>, IniError> {
+    let quoted = quoted_value();
+    move |input: &'a str| {
+        let start = skip_spaces(input);
+        let (line, _) = split_line(start);
+        let Some((key, _)) = line.split_once('=') else {
+            return Err((start.get(line.len()..).unwrap_or(start), IniError::ExpectedEquals));
+        };
+        let value_start = skip_spaces(start.get(key.len() + 1..).unwrap_or_default());
+        let key = key.trim_end();
+        if key.is_empty() {
+            return Err((start, IniError::EmptyKey));
+        }
+        if value_start.starts_with(['"', '\'']) {
+            let (rest, value) = quoted.parse(value_start)?;
+            return Ok((line_end(rest)?, IniEntry { key, value }));
+        }
+        let (value_line, _) = split_line(value_start);
+        let value = before_comment(value_line).trim_end();
+        let rest = value_start.get(value.len()..).unwrap_or_default();
+        Ok((line_end(rest)?, IniEntry { key, value: Cow::Borrowed(value) }))
+    }
+}
+
+/// Parses a whole configuration file up to the end of input.
+pub fn ini_document<'a>() -> impl Parser<&'a str, IniDocument<'a>, IniError> {
+    let header = ini_section_header();
+    let entry = ini_entry();
+    move |input: &'a str| {
+        let mut document = IniDocument::default();
+        let mut rest = skip_blank_lines(input);
+        while !rest.is_empty() {
+            if skip_spaces(rest).starts_with('[') {
+                let (next, name) = header.parse(rest)?;
+                document.sections.push(IniSection { name, entries: Vec::new() });
+                rest = next;
+            } else {
+                let (next, entry) = entry.parse(rest)?;
+                match document.sections.last_mut() {
+                    Some(section) => section.entries.push(entry),
+                    None => document.global.push(entry),
+                }
+                rest = next;
+            }
+            rest = skip_blank_lines(rest);
+        }
+        Ok((rest, document))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ini_values_and_errors() {
+        let entry = ini_entry();
+        let value = |line| entry.parse(line).map(|(rest, e)| (rest, e.key, e.value));
+        assert_eq!(value("a=b#c\nx"), Ok(("x", "a", Cow::Borrowed("b#c"))));
+        assert_eq!(value("a = b ;c\r\n"), Ok(("", "a", Cow::Borrowed("b"))));
+        assert_eq!(value("a =\n"), Ok(("", "a", Cow::Borrowed(""))));
+        assert_eq!(value("a = 'it''s' ; note"), Ok(("", "a", Cow::Borrowed("it's"))));
+        assert_eq!(value("a = \"x\" y"), Err(("y", IniError::ExpectedLineEnd)));
+        assert_eq!(value("a = \"x"), Err(("\"x", IniError::UnterminatedQuote)));
+        assert_eq!(value("a = \"\\q\""), Err(("\\q\"", IniError::InvalidEscape)));
+        assert_eq!(value(" = b"), Err(("= b", IniError::EmptyKey)));
+        assert_eq!(value("just words\nk = v"), Err(("\nk = v", IniError::ExpectedEquals)));
+
+        let header = ini_section_header();
+        assert_eq!(header.parse(" [ a.b ] # c\n"), Ok(("", "a.b")));
+        assert_eq!(header.parse("[]"), Err(("]", IniError::EmptySectionName)));
+        assert_eq!(header.parse("[a] b"), Err(("b", IniError::ExpectedLineEnd)));
+
+        let (_, ini) = ini_document().parse("k = 1\n[s]\nk = 2\n[t]\n[s]\nk = 3\n").unwrap();
+        assert_eq!(ini.global.len(), 1);
+        assert_eq!(ini.sections.len(), 3);
+        assert_eq!((ini.get("", "k"), ini.get("s", "k"), ini.get("t", "k")), (Some("1"), Some("3"), None));
+    }
+}
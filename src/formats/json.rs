@@ -0,0 +1,390 @@
+//! # JSON
+//!
+//! A parser for JSON as specified by RFC 8259: all escape sequences including surrogate
+//! pairs, numbers with fractions and exponents, and whitespace between tokens.
+//!
+//! `json_value` returns a plain `JsonValue`. `json_node` returns a `JsonNode` tree in which
+//! every value and object key carries its `Span`, counted in bytes from the start of the
+//! input given to the parser. Both skip whitespace around the value and leave the rest of
+//! the input unconsumed, so `parse_complete` parses a whole document and reports the line
+//! and column of the failing byte, while `iter` reads a stream of concatenated values.
+//!
+//! Arrays and objects nest at most `DEFAULT_MAX_DEPTH` levels deep, so that hostile input
+//! fails with `JsonError::TooDeep` instead of overflowing the stack. `json_node_with_max_depth`
+//! and `json_value_with_max_depth` choose another limit.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::formats::json::*;
+//!
+//! let value = json_value().parse_complete(r#"{"name": "café", "tags": [1, -2.5e3]}"#);
+//! assert_eq!(
+//!     value,
+//!     Ok(JsonValue::Object(vec![
+//!         ("name".to_string(), JsonValue::String("café".to_string())),
+//!         ("tags".to_string(), JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(-2500.0)])),
+//!     ]))
+//! );
+//!
+//! // Errors point at the failing byte
+//! let err = json_value().parse_complete("[1,\n 2 3]").unwrap_err();
+//! assert_eq!(err.offset, 7);
+//! assert_eq!(err.to_string(), "1:3: expected ',' or ']'");
+//!
+//! // Spans of values and keys
+//! let (_, node) = json_node().parse(r#" {"a": [true]}"#).unwrap();
+//! let JsonKind::Object(members) = &node.kind else { panic!() };
+//! assert_eq!(node.span, parsers::Span::new(1, 14));
+//! assert_eq!(members[0].key_span, parsers::Span::new(2, 5));
+//! assert_eq!(members[0].value.span, parsers::Span::new(7, 13));
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use crate::core::{Parsable, ParsableItem, Parser};
+use crate::parsers::text::{escaped_str, EscapeTable};
+use crate::parsers::{consumed, Span};
+
+/// A JSON value. Object members keep their order and duplicate keys.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+/// A JSON value annotated with the span of its source text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonNode {
+    pub span: Span,
+    pub kind: JsonKind,
+}
+
+/// The value of a `JsonNode`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonKind {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonNode>),
+    Object(Vec<JsonMember>),
+}
+
+/// An object member of a `JsonNode`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonMember {
+    pub key: String,
+    /// The span of the key, including its quotes
+    pub key_span: Span,
+    pub value: JsonNode,
+}
+
+impl JsonNode {
+    /// Drops the spans, returning the plain value.
+    pub fn into_value(self) -> JsonValue {
+        match self.kind {
+            JsonKind::Null => JsonValue::Null,
+            JsonKind::Bool(b) => JsonValue::Bool(b),
+            JsonKind::Number(n) => JsonValue::Number(n),
+            JsonKind::String(s) => JsonValue::String(s),
+            JsonKind::Array(items) => JsonValue::Array(items.into_iter().map(JsonNode::into_value).collect()),
+            JsonKind::Object(members) => JsonValue::Object(
+                members
+                    .into_iter()
+                    .map(|member| (member.key, member.value.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<JsonNode> for JsonValue {
+    fn from(node: JsonNode) -> Self {
+        node.into_value()
+    }
+}
+
+/// Errors of the JSON parsers. The remaining input of a failed parse starts at the
+/// offending byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonError {
+    /// No value starts here
+    ExpectedValue,
+    /// An object member does not start with a string key
+    ExpectedKey,
+    /// A key is not followed by `:`
+    ExpectedColon,
+    /// An array element is not followed by `,` or `]`
+    ExpectedCommaOrBracket,
+    /// An object member is not followed by `,` or `}`
+    ExpectedCommaOrBrace,
+    /// A string is not closed
+    UnterminatedString,
+    /// An escape sequence is unknown or malformed
+    InvalidEscape,
+    /// A string contains an unescaped control character
+    ControlCharacter,
+    /// A number is malformed
+    InvalidNumber,
+    /// An array or object is nested deeper than the limit
+    TooDeep,
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::ExpectedValue => write!(f, "expected a value"),
+            JsonError::ExpectedKey => write!(f, "expected a string key"),
+            JsonError::ExpectedColon => write!(f, "expected ':'"),
+            JsonError::ExpectedCommaOrBracket => write!(f, "expected ',' or ']'"),
+            JsonError::ExpectedCommaOrBrace => write!(f, "expected ',' or '}}'"),
+            JsonError::UnterminatedString => write!(f, "unterminated string"),
+            JsonError::InvalidEscape => write!(f, "invalid escape sequence"),
+            JsonError::ControlCharacter => write!(f, "unescaped control character in string"),
+            JsonError::InvalidNumber => write!(f, "invalid number"),
+            JsonError::TooDeep => write!(f, "nesting too deep"),
+        }
+    }
+}
+
+impl core::error::Error for JsonError {}
+
+/// The number of nested arrays and objects accepted by `json_node` and `json_value`.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+fn skip_whitespace(input: &str) -> &str {
+    input.trim_start_matches([' ', '\t', '\n', '\r'])
+}
+
+/// Parses a string, decoding its escape sequences.
+fn string<'a>() -> impl Parser<&'a str, String, JsonError> {
+    let string = escaped_str('"', '\\', EscapeTable::json(), ());
+    move |input: &'a str| match string.parse(input) {
+        Ok((rest, content)) => {
+            let raw = consumed(input, rest);
+            match raw.find(|c: char| c < ' ') {
+                Some(at) => Err((input.get(at..).unwrap_or(input), JsonError::ControlCharacter)),
+                None => Ok((rest, content.into_owned())),
+            }
+        }
+        // `escaped_str` fails at the escape character or, if unterminated, at the opening quote
+        Err((rest, ())) if rest.starts_with('\\') => Err((rest, JsonError::InvalidEscape)),
+        Err((rest, ())) => Err((rest, JsonError::UnterminatedString)),
+    }
+}
+
+/// Skips one or more ASCII digits.
+fn digits(input: &str) -> Result<&str, (&str, JsonError)> {
+    let rest = input.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == input.len() {
+        return Err((input, JsonError::InvalidNumber));
+    }
+    Ok(rest)
+}
+
+/// Parses `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`.
+fn number<'a>() -> impl Parser<&'a str, f64, JsonError> {
+    move |input: &'a str| {
+        let unsigned = input.strip_prefix('-').unwrap_or(input);
+        let mut rest = match unsigned.strip_prefix('0') {
+            Some(rest) => rest,
+            None => digits(unsigned)?,
+        };
+        if let Some(fraction) = rest.strip_prefix('.') {
+            rest = digits(fraction)?;
+        }
+        if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+            rest = digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent))?;
+        }
+        match consumed(input, rest).parse() {
+            Ok(number) => Ok((rest, number)),
+            Err(_) => Err((input, JsonError::InvalidNumber)),
+        }
+    }
+}
+
+/// Parses the elements of an array and its closing bracket, after the opening one.
+/// Elements may nest `depth` more levels.
+fn array<'a>(base: usize, depth: usize) -> impl Parser<&'a str, Vec<JsonNode>, JsonError> {
+    move |input: &'a str| {
+        let mut items = Vec::new();
+        let mut rest = skip_whitespace(input);
+        if let Some(rest) = rest.strip_prefix(']') {
+            return Ok((rest, items));
+        }
+        loop {
+            let (after, item) = node(base, depth).parse(rest)?;
+            items.push(item);
+            rest = skip_whitespace(after);
+            let mut chars = rest.chars();
+            match chars.next() {
+                Some(',') => rest = skip_whitespace(chars.as_str()),
+                Some(']') => return Ok((chars.as_str(), items)),
+                _ => return Err((rest, JsonError::ExpectedCommaOrBracket)),
+            }
+        }
+    }
+}
+
+/// Parses the members of an object and its closing brace, after the opening one.
+/// Values may nest `depth` more levels.
+fn object<'a>(base: usize, depth: usize) -> impl Parser<&'a str, Vec<JsonMember>, JsonError> {
+    let colon = ':'.make_character_matcher(JsonError::ExpectedColon);
+    move |input: &'a str| {
+        let mut members = Vec::new();
+        let mut rest = skip_whitespace(input);
+        if let Some(rest) = rest.strip_prefix('}') {
+            return Ok((rest, members));
+        }
+        loop {
+            if !rest.starts_with('"') {
+                return Err((rest, JsonError::ExpectedKey));
+            }
+            let (after, key) = string().parse(rest)?;
+            let key_span = Span::new(base - rest.len(), base - after.len());
+            let (after, _) = colon.parse(skip_whitespace(after))?;
+            let (after, value) = node(base, depth).parse(skip_whitespace(after))?;
+            members.push(JsonMember { key, key_span, value });
+            rest = skip_whitespace(after);
+            let mut chars = rest.chars();
+            match chars.next() {
+                Some(',') => rest = skip_whitespace(chars.as_str()),
+                Some('}') => return Ok((chars.as_str(), members)),
+                _ => return Err((rest, JsonError::ExpectedCommaOrBrace)),
+            }
+        }
+    }
+}
+
+/// Parses a value without surrounding whitespace, in which arrays and objects nest at most
+/// `depth` levels. Spans are offsets into an input of length `base`.
+fn node<'a>(base: usize, depth: usize) -> impl Parser<&'a str, JsonNode, JsonError> {
+    move |input: &'a str| {
+        let literal = |text: &'a str, kind: JsonKind| {
+            text.make_literal_matcher(JsonError::ExpectedValue)
+                .map(move |_| kind.clone())
+                .parse(input)
+        };
+        let mut chars = input.chars();
+        let (rest, kind) = match chars.next() {
+            Some('n') => literal("null", JsonKind::Null)?,
+            Some('t') => literal("true", JsonKind::Bool(true))?,
+            Some('f') => literal("false", JsonKind::Bool(false))?,
+            Some('"') => string().map(JsonKind::String).parse(input)?,
+            Some('-' | '0'..='9') => number().map(JsonKind::Number).parse(input)?,
+            Some('[' | '{') if depth == 0 => return Err((input, JsonError::TooDeep)),
+            Some('[') => array(base, depth - 1).map(JsonKind::Array).parse(chars.as_str())?,
+            Some('{') => object(base, depth - 1).map(JsonKind::Object).parse(chars.as_str())?,
+            _ => return Err((input, JsonError::ExpectedValue)),
+        };
+        let span = Span::new(base - input.len(), base - rest.len());
+        Ok((rest, JsonNode { span, kind }))
+    }
+}
+
+/// Parses a JSON value surrounded by optional whitespace, annotating it with spans relative
+/// to the start of the input.
+pub fn json_node<'a>() -> impl Parser<&'a str, JsonNode, JsonError> {
+    json_node_with_max_depth(DEFAULT_MAX_DEPTH)
+}
+
+/// Like `json_node`, but fails with `JsonError::TooDeep` at an array or object nested more
+/// than `max_depth` levels deep.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::formats::json::*;
+///
+/// let shallow = json_node_with_max_depth(2);
+///
+/// assert!(shallow.parse("[[1]]").is_ok());
+/// assert_eq!(shallow.parse("[[[1]]]").map(|_| ()), Err(("[1]]]", JsonError::TooDeep)));
+/// ```
+pub fn json_node_with_max_depth<'a>(max_depth: usize) -> impl Parser<&'a str, JsonNode, JsonError> {
+    move |input: &'a str| {
+        let (rest, node) = node(input.len(), max_depth).parse(skip_whitespace(input))?;
+        Ok((skip_whitespace(rest), node))
+    }
+}
+
+/// Parses a JSON value surrounded by optional whitespace.
+pub fn json_value<'a>() -> impl Parser<&'a str, JsonValue, JsonError> {
+    json_node().map(JsonNode::into_value)
+}
+
+/// Like `json_value`, but fails with `JsonError::TooDeep` at an array or object nested more
+/// than `max_depth` levels deep.
+pub fn json_value_with_max_depth<'a>(max_depth: usize) -> impl Parser<&'a str, JsonValue, JsonError> {
+    json_node_with_max_depth(max_depth).map(JsonNode::into_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn test_json_spec_edge_cases() {
+        let value = json_value();
+        let number = |text| value.parse(text).map(|(_, v)| v);
+        assert_eq!(number("-0"), Ok(JsonValue::Number(-0.0)));
+        assert_eq!(number("1E+2"), Ok(JsonValue::Number(100.0)));
+        assert_eq!(number("0.5e-1"), Ok(JsonValue::Number(0.05)));
+        assert_eq!(value.parse("01"), Ok(("1", JsonValue::Number(0.0))));
+        assert_eq!(value.parse("1."), Err(("", JsonError::InvalidNumber)));
+        assert_eq!(value.parse("-x"), Err(("x", JsonError::InvalidNumber)));
+        assert_eq!(value.parse("1e"), Err(("", JsonError::InvalidNumber)));
+
+        assert_eq!(
+            value.parse(r#""\"\\\/\b\f\n\r\t😀""#),
+            Ok(("", JsonValue::String("\"\\/\u{8}\u{c}\n\r\t😀".to_string())))
+        );
+        assert_eq!(value.parse(r#""a\x""#), Err((r#"\x""#, JsonError::InvalidEscape)));
+        assert_eq!(value.parse("\"a\tb\""), Err(("\tb\"", JsonError::ControlCharacter)));
+        assert_eq!(value.parse("\"open"), Err(("\"open", JsonError::UnterminatedString)));
+
+        assert_eq!(value.parse(" [ ] "), Ok(("", JsonValue::Array(vec![]))));
+        assert_eq!(value.parse("{ }"), Ok(("", JsonValue::Object(vec![]))));
+        assert_eq!(value.parse("[1,]"), Err(("]", JsonError::ExpectedValue)));
+        assert_eq!(value.parse("[1 2]"), Err(("2]", JsonError::ExpectedCommaOrBracket)));
+        assert_eq!(value.parse("{1:2}"), Err(("1:2}", JsonError::ExpectedKey)));
+        assert_eq!(value.parse(r#"{"a" 2}"#), Err(("2}", JsonError::ExpectedColon)));
+        assert_eq!(value.parse(r#"{"a":2,}"#), Err(("}", JsonError::ExpectedKey)));
+        assert_eq!(value.parse(r#"{"a":2]"#), Err(("]", JsonError::ExpectedCommaOrBrace)));
+        assert_eq!(value.parse("nul"), Err(("nul", JsonError::ExpectedValue)));
+        assert_eq!(value.parse(""), Err(("", JsonError::ExpectedValue)));
+
+        let (_, node) = json_node().parse("\n[null, \"é\"]").unwrap();
+        let JsonKind::Array(items) = &node.kind else { panic!("expected an array") };
+        assert_eq!(node.span, Span::new(1, 13));
+        assert_eq!(items[1].span, Span::new(8, 12));
+        assert_eq!(node.into_value(), JsonValue::Array(vec![JsonValue::Null, JsonValue::String("é".to_string())]));
+    }
+
+    #[test]
+    fn test_json_nesting_limit() {
+        let deep = "[".repeat(100_000);
+        let (rest, err) = json_value().parse(&deep).unwrap_err();
+        assert_eq!(err, JsonError::TooDeep);
+        assert_eq!(rest.len(), deep.len() - DEFAULT_MAX_DEPTH);
+
+        let objects = r#"{"a":"#.repeat(100_000);
+        assert_eq!(json_value().parse(&objects).map(|_| ()).unwrap_err().1, JsonError::TooDeep);
+
+        let nested = format!("{}{}", "[".repeat(DEFAULT_MAX_DEPTH), "]".repeat(DEFAULT_MAX_DEPTH));
+        assert!(json_value().parse(&nested).is_ok());
+        assert_eq!(json_value_with_max_depth(0).parse("[]"), Err(("[]", JsonError::TooDeep)));
+        assert_eq!(json_value_with_max_depth(0).parse("1"), Ok(("", JsonValue::Number(1.0))));
+    }
+}
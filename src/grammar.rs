@@ -0,0 +1,1103 @@
+//! # Grammar Builder
+//!
+//! This module provides a high-level `GrammarBuilder` that wires together the layers most
+//! text grammars need: a whitespace policy, comment syntax, identifier rules, a keyword set,
+//! number formats and string escapes. The builder produces a `Grammar` handing out ready-made
+//! token parsers, and a `ConfiguredParser` that runs the user's rules with trivia skipping,
+//! end-of-input checking and positioned error reporting enabled.
+//!
+//! Everything the builder produces is an ordinary `Parser`, so the regular combinators remain
+//! available for customization.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::grammar::*;
+//! use friss::parsers::Position;
+//!
+//! let parser = GrammarBuilder::new()
+//!     .line_comment("#")
+//!     .keywords(["let"])
+//!     .build(|g| {
+//!         (g.keyword("let"), g.identifier(), g.symbol("="), g.number())
+//!             .seq()
+//!             .map(|(_, name, _, value)| (name, value))
+//!             .map_err(|e| e.fold())
+//!     });
+//!
+//! assert_eq!(parser.parse_source("let answer = 42 # the answer"), Ok(("answer", 42.0)));
+//!
+//! let err = parser.parse_source("let\nlet = 1").unwrap_err();
+//! assert_eq!(err.expected, Expected::Identifier);
+//! assert_eq!(err.position, Position::new(1, 0));
+//! ```
+//!
+//! ## Two-Level Grammars
+//!
+//! A `LexerGrammar` turns the source into a spanned token stream that syntax-level parsers
+//! consume as `&[Token]`. Each token keeps the trivia preceding it, and errors from both levels
+//! are reported as `GrammarError`s located in the original source.
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::grammar::*;
+//! use friss::parsers::Position;
+//!
+//! #[derive(Debug, Clone, PartialEq, Eq)]
+//! enum Tok { Let, Ident, Eq, Num }
+//!
+//! let g = GrammarBuilder::new().line_comment("#").keywords(["let"]).grammar();
+//! let t = g.without_trivia();
+//! let lexer = g
+//!     .lexer()
+//!     .token(Tok::Let, t.keyword("let"))
+//!     .token(Tok::Ident, t.identifier())
+//!     .token(Tok::Eq, t.symbol("="))
+//!     .token(Tok::Num, t.number());
+//!
+//! let binding = (token(Tok::Let), token(Tok::Ident), token(Tok::Eq), token(Tok::Num))
+//!     .seq()
+//!     .map(|(_, name, _, value)| (name.text, value.text))
+//!     .map_err(|e| e.fold());
+//!
+//! let tokens = lexer.tokenize("# comment\nlet x = 1").unwrap();
+//! assert_eq!(tokens.tokens()[0].trivia, "# comment\n");
+//! assert_eq!(tokens.parse(&binding), Ok(("x", "1")));
+//!
+//! let err = lexer.tokenize("let x = 1 ?").unwrap_err();
+//! assert_eq!(err.position, Position::new(0, 10));
+//!
+//! let tokens = lexer.tokenize("let\nlet = 1").unwrap();
+//! let err = tokens.parse(&binding).unwrap_err();
+//! assert_eq!(err.expected, Expected::Token("Ident".to_string()));
+//! assert_eq!(err.position, Position::new(1, 0));
+//! ```
+
+use std::fmt::{self, Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::core::{Checkpoint, Parsable, Parser};
+use crate::parsers::text::{block_comment, escaped_string, line_comment, nested_block_comment, EscapeTable};
+use crate::parsers::{consumed, KeywordMatcher, Position, Span};
+use crate::types::Foldable;
+use crate::unicode::{identifier_with, is_xid_continue, is_xid_start};
+
+/// Describes what a grammar-level token parser expected to find.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    /// An identifier that is not a reserved keyword
+    Identifier,
+    /// A specific keyword
+    Keyword(String),
+    /// A number in the configured format
+    Number,
+    /// The opening quote of a string literal
+    String,
+    /// The closing quote of a string literal
+    StringEnd,
+    /// A known escape sequence inside a string literal
+    Escape,
+    /// A specific symbol or punctuation
+    Symbol(String),
+    /// The closing delimiter of a block comment
+    CommentEnd,
+    /// The end of the input
+    EndOfInput,
+    /// Any one of several alternatives
+    OneOf(Vec<Expected>),
+    /// A token of the named kind
+    Token(String),
+    /// A user-supplied description
+    Custom(String),
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Identifier => write!(f, "identifier"),
+            Expected::Keyword(k) => write!(f, "keyword `{}`", k),
+            Expected::Number => write!(f, "number"),
+            Expected::String => write!(f, "string"),
+            Expected::StringEnd => write!(f, "end of string"),
+            Expected::Escape => write!(f, "escape sequence"),
+            Expected::Symbol(s) => write!(f, "`{}`", s),
+            Expected::CommentEnd => write!(f, "end of comment"),
+            Expected::EndOfInput => write!(f, "end of input"),
+            Expected::OneOf(all) => {
+                for (i, e) in all.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " or ")?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                Ok(())
+            }
+            Expected::Token(kind) => write!(f, "{} token", kind),
+            Expected::Custom(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// An error produced by a `ConfiguredParser`, located in the original source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrammarError {
+    /// What was expected at the failure location
+    pub expected: Expected,
+    /// Byte offset of the failure in the source
+    pub offset: usize,
+    /// Line and column of the failure in the source
+    pub position: Position,
+}
+
+impl GrammarError {
+    /// Creates an error for the remaining input `rest` of `source`.
+    pub fn new(source: &str, rest: &str, expected: Expected) -> Self {
+        Self::at(source, source.len() - rest.len(), expected)
+    }
+
+    /// Creates an error at byte `offset` of `source`.
+    pub fn at(source: &str, offset: usize, expected: Expected) -> Self {
+        GrammarError {
+            expected,
+            offset,
+            position: Position::from_offset(source, offset),
+        }
+    }
+
+    /// Renders the error together with the offending source line and a caret under the failure.
+    pub fn report(&self, source: &str) -> String {
+        let line = source.lines().nth(self.position.line).unwrap_or("");
+        format!(
+            "{}\n{}\n{}^",
+            self,
+            line,
+            " ".repeat(self.position.column)
+        )
+    }
+}
+
+impl Display for GrammarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: expected {}", self.position, self.expected)
+    }
+}
+
+/// Which characters count as insignificant whitespace between tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Whitespace {
+    /// No whitespace is skipped
+    None,
+    /// Spaces and tabs are skipped, line breaks are significant
+    Inline,
+    /// All whitespace including line breaks is skipped
+    #[default]
+    All,
+}
+
+/// Supported number literal formats. Numbers are always produced as `f64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// Digits only: `42`
+    Integer,
+    /// Digits with an optional fraction: `4.2`
+    #[default]
+    Decimal,
+    /// Decimal with an optional exponent: `4.2e-1`
+    Scientific,
+}
+
+/// Configuration of string literals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringFormat {
+    /// The quote character delimiting strings
+    pub quote: char,
+    /// The character introducing an escape sequence
+    pub escape: char,
+    /// The escape sequences recognized after the escape character
+    pub table: EscapeTable,
+}
+
+impl Default for StringFormat {
+    fn default() -> Self {
+        StringFormat {
+            quote: '"',
+            escape: '\\',
+            table: EscapeTable::new()
+                .escape('n', '\n')
+                .escape('t', '\t')
+                .escape('r', '\r')
+                .escape('\\', '\\')
+                .escape('"', '"'),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct GrammarConfig {
+    whitespace: Whitespace,
+    line_comment: Option<String>,
+    block_comment: Option<(String, String)>,
+    nested_comments: bool,
+    ident_start: Rc<dyn Fn(char) -> bool>,
+    ident_continue: Rc<dyn Fn(char) -> bool>,
+    keywords: Vec<String>,
+    numbers: NumberFormat,
+    strings: StringFormat,
+}
+
+/// Fluent builder assembling the common layers of a text grammar.
+///
+/// Defaults: all whitespace is skipped, no comments, Unicode identifiers as matched by
+/// `unicode::identifier`, no keywords, decimal numbers and double-quoted strings
+/// with the usual C-style escapes.
+#[derive(Clone)]
+pub struct GrammarBuilder {
+    config: GrammarConfig,
+}
+
+impl Default for GrammarBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GrammarBuilder {
+    /// Creates a builder with the default configuration.
+    pub fn new() -> Self {
+        GrammarBuilder {
+            config: GrammarConfig {
+                whitespace: Whitespace::default(),
+                line_comment: None,
+                block_comment: None,
+                nested_comments: false,
+                ident_start: Rc::new(|c| c == '_' || is_xid_start(c)),
+                ident_continue: Rc::new(is_xid_continue),
+                keywords: Vec::new(),
+                numbers: NumberFormat::default(),
+                strings: StringFormat::default(),
+            },
+        }
+    }
+
+    /// Sets which whitespace is skipped between tokens.
+    pub fn whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.config.whitespace = whitespace;
+        self
+    }
+
+    /// Enables line comments starting with `prefix` and running to the end of the line.
+    pub fn line_comment(mut self, prefix: impl Into<String>) -> Self {
+        self.config.line_comment = Some(prefix.into());
+        self
+    }
+
+    /// Enables block comments delimited by `open` and `close`.
+    pub fn block_comment(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.config.block_comment = Some((open.into(), close.into()));
+        self.config.nested_comments = false;
+        self
+    }
+
+    /// Enables block comments delimited by `open` and `close` that nest, so each `open`
+    /// inside a comment needs its own `close`.
+    pub fn nested_block_comment(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.config.block_comment = Some((open.into(), close.into()));
+        self.config.nested_comments = true;
+        self
+    }
+
+    /// Sets the predicates for the first and the following characters of identifiers.
+    pub fn identifiers(
+        mut self,
+        start: impl Fn(char) -> bool + 'static,
+        cont: impl Fn(char) -> bool + 'static,
+    ) -> Self {
+        self.config.ident_start = Rc::new(start);
+        self.config.ident_continue = Rc::new(cont);
+        self
+    }
+
+    /// Adds reserved keywords. Keywords are rejected by `Grammar::identifier`.
+    pub fn keywords<K: Into<String>>(mut self, keywords: impl IntoIterator<Item = K>) -> Self {
+        self.config
+            .keywords
+            .extend(keywords.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the accepted number format.
+    pub fn numbers(mut self, format: NumberFormat) -> Self {
+        self.config.numbers = format;
+        self
+    }
+
+    /// Sets the string quote, escape character and escape table.
+    pub fn string_escapes(mut self, quote: char, escape: char, table: EscapeTable) -> Self {
+        self.config.strings = StringFormat { quote, escape, table };
+        self
+    }
+
+    /// Finishes the configuration, returning the token-level `Grammar`.
+    pub fn grammar(self) -> Grammar {
+        Grammar {
+            config: Rc::new(self.config),
+        }
+    }
+
+    /// Finishes the configuration and builds the top-level parser from the user's rules.
+    ///
+    /// The closure receives the configured `Grammar` and returns the start rule.
+    pub fn build<'a, Output, P, F>(self, rules: F) -> ConfiguredParser<'a, Output, P>
+    where
+        F: FnOnce(&Grammar) -> P,
+        P: Parser<&'a str, Output, Expected>,
+    {
+        let grammar = self.grammar();
+        let rule = rules(&grammar);
+        ConfiguredParser {
+            grammar,
+            rule,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// A configured grammar handing out token parsers that skip trailing trivia.
+#[derive(Clone)]
+pub struct Grammar {
+    config: Rc<GrammarConfig>,
+}
+
+impl Grammar {
+    /// Skips whitespace and comments according to the configuration.
+    pub fn trivia<'a>(&self) -> impl Parser<&'a str, (), Expected> {
+        let config = self.config.clone();
+        move |input: &'a str| match skip_trivia(&config, input) {
+            Ok(rest) => Ok((rest, ())),
+            Err(rest) => Err((rest, Expected::CommentEnd)),
+        }
+    }
+
+    /// Wraps a parser so that trivia following it is skipped.
+    pub fn lexeme<'a, Output>(
+        &self,
+        parser: impl Parser<&'a str, Output, Expected>,
+    ) -> impl Parser<&'a str, Output, Expected> {
+        let config = self.config.clone();
+        move |input: &'a str| {
+            let (rest, out) = parser.parse(input)?;
+            match skip_trivia(&config, rest) {
+                Ok(rest) => Ok((rest, out)),
+                Err(rest) => Err((rest, Expected::CommentEnd)),
+            }
+        }
+    }
+
+    /// Matches the literal symbol `symbol`.
+    pub fn symbol<'a>(&self, symbol: &'static str) -> impl Parser<&'a str, &'a str, Expected> {
+        self.lexeme(move |input: &'a str| match input.strip_prefix(symbol) {
+            Some(rest) => Ok((rest, consumed(input, rest))),
+            None => Err((input, Expected::Symbol(symbol.to_string()))),
+        })
+    }
+
+    /// Matches `keyword` when it is not immediately followed by an identifier character.
+    pub fn keyword<'a>(&self, keyword: &'static str) -> impl Parser<&'a str, &'a str, Expected> {
+        let config = self.config.clone();
+        self.lexeme(keyword.make_keyword_matcher_with(
+            move |c| (config.ident_continue)(c),
+            Expected::Keyword(keyword.to_string()),
+        ))
+    }
+
+    /// Matches an identifier that is not one of the reserved keywords.
+    pub fn identifier<'a>(&self) -> impl Parser<&'a str, &'a str, Expected> {
+        let config = self.config.clone();
+        let (start, cont) = (config.ident_start.clone(), config.ident_continue.clone());
+        let name = identifier_with(move |c| start(c), move |c| cont(c), Expected::Identifier);
+        self.lexeme(move |input: &'a str| {
+            let (rest, ident) = name.parse(input)?;
+            if config.keywords.iter().any(|k| k == ident) {
+                Err((input, Expected::Identifier))
+            } else {
+                Ok((rest, ident))
+            }
+        })
+    }
+
+    /// Matches a number in the configured format.
+    pub fn number<'a>(&self) -> impl Parser<&'a str, f64, Expected> {
+        let format = self.config.numbers;
+        self.lexeme(move |input: &'a str| {
+            let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            // Every offset below follows an ASCII character, so it is always a character boundary
+            let from = |at: usize| input.get(at..).unwrap_or_default();
+            let mut end = digits(input);
+            if end == 0 {
+                return Err((input, Expected::Number));
+            }
+            if format != NumberFormat::Integer && from(end).starts_with('.') {
+                let fraction = digits(from(end + 1));
+                if fraction > 0 {
+                    end += 1 + fraction;
+                }
+            }
+            if format == NumberFormat::Scientific && from(end).starts_with(['e', 'E']) {
+                let mut exp = end + 1;
+                if from(exp).starts_with(['+', '-']) {
+                    exp += 1;
+                }
+                let exp_digits = digits(from(exp));
+                if exp_digits > 0 {
+                    end = exp + exp_digits;
+                }
+            }
+            match consumed(input, from(end)).parse::<f64>() {
+                Ok(n) => Ok((from(end), n)),
+                Err(_) => Err((input, Expected::Number)),
+            }
+        })
+    }
+
+    /// Matches a quoted string, decoding escape sequences from the configured table.
+    pub fn string<'a>(&self) -> impl Parser<&'a str, String, Expected> {
+        let StringFormat { quote, escape, table } = self.config.strings.clone();
+        let string = escaped_string(quote, escape, table, Expected::Escape);
+        self.lexeme(move |input: &'a str| {
+            if !input.starts_with(quote) {
+                return Err((input, Expected::String));
+            }
+            // `escaped_string` fails at the opening quote when the string is unterminated
+            // and at the escape character for an unknown escape sequence
+            string.parse(input).map_err(|(rest, expected)| {
+                if rest.len() == input.len() {
+                    (input.get(input.len()..).unwrap_or_default(), Expected::StringEnd)
+                } else {
+                    (rest, expected)
+                }
+            })
+        })
+    }
+
+    /// Returns a copy of this grammar whose token parsers do not skip trailing trivia,
+    /// for use as token rules in a `LexerGrammar`.
+    pub fn without_trivia(&self) -> Grammar {
+        let mut config = (*self.config).clone();
+        config.whitespace = Whitespace::None;
+        config.line_comment = None;
+        config.block_comment = None;
+        Grammar {
+            config: Rc::new(config),
+        }
+    }
+
+    /// Creates a `LexerGrammar` that skips this grammar's whitespace and comments between tokens.
+    pub fn lexer<'a, K>(&self) -> LexerGrammar<'a, K> {
+        let config = self.config.clone();
+        LexerGrammar {
+            trivia: vec![Box::new(move |input| {
+                skip_trivia(&config, input).map_err(|rest| (rest, Expected::CommentEnd))
+            })],
+            rules: Vec::new(),
+        }
+    }
+
+    /// Succeeds only at the end of the input.
+    pub fn end_of_input<'a>(&self) -> impl Parser<&'a str, (), Expected> {
+        move |input: &'a str| {
+            if input.is_empty() {
+                Ok((input, ()))
+            } else {
+                Err((input, Expected::EndOfInput))
+            }
+        }
+    }
+}
+
+/// The top-level parser produced by `GrammarBuilder::build`.
+///
+/// Leading trivia is skipped before the start rule runs and errors are converted into
+/// `GrammarError`s located relative to the parsed input.
+pub struct ConfiguredParser<'a, Output, P> {
+    grammar: Grammar,
+    rule: P,
+    _phantom: PhantomData<(&'a str, Output)>,
+}
+
+impl<'a, Output, P> ConfiguredParser<'a, Output, P>
+where
+    P: Parser<&'a str, Output, Expected>,
+{
+    /// Returns the grammar the parser was built with.
+    pub fn grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+
+    /// Parses the whole `source`, failing if any non-trivia input is left over.
+    pub fn parse_source(&self, source: &'a str) -> Result<Output, GrammarError> {
+        match self.parse(source) {
+            Ok(("", out)) => Ok(out),
+            Ok((rest, _)) => Err(GrammarError::new(source, rest, Expected::EndOfInput)),
+            Err((_, err)) => Err(err),
+        }
+    }
+}
+
+impl<'a, Output, P> Parser<&'a str, Output, GrammarError> for ConfiguredParser<'a, Output, P>
+where
+    P: Parser<&'a str, Output, Expected>,
+{
+    fn parse(&self, input: &'a str) -> Result<(&'a str, Output), (&'a str, GrammarError)> {
+        self.grammar
+            .trivia()
+            .seq(|i| self.rule.parse(i))
+            .map(|(_, out)| out)
+            .map_err(|e| e.fold())
+            .parse(input)
+            .map_err(|(rest, e)| (rest, GrammarError::new(input, rest, e)))
+    }
+}
+
+type TriviaRule<'a> = Box<dyn Fn(&'a str) -> Result<&'a str, (&'a str, Expected)> + 'a>;
+type TokenRule<'a> = Box<dyn Fn(&'a str) -> Option<&'a str> + 'a>;
+
+/// A token produced by a `LexerGrammar`.
+///
+/// Besides its kind and text, every token keeps its byte span in the source and the trivia
+/// (whitespace and comments) that preceded it, so no source text is lost by lexing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a, K> {
+    /// The kind of the token
+    pub kind: K,
+    /// The matched source text
+    pub text: &'a str,
+    /// Byte span of the token in the source
+    pub span: Span,
+    /// The trivia directly preceding the token
+    pub trivia: &'a str,
+}
+
+/// The token-level half of a two-level grammar.
+///
+/// Token rules are tried at every position after skipping trivia; the longest match wins and
+/// ties go to the rule declared first, so keywords should be declared before identifiers.
+pub struct LexerGrammar<'a, K> {
+    trivia: Vec<TriviaRule<'a>>,
+    rules: Vec<(K, TokenRule<'a>)>,
+}
+
+impl<'a, K> Default for LexerGrammar<'a, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, K> LexerGrammar<'a, K> {
+    /// Creates a lexer grammar without trivia or token rules.
+    pub fn new() -> Self {
+        LexerGrammar {
+            trivia: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Adds a parser whose matches are skipped as trivia between tokens.
+    pub fn trivia<Output, Error: Clone>(
+        mut self,
+        parser: impl Parser<&'a str, Output, Error> + 'a,
+    ) -> Self {
+        self.trivia.push(Box::new(move |input| {
+            Ok(parser.parse(input).map_or(input, |(rest, _)| rest))
+        }));
+        self
+    }
+
+    /// Adds a token rule producing tokens of `kind` from the text matched by `parser`.
+    pub fn token<Output, Error: Clone>(
+        mut self,
+        kind: K,
+        parser: impl Parser<&'a str, Output, Error> + 'a,
+    ) -> Self {
+        self.rules.push((
+            kind,
+            Box::new(move |input| parser.parse(input).ok().map(|(rest, _)| rest)),
+        ));
+        self
+    }
+
+    /// Splits `source` into a token stream.
+    ///
+    /// Fails with an `Expected::OneOf` listing every token kind when no rule matches.
+    pub fn tokenize(&self, source: &'a str) -> Result<TokenStream<'a, K>, GrammarError>
+    where
+        K: Clone + Debug,
+    {
+        let mut tokens = Vec::new();
+        let mut input = source;
+        loop {
+            let start = input;
+            input = self.skip_trivia(source, input)?;
+            let trivia = consumed(start, input);
+            if input.is_empty() {
+                return Ok(TokenStream {
+                    source,
+                    tokens: tokens.into(),
+                    trailing: trivia,
+                });
+            }
+
+            let best = self
+                .rules
+                .iter()
+                .filter_map(|(kind, rule)| rule(input).map(|rest| (kind, rest)))
+                .filter(|(_, rest)| rest.len() < input.len())
+                .min_by_key(|(_, rest)| rest.len());
+            let Some((kind, rest)) = best else {
+                let kinds = self
+                    .rules
+                    .iter()
+                    .map(|(kind, _)| Expected::Token(format!("{:?}", kind)))
+                    .collect();
+                return Err(GrammarError::new(source, input, Expected::OneOf(kinds)));
+            };
+
+            let offset = source.len() - input.len();
+            let len = input.len() - rest.len();
+            tokens.push(Token {
+                kind: kind.clone(),
+                text: consumed(input, rest),
+                span: Span::new(offset, offset + len),
+                trivia,
+            });
+            input = rest;
+        }
+    }
+
+    fn skip_trivia(&self, source: &'a str, mut input: &'a str) -> Result<&'a str, GrammarError> {
+        loop {
+            let before = input.len();
+            for rule in &self.trivia {
+                input = rule(input).map_err(|(rest, e)| GrammarError::new(source, rest, e))?;
+            }
+            if input.len() == before {
+                return Ok(input);
+            }
+        }
+    }
+}
+
+/// A spanned token stream produced by `LexerGrammar::tokenize`, ready to be consumed by
+/// syntax-level parsers over `TokenInput`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenStream<'a, K> {
+    source: &'a str,
+    tokens: Rc<[Token<'a, K>]>,
+    trailing: &'a str,
+}
+
+impl<'a, K: Clone + Eq> TokenStream<'a, K> {
+    /// Returns the source the tokens were produced from.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Returns the tokens.
+    pub fn tokens(&self) -> &[Token<'a, K>] {
+        &self.tokens
+    }
+
+    /// Returns the trivia following the last token.
+    pub fn trailing_trivia(&self) -> &'a str {
+        self.trailing
+    }
+
+    /// Returns a parser input positioned at the first token.
+    pub fn input(&self) -> TokenInput<'a, K> {
+        TokenInput {
+            tokens: self.tokens.clone(),
+            start: 0,
+            end: self.tokens.len(),
+        }
+    }
+
+    /// Runs a syntax-level parser over all tokens.
+    ///
+    /// Errors, including leftover tokens, are located at the start of the offending token in
+    /// the original source, so both grammar levels report through `GrammarError`.
+    pub fn parse<Output>(
+        &self,
+        parser: &impl Parser<TokenInput<'a, K>, Output, Expected>,
+    ) -> Result<Output, GrammarError> {
+        match parser.parse(self.input()) {
+            Ok((rest, out)) if rest.is_empty() => Ok(out),
+            Ok((rest, _)) => Err(self.error_at(&rest, Expected::EndOfInput)),
+            Err((rest, e)) => Err(self.error_at(&rest, e)),
+        }
+    }
+
+    fn error_at(&self, rest: &TokenInput<'a, K>, expected: Expected) -> GrammarError {
+        let offset = rest.peek().map_or(self.source.len(), |t| t.span.start);
+        GrammarError::at(self.source, offset, expected)
+    }
+}
+
+/// Parser input over a shared token stream.
+///
+/// Cloning is cheap: the tokens are shared and only the window into them is copied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenInput<'a, K> {
+    tokens: Rc<[Token<'a, K>]>,
+    start: usize,
+    end: usize,
+}
+
+impl<'a, K> TokenInput<'a, K> {
+    /// Returns the tokens remaining in this input.
+    pub fn remaining(&self) -> &[Token<'a, K>] {
+        self.tokens.get(self.start..self.end).unwrap_or_default()
+    }
+
+    /// Returns the next token without consuming it.
+    pub fn peek(&self) -> Option<&Token<'a, K>> {
+        self.remaining().first()
+    }
+
+    /// Returns the number of remaining tokens.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns true if no tokens remain.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    fn split_at(&self, n: usize) -> (Self, Self) {
+        let mid = self.start + n;
+        (
+            TokenInput {
+                tokens: self.tokens.clone(),
+                start: self.start,
+                end: mid,
+            },
+            TokenInput {
+                tokens: self.tokens.clone(),
+                start: mid,
+                end: self.end,
+            },
+        )
+    }
+}
+
+impl<K: Clone> Checkpoint for TokenInput<'_, K> {
+    type Checkpoint = Self;
+
+    fn checkpoint(&self) -> Self {
+        self.clone()
+    }
+
+    fn rewind(checkpoint: Self) -> Self {
+        checkpoint
+    }
+}
+
+impl<'a, Error: Clone, K: Clone + Eq> Parsable<Error> for TokenInput<'a, K> {
+    type Item = Token<'a, K>;
+
+    fn make_literal_matcher(self, err: Error) -> impl Parser<Self, Self, Error> {
+        move |input: TokenInput<'a, K>| {
+            if input.remaining().starts_with(self.remaining()) {
+                let (matched, rest) = input.split_at(self.len());
+                Ok((rest, matched))
+            } else {
+                Err((input, err.clone()))
+            }
+        }
+    }
+
+    fn make_anything_matcher(err: Error) -> impl Parser<Self, Self::Item, Error> {
+        move |input: TokenInput<'a, K>| match input.peek().cloned() {
+            Some(t) => Ok((input.split_at(1).1, t)),
+            None => Err((input, err.clone())),
+        }
+    }
+
+    fn make_item_matcher(character: Self::Item, err: Error) -> impl Parser<Self, Self::Item, Error> {
+        move |input: TokenInput<'a, K>| match input.peek() {
+            Some(t) if *t == character => Ok((input.split_at(1).1, character.clone())),
+            _ => Err((input, err.clone())),
+        }
+    }
+
+    fn make_empty_matcher(err: Error) -> impl Parser<Self, (), Error> {
+        move |input: TokenInput<'a, K>| {
+            if input.is_empty() {
+                Ok((input, ()))
+            } else {
+                Err((input, err.clone()))
+            }
+        }
+    }
+}
+
+/// Matches a single token of the given kind.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::grammar::*;
+///
+/// #[derive(Debug, Clone, PartialEq, Eq)]
+/// enum Tok { Word, Comma }
+///
+/// let lexer = LexerGrammar::new()
+///     .trivia(' '.make_character_matcher(()))
+///     .token(Tok::Comma, ','.make_character_matcher(()))
+///     .token(Tok::Word, GrammarBuilder::new().grammar().without_trivia().identifier());
+///
+/// let tokens = lexer.tokenize("a, b").unwrap();
+/// let words = tokens.parse(&token(Tok::Word).sep_by(token(Tok::Comma)));
+/// assert_eq!(words.unwrap().iter().map(|t| t.text).collect::<Vec<_>>(), vec!["a", "b"]);
+/// ```
+pub fn token<'a, K: Clone + Eq + Debug>(
+    kind: K,
+) -> impl Parser<TokenInput<'a, K>, Token<'a, K>, Expected> {
+    move |input: TokenInput<'a, K>| match input.peek().cloned() {
+        Some(t) if t.kind == kind => Ok((input.split_at(1).1, t)),
+        _ => Err((input, Expected::Token(format!("{:?}", kind)))),
+    }
+}
+
+/// Matches a single token of the given kind whose text equals `text`.
+pub fn token_text<'a, K: Clone + Eq + Debug>(
+    kind: K,
+    text: &'static str,
+) -> impl Parser<TokenInput<'a, K>, Token<'a, K>, Expected> {
+    move |input: TokenInput<'a, K>| match input.peek().cloned() {
+        Some(t) if t.kind == kind && t.text == text => Ok((input.split_at(1).1, t)),
+        _ => Err((input, Expected::Symbol(text.to_string()))),
+    }
+}
+
+/// Skips whitespace and comments, returning the rest or, for an unterminated
+/// block comment, the input at the end of that comment.
+fn skip_trivia<'a>(config: &GrammarConfig, mut input: &'a str) -> Result<&'a str, &'a str> {
+    loop {
+        let before = input.len();
+        input = match config.whitespace {
+            Whitespace::None => input,
+            Whitespace::Inline => input.trim_start_matches([' ', '\t']),
+            Whitespace::All => input.trim_start(),
+        };
+        if let Some(prefix) = &config.line_comment {
+            if let Ok((rest, _)) = line_comment(prefix, ()).parse(input) {
+                input = rest;
+            }
+        }
+        if let Some((open, close)) = &config.block_comment {
+            let comment = if config.nested_comments {
+                nested_block_comment(open, close, ()).parse(input)
+            } else {
+                block_comment(open, close, ()).parse(input)
+            };
+            match comment {
+                Ok((rest, _)) => input = rest,
+                // Both comment parsers fail at the opening delimiter when it is unterminated
+                Err(_) if !open.is_empty() && input.starts_with(open.as_str()) => {
+                    return Err(input.get(input.len()..).unwrap_or_default())
+                }
+                Err(_) => {}
+            }
+        }
+        if input.len() == before {
+            return Ok(input);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_trivia_and_comments() {
+        let g = GrammarBuilder::new()
+            .line_comment("//")
+            .block_comment("/*", "*/")
+            .grammar();
+        let parser = g.symbol("a").many();
+
+        assert_eq!(
+            parser.parse("a // one\n /* two */ a\ta rest"),
+            Ok(("rest", vec!["a", "a", "a"]))
+        );
+        assert_eq!(g.trivia().parse("/* open"), Err(("", Expected::CommentEnd)));
+
+        let nested = GrammarBuilder::new().nested_block_comment("/*", "*/").grammar();
+        let parser = nested.symbol("a").many();
+        assert_eq!(parser.parse("a /* x /* y */ z */ a!"), Ok(("!", vec!["a", "a"])));
+        assert_eq!(nested.trivia().parse("/* /* */"), Err(("", Expected::CommentEnd)));
+    }
+
+    #[test]
+    fn test_inline_whitespace_keeps_newlines() {
+        let g = GrammarBuilder::new().whitespace(Whitespace::Inline).grammar();
+
+        assert_eq!(g.symbol("x").parse("x  \ny"), Ok(("\ny", "x")));
+    }
+
+    #[test]
+    fn test_keywords_and_identifiers() {
+        let g = GrammarBuilder::new().keywords(["if", "else"]).grammar();
+
+        assert_eq!(g.keyword("if").parse("if x"), Ok(("x", "if")));
+        assert_eq!(
+            g.keyword("if").parse("iffy"),
+            Err(("iffy", Expected::Keyword("if".to_string())))
+        );
+        assert_eq!(g.identifier().parse("iffy x"), Ok(("x", "iffy")));
+        assert_eq!(g.identifier().parse("else"), Err(("else", Expected::Identifier)));
+        assert_eq!(g.identifier().parse("日本 x"), Ok(("x", "日本")));
+    }
+
+    #[test]
+    fn test_number_formats() {
+        let int = GrammarBuilder::new().numbers(NumberFormat::Integer).grammar();
+        let dec = GrammarBuilder::new().grammar();
+        let sci = GrammarBuilder::new().numbers(NumberFormat::Scientific).grammar();
+
+        assert_eq!(int.number().parse("4.5"), Ok((".5", 4.0)));
+        assert_eq!(dec.number().parse("4.5e3"), Ok(("e3", 4.5)));
+        assert_eq!(sci.number().parse("4.5e-3"), Ok(("", 4.5e-3)));
+        assert_eq!(sci.number().parse("x"), Err(("x", Expected::Number)));
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let g = GrammarBuilder::new().grammar();
+
+        assert_eq!(
+            g.string().parse(r#""a\"b\n" rest"#),
+            Ok(("rest", "a\"b\n".to_string()))
+        );
+        assert_eq!(g.string().parse(r#""a\q""#), Err((r#"\q""#, Expected::Escape)));
+        assert_eq!(g.string().parse(r#""abc"#), Err(("", Expected::StringEnd)));
+
+        let single = GrammarBuilder::new()
+            .string_escapes('\'', '\\', EscapeTable::new().escape('\'', '\''))
+            .grammar();
+        assert_eq!(single.string().parse(r"'it\'s'"), Ok(("", "it's".to_string())));
+    }
+
+    #[test]
+    fn test_configured_parser_reports_positions() {
+        let parser = GrammarBuilder::new().build(|g| {
+            (
+                g.symbol("["),
+                g.number().sep_by(g.symbol(",").map(|_| 0.0)),
+                g.symbol("]"),
+            )
+                .seq()
+                .map(|(_, items, _)| items)
+                .map_err(|e| e.fold())
+        });
+
+        assert_eq!(parser.parse_source("  [1, 2,\n 3 ] "), Ok(vec![1.0, 2.0, 3.0]));
+
+        let source = "[1, 2\n  x]";
+        let err = parser.parse_source(source).unwrap_err();
+        assert_eq!(err.expected, Expected::Symbol("]".to_string()));
+        assert_eq!(err.position, Position::new(1, 2));
+        assert_eq!(err.report(source), "1:2: expected `]`\n  x]\n  ^");
+
+        let err = parser.parse_source("[1] 2").unwrap_err();
+        assert_eq!(err.expected, Expected::EndOfInput);
+        assert_eq!(err.offset, 4);
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Tok {
+        Ident,
+        Plus,
+        Num,
+    }
+
+    fn expr_lexer<'a>() -> LexerGrammar<'a, Tok> {
+        let g = GrammarBuilder::new().block_comment("/*", "*/").grammar();
+        let t = g.without_trivia();
+        g.lexer()
+            .token(Tok::Ident, t.identifier())
+            .token(Tok::Plus, t.symbol("+"))
+            .token(Tok::Num, t.number())
+    }
+
+    #[test]
+    fn test_tokenize_preserves_trivia_and_spans() {
+        let tokens = expr_lexer().tokenize(" a /* c */+ 12 ").unwrap();
+        let kinds: Vec<_> = tokens.tokens().iter().map(|t| t.kind.clone()).collect();
+
+        assert_eq!(kinds, vec![Tok::Ident, Tok::Plus, Tok::Num]);
+        assert_eq!(tokens.tokens()[1].trivia, " /* c */");
+        assert_eq!(tokens.tokens()[2].span, Span::new(12, 14));
+        assert_eq!(tokens.trailing_trivia(), " ");
+
+        let rebuilt: String = tokens
+            .tokens()
+            .iter()
+            .flat_map(|t| [t.trivia, t.text])
+            .chain([tokens.trailing_trivia()])
+            .collect();
+        assert_eq!(rebuilt, " a /* c */+ 12 ");
+    }
+
+    #[test]
+    fn test_tokenize_longest_match() {
+        let lexer = LexerGrammar::new()
+            .token(Tok::Plus, "+".make_literal_matcher(()))
+            .token(Tok::Num, "++".make_literal_matcher(()));
+        let tokens = lexer.tokenize("+++").unwrap();
+        let texts: Vec<_> = tokens.tokens().iter().map(|t| t.text).collect();
+
+        assert_eq!(texts, vec!["++", "+"]);
+    }
+
+    #[test]
+    fn test_lexer_errors() {
+        let err = expr_lexer().tokenize("a +\n $").unwrap_err();
+        assert_eq!(err.position, Position::new(1, 1));
+        assert_eq!(
+            err.expected,
+            Expected::OneOf(vec![
+                Expected::Token("Ident".to_string()),
+                Expected::Token("Plus".to_string()),
+                Expected::Token("Num".to_string()),
+            ])
+        );
+
+        let err = expr_lexer().tokenize("a /* b").unwrap_err();
+        assert_eq!(err.expected, Expected::CommentEnd);
+        assert_eq!(err.offset, 6);
+    }
+
+    #[test]
+    fn test_token_stream_parse_errors() {
+        let sum = token(Tok::Num)
+            .sep_by1(token(Tok::Plus), Expected::Token("Num".to_string()));
+
+        let tokens = expr_lexer().tokenize("1 + 2").unwrap();
+        assert_eq!(tokens.parse(&sum).map(|v| v.len()), Ok(2));
+
+        let tokens = expr_lexer().tokenize("1 + 2\n  x").unwrap();
+        let err = tokens.parse(&sum).unwrap_err();
+        assert_eq!(err.expected, Expected::EndOfInput);
+        assert_eq!(err.position, Position::new(1, 2));
+
+        let tokens = expr_lexer().tokenize("  ").unwrap();
+        let err = tokens.parse(&sum).unwrap_err();
+        assert_eq!(err.offset, 2);
+    }
+}
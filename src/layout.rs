@@ -0,0 +1,377 @@
+//! # Layout-Sensitive Parsing
+//!
+//! This module parses Python and YAML style layouts, where indentation delimits blocks.
+//! The combinators run on `StateCarrier<Indentation, &str>` and keep the levels of the
+//! enclosing blocks in the `Indentation` state:
+//!
+//! - `aligned_items(item)` parses items starting at the current indentation level
+//! - `indented_block(item)` parses a block indented deeper than the current level
+//! - `line_fold(line)` parses a line continued on the lines indented deeper than it
+//!
+//! For Haskell-style layout rules, where a token must start right of some earlier token,
+//! the column guards run on `StateCarrier<Position, &str>` and consume no input:
+//!
+//! - `current_column()` returns the column of the input, to be used as a reference
+//! - `at_column(n, err)` succeeds if the input is at column `n`
+//! - `indented_past(reference, err)` succeeds if the input is right of column `reference`
+//!
+//! Indentation is counted in spaces, and lines holding only spaces are skipped. Items start
+//! after the indentation of their line and consume everything up to the start of the next
+//! line, including the newline and any nested block. Item errors are `LayoutError`s, so
+//! nested blocks compose; wrap the errors of plain parsers with `LayoutError::Item`.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::layout::*;
+//! use friss::parsers::{Indentation, WithState};
+//! use friss::state::StateCarrier;
+//!
+//! type Input = StateCarrier<Indentation, &'static str>;
+//!
+//! let name = || {
+//!     <Input as Parsable<&str>>::make_anything_matcher("Expected name")
+//!         .validate(|c: &char| c.is_alphabetic(), "Expected name")
+//!         .many()
+//!         .map(|chars| chars.into_iter().collect::<String>())
+//!         .map_err(LayoutError::Item)
+//! };
+//! let newline = || '\n'.make_character_matcher(LayoutError::Item("Expected newline"));
+//! let colon = ':'.make_character_matcher(LayoutError::Item("Expected :"));
+//!
+//! let entry = name().skip(newline());
+//! let section = name().skip(colon).skip(newline()).seq(indented_block(entry)).map_err(Foldable::fold);
+//! let document = aligned_items(section);
+//!
+//! let (_, sections) = document.parse("fruit:\n  apple\n  pear\n\nveg:\n  kale\n".with_state(Indentation::new())).unwrap();
+//! assert_eq!(sections[0], ("fruit".to_string(), vec!["apple".to_string(), "pear".to_string()]));
+//! assert_eq!(sections[1].1, vec!["kale".to_string()]);
+//!
+//! let (rest, err) = document.parse("fruit:\n  apple\n    pear\n".with_state(Indentation::new())).unwrap_err();
+//! assert_eq!(err, LayoutError::UnexpectedIndent { expected: 2, found: 4 });
+//! assert_eq!(rest.input, "    pear\n");
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use crate::core::Parser;
+use crate::parsers::{Indentation, Position};
+use crate::state::{StateCarrier, StatefulParser};
+
+/// Errors of the layout combinators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError<E> {
+    /// An item failed to parse
+    Item(E),
+    /// A block was expected, but the line is not indented deeper than the current level
+    ExpectedIndent { current: usize, found: usize },
+    /// A line is indented deeper than the items of its block
+    UnexpectedIndent { expected: usize, found: usize },
+    /// A line dedents to a level that matches no enclosing block
+    InconsistentDedent { found: usize },
+    /// A folded line did not end at the end of the line
+    ExpectedLineEnd,
+}
+
+impl<E: Display> Display for LayoutError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::Item(e) => write!(f, "{}", e),
+            LayoutError::ExpectedIndent { current, found } => {
+                write!(f, "expected a block indented past column {}, found column {}", current, found)
+            }
+            LayoutError::UnexpectedIndent { expected, found } => {
+                write!(f, "unexpected indent: expected column {}, found column {}", expected, found)
+            }
+            LayoutError::InconsistentDedent { found } => {
+                write!(f, "dedent to column {} matches no enclosing block", found)
+            }
+            LayoutError::ExpectedLineEnd => write!(f, "expected end of line"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + Display> core::error::Error for LayoutError<E> {}
+
+type Layout<'a> = StateCarrier<Indentation, &'a str>;
+
+type LayoutResult<'a, O, E> = Result<(Layout<'a>, O), (Layout<'a>, LayoutError<E>)>;
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn strip_newline(input: &str) -> Option<&str> {
+    input.strip_prefix('\n').or_else(|| input.strip_prefix("\r\n"))
+}
+
+/// Skips the lines holding only spaces, returning the start of the next line with content.
+fn skip_blank_lines(mut input: &str) -> &str {
+    loop {
+        let content = input.trim_start_matches(' ');
+        match strip_newline(content) {
+            Some(next) => input = next,
+            None if content.is_empty() => return content,
+            None => return input,
+        }
+    }
+}
+
+/// Parses the items indented exactly `level` spaces, until a dedent or the end of input.
+fn parse_aligned<'a, O, E>(
+    item: &impl Parser<Layout<'a>, O, LayoutError<E>>,
+    carrier: Layout<'a>,
+    level: usize,
+) -> LayoutResult<'a, Vec<O>, E>
+where
+    E: Clone,
+{
+    let StateCarrier { mut state, mut input } = carrier;
+    let mut items = Vec::new();
+    loop {
+        let line = skip_blank_lines(input);
+        if line.is_empty() {
+            input = line;
+            break;
+        }
+        let found = indent_of(line);
+        if found > level {
+            return Err((StateCarrier::new(state, line), LayoutError::UnexpectedIndent { expected: level, found }));
+        }
+        if found < level {
+            if found != 0 && !state.levels.contains(&found) {
+                return Err((StateCarrier::new(state, line), LayoutError::InconsistentDedent { found }));
+            }
+            break;
+        }
+        let start = line.trim_start_matches(' ');
+        let (rest, output) = item.parse(StateCarrier::new(state, start))?;
+        items.push(output);
+        state = rest.state;
+        input = rest.input;
+        // An item that consumed nothing would repeat forever
+        if input == start {
+            break;
+        }
+    }
+    Ok((StateCarrier::new(state, input), items))
+}
+
+/// Parses the items aligned at the current indentation level, stopping at the first line
+/// indented less. A line indented deeper is an `UnexpectedIndent` error.
+pub fn aligned_items<'a, O, E>(
+    item: impl Parser<Layout<'a>, O, LayoutError<E>>,
+) -> impl StatefulParser<Indentation, &'a str, Vec<O>, LayoutError<E>>
+where
+    E: Clone,
+{
+    move |carrier: Layout<'a>| {
+        let level = carrier.state.current_level();
+        parse_aligned(&item, carrier, level)
+    }
+}
+
+/// Parses a block of items indented deeper than the current level, all aligned with its
+/// first line. The block's level is pushed on the `Indentation` state while its items are
+/// parsed, and popped afterwards.
+pub fn indented_block<'a, O, E>(
+    item: impl Parser<Layout<'a>, O, LayoutError<E>>,
+) -> impl StatefulParser<Indentation, &'a str, Vec<O>, LayoutError<E>>
+where
+    E: Clone,
+{
+    move |carrier: Layout<'a>| {
+        let StateCarrier { mut state, input } = carrier;
+        let current = state.current_level();
+        let line = skip_blank_lines(input);
+        let found = indent_of(line);
+        if line.is_empty() || found <= current {
+            return Err((StateCarrier::new(state, line), LayoutError::ExpectedIndent { current, found }));
+        }
+        state.push_level(found);
+        match parse_aligned(&item, StateCarrier::new(state, line), found) {
+            Ok((mut rest, items)) => {
+                rest.state.pop_level();
+                Ok((rest, items))
+            }
+            Err((mut rest, err)) => {
+                rest.state.pop_level();
+                Err((rest, err))
+            }
+        }
+    }
+}
+
+/// Parses `line` on the current line, then again on each following line indented deeper
+/// than the current level, and consumes the final newline. `line` must stop at the end of
+/// its line.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::layout::*;
+/// use friss::parsers::{Indentation, WithState};
+/// use friss::state::StateCarrier;
+///
+/// let words = <StateCarrier<Indentation, &str> as Parsable<&str>>::make_anything_matcher("Expected text")
+///     .validate(|c: &char| *c != '\n', "Expected text")
+///     .many()
+///     .map(|chars| chars.into_iter().collect::<String>())
+///     .map_err(LayoutError::Item);
+///
+/// let (rest, parts) = line_fold(words).parse("a long\n  folded line\nnext\n".with_state(Indentation::new())).unwrap();
+/// assert_eq!(parts, vec!["a long".to_string(), "folded line".to_string()]);
+/// assert_eq!(rest.input, "next\n");
+/// ```
+pub fn line_fold<'a, O, E>(
+    line: impl Parser<Layout<'a>, O, LayoutError<E>>,
+) -> impl StatefulParser<Indentation, &'a str, Vec<O>, LayoutError<E>>
+where
+    E: Clone,
+{
+    move |carrier: Layout<'a>| {
+        let level = carrier.state.current_level();
+        let (mut rest, first) = line.parse(carrier)?;
+        let mut parts = Vec::from([first]);
+        loop {
+            let next_line = match strip_newline(rest.input) {
+                Some(next_line) => next_line,
+                None if rest.input.is_empty() => break,
+                None => return Err((rest, LayoutError::ExpectedLineEnd)),
+            };
+            let found = indent_of(next_line);
+            let content = next_line.trim_start_matches(' ');
+            let continued = found > level && strip_newline(content).is_none() && !content.is_empty();
+            if !continued {
+                rest.input = next_line;
+                break;
+            }
+            let (next, part) = line.parse(StateCarrier::new(rest.state, content))?;
+            parts.push(part);
+            rest = next;
+        }
+        Ok((rest, parts))
+    }
+}
+
+/// Returns the column of the input without consuming it. Bind the column with
+/// `bind_output` to guard the following parsers against it.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::layout::*;
+/// use friss::parsers::{Position, WithState};
+///
+/// let word = || "let".make_literal_matcher("Expected let").lift::<Position>();
+/// let space = || " ".make_literal_matcher("Expected space").lift::<Position>().many();
+/// let newline = || "\n".make_literal_matcher("Expected newline").lift::<Position>();
+///
+/// // The second `let` must start right of the column of the first
+/// let block = space()
+///     .seq(current_column())
+///     .map(|(_, column)| column)
+///     .map_err(Foldable::fold)
+///     .bind_output(move |column| {
+///         word()
+///             .skip(newline())
+///             .skip(space())
+///             .skip(indented_past(column, "Expected indented let"))
+///             .skip(word())
+///     });
+///
+/// assert!(block.parse(" let\n  let".with_state(Position::default())).is_ok());
+/// let (_, err) = block.parse(" let\n let".with_state(Position::default())).unwrap_err();
+/// assert_eq!(err, "Expected indented let");
+/// ```
+pub fn current_column<'a, E>() -> impl StatefulParser<Position, &'a str, usize, E>
+where
+    E: Clone,
+{
+    move |carrier: StateCarrier<Position, &'a str>| {
+        let column = carrier.state.column;
+        Ok((carrier, column))
+    }
+}
+
+/// Succeeds without consuming input if the input is at column `column`.
+pub fn at_column<'a, E>(column: usize, err: E) -> impl StatefulParser<Position, &'a str, (), E>
+where
+    E: Clone,
+{
+    move |carrier: StateCarrier<Position, &'a str>| {
+        if carrier.state.column == column {
+            Ok((carrier, ()))
+        } else {
+            Err((carrier, err.clone()))
+        }
+    }
+}
+
+/// Succeeds without consuming input if the input is right of column `reference`.
+pub fn indented_past<'a, E>(reference: usize, err: E) -> impl StatefulParser<Position, &'a str, (), E>
+where
+    E: Clone,
+{
+    move |carrier: StateCarrier<Position, &'a str>| {
+        if carrier.state.column > reference {
+            Ok((carrier, ()))
+        } else {
+            Err((carrier, err.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::WithState;
+    use crate::*;
+
+    #[test]
+    fn test_nested_blocks_and_dedent_errors() {
+        let word = || {
+            <Layout as Parsable<&str>>::make_anything_matcher("Expected word")
+                .validate(|c: &char| c.is_alphanumeric(), "Expected word")
+                .many()
+                .map(|chars| chars.into_iter().collect::<alloc::string::String>())
+                .map_err(LayoutError::Item)
+        };
+        let newline = || '\n'.make_character_matcher(LayoutError::Item("Expected newline"));
+        let leaf = word().skip(newline());
+        let inner = word().skip(newline()).seq(indented_block(leaf)).map_err(Foldable::fold);
+        let outer = word().skip(newline()).seq(indented_block(inner)).map_err(Foldable::fold);
+        let document = aligned_items(outer);
+
+        let (rest, tree) = document.parse("a\n  b\n    c\n    d\n\n  e\n      f\nz\n  y\n    x\n".with_state(Indentation::new())).unwrap();
+        assert_eq!(rest.input, "");
+        assert_eq!(rest.state, Indentation::new());
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].1[0].1, vec!["c", "d"]);
+        assert_eq!(tree[0].1[1].1, vec!["f"]);
+
+        let (rest, err) = document.parse("a\n    b\n      c\n  d\n".with_state(Indentation::new())).unwrap_err();
+        assert_eq!(err, LayoutError::InconsistentDedent { found: 2 });
+        assert_eq!(rest.input, "  d\n");
+
+        let (_, err) = document.parse("a\nb\n".with_state(Indentation::new())).unwrap_err();
+        assert_eq!(err, LayoutError::ExpectedIndent { current: 0, found: 0 });
+    }
+
+    #[test]
+    fn test_column_guards() {
+        let start = "ab\n  c".with_state(Position::default());
+        let letters = "ab\n  ".make_literal_matcher("Expected letters").lift::<Position>();
+
+        let (rest, column) = letters.seq(current_column()).map(|(_, column)| column).map_err(Foldable::fold).parse(start).unwrap();
+        assert_eq!((rest.input, column), ("c", 2));
+        assert!(at_column(2, "Expected column 2").parse(rest).is_ok());
+        assert_eq!(at_column(0, "Expected column 0").parse(rest), Err((rest, "Expected column 0")));
+        assert!(indented_past(1, "Expected indent").parse(rest).is_ok());
+        assert_eq!(indented_past(2, "Expected indent").parse(rest), Err((rest, "Expected indent")));
+    }
+}
@@ -2,11 +2,52 @@
 //!
 //! Provides ergonomic parsing utilities for lexical analysis, 
 //! including whitespace handling, token parsing, and lexeme helpers.
+//!
+//! ## Token Streams
+//!
+//! Language frontends usually lex first and parse the tokens afterwards. A `Lexer` is built
+//! from token rules (literals, character classes, regexes or arbitrary parsers) and skip rules
+//! for whitespace and comments. It turns a `&str` into `Spanned` tokens, either eagerly with
+//! `tokenize` or lazily with `tokens`. Token vectors are parsed as slices, with `token_eq`
+//! and `token_if` matching single tokens.
+//!
+//! At every position the skip rules are applied until none of them matches; then the token
+//! rule with the longest match wins, and ties go to the rule added first.
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::lexer::*;
+//!
+//! #[derive(Debug, Clone, PartialEq, Eq)]
+//! enum Tok<'a> { Let, Eq, Ident(&'a str), Num(u32) }
+//!
+//! let lexer = Lexer::new()
+//!     .skip_char_class(char::is_whitespace)
+//!     .skip(line_comment("#"))
+//!     .literal("let", Tok::Let)
+//!     .literal("=", Tok::Eq)
+//!     .char_class(char::is_alphabetic, Tok::Ident)
+//!     .char_class(|c| c.is_ascii_digit(), |digits| Tok::Num(digits.parse().unwrap()));
+//!
+//! let tokens = lexer.tokenize("let x = 42 # answer").unwrap();
+//! assert_eq!(tokens[1].value, Tok::Ident("x"));
+//! assert_eq!((tokens[3].span.start, tokens[3].span.end), (8, 10));
+//!
+//! let name = token_if(|t: &Tok| matches!(t, Tok::Ident(_)), "Expected name");
+//! let binding = token_eq(Tok::Let, "Expected let").seq(name).map_err(Foldable::fold).skip(token_eq(Tok::Eq, "Expected ="));
+//! let (rest, (_, name)) = binding.parse(&tokens).unwrap();
+//! assert_eq!((name.value.clone(), rest.len()), (Tok::Ident("x"), 1));
+//!
+//! assert_eq!(lexer.tokenize("let ?"), Err(LexError { offset: 4 }));
+//! ```
 
+use std::fmt::{self, Display, Formatter};
 
+use crate::parsers::Span;
 use crate::{core::{Parsable, Parser}, Either3, Either4, ParsableItem, ParserSugar};
 
 /// Extension trait for lexical parsing utilities
+#[allow(clippy::panic)] // Repetitions never fail, so their error arms are unreachable
 pub trait LexerExt<'a, Output, Error>: Parser<&'a str, Output, Error> 
 where 
     Error: Clone,
@@ -109,10 +150,11 @@ pub fn line_comment<'a>(comment_start: &'a str) -> impl Parser<&'a str, String,
 
 /// Parse block comment with specified start and end delimiters
 pub fn block_comment<'a>(start: &'a str, end: &'a str) -> impl Parser<&'a str, String, &'a str> {
+    let end_start = end.chars().next();
     (
         start.make_literal_matcher("Expected block comment start"),
         <&'a str as Parsable<&'a str>>::make_anything_matcher("Expected block comment content")
-            .validate(|c| c != &end.chars().next().unwrap(), "Block comment content")
+            .validate(move |c| Some(*c) != end_start, "Block comment content")
             .many()
             .map(|chars| chars.into_iter().collect::<String>()),
         end.make_literal_matcher("Expected block comment end")
@@ -145,6 +187,202 @@ pub fn to_lowercase<'a, Output: Clone>(parser: impl Parser<&'a str, Output, &'a
     parser.map(|out| out)
 }
 
+/// A value together with the byte span of the source text it was produced from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(value: T, span: Span) -> Self {
+        Spanned { value, span }
+    }
+}
+
+/// No token rule matched at byte `offset` of the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexError {
+    pub offset: usize,
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "no token matches at byte {}", self.offset)
+    }
+}
+
+impl std::error::Error for LexError {}
+
+type MatchRule<'a> = Box<dyn Fn(&'a str) -> usize + 'a>;
+type MakeToken<'a, T> = Box<dyn Fn(&'a str) -> T + 'a>;
+
+/// Splits source text into `Spanned` tokens.
+///
+/// Rules report the length of their match at the current position; matches of length zero
+/// are ignored, so rules cannot make the lexer loop.
+pub struct Lexer<'a, T> {
+    skip: Vec<MatchRule<'a>>,
+    rules: Vec<(MatchRule<'a>, MakeToken<'a, T>)>,
+}
+
+impl<'a, T> Default for Lexer<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Lexer<'a, T> {
+    /// Creates a lexer without rules.
+    pub fn new() -> Self {
+        Lexer {
+            skip: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Adds a token rule producing `make(text)` for the text matched by `parser`.
+    pub fn rule<Output, Error: Clone>(
+        mut self,
+        parser: impl Parser<&'a str, Output, Error> + 'a,
+        make: impl Fn(&'a str) -> T + 'a,
+    ) -> Self {
+        self.rules.push((parser_rule(parser), Box::new(make)));
+        self
+    }
+
+    /// Adds a token rule producing `token` for the literal `text`.
+    pub fn literal(self, text: &'a str, token: T) -> Self
+    where
+        T: Clone + 'a,
+    {
+        self.rule(text.make_literal_matcher(()), move |_| token.clone())
+    }
+
+    /// Adds a token rule producing `make(text)` for the longest run of characters satisfying
+    /// `class`.
+    pub fn char_class(mut self, class: impl Fn(char) -> bool + 'a, make: impl Fn(&'a str) -> T + 'a) -> Self {
+        self.rules.push((class_rule(class), Box::new(make)));
+        self
+    }
+
+    /// Adds a token rule producing `make(text)` for the text matched by the regex `pattern`.
+    ///
+    /// Panics if `pattern` is not a valid regex.
+    #[cfg(feature = "regex")]
+    pub fn regex(self, pattern: &str, make: impl Fn(&'a str) -> T + 'a) -> Self {
+        self.rule(crate::regex::regex_matcher(pattern, ()), make)
+    }
+
+    /// Adds a rule whose matches are skipped between tokens, such as comments.
+    pub fn skip<Output, Error: Clone>(mut self, parser: impl Parser<&'a str, Output, Error> + 'a) -> Self {
+        self.skip.push(parser_rule(parser));
+        self
+    }
+
+    /// Adds a rule skipping runs of characters satisfying `class`, such as whitespace.
+    pub fn skip_char_class(mut self, class: impl Fn(char) -> bool + 'a) -> Self {
+        self.skip.push(class_rule(class));
+        self
+    }
+
+    /// Splits all of `source` into tokens, failing at the first position no rule matches.
+    pub fn tokenize(&self, source: &'a str) -> Result<Vec<Spanned<T>>, LexError> {
+        self.tokens(source).collect()
+    }
+
+    /// Returns an iterator lexing `source` on demand. After an error, the iterator ends.
+    pub fn tokens<'l>(&'l self, source: &'a str) -> Tokens<'l, 'a, T> {
+        Tokens {
+            lexer: self,
+            source,
+            offset: 0,
+        }
+    }
+
+    fn skip_from(&self, source: &'a str, mut offset: usize) -> usize {
+        loop {
+            let before = offset;
+            for rule in &self.skip {
+                offset += source.get(offset..).map_or(0, rule);
+            }
+            if offset == before {
+                return offset;
+            }
+        }
+    }
+}
+
+fn parser_rule<'a, Output, Error: Clone>(parser: impl Parser<&'a str, Output, Error> + 'a) -> MatchRule<'a> {
+    Box::new(move |input| parser.parse(input).map_or(0, |(rest, _)| input.len() - rest.len()))
+}
+
+fn class_rule<'a>(class: impl Fn(char) -> bool + 'a) -> MatchRule<'a> {
+    Box::new(move |input| input.len() - input.trim_start_matches(&class).len())
+}
+
+/// Lazy token iterator returned by `Lexer::tokens`.
+pub struct Tokens<'l, 'a, T> {
+    lexer: &'l Lexer<'a, T>,
+    source: &'a str,
+    offset: usize,
+}
+
+impl<'a, T> Iterator for Tokens<'_, 'a, T> {
+    type Item = Result<Spanned<T>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.lexer.skip_from(self.source, self.offset);
+        let input = match self.source.get(start..) {
+            Some(input) if !input.is_empty() => input,
+            _ => {
+                self.offset = self.source.len();
+                return None;
+            }
+        };
+        let mut best: Option<(usize, &MakeToken<'a, T>)> = None;
+        for (rule, make) in &self.lexer.rules {
+            let len = rule(input);
+            if len > best.map_or(0, |(best_len, _)| best_len) {
+                best = Some((len, make));
+            }
+        }
+        // Consume the rest of the source so the iterator ends after the error
+        self.offset = self.source.len();
+        // A rule whose length ends inside a character matched nothing usable either
+        let (len, make, text) = match best.and_then(|(len, make)| Some((len, make, input.get(..len)?))) {
+            Some(best) => best,
+            None => return Some(Err(LexError { offset: start })),
+        };
+        self.offset = start + len;
+        Some(Ok(Spanned::new(make(text), Span::new(start, start + len))))
+    }
+}
+
+/// Matches a single token whose value equals `value`, ignoring its span.
+pub fn token_eq<'t, T, Error>(value: T, err: Error) -> impl Parser<&'t [Spanned<T>], &'t Spanned<T>, Error>
+where
+    T: Eq,
+    Error: Clone,
+{
+    token_if(move |token: &T| *token == value, err)
+}
+
+/// Matches a single token whose value satisfies `predicate`.
+pub fn token_if<'t, T, Error>(
+    predicate: impl Fn(&T) -> bool,
+    err: Error,
+) -> impl Parser<&'t [Spanned<T>], &'t Spanned<T>, Error>
+where
+    T: Eq,
+    Error: Clone,
+{
+    move |input: &'t [Spanned<T>]| match input.split_first() {
+        Some((token, rest)) if predicate(&token.value) => Ok((rest, token)),
+        _ => Err((input, err.clone())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,4 +438,25 @@ mod tests {
         assert_eq!(lexeme_parser.parse("hello  world"), Ok(("world", "hello")));
         //assert_eq!(lexeme_parser.parse("hello world"), Ok(("world", "hello")));
     }
+
+    #[test]
+    fn test_lexer_longest_match_and_lazy_tokens() {
+        let lexer = Lexer::new()
+            .skip_char_class(|c| c == ' ')
+            .skip(block_comment("/*", "*/"))
+            .literal("=", "eq")
+            .literal("==", "eqeq")
+            .literal("if", "if")
+            .char_class(char::is_alphabetic, |_| "ident");
+
+        let kinds = |source| lexer.tokenize(source).map(|tokens| tokens.into_iter().map(|t| t.value).collect::<Vec<_>>());
+        assert_eq!(kinds("if /* c */ iffy == ="), Ok(vec!["if", "ident", "eqeq", "eq"]));
+        assert_eq!(kinds("   "), Ok(vec![]));
+
+        let mut tokens = lexer.tokens("a = 1 b");
+        assert_eq!(tokens.next(), Some(Ok(Spanned::new("ident", Span::new(0, 1)))));
+        assert_eq!(tokens.next(), Some(Ok(Spanned::new("eq", Span::new(2, 3)))));
+        assert_eq!(tokens.next(), Some(Err(LexError { offset: 4 })));
+        assert_eq!(tokens.next(), None);
+    }
 }
@@ -151,9 +151,69 @@
 //! assert_eq!(multi_alt_parser.parse("two"), Ok(("", Either3::Middle("two"))));
 //! ```
 //!
+//! Tuples of up to 10 parsers are supported. Enable the `wide-tuples` feature for tuples of
+//! up to 16 parsers, with `Either11` to `Either16` as their alternation outputs.
+//!
+//! `Either` converts to and from `Result`, with `Right` as `Ok`, and with the `either`
+//! feature also to and from `either::Either`.
+//!
+//! ## `no_std` Support
+//!
+//! The core combinators, the sugar, slice and string parsing, `bits`, `unicode`, `analysis`,
+//! `railroad`, `trampoline`, `expression`, `dispatch`, `statics` and the `parsers` submodules
+//! only need `alloc`. Disable the default `std` feature to use them in `#![no_std]` crates.
+//! Modules relying on hash maps, I/O, clocks or threads, such as `memo`, `packrat`, `grammar`,
+//! `reader` and `trace`, as well as `recursive_sync`, `SyncDeclared` and `StaticParser`,
+//! require `std`.
+//!
+//! Error types implement `core::error::Error` with or without `std`, which is why the crate
+//! needs Rust 1.81, the `rust-version` in `Cargo.toml`.
+//!
+//! ## Panic Freedom and Fuzzing
+//!
+//! Parsers must return errors rather than panic on malformed input. The `strict` feature
+//! turns this audit into lints: with it, `cargo clippy --features strict` rejects `panic!`,
+//! `unreachable!`, `unwrap`, `expect`, indexing and string slicing in library code, so
+//! offsets go through `get` or `split_at_checked`. The remaining panics are marked with an
+//! `allow` and a reason; they are reached through misuse of the API, such as an undefined
+//! `Declared` parser, or guarded by an invariant such as a byte indexing a 256-entry table,
+//! never through input. `Parser::parse_catch` converts panics into errors for parsers that
+//! are not audited, such as user closures.
+//!
+//! Clippy does not lint code expanded from `macro_rules!` macros, such as the tuple sugar
+//! impls, so `strict` cannot check them; they are kept free of these constructs by review.
+//!
+//! Deep nesting overflows the stack, which aborts the process instead of panicking. The
+//! recursive data formats bound it, as `formats::json` does with `DEFAULT_MAX_DEPTH`, and
+//! grammars over untrusted input should use `Parser::with_depth_limit` or `trampoline`.
+//!
+//! The `fuzz` directory holds `cargo fuzz` targets for the string matchers and the data
+//! formats:
+//!
+//! ```text
+//! cargo +nightly fuzz run json
+//! ```
+//!
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(
+    all(feature = "strict", not(test)),
+    deny(
+        clippy::panic,
+        clippy::unreachable,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::indexing_slicing,
+        clippy::string_slice
+    )
+)]
+
+extern crate alloc;
 
 // Re-export all public items
-pub use crate::core::{fail, pure, recursive, Parsable, ParsableItem, Parser};
+pub use crate::core::{end_of_input, BoxedParser, Checkpoint, Declared, fail, ParseIter, pure, recursive, recursive_left, rest, SharedParser, SyncParser, SyncSharedParser, Parsable, ParsableItem, Parser};
+#[cfg(feature = "std")]
+pub use crate::core::{recursive_sync, SyncDeclared};
+pub use crate::parsers::{make_char_predicate_matcher, parse_str, KeywordMatcher};
 pub use crate::sugar::*;
 pub use crate::types::*;
 pub use crate::state::*;
@@ -164,6 +224,37 @@ pub mod parsers;
 pub mod sugar;
 pub mod types;
 pub mod state;
+#[cfg(feature = "std")]
+pub mod bench;
+#[cfg(feature = "std")]
+pub mod stress;
+pub mod scan;
+pub mod patterns;
+pub mod recovery;
+pub mod remainder;
+pub mod versioning;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod observe;
+#[cfg(feature = "std")]
+pub mod repl;
+pub mod unicode;
+pub mod bits;
+pub mod context;
+pub mod layout;
+pub mod unparse;
+pub mod cst;
+pub mod testgen;
+pub mod analysis;
+pub mod railroad;
+pub mod trampoline;
+pub mod expression;
+pub mod dispatch;
+pub mod statics;
+pub mod formats;
+#[cfg(feature = "std")]
+pub mod reader;
 
 
 //TODO document
@@ -174,13 +265,26 @@ pub mod state;
 
 
 //TODO reconsider
+#[cfg(feature = "std")]
 pub mod lexer /*integrate with stateful parsers and builtin states*/;
+#[cfg(feature = "std")]
 pub mod memo; /*needs a sanity check, not sure if i like the api*/
+#[cfg(feature = "std")]
 pub mod packrat; //"this one needs a serious check!!"
+#[cfg(feature = "persist")]
+pub mod persist;
+#[cfg(feature = "serde")]
+pub mod de;
+#[cfg(feature = "regex")]
+pub mod regex;
+#[cfg(feature = "async")]
+pub mod async_reader;
 
 //TODO implement
 pub mod pratt; /* Pratt parser with pre/in/pos/mix-fix precedence*/
-pub mod context; /*contextual parsers*/
+#[cfg(feature = "std")]
+pub mod compose;
+#[cfg(feature = "std")]
 pub mod grammar; /* Grammar builder: api
 
 let mut builder = GrammarBuilder::new();
@@ -213,6 +317,5 @@ usual .methods */
 // remove seperate traits like SeqParser, AltParser
 // move stuff to extension traits
 // macrofy repeated impls
-// feature flag for regexp
 #[cfg(test)]
 pub mod tests;
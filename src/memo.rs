@@ -23,16 +23,52 @@
 //! let result1 = memoized_parser.parse("hello world");
 //! let result2 = memoized_parser.parse("hello world"); // Uses cached result
 //! ```
+//!
+//! ## Stateful Parsers
+//!
+//! Cache keys are built from the whole input. For a `StateCarrier` input, which hashes and
+//! compares its state along with the text, a result is therefore cached per position and
+//! state, and the state must implement `Hash` and `Eq`. A cached result is reused whenever
+//! the key compares equal, so the contract is: a parser's result may depend only on the
+//! input text and on the parts of the state that `Eq` compares. `Position` compares line
+//! and column but not its `ColumnPolicy`, so one cache must not serve several policies.
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::context::Context;
+//! use friss::memo::*;
+//! use friss::parsers::WithState;
+//!
+//! // Takes as many characters as the context says
+//! let take = |input: StateCarrier<Context<usize>, &'static str>| {
+//!     let n = input.state.0;
+//!     match input.input.get(..n) {
+//!         Some(taken) => Ok((StateCarrier::new(input.state, &input.input[n..]), taken)),
+//!         None => Err((input, "Too short")),
+//!     }
+//! };
+//! let memoized = take.memoize();
+//!
+//! assert_eq!(memoized.parse("abc".with_state(Context(1))).map(|(_, taken)| taken), Ok("a"));
+//! assert_eq!(memoized.parse("abc".with_state(Context(2))).map(|(_, taken)| taken), Ok("ab"));
+//! assert_eq!(memoized.parse("abc".with_state(Context(2))).map(|(_, taken)| taken), Ok("ab"));
+//! assert_eq!((memoized.stats().misses, memoized.stats().hits), (2, 1));
+//! ```
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::Infallible;
 use std::hash::Hash;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::str::FromStr;
 use std::marker::PhantomData;
+use std::ops::Range;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::sync::{Arc, PoisonError, RwLock};
 
 use crate::core::{Parser, ParserOutput};
-use crate::state::{StateCarrier, StatefulParser};
+use crate::scan::Scannable;
+use crate::state::StateCarrier;
 
 /// A key for the memoization cache.
 ///
@@ -54,22 +90,285 @@ pub enum MemoResult<I, O, E> {
     Failure(I, E),
 }
 
+/// Backend for the shared tables of memoized and packrat parsers.
+///
+/// `LocalStorage` (the default) uses `Rc<RefCell<...>>` and is the fastest choice for
+/// single-threaded parsing. `SyncStorage` uses `Arc<RwLock<...>>`, which makes memoized and
+/// packrat parsers `Send + Sync` so they can be shared between threads.
+pub trait CacheStorage {
+    /// The shared, interior-mutable cell holding a table.
+    type Cell<T>: Clone;
+
+    /// Creates a new cell holding `value`.
+    fn new_cell<T>(value: T) -> Self::Cell<T>;
+
+    /// Runs `f` with shared access to the cell contents.
+    fn read<T, R>(cell: &Self::Cell<T>, f: impl FnOnce(&T) -> R) -> R;
+
+    /// Runs `f` with exclusive access to the cell contents.
+    fn write<T, R>(cell: &Self::Cell<T>, f: impl FnOnce(&mut T) -> R) -> R;
+}
+
+/// Single-threaded storage based on `Rc<RefCell<...>>`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalStorage;
+
+impl CacheStorage for LocalStorage {
+    type Cell<T> = Rc<RefCell<T>>;
+
+    fn new_cell<T>(value: T) -> Self::Cell<T> {
+        Rc::new(RefCell::new(value))
+    }
+
+    fn read<T, R>(cell: &Self::Cell<T>, f: impl FnOnce(&T) -> R) -> R {
+        f(&cell.borrow())
+    }
+
+    fn write<T, R>(cell: &Self::Cell<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut cell.borrow_mut())
+    }
+}
+
+/// Thread-safe storage based on `Arc<RwLock<...>>`.
+///
+/// A poisoned lock is recovered, since the tables only hold cached results.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncStorage;
+
+impl CacheStorage for SyncStorage {
+    type Cell<T> = Arc<RwLock<T>>;
+
+    fn new_cell<T>(value: T) -> Self::Cell<T> {
+        Arc::new(RwLock::new(value))
+    }
+
+    fn read<T, R>(cell: &Self::Cell<T>, f: impl FnOnce(&T) -> R) -> R {
+        f(&cell.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn write<T, R>(cell: &Self::Cell<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut cell.write().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Cache statistics of a memoized parser.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Number of lookups answered from the cache
+    pub hits: usize,
+    /// Number of lookups that had to run the parser
+    pub misses: usize,
+    /// Number of entries evicted to stay within the capacity
+    pub evictions: usize,
+    /// Number of entries currently cached
+    pub entries: usize,
+}
+
+impl MemoStats {
+    /// Returns the fraction of lookups answered from the cache.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A cached result with the tick of its last use.
+type Entry<I, O, E> = (MemoResult<I, O, E>, u64);
+
+/// The table of cached results used by `MemoState`.
+///
+/// The table optionally holds at most `capacity` entries, evicting the least recently used
+/// entry when full, and counts hits and misses.
+#[derive(Debug, Clone)]
+pub struct MemoTable<I, O, E>
+where
+    I: Clone + Hash + Eq,
+{
+    entries: HashMap<MemoKey<I>, Entry<I, O, E>>,
+    recency: BTreeMap<u64, MemoKey<I>>,
+    capacity: Option<usize>,
+    tick: u64,
+    stats: MemoStats,
+}
+
+impl<I, O, E> Default for MemoTable<I, O, E>
+where
+    I: Clone + Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl<I, O, E> MemoTable<I, O, E>
+where
+    I: Clone + Hash + Eq,
+{
+    /// Creates an empty table, bounded to `capacity` entries if given.
+    pub fn new(capacity: Option<usize>) -> Self {
+        MemoTable {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            capacity,
+            tick: 0,
+            stats: MemoStats::default(),
+        }
+    }
+
+    /// Looks up `key`, counting a hit or miss and marking the entry as recently used.
+    pub fn lookup(&mut self, key: &MemoKey<I>) -> Option<MemoResult<I, O, E>>
+    where
+        O: Clone,
+        E: Clone,
+    {
+        self.tick += 1;
+        match self.entries.get_mut(key) {
+            Some((result, used)) => {
+                self.stats.hits += 1;
+                self.recency.remove(used);
+                *used = self.tick;
+                self.recency.insert(self.tick, key.clone());
+                Some(result.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Returns the entry for `key` without affecting statistics or recency.
+    pub fn get(&self, key: &MemoKey<I>) -> Option<&MemoResult<I, O, E>> {
+        self.entries.get(key).map(|(result, _)| result)
+    }
+
+    /// Inserts a result, evicting the least recently used entry if the table is full.
+    pub fn insert(&mut self, key: MemoKey<I>, result: MemoResult<I, O, E>) {
+        if self.capacity == Some(0) {
+            return;
+        }
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.insert(key.clone(), (result, self.tick)) {
+            self.recency.remove(&used);
+        }
+        self.recency.insert(self.tick, key);
+
+        while self.capacity.is_some_and(|capacity| self.entries.len() > capacity) {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+    }
+
+    /// Replaces every entry by the result of `f`, dropping the entries it returns `None`
+    /// for. The recency of kept entries is preserved.
+    pub fn update_entries(
+        &mut self,
+        mut f: impl FnMut(MemoKey<I>, MemoResult<I, O, E>) -> Option<(MemoKey<I>, MemoResult<I, O, E>)>,
+    ) {
+        self.recency.clear();
+        for (key, (result, used)) in std::mem::take(&mut self.entries) {
+            if let Some((key, result)) = f(key, result) {
+                self.recency.insert(used, key.clone());
+                self.entries.insert(key, (result, used));
+            }
+        }
+    }
+
+    /// Removes all entries, keeping the statistics.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    /// Returns the number of cached entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no entries are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the maximum number of entries, if bounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the cache statistics.
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+}
+
 /// State type for memoization.
 ///
 /// This stores the cache of parsing results for reuse.
-#[derive(Debug, Clone, Default)]
-pub struct MemoState<I, O, E> 
-where 
+pub struct MemoState<I, O, E, S = LocalStorage>
+where
     I: Clone + Hash + Eq,
     O: Clone,
     E: Clone,
+    S: CacheStorage,
 {
     /// The cache of parse results
-    pub cache: Rc<RefCell<HashMap<MemoKey<I>, MemoResult<I, O, E>>>>,
+    pub cache: S::Cell<MemoTable<I, O, E>>,
     /// An optional identifier for the parser, used for cache keys
     pub parser_id: Option<String>,
 }
 
+impl<I, O, E, S> Clone for MemoState<I, O, E, S>
+where
+    I: Clone + Hash + Eq,
+    O: Clone,
+    E: Clone,
+    S: CacheStorage,
+{
+    fn clone(&self) -> Self {
+        MemoState {
+            cache: self.cache.clone(),
+            parser_id: self.parser_id.clone(),
+        }
+    }
+}
+
+impl<I, O, E, S> Debug for MemoState<I, O, E, S>
+where
+    I: Clone + Hash + Eq,
+    O: Clone,
+    E: Clone,
+    S: CacheStorage,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoState")
+            .field("stats", &self.stats())
+            .field("parser_id", &self.parser_id)
+            .finish()
+    }
+}
+
+impl<I, O, E, S> Default for MemoState<I, O, E, S>
+where
+    I: Clone + Hash + Eq,
+    O: Clone,
+    E: Clone,
+    S: CacheStorage,
+{
+    fn default() -> Self {
+        Self::with_storage(None)
+    }
+}
+
 impl<I, O, E> MemoState<I, O, E>
 where
     I: Clone + Hash + Eq,
@@ -78,35 +377,67 @@ where
 {
     /// Creates a new memoization state.
     pub fn new() -> Self {
-        MemoState {
-            cache: Rc::new(RefCell::new(HashMap::new())),
-            parser_id: None,
-        }
+        Self::with_storage(None)
     }
 
     /// Creates a new memoization state with a parser identifier.
     pub fn with_id(id: impl Into<String>) -> Self {
+        Self::with_storage(Some(id.into()))
+    }
+}
+
+impl<I, O, E, S> MemoState<I, O, E, S>
+where
+    I: Clone + Hash + Eq,
+    O: Clone,
+    E: Clone,
+    S: CacheStorage,
+{
+    /// Creates a new memoization state backed by the storage `S`.
+    pub fn with_storage(parser_id: Option<String>) -> Self {
         MemoState {
-            cache: Rc::new(RefCell::new(HashMap::new())),
-            parser_id: Some(id.into()),
+            cache: S::new_cell(MemoTable::new(None)),
+            parser_id,
         }
     }
+
+    /// Creates a new memoization state holding at most `capacity` entries.
+    pub fn with_capacity(capacity: usize, parser_id: Option<String>) -> Self {
+        MemoState {
+            cache: S::new_cell(MemoTable::new(Some(capacity))),
+            parser_id,
+        }
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&self) {
+        S::write(&self.cache, |cache| cache.clear());
+    }
+
+    /// Returns the cache statistics.
+    pub fn stats(&self) -> MemoStats {
+        S::read(&self.cache, |cache| cache.stats())
+    }
 }
 
 /// A memoized parser that caches its results.
 ///
 /// This wraps a parser with memoization capabilities, storing results in a shared cache.
-pub struct MemoizedParser<I, O, E, P>
+///
+/// With `S = SyncStorage` the cache is thread-safe and the parser is `Send + Sync` whenever
+/// the wrapped parser and the cached types are.
+pub struct MemoizedParser<I, O, E, P, S = LocalStorage>
 where
     I: Clone + Hash + Eq + Parsable<E>,
     O: Clone,
     E: Clone,
     P: Parser<I, O, E>,
+    S: CacheStorage,
 {
     /// The underlying parser
     parser: P,
     /// The state for caching results
-    memo_state: MemoState<I, O, E>,
+    memo_state: MemoState<I, O, E, S>,
 }
 
 impl<I, O, E, P> MemoizedParser<I, O, E, P>
@@ -133,49 +464,94 @@ where
     }
 }
 
-impl<I, O, E, P> Parser<I, O, E> for MemoizedParser<I, O, E, P>
+impl<I, O, E, P, S> MemoizedParser<I, O, E, P, S>
 where
     I: Clone + Hash + Eq + Parsable<E>,
-    O: Clone + ParserOutput,
+    O: Clone,
     E: Clone,
     P: Parser<I, O, E>,
+    S: CacheStorage,
 {
-    fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
-        let key = MemoKey {
-            input: input.clone(),
-            parser_id: self.memo_state.parser_id.clone(),
-        };
+    /// Creates a new memoized parser whose cache is backed by the storage `S`.
+    pub fn with_storage(parser: P, parser_id: Option<String>) -> Self {
+        MemoizedParser {
+            parser,
+            memo_state: MemoState::with_storage(parser_id),
+        }
+    }
 
-        if let Some(result) = self.memo_state.cache.borrow().get(&key) {
-            match result {
-                MemoResult::Success(rest, output) => {
-                    return Ok((rest.clone(), output.clone()));
-                }
-                MemoResult::Failure(rest, error) => {
-                    return Err((rest.clone(), error.clone()));
-                }
-            }
+    /// Creates a new memoized parser whose cache holds at most `capacity` entries,
+    /// evicting the least recently used entry when full.
+    pub fn with_capacity(parser: P, capacity: usize) -> Self {
+        MemoizedParser {
+            parser,
+            memo_state: MemoState::with_capacity(capacity, None),
         }
+    }
 
-        let result = self.parser.parse(input.clone());
+    /// Removes all cached results.
+    pub fn clear_cache(&self) {
+        self.memo_state.clear();
+    }
 
-        match &result {
-            Ok((rest, output)) => {
-                self.memo_state.cache.borrow_mut().insert(
-                    key,
-                    MemoResult::Success(rest.clone(), output.clone()),
-                );
+    /// Returns the cache hit/miss statistics.
+    pub fn stats(&self) -> MemoStats {
+        self.memo_state.stats()
+    }
+}
+
+impl<I, O, E, P, S> Parser<I, O, E> for MemoizedParser<I, O, E, P, S>
+where
+    I: Clone + Hash + Eq + Parsable<E>,
+    O: Clone + ParserOutput,
+    E: Clone,
+    P: Parser<I, O, E>,
+    S: CacheStorage,
+{
+    fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
+        memoized_parse(&self.parser, &self.memo_state, input)
+    }
+}
+
+/// Runs `parser` through the cache of `memo_state`.
+fn memoized_parse<I, O, E, P, S>(
+    parser: &P,
+    memo_state: &MemoState<I, O, E, S>,
+    input: I,
+) -> Result<(I, O), (I, E)>
+where
+    I: Clone + Hash + Eq + Parsable<E>,
+    O: Clone + ParserOutput,
+    E: Clone,
+    P: Parser<I, O, E>,
+    S: CacheStorage,
+{
+    let key = MemoKey {
+        input: input.clone(),
+        parser_id: memo_state.parser_id.clone(),
+    };
+
+    let cached = S::write(&memo_state.cache, |cache| cache.lookup(&key));
+    if let Some(result) = cached {
+        match result {
+            MemoResult::Success(rest, output) => {
+                return Ok((rest, output));
             }
-            Err((rest, error)) => {
-                self.memo_state.cache.borrow_mut().insert(
-                    key,
-                    MemoResult::Failure(rest.clone(), error.clone()),
-                );
+            MemoResult::Failure(rest, error) => {
+                return Err((rest, error));
             }
         }
-
-        result
     }
+
+    let result = parser.parse(input);
+
+    let entry = match &result {
+        Ok((rest, output)) => MemoResult::Success(rest.clone(), output.clone()),
+        Err((rest, error)) => MemoResult::Failure(rest.clone(), error.clone()),
+    };
+    S::write(&memo_state.cache, |cache| cache.insert(key, entry));
+
+    result
 }
 
 /// A stateful memoized parser that uses a shared state for caching.
@@ -255,7 +631,8 @@ where
             input: inner_input.clone(),
             parser_id: memo_state.parser_id.clone(),
         };
-        if let Some(result) = memo_state.cache.borrow().get(&key) {
+        let cached = memo_state.cache.borrow_mut().lookup(&key);
+        if let Some(result) = cached {
             match result {
                 MemoResult::Success(rest, output) => {
                     let (new_memo_state, new_rest, new_output) = 
@@ -340,6 +717,462 @@ where
     }
 }
 
+/// Invocation metrics collected for a rule during a warm-up parse.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    /// Number of times the rule was invoked
+    pub calls: usize,
+    /// Number of invocations at an input the rule had already seen
+    pub repeats: usize,
+}
+
+impl RuleStats {
+    /// Returns the fraction of invocations that repeated an earlier input.
+    pub fn repeat_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.repeats as f64 / self.calls as f64
+        }
+    }
+}
+
+/// The set of rules chosen for memoization.
+///
+/// A configuration can be exported with `to_string` (one rule per line) and imported again
+/// with `parse`, so a tuning run can be reused without another warm-up.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoConfig {
+    rules: BTreeSet<String>,
+}
+
+impl MemoConfig {
+    /// Creates an empty configuration that memoizes nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables memoization for `rule`.
+    pub fn enable(mut self, rule: impl Into<String>) -> Self {
+        self.rules.insert(rule.into());
+        self
+    }
+
+    /// Returns true if `rule` is memoized.
+    pub fn is_enabled(&self, rule: &str) -> bool {
+        self.rules.contains(rule)
+    }
+
+    /// Returns the memoized rules in sorted order.
+    pub fn rules(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(String::as_str)
+    }
+}
+
+impl Display for MemoConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for rule in &self.rules {
+            writeln!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MemoConfig {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(MemoConfig {
+            rules: s
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct TunerState {
+    warming_up: bool,
+    threshold: f64,
+    stats: HashMap<String, RuleStats>,
+    /// Positions each rule ran at, as lengths of the remaining input
+    seen: HashMap<String, HashSet<usize>>,
+    config: MemoConfig,
+}
+
+/// Shared controller deciding which adaptively memoized rules use their cache.
+///
+/// A tuner starts in warm-up mode, where rules run unmemoized while their invocation counts
+/// and repeated inputs are recorded. `finish_warmup` then enables memoization only for rules
+/// whose repeat rate reaches the threshold.
+///
+/// Positions are keyed by the length of the remaining input, so a warm-up should parse a
+/// single document: the same offset in two documents of equal length counts as a repeat.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::memo::*;
+///
+/// let tuner = MemoTuner::new(0.25);
+/// let word = "word".make_literal_matcher("Expected word").memoize_adaptive(&tuner, "word");
+/// let bang = "!".make_literal_matcher("Expected !");
+/// let parser = |input| {
+///     word.parse(input)
+///         .and_then(|(rest, _)| bang.parse(rest))
+///         .or_else(|_| word.parse(input))
+/// };
+///
+/// // Warm-up: `word` runs twice at the same input when the first alternative fails
+/// assert_eq!(parser("word?"), Ok(("?", "word")));
+///
+/// let config = tuner.finish_warmup();
+/// assert!(config.is_enabled("word"));
+/// assert_eq!(config.to_string(), "word\n");
+///
+/// let imported = MemoTuner::from_config(config.to_string().parse().unwrap());
+/// assert!(imported.is_memoized("word"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoTuner {
+    state: Rc<RefCell<TunerState>>,
+}
+
+impl MemoTuner {
+    /// Creates a tuner in warm-up mode that memoizes rules with a repeat rate of at least `threshold`.
+    pub fn new(threshold: f64) -> Self {
+        MemoTuner {
+            state: Rc::new(RefCell::new(TunerState {
+                warming_up: true,
+                threshold,
+                ..TunerState::default()
+            })),
+        }
+    }
+
+    /// Creates a tuner that skips warm-up and uses a previously exported configuration.
+    pub fn from_config(config: MemoConfig) -> Self {
+        MemoTuner {
+            state: Rc::new(RefCell::new(TunerState {
+                config,
+                ..TunerState::default()
+            })),
+        }
+    }
+
+    /// Returns true while invocation metrics are being collected.
+    pub fn is_warming_up(&self) -> bool {
+        self.state.borrow().warming_up
+    }
+
+    /// Ends warm-up, selecting the rules to memoize from the collected metrics.
+    pub fn finish_warmup(&self) -> MemoConfig {
+        let mut state = self.state.borrow_mut();
+        let threshold = state.threshold;
+        state.config = MemoConfig {
+            rules: state
+                .stats
+                .iter()
+                .filter(|(_, stats)| stats.repeats > 0 && stats.repeat_rate() >= threshold)
+                .map(|(rule, _)| rule.clone())
+                .collect(),
+        };
+        state.warming_up = false;
+        state.seen.clear();
+        state.config.clone()
+    }
+
+    /// Returns the metrics collected so far for every rule.
+    pub fn stats(&self) -> HashMap<String, RuleStats> {
+        self.state.borrow().stats.clone()
+    }
+
+    /// Returns the current memoization configuration.
+    pub fn config(&self) -> MemoConfig {
+        self.state.borrow().config.clone()
+    }
+
+    /// Returns true if `rule` currently uses its cache.
+    pub fn is_memoized(&self, rule: &str) -> bool {
+        let state = self.state.borrow();
+        !state.warming_up && state.config.is_enabled(rule)
+    }
+
+    fn record(&self, rule: &str, position: usize) {
+        let mut state = self.state.borrow_mut();
+        let repeated = !state.seen.entry(rule.to_string()).or_default().insert(position);
+        let stats = state.stats.entry(rule.to_string()).or_default();
+        stats.calls += 1;
+        if repeated {
+            stats.repeats += 1;
+        }
+    }
+}
+
+/// A parser that is memoized only if its `MemoTuner` selected it.
+pub struct AdaptiveMemoizedParser<I, O, E, P>
+where
+    I: Clone + Hash + Eq + Parsable<E>,
+    O: Clone,
+    E: Clone,
+    P: Parser<I, O, E>,
+{
+    /// The underlying parser
+    parser: P,
+    /// The rule name used for metrics and configuration
+    rule: String,
+    /// The tuner deciding whether the cache is used
+    tuner: MemoTuner,
+    /// The state for caching results
+    memo_state: MemoState<I, O, E>,
+}
+
+impl<I, O, E, P> AdaptiveMemoizedParser<I, O, E, P>
+where
+    I: Clone + Hash + Eq + Parsable<E>,
+    O: Clone,
+    E: Clone,
+    P: Parser<I, O, E>,
+{
+    /// Creates a new adaptively memoized parser for `rule`.
+    pub fn new(parser: P, tuner: &MemoTuner, rule: impl Into<String>) -> Self {
+        let rule = rule.into();
+        AdaptiveMemoizedParser {
+            parser,
+            memo_state: MemoState::with_id(rule.clone()),
+            rule,
+            tuner: tuner.clone(),
+        }
+    }
+}
+
+impl<I, O, E, P> Parser<I, O, E> for AdaptiveMemoizedParser<I, O, E, P>
+where
+    I: Clone + Hash + Eq + Parsable<E> + Scannable,
+    O: Clone + ParserOutput,
+    E: Clone,
+    P: Parser<I, O, E>,
+{
+    fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
+        if self.tuner.is_warming_up() {
+            self.tuner.record(&self.rule, input.scan_len());
+            self.parser.parse(input)
+        } else if self.tuner.is_memoized(&self.rule) {
+            memoized_parse(&self.parser, &self.memo_state, input)
+        } else {
+            self.parser.parse(input)
+        }
+    }
+}
+
+/// A replacement of the bytes `range` of a document by `new_len` bytes of new text.
+///
+/// Offsets refer to the text before the edit and must lie on character boundaries, as
+/// the changes an editor reports do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The bytes of the old text that were replaced
+    pub range: Range<usize>,
+    /// The length in bytes of the text that replaced them
+    pub new_len: usize,
+}
+
+impl TextEdit {
+    /// Moves the span `span` of the text before the edit to its position after it.
+    ///
+    /// Returns `None` if the span, extended by `lookahead` bytes, overlaps the edit, since
+    /// a result computed for it may then change.
+    pub fn shift(&self, span: Range<usize>, lookahead: usize) -> Option<Range<usize>> {
+        let touched = self.range.start < span.end.saturating_add(lookahead)
+            && (span.start < self.range.end || span.start == self.range.start);
+        if touched {
+            None
+        } else if span.start >= self.range.end {
+            let removed = self.range.end.saturating_sub(self.range.start);
+            Some(span.start - removed + self.new_len..span.end - removed + self.new_len)
+        } else {
+            Some(span)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct IncrementalState {
+    /// The length of the current document
+    document_len: usize,
+    /// Every edit so far, with the document length before it
+    edits: Vec<(TextEdit, usize)>,
+}
+
+/// Shared controller for re-parsing a document incrementally after edits.
+///
+/// Rules created with `rule` cache their successful results by position. `edit` records a
+/// change of the document; at their next use the rules drop the results whose match,
+/// extended by the rule's lookahead, overlaps the change, and move the others to their new
+/// positions. Re-parsing the edited document with `parse` then runs the rules only where
+/// the text changed.
+///
+/// A rule's lookahead is the number of bytes after its match it may examine, 1 by default
+/// for the character that ends a repetition. Rules whose alternatives look further ahead
+/// before backtracking need a larger `IncrementalRule::lookahead`. Failures are not cached.
+///
+/// Positions are keyed by the length of the remaining input, so rules must only run on the
+/// document passed to `parse`. Cached outputs outlive the text they came from and should
+/// own their data.
+///
+/// With the `persist` feature, `PersistentCache::edit` applies the same edits to a cache
+/// that is saved between runs.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::memo::*;
+/// use std::cell::Cell;
+///
+/// let runs = Cell::new(0);
+/// let incremental = IncrementalParser::new();
+/// let number = incremental.rule(|input: &'static str| {
+///     runs.set(runs.get() + 1);
+///     let digits = input.len() - input.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+///     match input[..digits].parse::<u32>() {
+///         Ok(n) => Ok((&input[digits..], n)),
+///         Err(_) => Err((input, "Expected number")),
+///     }
+/// });
+/// let comma = ','.make_character_matcher("Expected comma").map(|_| 0);
+/// let list = number.sep_by(comma);
+///
+/// assert_eq!(incremental.parse(&list, "1,22,333,4444"), Ok(("", vec![1, 22, 333, 4444])));
+/// assert_eq!(runs.get(), 4);
+///
+/// // Replace "333" by "3"; only the changed number is parsed again
+/// incremental.edit(5..8, 1);
+/// assert_eq!(incremental.parse(&list, "1,22,3,4444"), Ok(("", vec![1, 22, 3, 4444])));
+/// assert_eq!(runs.get(), 5);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalParser {
+    state: Rc<RefCell<IncrementalState>>,
+}
+
+impl IncrementalParser {
+    /// Creates a controller with an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wraps `parser` in a rule whose results survive edits that do not touch them.
+    pub fn rule<O, E, P>(&self, parser: P) -> IncrementalRule<O, E, P> {
+        IncrementalRule {
+            parser,
+            controller: self.clone(),
+            table: RefCell::new(MemoTable::new(None)),
+            applied: Cell::new(self.state.borrow().edits.len()),
+            lookahead: 1,
+        }
+    }
+
+    /// Parses `document` with `parser`, remembering its length for later edits.
+    pub fn parse<'a, O, E: Clone>(
+        &self,
+        parser: &impl Parser<&'a str, O, E>,
+        document: &'a str,
+    ) -> Result<(&'a str, O), (&'a str, E)> {
+        self.state.borrow_mut().document_len = document.len();
+        parser.parse(document)
+    }
+
+    /// Records that the bytes `range` of the document were replaced by `new_len` bytes.
+    pub fn edit(&self, range: Range<usize>, new_len: usize) {
+        let mut state = self.state.borrow_mut();
+        let before = state.document_len;
+        let removed = range.end.saturating_sub(range.start);
+        state.document_len = before.saturating_sub(removed) + new_len;
+        state.edits.push((TextEdit { range, new_len }, before));
+    }
+
+    /// Returns the length of the document after the recorded edits.
+    pub fn document_len(&self) -> usize {
+        self.state.borrow().document_len
+    }
+}
+
+/// A rule of an incrementally re-parsed document, see `IncrementalParser`.
+pub struct IncrementalRule<O, E, P> {
+    /// The underlying parser
+    parser: P,
+    /// The controller recording edits
+    controller: IncrementalParser,
+    /// Results keyed by the length of the remaining input
+    table: RefCell<MemoTable<usize, O, E>>,
+    /// The number of the controller's edits already applied to the table
+    applied: Cell<usize>,
+    /// The number of bytes after a match the parser may examine
+    lookahead: usize,
+}
+
+impl<O, E, P> IncrementalRule<O, E, P> {
+    /// Sets the number of bytes after its match the parser may examine.
+    pub fn lookahead(mut self, bytes: usize) -> Self {
+        self.lookahead = bytes;
+        self
+    }
+
+    /// Returns the cache statistics.
+    pub fn stats(&self) -> MemoStats {
+        self.table.borrow().stats()
+    }
+
+    /// Drops or moves the cached results for the edits recorded since the last parse.
+    fn apply_edits(&self) {
+        let state = self.controller.state.borrow();
+        let mut table = self.table.borrow_mut();
+        for (edit, before) in state.edits.iter().skip(self.applied.get()) {
+            let after = before.saturating_sub(edit.range.end.saturating_sub(edit.range.start)) + edit.new_len;
+            table.update_entries(|key, result| {
+                let MemoResult::Success(rest, output) = result else {
+                    return None;
+                };
+                let start = before.checked_sub(key.input)?;
+                let end = before.checked_sub(rest)?;
+                let span = edit.shift(start..end, self.lookahead)?;
+                let key = MemoKey { input: after.checked_sub(span.start)?, parser_id: key.parser_id };
+                Some((key, MemoResult::Success(after.checked_sub(span.end)?, output)))
+            });
+        }
+        self.applied.set(state.edits.len());
+    }
+}
+
+impl<'a, O, E, P> Parser<&'a str, O, E> for IncrementalRule<O, E, P>
+where
+    O: Clone,
+    E: Clone,
+    P: Parser<&'a str, O, E>,
+{
+    fn parse(&self, input: &'a str) -> Result<(&'a str, O), (&'a str, E)> {
+        self.apply_edits();
+        let key = MemoKey { input: input.len(), parser_id: None };
+        let cached = self.table.borrow_mut().lookup(&key);
+        if let Some(MemoResult::Success(rest, output)) = cached {
+            if let Some(rest) = input.len().checked_sub(rest).and_then(|consumed| input.get(consumed..)) {
+                return Ok((rest, output));
+            }
+        }
+
+        let (rest, output) = self.parser.parse(input)?;
+        self.table
+            .borrow_mut()
+            .insert(key, MemoResult::Success(rest.len(), output.clone()));
+        Ok((rest, output))
+    }
+}
+
 /// Extension trait to add memoization capabilities to parsers.
 pub trait MemoizableParser<I, O, E>: Parser<I, O, E> + Sized
 where
@@ -380,6 +1213,93 @@ where
     fn memoize_with_id(self, id: impl Into<String>) -> MemoizedParser<I, O, E, Self> {
         MemoizedParser::with_id(self, id)
     }
+
+    /// Wraps the parser with memoization, keeping at most `capacity` cached results.
+    ///
+    /// When the cache is full the least recently used result is evicted, which bounds the
+    /// memory used by long-running services parsing many documents.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::memo::*;
+    ///
+    /// let parser = 'a'.make_character_matcher("Expected a").memoize_with_capacity(2);
+    /// parser.parse("a1").unwrap();
+    /// parser.parse("a2").unwrap();
+    /// parser.parse("a1").unwrap();
+    /// parser.parse("a3").unwrap(); // evicts "a2"
+    ///
+    /// let stats = parser.stats();
+    /// assert_eq!((stats.hits, stats.misses, stats.evictions, stats.entries), (1, 3, 1, 2));
+    ///
+    /// parser.clear_cache();
+    /// assert_eq!(parser.stats().entries, 0);
+    /// ```
+    fn memoize_with_capacity(self, capacity: usize) -> MemoizedParser<I, O, E, Self> {
+        MemoizedParser::with_capacity(self, capacity)
+    }
+
+    /// Wraps the parser with a thread-safe memoization cache.
+    ///
+    /// The resulting parser is `Send + Sync` whenever the wrapped parser, input, output and
+    /// error types are, so it can be shared between threads parsing in parallel.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::memo::*;
+    /// use std::sync::Arc;
+    ///
+    /// let parser = Arc::new("abc".make_literal_matcher("Expected abc").memoize_sync());
+    ///
+    /// let handles: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         let parser = parser.clone();
+    ///         std::thread::spawn(move || parser.parse("abcdef"))
+    ///     })
+    ///     .collect();
+    ///
+    /// for handle in handles {
+    ///     assert_eq!(handle.join().unwrap(), Ok(("def", "abc")));
+    /// }
+    /// ```
+    fn memoize_sync(self) -> MemoizedParser<I, O, E, Self, SyncStorage> {
+        MemoizedParser::with_storage(self, None)
+    }
+
+    /// Wraps the parser with a thread-safe memoization cache using a specific identifier.
+    fn memoize_sync_with_id(
+        self,
+        id: impl Into<String>,
+    ) -> MemoizedParser<I, O, E, Self, SyncStorage> {
+        MemoizedParser::with_storage(self, Some(id.into()))
+    }
+
+    /// Wraps the parser with memoization controlled by `tuner`.
+    ///
+    /// During the tuner's warm-up the parser runs unmemoized and reports its invocations
+    /// under `rule`; afterwards its cache is used only if the tuner selected `rule`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::memo::*;
+    ///
+    /// let tuner = MemoTuner::from_config(MemoConfig::new().enable("abc"));
+    /// let parser = "abc".make_literal_matcher("Expected abc").memoize_adaptive(&tuner, "abc");
+    /// assert_eq!(parser.parse("abcdef"), Ok(("def", "abc")));
+    /// ```
+    fn memoize_adaptive(
+        self,
+        tuner: &MemoTuner,
+        rule: impl Into<String>,
+    ) -> AdaptiveMemoizedParser<I, O, E, Self> {
+        AdaptiveMemoizedParser::new(self, tuner, rule)
+    }
 }
 
 impl<I, O, E, P> MemoizableParser<I, O, E> for P
@@ -505,10 +1425,12 @@ where
 mod tests {
     use super::*;
     use crate::core::recursive;
+    use crate::packrat::PackratParser;
     use crate::*;
 
     // Test basic memoization
     #[test]
+    #[allow(clippy::extra_unused_lifetimes)]
     fn test_basic_memoization<'a>() {
         // Create a counter to track how many times the parser is actually run
         let counter = Rc::new(RefCell::new(0));
@@ -592,4 +1514,172 @@ mod tests {
         // We're expecting one execution per unique input position, plus one for the initial call
         assert!(*counter.borrow() <= 10);
     }
+
+    // Test that only rules re-invoked at the same input get memoized after warm-up
+    #[test]
+    fn test_adaptive_memoization() {
+        let hot_counter = Rc::new(RefCell::new(0));
+        let cold_counter = Rc::new(RefCell::new(0));
+        let (hot_clone, cold_clone) = (hot_counter.clone(), cold_counter.clone());
+        let tuner = MemoTuner::new(0.5);
+
+        let hot = (move |input: &'static str| {
+            *hot_clone.borrow_mut() += 1;
+            "a".make_literal_matcher("Expected a").parse(input)
+        })
+        .memoize_adaptive(&tuner, "hot");
+        let cold = (move |input: &'static str| {
+            *cold_clone.borrow_mut() += 1;
+            "b".make_literal_matcher("Expected b").parse(input)
+        })
+        .memoize_adaptive(&tuner, "cold");
+
+        // `hot` is tried again at the same input when the first alternative fails
+        let bang = "!".make_literal_matcher("Expected !");
+        let parser = move |input: &'static str| {
+            let (rest, a) = hot
+                .parse(input)
+                .and_then(|(rest, a)| bang.parse(rest).map(|(rest, _)| (rest, a)))
+                .or_else(|_| hot.parse(input))?;
+            let (rest, b) = cold.parse(rest)?;
+            Ok((rest, (a, b)))
+        };
+
+        assert_eq!(parser.parse("ab"), Ok(("", ("a", "b"))));
+        assert_eq!(*hot_counter.borrow(), 2);
+
+        let config = tuner.finish_warmup();
+        assert_eq!(tuner.stats()["hot"], RuleStats { calls: 2, repeats: 1 });
+        assert_eq!(tuner.stats()["cold"], RuleStats { calls: 1, repeats: 0 });
+        assert_eq!(config.rules().collect::<Vec<_>>(), vec!["hot"]);
+
+        assert_eq!(parser.parse("ab"), Ok(("", ("a", "b"))));
+        assert_eq!(*hot_counter.borrow(), 3);
+        assert_eq!(*cold_counter.borrow(), 2);
+
+        assert_eq!("hot\n\ncold\n".parse::<MemoConfig>().unwrap().rules().count(), 2);
+    }
+
+    // Test that warm-up counts repeats by position, not by the text at it
+    #[test]
+    fn test_adaptive_warmup_keys_by_position() {
+        let tuner = MemoTuner::new(0.5);
+        let a = "a".make_literal_matcher("Expected a").memoize_adaptive(&tuner, "a");
+
+        let mut input = "aaa";
+        while let Ok((rest, _)) = a.parse(input) {
+            input = rest;
+        }
+        assert_eq!(tuner.stats()["a"], RuleStats { calls: 4, repeats: 0 });
+
+        assert!(a.parse("aa").is_ok());
+        assert_eq!(tuner.stats()["a"], RuleStats { calls: 5, repeats: 1 });
+    }
+
+    // Test that sync-backed memoized and packrat parsers can be shared between threads
+    #[test]
+    fn test_sync_memoization_across_threads() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let counter = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter_clone = counter.clone();
+        let counting = move |input: &'static str| {
+            counter_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            "hello".make_literal_matcher("Expected hello").parse(input)
+        };
+        let memoized = Arc::new(counting.memoize_sync());
+        let packrat = Arc::new(
+            "a".make_literal_matcher("Expected a")
+                .packrat_sync("a"),
+        );
+        assert_send_sync(&memoized);
+        assert_send_sync(&packrat);
+
+        memoized.parse("hello world").unwrap();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let (memoized, packrat) = (memoized.clone(), packrat.clone());
+                std::thread::spawn(move || (memoized.parse("hello world"), packrat.parse("ab")))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(
+                handle.join().unwrap(),
+                (Ok((" world", "hello")), Ok(("b", "a")))
+            );
+        }
+
+        assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    // Test that edits invalidate only the results they touch
+    #[test]
+    fn test_incremental_reparsing() {
+        let counter = Rc::new(RefCell::new(0));
+        let counter_clone = counter.clone();
+        let incremental = IncrementalParser::new();
+        let word = incremental.rule(move |input: &'static str| {
+            *counter_clone.borrow_mut() += 1;
+            let rest = input.trim_start_matches(|c: char| c.is_alphabetic());
+            if rest.len() == input.len() {
+                return Err((input, "Expected word"));
+            }
+            Ok((rest, input[..input.len() - rest.len()].to_string()))
+        });
+        let words = word.sep_by(' '.make_character_matcher("Expected space").map(|_| String::new()));
+
+        assert_eq!(incremental.parse(&words, "ab cd ef").unwrap().1, ["ab", "cd", "ef"]);
+        assert_eq!(*counter.borrow(), 3);
+
+        // Appending to "cd" changes the character after its match
+        incremental.edit(5..5, 1);
+        assert_eq!(incremental.parse(&words, "ab cdx ef").unwrap().1, ["ab", "cdx", "ef"]);
+        assert_eq!(*counter.borrow(), 4);
+
+        // Several edits are applied at the next parse: "cdx" moves, "ef" is extended
+        incremental.edit(0..3, 0);
+        incremental.edit(6..6, 3);
+        assert_eq!(incremental.document_len(), 9);
+        assert_eq!(incremental.parse(&words, "cdx ef gh").unwrap().1, ["cdx", "ef", "gh"]);
+        assert_eq!(*counter.borrow(), 6);
+
+        // Replacing "ef" by "é" touches "ef" only
+        incremental.edit(4..6, 2);
+        assert_eq!(incremental.parse(&words, "cdx é gh").unwrap().1, ["cdx", "é", "gh"]);
+        assert_eq!(*counter.borrow(), 7);
+    }
+
+    // Test that a bounded cache evicts the least recently used entry
+    #[test]
+    fn test_memoization_capacity() {
+        let counter = Rc::new(RefCell::new(0));
+        let counter_clone = counter.clone();
+        let counting = move |input: &'static str| {
+            *counter_clone.borrow_mut() += 1;
+            'x'.make_character_matcher("Expected x").parse(input)
+        };
+        let memoized = counting.memoize_with_capacity(2);
+
+        let _ = memoized.parse("x1");
+        let _ = memoized.parse("x2");
+        let _ = memoized.parse("x1");
+        let _ = memoized.parse("x3");
+        assert_eq!(*counter.borrow(), 3);
+
+        // "x1" was used more recently than "x2", so it survived the eviction
+        let _ = memoized.parse("x1");
+        assert_eq!(*counter.borrow(), 3);
+        let _ = memoized.parse("x2");
+        assert_eq!(*counter.borrow(), 4);
+
+        assert_eq!(
+            memoized.stats(),
+            MemoStats { hits: 2, misses: 4, evictions: 2, entries: 2 }
+        );
+        assert!((memoized.stats().hit_rate() - 1.0 / 3.0).abs() < 1e-9);
+
+        memoized.clear_cache();
+        let _ = memoized.parse("x1");
+        assert_eq!(*counter.borrow(), 5);
+    }
 }
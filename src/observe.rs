@@ -0,0 +1,398 @@
+//! # Parser Instrumentation
+//!
+//! This module lets profilers, tracers and coverage tools watch a parse without changing
+//! the grammar. A parser wrapped with `observed(name, &observer)` notifies the observer
+//! whenever it is entered and left. `Profiler` is a ready-made observer counting how often
+//! each rule ran, how often it succeeded and how much time was spent in it. `Coverage`
+//! records which observed rules and branches a test corpus exercised and lists the ones
+//! that never matched.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::observe::*;
+//!
+//! let profiler = Profiler::new();
+//! let digit = '1'.make_character_matcher("Expected 1").observed("digit", &profiler);
+//! let number = digit.many().observed("number", &profiler);
+//!
+//! assert_eq!(number.parse("11x"), Ok(("x", vec!['1', '1'])));
+//!
+//! let digit = profiler.stats("digit").unwrap();
+//! assert_eq!((digit.calls, digit.successes, digit.failures), (3, 2, 1));
+//! assert_eq!(profiler.stats("number").unwrap().calls, 1);
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::core::{Parsable, Parser, ParserOutput};
+
+/// Callbacks notified when observed parsers run.
+pub trait ParserObserver<Input> {
+    /// Called once when a parser named `name` is wrapped with `observed`, before it runs.
+    fn on_attach(&self, _name: &'static str) {}
+
+    /// Called before the parser named `name` runs on `input`.
+    fn on_enter(&self, name: &'static str, input: &Input);
+
+    /// Called after the parser named `name` ran, with the remaining input and whether it
+    /// succeeded.
+    fn on_exit(&self, name: &'static str, rest: &Input, succeeded: bool);
+}
+
+impl<Input, O: ParserObserver<Input>> ParserObserver<Input> for Rc<O> {
+    fn on_attach(&self, name: &'static str) {
+        (**self).on_attach(name)
+    }
+
+    fn on_enter(&self, name: &'static str, input: &Input) {
+        (**self).on_enter(name, input)
+    }
+
+    fn on_exit(&self, name: &'static str, rest: &Input, succeeded: bool) {
+        (**self).on_exit(name, rest, succeeded)
+    }
+}
+
+/// Statistics of one rule collected by a `Profiler`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RuleStats {
+    /// Number of times the rule ran
+    pub calls: usize,
+    /// Number of successful runs
+    pub successes: usize,
+    /// Number of failed runs
+    pub failures: usize,
+    /// Time spent in the rule, including nested rules
+    pub total: Duration,
+}
+
+#[derive(Default)]
+struct ProfilerState {
+    stats: HashMap<&'static str, RuleStats>,
+    started: Vec<Instant>,
+}
+
+/// An observer collecting per-rule call counts and timings.
+///
+/// Clones share the same statistics, so one profiler can observe a whole grammar.
+#[derive(Default)]
+pub struct Profiler {
+    state: Rc<RefCell<ProfilerState>>,
+}
+
+impl Clone for Profiler {
+    fn clone(&self) -> Self {
+        Profiler {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Profiler {
+    /// Creates a profiler without statistics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the statistics of the rule `name`, if it ran.
+    pub fn stats(&self, name: &str) -> Option<RuleStats> {
+        self.state.borrow().stats.get(name).copied()
+    }
+
+    /// Returns the statistics of all rules that ran, most time-consuming first.
+    pub fn report(&self) -> Vec<(&'static str, RuleStats)> {
+        let mut report: Vec<_> = self.state.borrow().stats.iter().map(|(name, stats)| (*name, *stats)).collect();
+        report.sort_by(|a, b| b.1.total.cmp(&a.1.total).then(a.0.cmp(b.0)));
+        report
+    }
+
+    /// Forgets all collected statistics.
+    pub fn reset(&self) {
+        self.state.borrow_mut().stats.clear();
+    }
+}
+
+impl<Input> ParserObserver<Input> for Profiler {
+    fn on_enter(&self, _name: &'static str, _input: &Input) {
+        self.state.borrow_mut().started.push(Instant::now());
+    }
+
+    fn on_exit(&self, name: &'static str, _rest: &Input, succeeded: bool) {
+        let mut state = self.state.borrow_mut();
+        let elapsed = state.started.pop().map_or(Duration::ZERO, |start| start.elapsed());
+        let stats = state.stats.entry(name).or_default();
+        stats.calls += 1;
+        stats.total += elapsed;
+        if succeeded {
+            stats.successes += 1;
+        } else {
+            stats.failures += 1;
+        }
+    }
+}
+
+/// How often a rule observed by `Coverage` ran and matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RuleCoverage {
+    /// Number of times the rule ran
+    pub entered: usize,
+    /// Number of times the rule matched
+    pub matched: usize,
+}
+
+impl RuleCoverage {
+    /// Returns true if the rule matched at least once.
+    pub fn is_covered(&self) -> bool {
+        self.matched > 0
+    }
+}
+
+#[derive(Default)]
+struct CoverageState {
+    /// Rules in the order they were attached
+    rules: Vec<(&'static str, RuleCoverage)>,
+    index: HashMap<&'static str, usize>,
+}
+
+impl CoverageState {
+    #[allow(clippy::indexing_slicing)] // `index` only maps names to rules pushed here
+    fn rule_mut(&mut self, name: &'static str) -> &mut RuleCoverage {
+        let next = self.rules.len();
+        let index = *self.index.entry(name).or_insert(next);
+        if index == next {
+            self.rules.push((name, RuleCoverage::default()));
+        }
+        &mut self.rules[index].1
+    }
+}
+
+/// An observer recording which rules a test corpus exercised.
+///
+/// Every parser wrapped with `observed(name, &coverage)` is known from the moment it is
+/// wrapped, so rules and branches that never ran are reported too. Observe each alternative
+/// of a choice under its own name, e.g. `"value/null"`, to find untested branches. Clones
+/// share the same records, and runs accumulate until `reset`.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::observe::*;
+///
+/// let coverage = Coverage::new();
+/// let yes = "yes".make_literal_matcher("Expected yes").observed("answer/yes", &coverage);
+/// let no = "no".make_literal_matcher("Expected no").observed("answer/no", &coverage);
+/// let maybe = "maybe".make_literal_matcher("Expected maybe").observed("answer/maybe", &coverage);
+/// let answer = (yes, no, maybe).alt().map(|answer| answer.fold()).observed("answer", &coverage);
+///
+/// for input in ["yes", "no", "nope"] {
+///     let _ = answer.parse(input);
+/// }
+///
+/// assert_eq!(coverage.uncovered(), ["answer/maybe"]);
+/// assert_eq!(coverage.rule("answer/no"), Some(RuleCoverage { entered: 2, matched: 2 }));
+/// assert_eq!(
+///     coverage.report().to_string(),
+///     "3 of 4 rules covered\nuncovered: answer/maybe (ran 0 times)\n"
+/// );
+/// ```
+#[derive(Default)]
+pub struct Coverage {
+    state: Rc<RefCell<CoverageState>>,
+}
+
+impl Clone for Coverage {
+    fn clone(&self) -> Self {
+        Coverage {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Coverage {
+    /// Creates a collector without any rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the record of the rule `name`, if it was observed.
+    pub fn rule(&self, name: &str) -> Option<RuleCoverage> {
+        let state = self.state.borrow();
+        state.index.get(name).and_then(|&index| state.rules.get(index)).map(|&(_, coverage)| coverage)
+    }
+
+    /// Returns the rules that never matched, in the order they were wrapped.
+    pub fn uncovered(&self) -> Vec<&'static str> {
+        let state = self.state.borrow();
+        state.rules.iter().filter(|(_, rule)| !rule.is_covered()).map(|(name, _)| *name).collect()
+    }
+
+    /// Returns the records of all observed rules, in the order they were wrapped.
+    pub fn report(&self) -> CoverageReport {
+        CoverageReport {
+            rules: self.state.borrow().rules.clone(),
+        }
+    }
+
+    /// Forgets the collected runs, keeping the observed rules.
+    pub fn reset(&self) {
+        for (_, rule) in &mut self.state.borrow_mut().rules {
+            *rule = RuleCoverage::default();
+        }
+    }
+}
+
+impl<Input> ParserObserver<Input> for Coverage {
+    fn on_attach(&self, name: &'static str) {
+        self.state.borrow_mut().rule_mut(name);
+    }
+
+    fn on_enter(&self, name: &'static str, _input: &Input) {
+        self.state.borrow_mut().rule_mut(name).entered += 1;
+    }
+
+    fn on_exit(&self, name: &'static str, _rest: &Input, succeeded: bool) {
+        if succeeded {
+            self.state.borrow_mut().rule_mut(name).matched += 1;
+        }
+    }
+}
+
+/// The coverage of a grammar, printed as a summary and a list of uncovered rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// All observed rules, in the order they were wrapped
+    pub rules: Vec<(&'static str, RuleCoverage)>,
+}
+
+impl CoverageReport {
+    /// Returns the number of rules that matched at least once.
+    pub fn covered(&self) -> usize {
+        self.rules.iter().filter(|(_, rule)| rule.is_covered()).count()
+    }
+}
+
+impl Display for CoverageReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} of {} rules covered", self.covered(), self.rules.len())?;
+        for (name, rule) in self.rules.iter().filter(|(_, rule)| !rule.is_covered()) {
+            writeln!(f, "uncovered: {} (ran {} times)", name, rule.entered)?;
+        }
+        Ok(())
+    }
+}
+
+/// Extension trait adding instrumentation to parsers.
+pub trait ObservedParser<Input, Output, Error>: Parser<Input, Output, Error>
+where
+    Input: Parsable<Error>,
+    Output: ParserOutput,
+    Error: Clone,
+{
+    /// Notifies `observer` under `name` whenever this parser is entered and left.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::observe::*;
+    /// use std::cell::RefCell;
+    ///
+    /// struct Log(RefCell<Vec<String>>);
+    ///
+    /// impl ParserObserver<&str> for Log {
+    ///     fn on_enter(&self, name: &'static str, input: &&str) {
+    ///         self.0.borrow_mut().push(format!("enter {} at {:?}", name, input));
+    ///     }
+    ///     fn on_exit(&self, name: &'static str, _rest: &&str, succeeded: bool) {
+    ///         self.0.borrow_mut().push(format!("exit {} {}", name, succeeded));
+    ///     }
+    /// }
+    ///
+    /// let log = std::rc::Rc::new(Log(RefCell::new(Vec::new())));
+    /// let keyword = "if".make_literal_matcher("Expected if").observed("keyword", &log);
+    /// assert!(keyword.parse("else").is_err());
+    /// assert_eq!(*log.0.borrow(), ["enter keyword at \"else\"", "exit keyword false"]);
+    /// ```
+    fn observed<Observer>(self, name: &'static str, observer: &Observer) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Observer: ParserObserver<Input> + Clone,
+    {
+        let observer = observer.clone();
+        observer.on_attach(name);
+        move |input: Input| {
+            observer.on_enter(name, &input);
+            let result = self.parse(input);
+            match &result {
+                Ok((rest, _)) => observer.on_exit(name, rest, true),
+                Err((rest, _)) => observer.on_exit(name, rest, false),
+            }
+            result
+        }
+    }
+}
+
+impl<Input, Output, Error, P> ObservedParser<Input, Output, Error> for P
+where
+    Input: Parsable<Error>,
+    Output: ParserOutput,
+    Error: Clone,
+    P: Parser<Input, Output, Error>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_profiler_report() {
+        let profiler = Profiler::new();
+        let a = 'a'.make_character_matcher("Expected a").observed("a", &profiler);
+        let b = 'b'.make_character_matcher("Expected b").observed("b", &profiler);
+        let pair = a.seq(b).observed("pair", &profiler);
+
+        assert!(pair.parse("ab").is_ok());
+        assert!(pair.parse("x").is_err());
+
+        let report = profiler.report();
+        assert_eq!(report.len(), 3);
+        assert!(report.windows(2).all(|w| w[0].1.total >= w[1].1.total));
+        assert!(profiler.stats("pair").unwrap().total >= profiler.stats("a").unwrap().total);
+        assert_eq!(profiler.stats("a").unwrap().failures, 1);
+        assert_eq!(profiler.stats("b").unwrap().calls, 1);
+
+        profiler.reset();
+        assert!(profiler.report().is_empty());
+    }
+
+    #[test]
+    fn test_coverage_of_unreached_rules() {
+        let coverage = Coverage::new();
+        let digit = '1'.make_character_matcher("Expected 1").observed("digit", &coverage);
+        let sign = '-'.make_character_matcher("Expected -").observed("sign", &coverage);
+        let _dead = ParsableItem::<&str, _>::make_character_matcher('x', "Expected x").observed("dead", &coverage);
+        let number = sign.maybe().seq(digit.many()).observed("number", &coverage);
+
+        assert!(number.parse("11").is_ok());
+        assert_eq!(coverage.uncovered(), ["sign", "dead"]);
+        assert_eq!(coverage.rule("sign"), Some(RuleCoverage { entered: 1, matched: 0 }));
+        assert_eq!(coverage.rule("dead"), Some(RuleCoverage::default()));
+        assert_eq!(coverage.report().covered(), 2);
+
+        assert!(number.parse("-1").is_ok());
+        assert_eq!(coverage.uncovered(), ["dead"]);
+
+        coverage.reset();
+        assert_eq!(coverage.uncovered(), ["digit", "sign", "dead", "number"]);
+        assert_eq!(coverage.rule("unknown"), None);
+    }
+}
@@ -4,17 +4,17 @@
 //! for handling left-recursive grammars, based on the algorithm described by
 //! Warth, Douglass, and Millstein in their paper "Packrat Parsers Can Support Left Recursion".
 
-use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::Hash;
-use std::rc::Rc;
+use std::thread::{self, ThreadId};
 
 use crate::core::{Parsable, Parser, ParserOutput};
+use crate::memo::{CacheStorage, LocalStorage, SyncStorage};
 
 /// Represents the growth status of a left-recursive parser.
 #[derive(Debug, Clone, PartialEq)]
-enum GrowthStatus {
+pub enum GrowthStatus {
     /// The parser has matched more input.
     Growing,
     /// The parser has reached a fixed point and is no longer growing.
@@ -24,6 +24,9 @@ enum GrowthStatus {
 }
 
 /// A key for the packrat cache.
+///
+/// For a `StateCarrier` input the key includes the state, which must then implement `Hash`
+/// and `Eq`; see the memo module for the contract this places on stateful rules.
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct PackratKey<I: 'static + Clone + Hash + Eq> {
     /// The input position
@@ -43,6 +46,9 @@ pub enum PackratResult<I, O, E> {
     Evaluating,
 }
 
+/// The table of cached results, keyed by input position and rule.
+type PackratTable<I, O, E> = HashMap<PackratKey<I>, PackratResult<I, O, E>>;
+
 /// State for tracking left recursion for a specific rule.
 #[derive(Clone, Debug)]
 pub struct RecursionState<I: 'static + Clone + Hash + Eq> {
@@ -56,20 +62,76 @@ pub struct RecursionState<I: 'static + Clone + Hash + Eq> {
     pub status: GrowthStatus,
 }
 
+/// The parse in progress on one thread.
+///
+/// Rules being evaluated, left recursion seeds and the call stack belong to the parse that
+/// created them, so each thread keeps its own frame and only finished results are shared.
+struct ParseFrame<I, O, E>
+where
+    I: 'static + Clone + Hash + Eq,
+{
+    /// Results of rules being evaluated or grown
+    pending: PackratTable<I, O, E>,
+    /// The left recursion state for each rule
+    recursion_state: HashMap<String, RecursionState<I>>,
+    /// The call stack for detecting recursive rules
+    call_stack: Vec<String>,
+}
+
+impl<I, O, E> Default for ParseFrame<I, O, E>
+where
+    I: 'static + Clone + Hash + Eq,
+{
+    fn default() -> Self {
+        ParseFrame {
+            pending: HashMap::new(),
+            recursion_state: HashMap::new(),
+            call_stack: Vec::new(),
+        }
+    }
+}
+
+impl<I, O, E> ParseFrame<I, O, E>
+where
+    I: 'static + Clone + Hash + Eq,
+{
+    /// Returns true while a left-recursive rule is growing its seed.
+    fn is_growing(&self) -> bool {
+        self.recursion_state.values().any(|state| state.status == GrowthStatus::Growing)
+    }
+}
+
 /// State for packrat parsing with left recursion support.
-#[derive(Clone)]
-pub struct PackratState<I, O, E>
+///
+/// The tables live in the storage `S`; with `SyncStorage` packrat parsers sharing the state
+/// are `Send + Sync`. Only finished results go to the shared memo table: the call stack and
+/// the left recursion state are kept per thread and dropped when its outermost rule returns.
+pub struct PackratState<I, O, E, S = LocalStorage>
 where
     I: 'static + Clone + Hash + Eq,
     O: Clone,
     E: Clone + 'static,
+    S: CacheStorage,
 {
     /// The parse result cache
-    pub memo_table: Rc<RefCell<HashMap<PackratKey<I>, PackratResult<I, O, E>>>>,
-    /// The left recursion state for each rule
-    pub recursion_state: Rc<RefCell<HashMap<String, RecursionState<I>>>>,
-    /// The call stack for detecting recursive rules
-    pub call_stack: Rc<RefCell<Vec<String>>>,
+    pub memo_table: S::Cell<PackratTable<I, O, E>>,
+    /// The parse in progress on each thread
+    frames: S::Cell<HashMap<ThreadId, ParseFrame<I, O, E>>>,
+}
+
+impl<I, O, E, S> Clone for PackratState<I, O, E, S>
+where
+    I: 'static + Clone + Hash + Eq,
+    O: Clone,
+    E: Clone + 'static,
+    S: CacheStorage,
+{
+    fn clone(&self) -> Self {
+        PackratState {
+            memo_table: self.memo_table.clone(),
+            frames: self.frames.clone(),
+        }
+    }
 }
 
 impl<I, O, E> PackratState<I, O, E>
@@ -80,42 +142,121 @@ where
 {
     /// Creates a new packrat state.
     pub fn new() -> Self {
+        Self::with_storage()
+    }
+}
+
+impl<I, O, E, S> PackratState<I, O, E, S>
+where
+    I: 'static + Clone + Hash + Eq,
+    O: Clone,
+    E: Clone + 'static,
+    S: CacheStorage,
+{
+    /// Creates a new packrat state backed by the storage `S`.
+    pub fn with_storage() -> Self {
         PackratState {
-            memo_table: Rc::new(RefCell::new(HashMap::new())),
-            recursion_state: Rc::new(RefCell::new(HashMap::new())),
-            call_stack: Rc::new(RefCell::new(Vec::new())),
+            memo_table: S::new_cell(HashMap::new()),
+            frames: S::new_cell(HashMap::new()),
+        }
+    }
+
+    /// Runs `f` with the frame of the calling thread, creating it if needed.
+    fn with_frame<R>(&self, f: impl FnOnce(&mut ParseFrame<I, O, E>) -> R) -> R {
+        S::write(&self.frames, |frames| f(frames.entry(thread::current().id()).or_default()))
+    }
+
+    /// Runs `f` with the frame of the calling thread, if it is parsing.
+    fn read_frame<R>(&self, f: impl FnOnce(&ParseFrame<I, O, E>) -> Option<R>) -> Option<R> {
+        S::read(&self.frames, |frames| frames.get(&thread::current().id()).and_then(f))
+    }
+
+    /// Looks up a result, preferring the calling thread's rules in progress.
+    fn lookup(&self, key: &PackratKey<I>) -> Option<PackratResult<I, O, E>> {
+        self.read_frame(|frame| frame.pending.get(key).cloned())
+            .or_else(|| S::read(&self.memo_table, |table| table.get(key).cloned()))
+    }
+
+    /// Records a result in progress, visible to the calling thread only.
+    fn store_pending(&self, key: PackratKey<I>, result: PackratResult<I, O, E>) {
+        self.with_frame(|frame| frame.pending.insert(key, result));
+    }
+
+    /// Records a finished result.
+    ///
+    /// While a left-recursive rule of the calling thread is growing, results may depend on its
+    /// seed, so they stay pending and are dropped with the frame instead of being shared.
+    fn store(&self, key: PackratKey<I>, result: PackratResult<I, O, E>) {
+        let shared = self.with_frame(|frame| {
+            if frame.is_growing() {
+                frame.pending.insert(key.clone(), result.clone());
+                false
+            } else {
+                frame.pending.remove(&key);
+                true
+            }
+        });
+        if shared {
+            S::write(&self.memo_table, |table| table.insert(key, result));
         }
     }
 
     /// Records that we're entering a parser rule.
     pub fn enter_rule(&self, rule_id: &str) {
-        self.call_stack.borrow_mut().push(rule_id.to_string());
+        self.with_frame(|frame| frame.call_stack.push(rule_id.to_string()));
     }
 
     /// Records that we're exiting a parser rule.
+    ///
+    /// Leaving the outermost rule drops the calling thread's frame.
     pub fn exit_rule(&self) {
-        self.call_stack.borrow_mut().pop();
+        S::write(&self.frames, |frames| {
+            let id = thread::current().id();
+            if let Some(frame) = frames.get_mut(&id) {
+                frame.call_stack.pop();
+                if frame.call_stack.is_empty() {
+                    frames.remove(&id);
+                }
+            }
+        });
     }
 
     /// Checks if we're currently in a left-recursive call.
     pub fn is_left_recursive(&self, rule_id: &str) -> bool {
-        self.call_stack.borrow().contains(&rule_id.to_string())
+        self.read_frame(|frame| frame.call_stack.iter().any(|r| r == rule_id).then_some(()))
+            .is_some()
     }
 
-    /// Gets the current call stack.
+    /// Gets the current call stack of the calling thread.
     pub fn get_call_stack(&self) -> Vec<String> {
-        self.call_stack.borrow().clone()
+        self.read_frame(|frame| Some(frame.call_stack.clone())).unwrap_or_default()
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&self) {
+        S::write(&self.memo_table, |table| table.clear());
+    }
+
+    /// Returns the number of cached results.
+    pub fn len(&self) -> usize {
+        S::read(&self.memo_table, |table| table.len())
+    }
+
+    /// Returns true if no results are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
-impl<I, O, E> Default for PackratState<I, O, E>
+impl<I, O, E, S> Default for PackratState<I, O, E, S>
 where
     I: 'static + Clone + Hash + Eq,
     O: Clone,
     E: Clone + 'static,
+    S: CacheStorage,
 {
     fn default() -> Self {
-        Self::new()
+        Self::with_storage()
     }
 }
 
@@ -131,26 +272,45 @@ where
 /// * `E` - The error type
 /// * `P` - The underlying parser type
 ///
+/// By default the cache lives as long as the parser, so results are reused across calls to
+/// `parse`. Since `&str` keys compare by content, a result cached for one document is then
+/// returned for an equal suffix of another, with its remaining input pointing into the first
+/// document. With `per_parse` the results of a rule only live as long as the top-level call
+/// that computed them.
+///
 /// # Example
 ///
 /// ```rust
 /// use friss::*;
 /// use friss::packrat::*;
 ///
+/// let word = "ab".make_literal_matcher("Expected ab").packrat("word").per_parse();
+/// // Packrat inputs are `'static`, so two documents read at runtime are leaked here
+/// let first: &'static str = String::from("ab!").leak();
+/// let second: &'static str = String::from("ab!").leak();
+///
+/// let (rest, _) = word.parse(first).unwrap();
+/// assert_eq!(rest.as_ptr(), first[2..].as_ptr());
+/// let (rest, _) = word.parse(second).unwrap();
+/// assert_eq!(rest.as_ptr(), second[2..].as_ptr());
+/// assert!(word.state().is_empty());
 /// ```
-pub struct PackratParserImpl<I, O, E, P>
+pub struct PackratParserImpl<I, O, E, P, S = LocalStorage>
 where
     I: 'static + Clone + Hash + Eq + Parsable<E>,
     O: Clone,
     E: Clone + 'static,
     P: Parser<I, O, E>,
+    S: CacheStorage,
 {
     /// The underlying parser
     parser: P,
     /// The packrat state for the parser
-    state: PackratState<I, O, E>,
+    state: PackratState<I, O, E, S>,
     /// Unique identifier for this parser rule
     rule_id: String,
+    /// Whether results are kept for the current top-level call only
+    per_parse: bool,
 }
 
 impl<I, O, E, P> PackratParserImpl<I, O, E, P>
@@ -166,15 +326,51 @@ where
             parser,
             state: PackratState::new(),
             rule_id: rule_id.into(),
+            per_parse: false,
         }
     }
+}
 
+impl<I, O, E, P, S> PackratParserImpl<I, O, E, P, S>
+where
+    I: Clone + Hash + Eq + Parsable<E> + 'static,
+    O: Clone,
+    E: Clone + 'static,
+    P: Parser<I, O, E>,
+    S: CacheStorage,
+{
     /// Creates a new packrat parser with a shared state.
-    pub fn with_state(parser: P, rule_id: impl Into<String>, state: PackratState<I, O, E>) -> Self {
+    pub fn with_state(parser: P, rule_id: impl Into<String>, state: PackratState<I, O, E, S>) -> Self {
         PackratParserImpl {
             parser,
             state,
             rule_id: rule_id.into(),
+            per_parse: false,
+        }
+    }
+
+    /// Scopes the results of this rule to a single top-level call of `parse`.
+    ///
+    /// A call is top-level when no packrat rule sharing the state is running on the calling
+    /// thread. The rule's results are kept with that thread's rules in progress instead of
+    /// the shared table and dropped when the top-level call returns, so no result outlives
+    /// the parse that computed it and parses on other threads neither see nor clear them.
+    pub fn per_parse(mut self) -> Self {
+        self.per_parse = true;
+        self
+    }
+
+    /// Returns the packrat state holding the tables.
+    pub fn state(&self) -> &PackratState<I, O, E, S> {
+        &self.state
+    }
+
+    /// Records a finished result, for the current top-level call only with `per_parse`.
+    fn store(&self, key: PackratKey<I>, result: PackratResult<I, O, E>) {
+        if self.per_parse {
+            self.state.store_pending(key, result);
+        } else {
+            self.state.store(key, result);
         }
     }
 
@@ -187,7 +383,7 @@ where
         };
 
         // Check if we have a result in the memo table
-        let memo_result = self.state.memo_table.borrow().get(&key).cloned();
+        let memo_result = self.state.lookup(&key);
 
         if let Some(result) = memo_result {
             match result {
@@ -196,51 +392,25 @@ where
                 PackratResult::Evaluating => {
                     // Left recursion detected
 
-                    // Create initial recursion state if needed
-                    if !self.state.recursion_state.borrow().contains_key(&rule_id) {
-                        let mut involved_set = HashSet::new();
-                        involved_set.insert(rule_id.clone());
-
-                        let recursion_state = RecursionState {
-                            involved: true,
-                            input_pos: input.clone(),
-                            involved_set,
-                            status: GrowthStatus::Growing,
-                        };
-
-                        self.state
-                            .recursion_state
-                            .borrow_mut()
-                            .insert(rule_id.clone(), recursion_state);
-
-                        // Initial failure to bootstrap recursive parsing
-                        return Err((input, self.create_error("Left-recursive rule encountered")));
-                    } else {
-                        // Get the current result for left recursion
-                        let recursion_state = self
-                            .state
-                            .recursion_state
-                            .borrow()
-                            .get(&rule_id)
-                            .cloned()
-                            .expect("Recursion state should exist at this point");
-
+                    let recursion_state = self
+                        .state
+                        .read_frame(|frame| frame.recursion_state.get(&rule_id).cloned());
+                    if let Some(recursion_state) = recursion_state {
                         // If we're in a growing state, apply the current best result
                         if recursion_state.status == GrowthStatus::Growing {
-                            // Try to find a successful result in the memo table
-                            for (memo_key, memo_result) in self.state.memo_table.borrow().iter() {
-                                if memo_key.parser_id == rule_id
-                                    && matches!(memo_result, PackratResult::Success(..))
-                                {
-                                    match memo_result {
-                                        PackratResult::Success(rest, output) => {
-                                            return Ok((rest.clone(), output.clone()));
-                                        }
-                                        _ => {
-                                            unreachable!("Already checked this is a success result")
-                                        }
+                            // Try to find a successful result among the seeds of this parse
+                            let best = self.state.read_frame(|frame| {
+                                frame.pending.iter().find_map(|(memo_key, memo_result)| match memo_result {
+                                    PackratResult::Success(rest, output)
+                                        if memo_key.parser_id == rule_id =>
+                                    {
+                                        Some((rest.clone(), output.clone()))
                                     }
-                                }
+                                    _ => None,
+                                })
+                            });
+                            if let Some(best) = best {
+                                return Ok(best);
                             }
 
                             // No successful result yet, return failure
@@ -255,6 +425,24 @@ where
                                 self.create_error("Left-recursive rule reached fixed point"),
                             ));
                         }
+                    } else {
+                        // Create initial recursion state
+                        let mut involved_set = HashSet::new();
+                        involved_set.insert(rule_id.clone());
+
+                        let recursion_state = RecursionState {
+                            involved: true,
+                            input_pos: input.clone(),
+                            involved_set,
+                            status: GrowthStatus::Growing,
+                        };
+
+                        self.state.with_frame(|frame| {
+                            frame.recursion_state.insert(rule_id.clone(), recursion_state)
+                        });
+
+                        // Initial failure to bootstrap recursive parsing
+                        return Err((input, self.create_error("Left-recursive rule encountered")));
                     }
                 }
             }
@@ -263,10 +451,7 @@ where
         // Check for left recursion
         if self.state.is_left_recursive(&rule_id) {
             // Mark that we're evaluating this rule
-            self.state
-                .memo_table
-                .borrow_mut()
-                .insert(key, PackratResult::Evaluating);
+            self.state.store_pending(key, PackratResult::Evaluating);
 
             return Err((input, self.create_error("Left-recursive rule detected")));
         }
@@ -275,10 +460,7 @@ where
         self.state.enter_rule(&rule_id);
 
         // Mark that we're evaluating this rule
-        self.state
-            .memo_table
-            .borrow_mut()
-            .insert(key.clone(), PackratResult::Evaluating);
+        self.state.store_pending(key.clone(), PackratResult::Evaluating);
 
         // Attempt to parse
         let result = self.parser.parse(input.clone());
@@ -287,66 +469,59 @@ where
         let final_result = match &result {
             Ok((rest, output)) => {
                 // Success: store in memo table
-                self.state.memo_table.borrow_mut().insert(
-                    key.clone(),
-                    PackratResult::Success(rest.clone(), output.clone()),
-                );
+                self.store(key.clone(), PackratResult::Success(rest.clone(), output.clone()));
 
                 // If this is part of a left-recursive rule, try to grow the result
-                if let Some(mut recursion_state) =
-                    self.state.recursion_state.borrow_mut().get_mut(&rule_id)
-                {
-                    if recursion_state.involved && recursion_state.status == GrowthStatus::Growing {
-                        // Enter growth stage - repeatedly apply the rule until it stops growing
-                        let mut previous_rest = rest.clone();
-                        let mut previous_output = output.clone();
-
-                        loop {
-                            // Update memo table with current best result
-                            self.state.memo_table.borrow_mut().insert(
-                                key.clone(),
-                                PackratResult::Success(
-                                    previous_rest.clone(),
-                                    previous_output.clone(),
-                                ),
-                            );
-
-                            // Try parsing again
-                            match self.parser.parse(input.clone()) {
-                                Ok((new_rest, new_output)) => {
-                                    // If we matched more input, continue growing
-                                    if is_more_matched(&previous_rest, &new_rest) {
-                                        previous_rest = new_rest;
-                                        previous_output = new_output;
-                                    } else {
-                                        // Reached a fixed point
-                                        recursion_state.status = GrowthStatus::Stable;
-                                        break;
-                                    }
-                                }
-                                Err(_) => {
-                                    // Failed to grow further
-                                    recursion_state.status = GrowthStatus::Stable;
-                                    break;
-                                }
+                let growing = self
+                    .state
+                    .read_frame(|frame| {
+                        frame.recursion_state.get(&rule_id).map(|recursion_state| {
+                            recursion_state.involved && recursion_state.status == GrowthStatus::Growing
+                        })
+                    })
+                    .unwrap_or(false);
+                if growing {
+                    // Enter growth stage - repeatedly apply the rule until it stops growing
+                    let mut previous_rest = rest.clone();
+                    let mut previous_output = output.clone();
+
+                    loop {
+                        // Update the seed with the current best result
+                        self.state.store_pending(
+                            key.clone(),
+                            PackratResult::Success(previous_rest.clone(), previous_output.clone()),
+                        );
+
+                        // Try parsing again; stop when no more input is matched
+                        match self.parser.parse(input.clone()) {
+                            Ok((new_rest, new_output)) if is_more_matched(&previous_rest, &new_rest) => {
+                                previous_rest = new_rest;
+                                previous_output = new_output;
                             }
+                            _ => break,
                         }
-
-                        // Return the best result
-                        Ok((previous_rest, previous_output))
-                    } else {
-                        result.clone()
                     }
+
+                    // Reached a fixed point
+                    self.state.with_frame(|frame| {
+                        if let Some(recursion_state) = frame.recursion_state.get_mut(&rule_id) {
+                            recursion_state.status = GrowthStatus::Stable;
+                        }
+                    });
+                    self.store(
+                        key,
+                        PackratResult::Success(previous_rest.clone(), previous_output.clone()),
+                    );
+
+                    // Return the best result
+                    Ok((previous_rest, previous_output))
                 } else {
                     result.clone()
                 }
             }
             Err((rest, error)) => {
                 // Failure: store in memo table
-                self.state
-                    .memo_table
-                    .borrow_mut()
-                    .insert(key, PackratResult::Failure(rest.clone(), error.clone()));
+                self.store(key, PackratResult::Failure(rest.clone(), error.clone()));
 
                 result.clone()
             }
@@ -367,6 +542,7 @@ where
 
     /// Converts a string to an error value.
     /// This method should be specialized based on the error type E.
+    #[allow(clippy::panic)] // Depends on the error type, not on the input
     fn string_to_error(&self, message: &str) -> E {
         // This implementation handles common error types
         use std::any::TypeId;
@@ -387,12 +563,13 @@ where
     }
 }
 
-impl<I, O, E, P> Parser<I, O, E> for PackratParserImpl<I, O, E, P>
+impl<I, O, E, P, S> Parser<I, O, E> for PackratParserImpl<I, O, E, P, S>
 where
     I: 'static + Clone + Hash + Eq + Parsable<E>,
     O: Clone + ParserOutput,
     E: Clone + 'static,
     P: Parser<I, O, E>,
+    S: CacheStorage,
 {
     fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
         self.packrat_parse(input)
@@ -461,13 +638,21 @@ where
     /// # Returns
     ///
     /// A packrat parser that can handle left recursion using the shared state
-    fn packrat_with_state(
+    fn packrat_with_state<S: CacheStorage>(
         self,
         rule_id: impl Into<String>,
-        state: PackratState<I, O, E>,
-    ) -> PackratParserImpl<I, O, E, Self> {
+        state: PackratState<I, O, E, S>,
+    ) -> PackratParserImpl<I, O, E, Self, S> {
         PackratParserImpl::with_state(self, rule_id, state)
     }
+
+    /// Wraps the parser with packrat parsing capabilities using thread-safe tables.
+    ///
+    /// The resulting parser is `Send + Sync` whenever the wrapped parser, input, output and
+    /// error types are.
+    fn packrat_sync(self, rule_id: impl Into<String>) -> PackratParserImpl<I, O, E, Self, SyncStorage> {
+        PackratParserImpl::with_state(self, rule_id, PackratState::with_storage())
+    }
 }
 
 // Implement PackratParser for all parsers
@@ -545,12 +730,12 @@ fn input_len<I: 'static>(input: &I) -> Option<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::recursive;
     use crate::*;
     use std::cell::RefCell;
     use std::rc::Rc;
 
     // Define a simple expression AST for testing
+    #[allow(dead_code)]
     #[derive(Debug, Clone, PartialEq)]
     enum Expr {
         Number(f64),
@@ -558,6 +743,7 @@ mod tests {
         BinaryOp(Box<Expr>, Op, Box<Expr>),
     }
 
+    #[allow(dead_code)]
     #[derive(Debug, Clone, PartialEq)]
     enum Op {
         Add,
@@ -757,6 +943,7 @@ mod tests {
 
     // Test custom input type with packrat parsing
     #[test]
+    #[allow(clippy::byte_char_slices)]
     fn test_packrat_custom_input() {
         // Create a simple parser for a slice of bytes
         let byte_parser = |input: &'static [u8]| {
@@ -778,4 +965,111 @@ mod tests {
         let result = packrat_parser.parse(b"xyz");
         assert!(result.is_err());
     }
+
+    // Test that a rule in progress on one thread is not taken for left recursion on another
+    #[test]
+    fn test_packrat_sync_threads_keep_own_call_stack() {
+        use std::sync::mpsc;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        let (entered_tx, entered_rx) = mpsc::channel();
+        let (resume_tx, resume_rx) = mpsc::channel::<()>();
+        let (entered_tx, resume_rx) = (Mutex::new(Some(entered_tx)), Mutex::new(resume_rx));
+        // The first call waits inside the rule until the main thread has parsed the same input
+        let rule = Arc::new(
+            (move |input: &'static str| {
+                let entered = entered_tx.lock().unwrap().take();
+                if let Some(entered) = entered {
+                    entered.send(()).unwrap();
+                    let _ = resume_rx.lock().unwrap().recv_timeout(Duration::from_secs(5));
+                }
+                "a".make_literal_matcher("Expected a").parse(input)
+            })
+            .packrat_sync("a"),
+        );
+
+        let worker = {
+            let rule = rule.clone();
+            std::thread::spawn(move || rule.parse("ab"))
+        };
+        entered_rx.recv().unwrap();
+        assert_eq!(rule.parse("ab"), Ok(("b", "a")));
+        resume_tx.send(()).unwrap();
+        assert_eq!(worker.join().unwrap(), Ok(("b", "a")));
+        assert!(rule.state.get_call_stack().is_empty());
+    }
+
+    // Test that per-parse tables are shared by nested rules but not across top-level calls
+    #[test]
+    fn test_packrat_per_parse() {
+        let calls = Rc::new(RefCell::new(0));
+        let counter = calls.clone();
+        let state = PackratState::new();
+        let digit = (move |input: &'static str| {
+            *counter.borrow_mut() += 1;
+            match input.strip_prefix('1') {
+                Some(rest) => Ok((rest, 1)),
+                None => Err((input, "Expected 1")),
+            }
+        })
+        .packrat_with_state("digit", state.clone())
+        .per_parse();
+        let digit = Rc::new(digit);
+        let inner = digit.clone();
+        // Tries the digit twice at the same position, once as a lookahead
+        let pair = (move |input: &'static str| {
+            let _ = inner.parse(input)?;
+            let (rest, a) = inner.parse(input)?;
+            let (rest, b) = inner.parse(rest)?;
+            Ok((rest, a + b))
+        })
+        .packrat_with_state("pair", state.clone())
+        .per_parse();
+
+        assert_eq!(pair.parse("11"), Ok(("", 2)));
+        assert_eq!(*calls.borrow(), 2);
+        assert!(state.is_empty());
+        assert_eq!(pair.parse("11"), Ok(("", 2)));
+        assert_eq!(*calls.borrow(), 4);
+
+        // Without `per_parse` the second parse is answered from the cache
+        let cached = (|input: &'static str| Ok::<_, (&str, &str)>((input, 0))).packrat("cached");
+        assert_eq!(cached.parse("x"), Ok(("x", 0)));
+        assert_eq!(cached.state().len(), 1);
+
+        // A per-parse rule leaves the shared results of other rules alone
+        let shared = PackratState::<&'static str, i32, &'static str, SyncStorage>::with_storage();
+        let cached = (|input: &'static str| Ok((input, 0))).packrat_with_state("cached", shared.clone());
+        let scoped = (|input: &'static str| Ok((input, 1)))
+            .packrat_with_state("scoped", shared.clone())
+            .per_parse();
+        assert_eq!(cached.parse("x"), Ok(("x", 0)));
+        assert_eq!(scoped.parse("x"), Ok(("x", 1)));
+        assert_eq!(shared.len(), 1);
+        assert_eq!(cached.parse("x"), Ok(("x", 0)));
+    }
+
+    // Test that results are cached per position and state
+    #[test]
+    fn test_packrat_keyed_by_state() {
+        use crate::parsers::{Indentation, WithState};
+
+        // Accepts a line only if it is indented exactly to the current level
+        let line = |input: StateCarrier<Indentation, &'static str>| {
+            let level = input.state.current_level();
+            let spaces = input.input.len() - input.input.trim_start_matches(' ').len();
+            if spaces == level {
+                Ok((StateCarrier::new(input.state.clone(), &input.input[spaces..]), spaces))
+            } else {
+                Err((input, "Wrong indentation"))
+            }
+        };
+        let packrat_parser = line.packrat("line");
+
+        let mut nested = Indentation::new();
+        nested.push_level(2);
+        assert_eq!(packrat_parser.parse("  x".with_state(nested)).map(|(_, n)| n), Ok(2));
+        assert_eq!(packrat_parser.parse("  x".with_state(Indentation::new())).map_err(|(_, err)| err), Err("Wrong indentation"));
+    }
 }
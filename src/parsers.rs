@@ -1,11 +1,74 @@
 //! # Input Parser Implementations
 //!
 //! This module provides implementations of the `Parsable` trait for common input types
-//! like strings and slices.
+//! like strings and slices. Ready-made parsers for common textual constructs live in the
+//! `text` submodule, and primitives for binary formats over `&[u8]` in `binary`.
 
-use crate::core::{Parsable, Parser};
+use crate::core::{Checkpoint, Parsable, Parser};
+use alloc::vec::Vec;
 
-/// Implementation of `Parsable` for string slices.
+pub mod binary;
+pub mod text;
+
+/// String slices are their own checkpoints.
+impl Checkpoint for &str {
+    type Checkpoint = Self;
+
+    fn checkpoint(&self) -> Self {
+        self
+    }
+
+    fn rewind(checkpoint: Self) -> Self {
+        checkpoint
+    }
+}
+
+/// Slices are their own checkpoints.
+impl<T> Checkpoint for &[T] {
+    type Checkpoint = Self;
+
+    fn checkpoint(&self) -> Self {
+        self
+    }
+
+    fn rewind(checkpoint: Self) -> Self {
+        checkpoint
+    }
+}
+
+/// Splits off the first character of `input`, keeping multi-byte characters whole.
+fn split_first_char(input: &str) -> Option<(char, &str)> {
+    let mut chars = input.chars();
+    chars.next().map(|c| (c, chars.as_str()))
+}
+
+/// Creates a parser matching one character that satisfies `predicate`.
+///
+/// Unlike `make_anything_matcher` followed by `validate`, the predicate sees whole
+/// characters and a rejected character is not consumed.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+///
+/// let kanji = make_char_predicate_matcher(|c| ('\u{4e00}'..='\u{9fff}').contains(&c), "Expected kanji");
+///
+/// assert_eq!(kanji.parse("🦀"), Err(("🦀", "Expected kanji")));
+/// assert_eq!(kanji.many().parse("日本語です"), Ok(("です", vec!['日', '本', '語'])));
+/// ```
+pub fn make_char_predicate_matcher<'a, Error: Clone>(
+    predicate: impl Fn(char) -> bool,
+    err: Error,
+) -> impl Parser<&'a str, char, Error> {
+    move |input: &'a str| match split_first_char(input) {
+        Some((c, rest)) if predicate(c) => Ok((rest, c)),
+        _ => Err((input, err.clone())),
+    }
+}
+
+/// Implementation of `Parsable` for string slices. Items are whole characters, so the
+/// matchers never split a multi-byte character.
 impl<'a, Error: Clone> Parsable<Error> for &'a str {
     type Item = char;
 
@@ -32,12 +95,9 @@ impl<'a, Error: Clone> Parsable<Error> for &'a str {
     where
         Error: Clone,
     {
-        move |input: &'a str| {
-            if input.is_empty() {
-                return Err((input, err.clone()));
-            }
-            let (ret, rest) = input.split_at(1);
-            Ok((rest, ret.chars().next().unwrap()))
+        move |input: &'a str| match split_first_char(input) {
+            Some((c, rest)) => Ok((rest, c)),
+            None => Err((input, err.clone())),
         }
     }
 
@@ -45,17 +105,19 @@ impl<'a, Error: Clone> Parsable<Error> for &'a str {
     where
         Error: Clone,
     {
-        move |input: &'a str| {
-            if input.is_empty() {
-                return Err((input, err.clone()));
-            }
-            let (ret, rest) = input.split_at(1);
-            let ret = ret.chars().next().unwrap();
-            if ret == character {
-                Ok((rest, ret))
-            } else {
-                Err((input, err.clone()))
-            }
+        move |input: &'a str| match split_first_char(input) {
+            Some((c, rest)) if c == character => Ok((rest, c)),
+            _ => Err((input, err.clone())),
+        }
+    }
+
+    fn make_predicate_matcher(
+        predicate: impl Fn(&Self::Item) -> bool,
+        err: Error,
+    ) -> impl Parser<Self, Self::Item, Error> {
+        move |input: &'a str| match split_first_char(input) {
+            Some((c, rest)) if predicate(&c) => Ok((rest, c)),
+            _ => Err((input, err.clone())),
         }
     }
 
@@ -81,14 +143,10 @@ impl<'a, Error: Clone, Input: Eq> Parsable<Error> for &'a [Input] {
         Error: Clone,
     {
         move |input: &'a [Input]| {
-            if input.len() < self.len() {
+            // `starts_with` compares the whole prefix at once, which is a `memcmp` for bytes
+            if !input.starts_with(self) {
                 return Err((input, err.clone()));
             }
-            for i in 0..self.len() {
-                if input[i] != self[i] {
-                    return Err((input, err.clone()));
-                }
-            }
             let (ret, rest) = input.split_at(self.len());
             Ok((rest, ret))
         }
@@ -98,12 +156,9 @@ impl<'a, Error: Clone, Input: Eq> Parsable<Error> for &'a [Input] {
     where
         Error: Clone,
     {
-        move |input: &'a [Input]| {
-            if input.is_empty() {
-                return Err((input, err.clone()));
-            }
-            let (ret, rest) = input.split_at(1);
-            Ok((rest, &ret[0]))
+        move |input: &'a [Input]| match input.split_first() {
+            Some((ret, rest)) => Ok((rest, ret)),
+            None => Err((input, err.clone())),
         }
     }
 
@@ -124,29 +179,32 @@ impl<'a, Error: Clone, Input: Eq> Parsable<Error> for &'a [Input] {
     where
         Error: Clone,
     {
-        move |input: &'a [Input]| {
-            if input.is_empty() {
-                return Err((input, err.clone()));
-            }
-            let (ret, rest) = input.split_at(1);
-            if ret[0] == *character {
-                Ok((rest, &ret[0]))
-            } else {
-                Err((input, err.clone()))
-            }
+        move |input: &'a [Input]| match input.split_first() {
+            Some((ret, rest)) if ret == character => Ok((rest, ret)),
+            _ => Err((input, err.clone())),
+        }
+    }
+
+    fn make_predicate_matcher(
+        predicate: impl Fn(&Self::Item) -> bool,
+        err: Error,
+    ) -> impl Parser<Self, Self::Item, Error> {
+        move |input: &'a [Input]| match input.split_first() {
+            Some((item, rest)) if predicate(&item) => Ok((rest, item)),
+            _ => Err((input, err.clone())),
         }
     }
 }
 
-use crate::state::{StateCarrier,  StatefulParser};
-use std::fmt::{self, Display, Formatter};
+use crate::state::{StateCarrier, StateUpdate, StatefulParser};
+use core::fmt::{self, Display, Formatter};
 
 /// Offset state that works for all parsable types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Offset(pub usize);
 
 impl Offset {
-    pub fn new(offset: usize) -> Self {
+    pub const fn new(offset: usize) -> Self {
         Offset(offset)
     }
 
@@ -159,16 +217,121 @@ impl Offset {
     }
 }
 
-/// Position state for string slices with line and column information
+/// The unit in which `Position` counts columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnUnit {
+    /// UTF-8 bytes, as LSP clients using the `utf-8` position encoding expect
+    Bytes,
+    /// Unicode scalar values
+    #[default]
+    Chars,
+    /// Extended grapheme clusters, the characters a user perceives
+    #[cfg(feature = "unicode-segmentation")]
+    Graphemes,
+}
+
+/// How `Position` counts columns: the unit, and optionally tab stops.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ColumnPolicy {
+    /// What one column counts
+    pub unit: ColumnUnit,
+    /// When not zero, a tab advances the column to the next multiple of this width
+    pub tab_width: usize,
+}
+
+impl ColumnPolicy {
+    /// Counts one column per byte.
+    pub const fn bytes() -> Self {
+        ColumnPolicy { unit: ColumnUnit::Bytes, tab_width: 0 }
+    }
+
+    /// Counts one column per `char`, the default.
+    pub const fn chars() -> Self {
+        ColumnPolicy { unit: ColumnUnit::Chars, tab_width: 0 }
+    }
+
+    /// Counts one column per grapheme cluster.
+    #[cfg(feature = "unicode-segmentation")]
+    pub const fn graphemes() -> Self {
+        ColumnPolicy { unit: ColumnUnit::Graphemes, tab_width: 0 }
+    }
+
+    /// Sets tab stops every `width` columns.
+    pub const fn tab_stops(mut self, width: usize) -> Self {
+        self.tab_width = width;
+        self
+    }
+}
+
+/// Position state for string slices with line and column information
+///
+/// Lines are counted at each `\n`; columns are counted according to a `ColumnPolicy`, one
+/// per `char` unless `with_policy` chooses bytes, grapheme clusters or tab stops. Positions
+/// compare by line and column only.
+///
+/// With `ColumnUnit::Graphemes`, a cluster consumed in several pieces, e.g. one `char` at a
+/// time, is recognised from its first and last character so far. This covers combining
+/// marks, joined emoji, flags and Hangul syllables.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::{ColumnPolicy, Position, WithState};
+///
+/// let columns = |policy: ColumnPolicy| {
+///     let any = <StateCarrier<Position, &str> as Parsable<&str>>::make_anything_matcher("Expected character");
+///     let (rest, _) = any.many().parse("\té🦀".with_state(Position::with_policy(policy))).unwrap();
+///     rest.state.column
+/// };
+///
+/// assert_eq!(columns(ColumnPolicy::chars()), 3);
+/// assert_eq!(columns(ColumnPolicy::bytes()), 7);
+/// assert_eq!(columns(ColumnPolicy::chars().tab_stops(4)), 6);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Position {
     pub line: usize,
     pub column: usize,
+    policy: ColumnPolicy,
+    /// The first and, if different, the last character of the current grapheme cluster
+    #[cfg(feature = "unicode-segmentation")]
+    cluster: Option<(char, Option<char>)>,
+}
+
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.line == other.line && self.column == other.column
+    }
+}
+
+impl Eq for Position {}
+
+impl core::hash::Hash for Position {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.line, self.column).hash(state);
+    }
 }
 
 impl Position {
     pub fn new(line: usize, column: usize) -> Self {
-        Position { line, column }
+        Position {
+            line,
+            column,
+            ..Position::default()
+        }
+    }
+
+    /// Creates the start position of a text whose columns are counted by `policy`.
+    pub fn with_policy(policy: ColumnPolicy) -> Self {
+        Position {
+            policy,
+            ..Position::default()
+        }
+    }
+
+    pub fn policy(&self) -> ColumnPolicy {
+        self.policy
     }
 
     pub fn advance_column(&mut self, n: usize) {
@@ -178,7 +341,78 @@ impl Position {
     pub fn advance_line(&mut self) {
         self.line += 1;
         self.column = 0;
+        #[cfg(feature = "unicode-segmentation")]
+        {
+            self.cluster = None;
+        }
     }
+
+    /// Computes the position reached after consuming the first `offset` bytes of `source`,
+    /// counting lines and columns the same way the `StateCarrier<Position, &str>` matchers do.
+    ///
+    /// An offset past the end counts the whole source, and one inside a multi-byte
+    /// character counts up to the start of that character.
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let end = (0..=offset.min(source.len()))
+            .rev()
+            .find(|&i| source.is_char_boundary(i))
+            .unwrap_or(0);
+        let mut position = Position::default();
+        position.advance_over(source.get(..end).unwrap_or_default());
+        position
+    }
+
+    /// Advances past `c`, counting columns by the policy.
+    pub fn advance_char(&mut self, c: char) {
+        if c == '\n' {
+            self.advance_line();
+            return;
+        }
+        if c == '\t' && self.policy.tab_width > 0 {
+            self.column = (self.column / self.policy.tab_width + 1) * self.policy.tab_width;
+        } else {
+            match self.policy.unit {
+                ColumnUnit::Bytes => self.advance_column(c.len_utf8()),
+                ColumnUnit::Chars => self.advance_column(1),
+                #[cfg(feature = "unicode-segmentation")]
+                ColumnUnit::Graphemes => {
+                    self.cluster = match self.cluster {
+                        Some((first, last)) if continues_grapheme(first, last, c) => Some((first, Some(c))),
+                        _ => {
+                            self.advance_column(1);
+                            Some((c, None))
+                        }
+                    };
+                }
+            }
+        }
+    }
+
+    /// Advances past `text`, starting a new line at each newline.
+    pub fn advance_over(&mut self, text: &str) {
+        for c in text.chars() {
+            self.advance_char(c);
+        }
+    }
+}
+
+/// Whether `next` continues the grapheme cluster from `first` to `last`.
+#[cfg(feature = "unicode-segmentation")]
+fn continues_grapheme(first: char, last: Option<char>, next: char) -> bool {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let mut buffer = [0; 12];
+    let mut len = first.encode_utf8(&mut buffer).len();
+    #[allow(clippy::indexing_slicing)] // Three characters of at most four bytes fit in the buffer
+    {
+        if let Some(last) = last {
+            len += last.encode_utf8(&mut buffer[len..]).len();
+        }
+        len += next.encode_utf8(&mut buffer[len..]).len();
+    }
+    // Only the boundary before `next` matters, which the first and last character decide
+    core::str::from_utf8(buffer.get(..len).unwrap_or_default())
+        .is_ok_and(|text| text.graphemes(true).next_back().is_some_and(|cluster| cluster.len() > next.len_utf8()))
 }
 
 impl Display for Position {
@@ -187,15 +421,70 @@ impl Display for Position {
     }
 }
 
+/// An error with the line and column where it occurred, returned by `parse_str`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionedError<E> {
+    pub error: E,
+    pub position: Position,
+}
+
+impl<E: Display> Display for PositionedError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.position, self.error)
+    }
+}
+
+impl<E: fmt::Debug + Display> core::error::Error for PositionedError<E> {}
+
+/// Runs a plain string parser on `input`, reporting the line and column of a failure.
+///
+/// The parser is lifted into a `StateCarrier<Position, &str>` with `Parser::lift`, so no
+/// stateful parsers are needed.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::Position;
+///
+/// let line = "ok\n".make_literal_matcher("Expected ok");
+/// let parser = line.many().skip("end".make_literal_matcher("Expected end"));
+///
+/// assert_eq!(parse_str(&parser, "ok\nend!"), Ok(("!", vec!["ok\n"])));
+///
+/// let err = parse_str(&parser, "ok\nok\nen").unwrap_err();
+/// assert_eq!(err.position, Position::new(2, 0));
+/// assert_eq!(err.to_string(), "2:0: Expected end");
+/// ```
+pub fn parse_str<'a, Output, Error: Clone>(
+    parser: &impl Parser<&'a str, Output, Error>,
+    input: &'a str,
+) -> Result<(&'a str, Output), PositionedError<Error>> {
+    let positioned = (move |input: &'a str| parser.parse(input)).lift::<Position>();
+    match positioned.parse(StateCarrier::new(Position::default(), input)) {
+        Ok((carrier, output)) => Ok((carrier.input, output)),
+        Err((carrier, error)) => Err(PositionedError {
+            error,
+            position: carrier.state,
+        }),
+    }
+}
+
+/// Returns the part of `input` consumed by a parser that left `rest`, or all of `input` if
+/// `rest` is not one of its suffixes.
+pub(crate) fn consumed<'a>(input: &'a str, rest: &str) -> &'a str {
+    input.get(..input.len().saturating_sub(rest.len())).unwrap_or(input)
+}
+
 /// Indentation state for tracking indentation levels in string parsing
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct Indentation {
     pub levels: Vec<usize>,
     pub current: usize,
 }
 
 impl Indentation {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Indentation {
             levels: Vec::new(),
             current: 0,
@@ -223,7 +512,7 @@ impl Indentation {
 }
 
 /// Span information for tracking source positions
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[derive(Default)]
 pub struct Span {
     pub start: usize,
@@ -231,7 +520,7 @@ pub struct Span {
 }
 
 impl Span {
-    pub fn new(start: usize, end: usize) -> Self {
+    pub const fn new(start: usize, end: usize) -> Self {
         Span { start, end }
     }
 
@@ -252,6 +541,36 @@ impl Span {
 }
 
 
+impl StateUpdate<&str> for Offset {
+    fn update(&mut self, before: &&str, after: &&str) {
+        self.increment(before.len() - after.len());
+    }
+}
+
+impl<T> StateUpdate<&[T]> for Offset {
+    fn update(&mut self, before: &&[T], after: &&[T]) {
+        self.increment(before.len() - after.len());
+    }
+}
+
+impl StateUpdate<&str> for Position {
+    fn update(&mut self, before: &&str, after: &&str) {
+        self.advance_over(consumed(before, after));
+    }
+}
+
+impl StateUpdate<&str> for Span {
+    fn update(&mut self, before: &&str, after: &&str) {
+        self.end += before.len() - after.len();
+    }
+}
+
+impl<T> StateUpdate<&[T]> for Span {
+    fn update(&mut self, before: &&[T], after: &&[T]) {
+        self.end += before.len() - after.len();
+    }
+}
+
 // Implement Parsable for StateCarrier<Offset, Input>
 impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Offset, &'a str> {
     type Item = char;
@@ -292,13 +611,10 @@ impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Offset, &'a str> {
                 input: inner,
             } = input;
 
-            if inner.is_empty() {
+            let Some((c, rest)) = split_first_char(inner) else {
                 return Err((input, err.clone()));
-            }
-
-            let (ret, rest) = inner.split_at(1);
-            let c = ret.chars().next().unwrap();
-            state.increment(1);
+            };
+            state.increment(c.len_utf8());
             Ok((StateCarrier { state, input: rest }, c))
         }
     }
@@ -314,15 +630,12 @@ impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Offset, &'a str> {
                 input: inner,
             } = input;
 
-            if inner.is_empty() {
+            let Some((ret_char, rest)) = split_first_char(inner) else {
                 return Err((input, err.clone()));
-            }
-
-            let (ret, rest) = inner.split_at(1);
-            let ret_char = ret.chars().next().unwrap();
+            };
 
             if ret_char == character {
-                state.increment(1);
+                state.increment(ret_char.len_utf8());
                 Ok((StateCarrier { state, input: rest }, ret_char))
             } else {
                 Err((input, err.clone()))
@@ -361,14 +674,7 @@ impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Position, &'a str> {
             if inner.starts_with(self.input) {
                 let (ret, rest) = inner.split_at(self.input.len());
 
-                // Update position based on newlines in the matched text
-                for c in self.input.chars() {
-                    if c == '\n' {
-                        state.advance_line();
-                    } else {
-                        state.advance_column(1);
-                    }
-                }
+                state.advance_over(ret);
 
                 Ok((
                     StateCarrier { state, input: rest },
@@ -391,18 +697,11 @@ impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Position, &'a str> {
                 input: inner,
             } = input;
 
-            if inner.is_empty() {
+            let Some((c, rest)) = split_first_char(inner) else {
                 return Err((input, err.clone()));
-            }
+            };
 
-            let (ret, rest) = inner.split_at(1);
-            let c = ret.chars().next().unwrap();
-
-            if c == '\n' {
-                state.advance_line();
-            } else {
-                state.advance_column(1);
-            }
+            state.advance_char(c);
 
             Ok((StateCarrier { state, input: rest }, c))
         }
@@ -419,19 +718,12 @@ impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Position, &'a str> {
                 input: inner,
             } = input;
 
-            if inner.is_empty() {
+            let Some((ret_char, rest)) = split_first_char(inner) else {
                 return Err((input, err.clone()));
-            }
-
-            let (ret, rest) = inner.split_at(1);
-            let ret_char = ret.chars().next().unwrap();
+            };
 
             if ret_char == character {
-                if ret_char == '\n' {
-                    state.advance_line();
-                } else {
-                    state.advance_column(1);
-                }
+                state.advance_char(ret_char);
 
                 Ok((StateCarrier { state, input: rest }, ret_char))
             } else {
@@ -491,12 +783,9 @@ impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Indentation, &'a str> {
                 input: inner,
             } = input.clone();
 
-            if inner.is_empty() {
+            let Some((c, rest)) = split_first_char(inner) else {
                 return Err((input, err.clone()));
-            }
-
-            let (ret, rest) = inner.split_at(1);
-            let c = ret.chars().next().unwrap();
+            };
 
             Ok((StateCarrier { state, input: rest }, c))
         }
@@ -513,12 +802,9 @@ impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Indentation, &'a str> {
                 input: inner,
             } = input.clone();
 
-            if inner.is_empty() {
+            let Some((ret_char, rest)) = split_first_char(inner) else {
                 return Err((input, err.clone()));
-            }
-
-            let (ret, rest) = inner.split_at(1);
-            let ret_char = ret.chars().next().unwrap();
+            };
 
             if ret_char == character {
                 Ok((StateCarrier { state, input: rest }, ret_char))
@@ -587,15 +873,12 @@ impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Span, &'a str> {
                 input: inner,
             } = input;
 
-            if inner.is_empty() {
+            let Some((c, rest)) = split_first_char(inner) else {
                 return Err((input, err.clone()));
-            }
-
-            let (ret, rest) = inner.split_at(1);
-            let c = ret.chars().next().unwrap();
+            };
 
             // Update span
-            state.end += 1;
+            state.end += c.len_utf8();
 
             Ok((StateCarrier { state, input: rest }, c))
         }
@@ -612,16 +895,13 @@ impl<'a, Error: Clone> Parsable<Error> for StateCarrier<Span, &'a str> {
                 input: inner,
             } = input;
 
-            if inner.is_empty() {
+            let Some((ret_char, rest)) = split_first_char(inner) else {
                 return Err((input, err.clone()));
-            }
-
-            let (ret, rest) = inner.split_at(1);
-            let ret_char = ret.chars().next().unwrap();
+            };
 
             if ret_char == character {
                 // Update span
-                state.end += 1;
+                state.end += ret_char.len_utf8();
 
                 Ok((StateCarrier { state, input: rest }, ret_char))
             } else {
@@ -654,17 +934,10 @@ impl<'a, Error: Clone, T: Eq> Parsable<Error> for StateCarrier<Offset, &'a [T]>
                 input: inner,
             } = input;
 
-            if inner.len() < self.input.len() {
-                return Err((input, err.clone()));
-            }
-
-            for i in 0..self.input.len() {
-                if inner[i] != self.input[i] {
-                    return Err((input, err.clone()));
-                }
-            }
-
-            let (ret, rest) = inner.split_at(self.input.len());
+            let (ret, rest) = match inner.split_at_checked(self.input.len()) {
+                Some((ret, rest)) if ret == self.input => (ret, rest),
+                _ => return Err((input, err.clone())),
+            };
             state.increment(self.input.len());
 
             Ok((
@@ -685,14 +958,12 @@ impl<'a, Error: Clone, T: Eq> Parsable<Error> for StateCarrier<Offset, &'a [T]>
                 input: inner,
             } = input;
 
-            if inner.is_empty() {
+            let Some((ret, rest)) = inner.split_first() else {
                 return Err((input, err.clone()));
-            }
-
-            let (ret, rest) = inner.split_at(1);
+            };
             state.increment(1);
 
-            Ok((StateCarrier { state, input: rest }, &ret[0]))
+            Ok((StateCarrier { state, input: rest }, ret))
         }
     }
 
@@ -707,17 +978,12 @@ impl<'a, Error: Clone, T: Eq> Parsable<Error> for StateCarrier<Offset, &'a [T]>
                 input: inner,
             } = input;
 
-            if inner.is_empty() {
-                return Err((input, err.clone()));
-            }
-
-            let (ret, rest) = inner.split_at(1);
-
-            if ret[0] == *character {
-                state.increment(1);
-                Ok((StateCarrier { state, input: rest }, &ret[0]))
-            } else {
-                Err((input, err.clone()))
+            match inner.split_first() {
+                Some((ret, rest)) if ret == character => {
+                    state.increment(1);
+                    Ok((StateCarrier { state, input: rest }, ret))
+                }
+                _ => Err((input, err.clone())),
             }
         }
     }
@@ -734,6 +1000,61 @@ impl<'a, Error: Clone, T: Eq> Parsable<Error> for StateCarrier<Offset, &'a [T]>
     }
 }
 
+/// Keyword matching for string slices.
+pub trait KeywordMatcher<Error: Clone>: Parsable<Error> {
+    /// Creates a parser matching this literal only at a word boundary, so that it is not
+    /// followed by an identifier character (alphanumeric or `_`).
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let keyword = "if".make_keyword_matcher("Expected if");
+    ///
+    /// assert_eq!(keyword.parse("if x"), Ok((" x", "if")));
+    /// assert_eq!(keyword.parse("if(x)"), Ok(("(x)", "if")));
+    /// assert_eq!(keyword.parse("iffy"), Err(("iffy", "Expected if")));
+    /// ```
+    fn make_keyword_matcher(self, err: Error) -> impl Parser<Self, Self, Error>;
+
+    /// Like `make_keyword_matcher`, but the literal must not be followed by a character
+    /// satisfying `is_continue`, for languages with their own identifier characters.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let keyword = "let".make_keyword_matcher_with(|c| c.is_alphanumeric() || c == '-', "Expected let");
+    ///
+    /// assert_eq!(keyword.parse("let x"), Ok((" x", "let")));
+    /// assert_eq!(keyword.parse("let-values"), Err(("let-values", "Expected let")));
+    /// ```
+    fn make_keyword_matcher_with(
+        self,
+        is_continue: impl Fn(char) -> bool,
+        err: Error,
+    ) -> impl Parser<Self, Self, Error>;
+}
+
+impl<'a, Error: Clone> KeywordMatcher<Error> for &'a str {
+    fn make_keyword_matcher(self, err: Error) -> impl Parser<Self, Self, Error> {
+        self.make_keyword_matcher_with(|c| c.is_alphanumeric() || c == '_', err)
+    }
+
+    fn make_keyword_matcher_with(
+        self,
+        is_continue: impl Fn(char) -> bool,
+        err: Error,
+    ) -> impl Parser<Self, Self, Error> {
+        move |input: &'a str| match input.strip_prefix(self) {
+            Some(rest) if !rest.starts_with(&is_continue) => Ok((rest, consumed(input, rest))),
+            _ => Err((input, err.clone())),
+        }
+    }
+}
+
 // Helper trait for &str and &[T] to create state carriers
 pub trait WithState<S> {
     /// Create a new state carrier with the given state
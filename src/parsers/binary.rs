@@ -0,0 +1,178 @@
+//! # Binary Parsers
+//!
+//! Primitives for binary formats over `&[u8]` input, such as PNG chunks, DNS messages or
+//! MQTT packets: fixed-width integers in big and little endian byte order, `take(n)` for
+//! raw bytes and `length_value` for length-prefixed fields. When the input ends early the
+//! error tells how many more bytes were needed, so a streaming reader knows how much to
+//! wait for.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::parsers::binary::*;
+//!
+//! // A chunk: 4-byte big endian length, 4-byte tag, then the data
+//! let chunk = be_u32().seq(take(4)).seq(u8());
+//! let bytes: &[u8] = &[0, 0, 0, 13, b'I', b'H', b'D', b'R', 7, 1];
+//!
+//! assert_eq!(chunk.parse(bytes), Ok((&[1u8][..], ((13, &b"IHDR"[..]), 7))));
+//! assert_eq!(be_u32().parse(&bytes[..2]), Err((&bytes[..2], Incomplete { needed: 2 })));
+//! ```
+
+use core::fmt::{self, Display, Formatter};
+
+use crate::core::Parser;
+use crate::types::Either;
+
+/// The input ended before a binary field was complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Incomplete {
+    /// Number of bytes missing from the input
+    pub needed: usize,
+}
+
+impl Display for Incomplete {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "input ended, {} more bytes needed", self.needed)
+    }
+}
+
+impl core::error::Error for Incomplete {}
+
+/// Takes exactly `n` bytes.
+pub fn take<'a>(n: usize) -> impl Parser<&'a [u8], &'a [u8], Incomplete> {
+    move |input: &'a [u8]| {
+        if input.len() < n {
+            return Err((input, Incomplete { needed: n - input.len() }));
+        }
+        let (bytes, rest) = input.split_at(n);
+        Ok((rest, bytes))
+    }
+}
+
+/// Takes exactly `N` bytes as an array.
+pub fn take_array<'a, const N: usize>() -> impl Parser<&'a [u8], [u8; N], Incomplete> {
+    move |input: &'a [u8]| {
+        let (rest, bytes) = take(N).parse(input)?;
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        Ok((rest, array))
+    }
+}
+
+macro_rules! integer_parsers {
+    ($($be:ident $le:ident $ty:ty),+ $(,)?) => {
+        $(
+            #[doc = concat!("Parses a big endian `", stringify!($ty), "`.")]
+            pub fn $be<'a>() -> impl Parser<&'a [u8], $ty, Incomplete> {
+                move |input: &'a [u8]| {
+                    let (rest, bytes) = take_array().parse(input)?;
+                    Ok((rest, <$ty>::from_be_bytes(bytes)))
+                }
+            }
+
+            #[doc = concat!("Parses a little endian `", stringify!($ty), "`.")]
+            pub fn $le<'a>() -> impl Parser<&'a [u8], $ty, Incomplete> {
+                move |input: &'a [u8]| {
+                    let (rest, bytes) = take_array().parse(input)?;
+                    Ok((rest, <$ty>::from_le_bytes(bytes)))
+                }
+            }
+        )+
+    };
+}
+
+integer_parsers!(
+    be_u16 le_u16 u16,
+    be_u32 le_u32 u32,
+    be_u64 le_u64 u64,
+    be_i16 le_i16 i16,
+    be_i32 le_i32 i32,
+    be_i64 le_i64 i64,
+);
+
+/// Parses one byte.
+pub fn u8<'a>() -> impl Parser<&'a [u8], u8, Incomplete> {
+    move |input: &'a [u8]| match input.split_first() {
+        Some((&byte, rest)) => Ok((rest, byte)),
+        None => Err((input, Incomplete { needed: 1 })),
+    }
+}
+
+/// Parses one byte as a signed integer.
+pub fn i8<'a>() -> impl Parser<&'a [u8], i8, Incomplete> {
+    move |input: &'a [u8]| u8().parse(input).map(|(rest, byte)| (rest, byte as i8))
+}
+
+/// Parses a length with `length`, then runs `body` on exactly that many following bytes.
+///
+/// The body does not have to consume all of its bytes; parsing continues after them
+/// either way. Missing bytes are reported as `Either::Left`, body errors as `Either::Right`.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::binary::*;
+///
+/// // A record of a tag byte and a u16, prefixed by its 2-byte length
+/// let string = length_value(be_u16(), take(1).seq(be_u16()));
+/// let bytes: &[u8] = &[0, 3, b'a', 0, 9, 0xff];
+///
+/// assert_eq!(string.parse(bytes), Ok((&[0xffu8][..], (&b"a"[..], 9))));
+/// assert_eq!(string.parse(&bytes[..4]), Err((&bytes[2..4], Either::Left(Incomplete { needed: 1 }))));
+/// ```
+pub fn length_value<'a, Length, Output, Error>(
+    length: impl Parser<&'a [u8], Length, Incomplete>,
+    body: impl Parser<&'a [u8], Output, Error>,
+) -> impl Parser<&'a [u8], Output, Either<Incomplete, Error>>
+where
+    Length: TryInto<usize>,
+    Error: Clone,
+{
+    move |input: &'a [u8]| {
+        let (rest, len) = length.parse(input).map_err(|(rest, e)| (rest, Either::Left(e)))?;
+        let len = len.try_into().unwrap_or(usize::MAX);
+        let (rest, value) = take(len).parse(rest).map_err(|(rest, e)| (rest, Either::Left(e)))?;
+        let (_, output) = body.parse(value).map_err(|(rest, e)| (rest, Either::Right(e)))?;
+        Ok((rest, output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integers_and_byte_order() {
+        let bytes: &[u8] = &[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0];
+        assert_eq!(be_u16().parse(bytes).unwrap().1, 0x1234);
+        assert_eq!(le_u16().parse(bytes).unwrap().1, 0x3412);
+        assert_eq!(be_u32().parse(bytes).unwrap().1, 0x12345678);
+        assert_eq!(le_u32().parse(bytes).unwrap().1, 0x78563412);
+        assert_eq!(be_u64().parse(bytes), Ok((&[][..], 0x123456789abcdef0)));
+        assert_eq!(le_i16().parse(&[0xfe, 0xff][..]).unwrap().1, -2);
+        assert_eq!(i8().parse(&[0x80][..]).unwrap().1, -128);
+
+        assert_eq!(le_u64().parse(&bytes[..5]), Err((&bytes[..5], Incomplete { needed: 3 })));
+        assert_eq!(u8().parse(&[][..]), Err((&[][..], Incomplete { needed: 1 })));
+        assert_eq!(Incomplete { needed: 3 }.to_string(), "input ended, 3 more bytes needed");
+    }
+
+    #[test]
+    fn test_length_value() {
+        let field = length_value(u8(), take(2));
+        let bytes: &[u8] = &[4, 1, 2, 3, 4, 5];
+        // The unparsed bytes of the value are skipped
+        assert_eq!(field.parse(bytes), Ok((&[5u8][..], &[1u8, 2][..])));
+        assert_eq!(
+            field.parse(&[1, 9][..]),
+            Err((&[9u8][..], Either::Right(Incomplete { needed: 1 })))
+        );
+        assert_eq!(
+            field.parse(&[][..]),
+            Err((&[][..], Either::Left(Incomplete { needed: 1 })))
+        );
+    }
+}
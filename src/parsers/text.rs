@@ -0,0 +1,439 @@
+//! # Text Parsers
+//!
+//! Ready-made parsers for textual constructs shared by many formats. `escaped_string`
+//! parses a quoted string and decodes its escape sequences according to an `EscapeTable`;
+//! `escaped_str` does the same but borrows the content from the input when it contains no
+//! escapes. `between_balanced` captures the raw text of a block with nested delimiters,
+//! such as a `{ ... }` body to be skipped or handed to another parser.
+//!
+//! `line_comment`, `block_comment` and `nested_block_comment` parse comments and return
+//! their text. The same comment syntaxes can be skipped as trivia by `Grammar::lexeme`, see
+//! `GrammarBuilder::line_comment` and its siblings.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::parsers::text::*;
+//!
+//! let string = escaped_string('"', '\\', EscapeTable::standard(), "Invalid string");
+//!
+//! assert_eq!(string.parse(r#""a\tb\u{e9}" rest"#), Ok((" rest", "a\tbé".to_string())));
+//! assert_eq!(string.parse(r#""unterminated"#), Err((r#""unterminated"#, "Invalid string")));
+//! assert_eq!(string.parse(r#""bad \q""#), Err((r#"\q""#, "Invalid string")));
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::core::Parser;
+use crate::parsers::consumed;
+
+/// How `\u` escapes are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnicodeEscape {
+    /// `\u` escapes are not recognized
+    #[default]
+    None,
+    /// Rust style `\u{1F600}` with one to six hex digits
+    Braced,
+    /// JSON style `\u00e9`, with surrogate pairs such as `\ud83d\ude00` combined
+    Hex4,
+}
+
+/// The escape sequences recognized inside a quoted string.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EscapeTable {
+    escapes: Vec<(char, char)>,
+    unicode: UnicodeEscape,
+}
+
+impl EscapeTable {
+    /// Creates a table without any escapes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a table with the escapes of Rust string literals: `\n`, `\r`, `\t`, `\0`,
+    /// `\\`, `\"`, `\'` and `\u{...}`.
+    pub fn standard() -> Self {
+        Self::new()
+            .escape('n', '\n')
+            .escape('r', '\r')
+            .escape('t', '\t')
+            .escape('0', '\0')
+            .escape('\\', '\\')
+            .escape('"', '"')
+            .escape('\'', '\'')
+            .unicode(UnicodeEscape::Braced)
+    }
+
+    /// Creates a table with the escapes of JSON strings: `\"`, `\\`, `\/`, `\b`, `\f`,
+    /// `\n`, `\r`, `\t` and `\uXXXX`.
+    pub fn json() -> Self {
+        Self::new()
+            .escape('"', '"')
+            .escape('\\', '\\')
+            .escape('/', '/')
+            .escape('b', '\u{8}')
+            .escape('f', '\u{c}')
+            .escape('n', '\n')
+            .escape('r', '\r')
+            .escape('t', '\t')
+            .unicode(UnicodeEscape::Hex4)
+    }
+
+    /// Decodes the escape character followed by `code` as `decoded`.
+    pub fn escape(mut self, code: char, decoded: char) -> Self {
+        self.escapes.retain(|&(c, _)| c != code);
+        self.escapes.push((code, decoded));
+        self
+    }
+
+    /// Sets how `\u` escapes are written.
+    pub fn unicode(mut self, unicode: UnicodeEscape) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    fn lookup(&self, code: char) -> Option<char> {
+        self.escapes.iter().find(|&&(c, _)| c == code).map(|&(_, decoded)| decoded)
+    }
+
+    /// Decodes the escape sequence at the start of `input`, just after the escape
+    /// character, returning the character and the length of the sequence.
+    fn decode(&self, input: &str) -> Option<(char, usize)> {
+        let code = input.chars().next()?;
+        if let Some(hex) = input.strip_prefix('u') {
+            match self.unicode {
+                UnicodeEscape::Braced => return decode_braced(hex).map(|(c, len)| (c, len + 1)),
+                UnicodeEscape::Hex4 => return decode_hex4(hex).map(|(c, len)| (c, len + 1)),
+                UnicodeEscape::None => {}
+            }
+        }
+        self.lookup(code).map(|decoded| (decoded, code.len_utf8()))
+    }
+}
+
+/// Decodes `{1F600}`.
+fn decode_braced(input: &str) -> Option<(char, usize)> {
+    let (digits, _) = input.strip_prefix('{')?.split_once('}')?;
+    if digits.is_empty() || digits.len() > 6 {
+        return None;
+    }
+    let code = u32::from_str_radix(digits, 16).ok()?;
+    char::from_u32(code).map(|c| (c, digits.len() + 2))
+}
+
+/// Decodes `00e9`, or a surrogate pair `d83d\ude00`.
+fn decode_hex4(input: &str) -> Option<(char, usize)> {
+    let hex4 = |s: &str| {
+        let digits = s.get(..4)?;
+        digits
+            .chars()
+            .all(|c| c.is_ascii_hexdigit())
+            .then(|| u32::from_str_radix(digits, 16).ok())?
+    };
+    let high = hex4(input)?;
+    if !(0xD800..0xDC00).contains(&high) {
+        return char::from_u32(high).map(|c| (c, 4));
+    }
+    let low = hex4(input.get(4..)?.strip_prefix("\\u")?)?;
+    if !(0xDC00..0xE000).contains(&low) {
+        return None;
+    }
+    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).map(|c| (c, 10))
+}
+
+/// Creates a parser for strings delimited by `quote`, in which `escape` starts an escape
+/// sequence from `table`, returning the decoded content without quotes.
+///
+/// An unterminated string fails at the opening quote, an unknown escape sequence at its
+/// escape character.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::text::*;
+///
+/// let string = escaped_string('"', '\\', EscapeTable::json(), "Invalid string");
+/// assert_eq!(string.parse(r#""\u00e9 \ud83d\ude00""#), Ok(("", "é 😀".to_string())));
+///
+/// let sql = escaped_string('\'', '\'', EscapeTable::new().escape('\'', '\''), "Invalid string");
+/// assert_eq!(sql.parse("'it''s'"), Ok(("", "it's".to_string())));
+/// ```
+pub fn escaped_string<'a, Error: Clone>(
+    quote: char,
+    escape: char,
+    table: EscapeTable,
+    err: Error,
+) -> impl Parser<&'a str, String, Error> {
+    let string = escaped_str(quote, escape, table, err);
+    move |input: &'a str| string.parse(input).map(|(rest, content)| (rest, content.into_owned()))
+}
+
+/// Like `escaped_string`, but borrows the content from the input when it contains no
+/// escape sequences.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::text::*;
+/// use std::borrow::Cow;
+///
+/// let string = escaped_str('"', '\\', EscapeTable::standard(), "Invalid string");
+///
+/// assert!(matches!(string.parse(r#""plain""#), Ok(("", Cow::Borrowed("plain")))));
+/// assert!(matches!(string.parse(r#""tab\t""#), Ok(("", Cow::Owned(s))) if s == "tab\t"));
+/// ```
+pub fn escaped_str<'a, Error: Clone>(
+    quote: char,
+    escape: char,
+    table: EscapeTable,
+    err: Error,
+) -> impl Parser<&'a str, Cow<'a, str>, Error> {
+    move |input: &'a str| {
+        let body = match input.strip_prefix(quote) {
+            Some(body) => body,
+            None => return Err((input, err.clone())),
+        };
+        let mut decoded: Option<String> = None;
+        // `pending` starts the text not yet copied into `decoded`
+        let mut pending = body;
+        let mut rest = body;
+        loop {
+            let mut chars = rest.chars();
+            let Some(c) = chars.next() else { break };
+            let after = chars.as_str();
+            // When the escape character is the quote itself, a lone quote ends the string
+            if c == escape && (escape != quote || after.starts_with(quote)) {
+                let (ch, len) = match table.decode(after) {
+                    Some(found) => found,
+                    None => return Err((rest, err.clone())),
+                };
+                let buffer = decoded.get_or_insert_with(String::new);
+                buffer.push_str(consumed(pending, rest));
+                buffer.push(ch);
+                rest = after.get(len..).unwrap_or_default();
+                pending = rest;
+            } else if c == quote {
+                let content = match decoded {
+                    Some(mut buffer) => {
+                        buffer.push_str(consumed(pending, rest));
+                        Cow::Owned(buffer)
+                    }
+                    None => Cow::Borrowed(consumed(body, rest)),
+                };
+                return Ok((after, content));
+            } else {
+                rest = after;
+            }
+        }
+        Err((input, err.clone()))
+    }
+}
+
+/// Creates a parser that consumes from `open` to its matching `close`, counting nested
+/// pairs, and returns the raw text between them.
+///
+/// Delimiters inside strings or comments are counted like any other, and when `open` and
+/// `close` are equal, the first `close` ends the block. An unterminated block fails at its
+/// opening delimiter.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::text::*;
+///
+/// let block = between_balanced("{", "}", "Unbalanced block");
+///
+/// assert_eq!(block.parse("{ a { b } { } c } d"), Ok((" d", " a { b } { } c ")));
+/// assert_eq!(block.parse("{ a { b }"), Err(("{ a { b }", "Unbalanced block")));
+///
+/// let region = between_balanced("begin", "end", "Unbalanced region");
+/// assert_eq!(region.parse("begin begin end end;"), Ok((";", " begin end ")));
+/// ```
+pub fn between_balanced<'a, Error: Clone>(
+    open: impl AsRef<str>,
+    close: impl AsRef<str>,
+    err: Error,
+) -> impl Parser<&'a str, &'a str, Error> {
+    move |input: &'a str| {
+        let (open, close) = (open.as_ref(), close.as_ref());
+        let body = match input.strip_prefix(open) {
+            Some(body) if !open.is_empty() => body,
+            _ => return Err((input, err.clone())),
+        };
+        match balanced_end(body, open, close) {
+            Some((inner, rest)) => Ok((rest, inner)),
+            None => Err((input, err.clone())),
+        }
+    }
+}
+
+/// Splits `body` around the `close` matching an `open` just before it, counting nested
+/// pairs, into the text before that `close` and the text after it.
+fn balanced_end<'a>(body: &'a str, open: &str, close: &str) -> Option<(&'a str, &'a str)> {
+    if open.is_empty() || close.is_empty() {
+        return None;
+    }
+    let mut depth = 0usize;
+    let mut rest = body;
+    loop {
+        if let Some(after) = rest.strip_prefix(close) {
+            if depth == 0 {
+                return Some((consumed(body, rest), after));
+            }
+            depth -= 1;
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix(open) {
+            depth += 1;
+            rest = after;
+        } else {
+            let mut chars = rest.chars();
+            chars.next()?;
+            rest = chars.as_str();
+        }
+    }
+}
+
+/// Creates a parser for a comment starting with `prefix` and running to the end of the
+/// line, returning the text after the prefix.
+///
+/// The line break is left in the input, for the whitespace handling that follows.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::text::*;
+///
+/// let comment = line_comment("//", "Expected comment");
+///
+/// assert_eq!(comment.parse("// note\nx"), Ok(("\nx", " note")));
+/// assert_eq!(comment.parse("// last"), Ok(("", " last")));
+/// assert_eq!(comment.parse("/ no"), Err(("/ no", "Expected comment")));
+/// ```
+pub fn line_comment<'a, Error: Clone>(prefix: impl AsRef<str>, err: Error) -> impl Parser<&'a str, &'a str, Error> {
+    move |input: &'a str| match input.strip_prefix(prefix.as_ref()) {
+        Some(body) if !prefix.as_ref().is_empty() => {
+            let comment = body.split('\n').next().unwrap_or_default();
+            Ok((body.get(comment.len()..).unwrap_or_default(), comment))
+        }
+        _ => Err((input, err.clone())),
+    }
+}
+
+/// Creates a parser for a comment from `open` to the first `close`, returning the text
+/// between them. An unterminated comment fails at `open`.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::text::*;
+///
+/// let comment = block_comment("/*", "*/", "Unterminated comment");
+///
+/// assert_eq!(comment.parse("/* a /* b */ c */"), Ok((" c */", " a /* b ")));
+/// assert_eq!(comment.parse("/* a"), Err(("/* a", "Unterminated comment")));
+/// ```
+pub fn block_comment<'a, Error: Clone>(
+    open: impl AsRef<str>,
+    close: impl AsRef<str>,
+    err: Error,
+) -> impl Parser<&'a str, &'a str, Error> {
+    move |input: &'a str| {
+        let (open, close) = (open.as_ref(), close.as_ref());
+        let body = match input.strip_prefix(open) {
+            Some(body) if !open.is_empty() && !close.is_empty() => body,
+            _ => return Err((input, err.clone())),
+        };
+        match body.split_once(close) {
+            Some((comment, rest)) => Ok((rest, comment)),
+            None => Err((input, err.clone())),
+        }
+    }
+}
+
+/// Like `block_comment`, but comments nest, as in Rust or Haskell: each `open` inside the
+/// comment needs its own `close`. This is `between_balanced` under a name that reads well
+/// in trivia rules.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::text::*;
+///
+/// let comment = nested_block_comment("{-", "-}", "Unterminated comment");
+///
+/// assert_eq!(comment.parse("{- a {- b -} c -} x"), Ok((" x", " a {- b -} c ")));
+/// assert_eq!(comment.parse("{- a {- b -}"), Err(("{- a {- b -}", "Unterminated comment")));
+/// ```
+pub fn nested_block_comment<'a, Error: Clone>(
+    open: impl AsRef<str>,
+    close: impl AsRef<str>,
+    err: Error,
+) -> impl Parser<&'a str, &'a str, Error> {
+    between_balanced(open, close, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_edge_cases() {
+        let rust = escaped_string('"', '\\', EscapeTable::standard(), "Invalid string");
+        assert_eq!(rust.parse(r#""\u{1F600}\\""#), Ok(("", "😀\\".to_string())));
+        assert_eq!(rust.parse(r#""\u{}""#), Err((r#"\u{}""#, "Invalid string")));
+        assert_eq!(rust.parse(r#""\u{D800}""#), Err((r#"\u{D800}""#, "Invalid string")));
+        assert_eq!(rust.parse(r#""end\""#), Err((r#""end\""#, "Invalid string")));
+        assert_eq!(rust.parse("x"), Err(("x", "Invalid string")));
+
+        let json = escaped_str('"', '\\', EscapeTable::json(), "Invalid string");
+        assert_eq!(json.parse(r#""\ud83d""#), Err((r#"\ud83d""#, "Invalid string")));
+        assert_eq!(json.parse(r#""\u12""#), Err((r#"\u12""#, "Invalid string")));
+        assert_eq!(json.parse(r#""a\/b""#).map(|(_, s)| s), Ok(Cow::Owned("a/b".to_string())));
+
+        let custom = escaped_str('|', '%', EscapeTable::new().escape('p', '|'), "Invalid string");
+        assert_eq!(custom.parse("|a%pb|c"), Ok(("c", Cow::Owned("a|b".to_string()))));
+    }
+
+    #[test]
+    fn test_balanced_edge_cases() {
+        let parens = between_balanced("(", ")", "Unbalanced");
+        assert_eq!(parens.parse("()x"), Ok(("x", "")));
+        assert_eq!(parens.parse("((é))"), Ok(("", "(é)")));
+        assert_eq!(parens.parse("x()"), Err(("x()", "Unbalanced")));
+        assert_eq!(parens.parse("(()"), Err(("(()", "Unbalanced")));
+
+        // Equal delimiters cannot nest, and overlapping ones prefer the closing delimiter
+        let quoted = between_balanced("|", "|", "Unbalanced");
+        assert_eq!(quoted.parse("|a|b|"), Ok(("b|", "a")));
+        let comment = between_balanced("/*", "*/", "Unbalanced");
+        assert_eq!(comment.parse("/* a /* b */ */*/"), Ok(("*/", " a /* b */ ")));
+        assert_eq!(between_balanced("", ")", "Unbalanced").parse(")"), Err((")", "Unbalanced")));
+    }
+
+    #[test]
+    fn test_comment_edge_cases() {
+        let hash = line_comment("#", "Expected comment");
+        assert_eq!(hash.parse("#\r\n"), Ok(("\n", "\r")));
+        assert_eq!(hash.parse("#"), Ok(("", "")));
+        assert_eq!(line_comment("", "Expected comment").parse("x"), Err(("x", "Expected comment")));
+
+        let block = block_comment("(*", "*)", "Unterminated");
+        assert_eq!(block.parse("(**)"), Ok(("", "")));
+        assert_eq!(block.parse("(*)"), Err(("(*)", "Unterminated")));
+
+        let nested = nested_block_comment("/*", "*/", "Unterminated");
+        assert_eq!(nested.parse("/*/**/*/x"), Ok(("x", "/**/")));
+        assert_eq!(nested.parse("/*/**/"), Err(("/*/**/", "Unterminated")));
+    }
+}
@@ -0,0 +1,163 @@
+//! # Pattern Library
+//!
+//! This module collects small combinators for patterns that come up constantly in real
+//! grammars. Each one is easy to write by hand, but getting the backtracking right every
+//! time is tedious:
+//!
+//! - `optional_prefixed(sign)`: an optional prefix such as a sign, `-x` or `x`
+//! - `wrapped_or_bare(open, close)`: a value that may be parenthesized, `(x)` or `x`
+//! - `either_order(other)`: two parts in any order, `a b` or `b a`
+//!
+//! All three restart from the original input when an attempt fails.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::patterns::*;
+//!
+//! let digit = '7'.make_character_matcher("Expected 7");
+//! let number = digit.optional_prefixed('-'.make_character_matcher("Expected -"));
+//!
+//! assert_eq!(number.parse("-7"), Ok(("", (Some('-'), '7'))));
+//! assert_eq!(number.parse("7"), Ok(("", (None, '7'))));
+//! ```
+
+use crate::core::{Parsable, Parser, ParserOutput};
+use crate::types::Either;
+
+/// Extension trait adding common grammar patterns to parsers.
+pub trait PatternParser<Input, Output, Error>: Parser<Input, Output, Error>
+where
+    Input: Parsable<Error>,
+    Output: ParserOutput,
+    Error: Clone,
+{
+    /// Parses an optional `prefix` followed by this parser.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::patterns::*;
+    ///
+    /// let name = "x".make_literal_matcher("Expected x");
+    /// let reference = name.optional_prefixed("&".make_literal_matcher("Expected &"));
+    ///
+    /// assert_eq!(reference.parse("&x"), Ok(("", (Some("&"), "x"))));
+    /// assert_eq!(reference.parse("y"), Err(("y", "Expected x")));
+    /// ```
+    fn optional_prefixed<Prefix, ErrorPrefix>(
+        self,
+        prefix: impl Parser<Input, Prefix, ErrorPrefix>,
+    ) -> impl Parser<Input, (Option<Prefix>, Output), Error>
+    where
+        Self: Sized,
+        Input: Parsable<ErrorPrefix>,
+        ErrorPrefix: Clone,
+    {
+        move |input: Input| {
+            let (rest, prefix) = match prefix.parse(input.clone()) {
+                Ok((rest, prefix)) => (rest, Some(prefix)),
+                Err(_) => (input, None),
+            };
+            let (rest, output) = self.parse(rest)?;
+            Ok((rest, (prefix, output)))
+        }
+    }
+
+    /// Parses this parser either between `open` and `close` or on its own.
+    ///
+    /// Once `open` matched, the wrapped form is committed to: a missing `close` is an error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::patterns::*;
+    ///
+    /// let atom = "a".make_literal_matcher("Expected a");
+    /// let parser = atom.wrapped_or_bare(
+    ///     "(".make_literal_matcher("Expected ("),
+    ///     ")".make_literal_matcher("Expected )"),
+    /// );
+    ///
+    /// assert_eq!(parser.parse("(a)"), Ok(("", "a")));
+    /// assert_eq!(parser.parse("a"), Ok(("", "a")));
+    /// assert_eq!(parser.parse("(a"), Err(("", Either::Right("Expected )"))));
+    /// ```
+    fn wrapped_or_bare<Open, Close, ErrorOpen, ErrorClose>(
+        self,
+        open: impl Parser<Input, Open, ErrorOpen>,
+        close: impl Parser<Input, Close, ErrorClose>,
+    ) -> impl Parser<Input, Output, Either<Error, ErrorClose>>
+    where
+        Self: Sized,
+        Input: Parsable<ErrorOpen> + Parsable<ErrorClose> + Parsable<Either<Error, ErrorClose>>,
+        ErrorOpen: Clone,
+        ErrorClose: Clone,
+    {
+        move |input: Input| match open.parse(input.clone()) {
+            Ok((rest, _)) => {
+                let (rest, output) = self.parse(rest).map_err(|(rest, e)| (rest, Either::Left(e)))?;
+                let (rest, _) = close.parse(rest).map_err(|(rest, e)| (rest, Either::Right(e)))?;
+                Ok((rest, output))
+            }
+            Err(_) => self.parse(input).map_err(|(rest, e)| (rest, Either::Left(e))),
+        }
+    }
+
+    /// Parses this parser and `other` in either order, returning the outputs in declaration
+    /// order. Fails with the errors of both attempts.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::patterns::*;
+    ///
+    /// let public = "pub ".make_literal_matcher("Expected pub");
+    /// let constant = "const ".make_literal_matcher("Expected const");
+    /// let modifiers = public.either_order(constant);
+    ///
+    /// assert_eq!(modifiers.parse("pub const "), Ok(("", ("pub ", "const "))));
+    /// assert_eq!(modifiers.parse("const pub "), Ok(("", ("pub ", "const "))));
+    /// assert!(modifiers.parse("pub pub ").is_err());
+    /// ```
+    fn either_order<Output2, Error2>(
+        self,
+        other: impl Parser<Input, Output2, Error2>,
+    ) -> impl Parser<Input, (Output, Output2), (Either<Error, Error2>, Either<Error2, Error>)>
+    where
+        Self: Sized,
+        Input: Parsable<Error2> + Parsable<(Either<Error, Error2>, Either<Error2, Error>)>,
+        Error2: Clone,
+    {
+        move |input: Input| {
+            let first = match self.parse(input.clone()) {
+                Ok((rest, a)) => match other.parse(rest) {
+                    Ok((rest, b)) => return Ok((rest, (a, b))),
+                    Err((_, e)) => Either::Right(e),
+                },
+                Err((_, e)) => Either::Left(e),
+            };
+            let second = match other.parse(input.clone()) {
+                Ok((rest, b)) => match self.parse(rest) {
+                    Ok((rest, a)) => return Ok((rest, (a, b))),
+                    Err((_, e)) => Either::Right(e),
+                },
+                Err((_, e)) => Either::Left(e),
+            };
+            Err((input, (first, second)))
+        }
+    }
+}
+
+impl<Input, Output, Error, P> PatternParser<Input, Output, Error> for P
+where
+    Input: Parsable<Error>,
+    Output: ParserOutput,
+    Error: Clone,
+    P: Parser<Input, Output, Error>,
+{
+}
@@ -0,0 +1,461 @@
+//! # Persistent Memoization
+//!
+//! This module provides a memo cache that can be saved to disk and reloaded in a later
+//! process, so that build tools parsing the same files over and over can skip re-parsing
+//! inputs they have already seen. It is available with the `persist` feature.
+//!
+//! Entries are keyed by the rule name and the byte offset of the rule invocation. Each
+//! entry also records the length and hash of the text its result depends on, so after the
+//! document changed an entry is only reused where that text is unchanged. `edit` moves the
+//! entries behind a change to their new offsets, the same way `IncrementalParser` does.
+//!
+//! The hash is 64-bit FNV-1a, which unlike `DefaultHasher` is fully specified, so a saved
+//! cache stays valid across Rust releases and platforms.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::persist::*;
+//!
+//! let cache = PersistentCache::new();
+//! let parser = "hello".make_literal_matcher("Expected hello".to_string())
+//!     .map(|s| s.to_string())
+//!     .memoize_persistent(&cache, "hello");
+//!
+//! let source = "hello world";
+//! cache.begin(source);
+//! assert_eq!(parser.parse(source), Ok((" world", "hello".to_string())));
+//!
+//! let mut saved = Vec::new();
+//! cache.save_to(&mut saved).unwrap();
+//!
+//! // In a later run the result is served from the loaded cache
+//! let reloaded = PersistentCache::<String, String>::load_from(saved.as_slice()).unwrap();
+//! reloaded.begin(source);
+//! assert_eq!(reloaded.len(), 1);
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Parsable, Parser, ParserOutput};
+use crate::memo::TextEdit;
+
+/// A key identifying a rule invocation in the document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PersistKey {
+    /// The rule name
+    pub rule: String,
+    /// Byte offset of the invocation in the document
+    pub offset: usize,
+}
+
+/// A stored parse result with the byte offset at which parsing stopped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistEntry<O, E> {
+    /// Byte offset of the remaining input
+    pub end: usize,
+    /// Number of bytes from the invocation offset the result depends on
+    pub checked: usize,
+    /// FNV-1a hash of those bytes
+    pub hash: u64,
+    /// The output or error of the rule
+    pub result: Result<O, E>,
+}
+
+/// Hashes the `checked` bytes at the start of `input` with 64-bit FNV-1a.
+///
+/// When they reach the end of `input` a `0xFF` byte, which never occurs in UTF-8, is hashed
+/// as well, so a result that depended on the end of the document is not reused once text
+/// follows it.
+fn region_hash(input: &str, checked: usize) -> Option<u64> {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let bytes = input.as_bytes().get(..checked)?;
+    let end_marker = (checked == input.len()).then_some(0xFF);
+    let hash = bytes
+        .iter()
+        .copied()
+        .chain(end_marker)
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME));
+    Some(hash)
+}
+
+#[derive(Debug)]
+struct CacheState<O, E> {
+    entries: HashMap<PersistKey, PersistEntry<O, E>>,
+    document: Option<(usize, usize)>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<O, E> Default for CacheState<O, E> {
+    fn default() -> Self {
+        CacheState {
+            entries: HashMap::new(),
+            document: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+/// A serializable memo cache shared by persistent memoized parsers.
+///
+/// Call `begin` with the document before parsing it; parsers only consult the cache for
+/// inputs that are suffixes of the current document.
+#[derive(Debug)]
+pub struct PersistentCache<O, E> {
+    state: Rc<RefCell<CacheState<O, E>>>,
+}
+
+impl<O, E> Clone for PersistentCache<O, E> {
+    fn clone(&self) -> Self {
+        PersistentCache {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<O, E> Default for PersistentCache<O, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O, E> PersistentCache<O, E> {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        PersistentCache {
+            state: Rc::new(RefCell::new(CacheState::default())),
+        }
+    }
+
+    /// Sets the document that subsequent parses run on.
+    ///
+    /// The document may differ from the one the entries were stored for; an entry is only
+    /// used if the text it depends on is unchanged at its offset.
+    pub fn begin(&self, source: &str) {
+        self.state.borrow_mut().document = Some((source.as_ptr() as usize, source.len()));
+    }
+
+    /// Records that the bytes `range` of the document were replaced by `new_len` bytes.
+    ///
+    /// Entries behind the change move to their new offsets and entries whose text overlaps
+    /// it are dropped, as `TextEdit::shift` describes. Entries before the change are kept.
+    /// Call `begin` with the edited document before parsing it.
+    pub fn edit(&self, range: Range<usize>, new_len: usize) {
+        let edit = TextEdit { range, new_len };
+        let mut state = self.state.borrow_mut();
+        state.entries = std::mem::take(&mut state.entries)
+            .into_iter()
+            .filter_map(|(key, entry)| {
+                let span = edit.shift(key.offset..key.offset.checked_add(entry.checked)?, 0)?;
+                let end = entry.end.checked_add(span.start)?.checked_sub(key.offset)?;
+                let key = PersistKey { offset: span.start, ..key };
+                Some((key, PersistEntry { end, ..entry }))
+            })
+            .collect();
+    }
+
+    /// Returns the number of stored entries.
+    pub fn len(&self) -> usize {
+        self.state.borrow().entries.len()
+    }
+
+    /// Returns true if the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.state.borrow().entries.is_empty()
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) {
+        self.state.borrow_mut().entries.clear();
+    }
+
+    /// Returns the number of cache hits and misses since the cache was created.
+    pub fn hits_and_misses(&self) -> (usize, usize) {
+        let state = self.state.borrow();
+        (state.hits, state.misses)
+    }
+
+    /// Returns the offset of `input` within the current document, if it is a suffix of it.
+    fn locate(&self, input: &str) -> Option<usize> {
+        let (start, len) = self.state.borrow().document?;
+        let end = start + len;
+        let ptr = input.as_ptr() as usize;
+        (ptr >= start && ptr + input.len() == end).then_some(ptr.wrapping_sub(start))
+    }
+}
+
+impl<O: Serialize, E: Serialize> PersistentCache<O, E> {
+    /// Writes all entries to `writer` as JSON.
+    pub fn save_to(&self, writer: impl Write) -> io::Result<()> {
+        let state = self.state.borrow();
+        let entries: Vec<_> = state.entries.iter().collect();
+        serde_json::to_writer(writer, &entries).map_err(io::Error::from)
+    }
+
+    /// Writes all entries to the file at `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.save_to(&mut writer)?;
+        writer.flush()
+    }
+}
+
+impl<O: DeserializeOwned, E: DeserializeOwned> PersistentCache<O, E> {
+    /// Reads a cache previously written with `save_to`.
+    pub fn load_from(reader: impl Read) -> io::Result<Self> {
+        let entries: Vec<(PersistKey, PersistEntry<O, E>)> =
+            serde_json::from_reader(reader).map_err(io::Error::from)?;
+        let cache = Self::new();
+        cache.state.borrow_mut().entries = entries.into_iter().collect();
+        Ok(cache)
+    }
+
+    /// Reads a cache from the file at `path`, or returns an empty cache if it does not exist.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match File::open(path) {
+            Ok(file) => Self::load_from(BufReader::new(file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A parser whose results are stored in a `PersistentCache`.
+///
+/// A success depends on the text it matched and the lookahead after it, 1 byte by default
+/// as for `IncrementalRule`. A failure depends on the whole remaining input, since the
+/// parser may have looked arbitrarily far before giving up.
+pub struct PersistentMemoizedParser<O, E, P> {
+    /// The underlying parser
+    parser: P,
+    /// The rule name used in cache keys
+    rule: String,
+    /// The shared cache
+    cache: PersistentCache<O, E>,
+    /// The number of bytes after a match the parser may examine
+    lookahead: usize,
+}
+
+impl<O, E, P> PersistentMemoizedParser<O, E, P> {
+    /// Creates a new persistent memoized parser for `rule`.
+    pub fn new(parser: P, cache: &PersistentCache<O, E>, rule: impl Into<String>) -> Self {
+        PersistentMemoizedParser {
+            parser,
+            rule: rule.into(),
+            cache: cache.clone(),
+            lookahead: 1,
+        }
+    }
+
+    /// Sets the number of bytes after its match the parser may examine.
+    pub fn lookahead(mut self, bytes: usize) -> Self {
+        self.lookahead = bytes;
+        self
+    }
+}
+
+impl<'a, O, E, P> Parser<&'a str, O, E> for PersistentMemoizedParser<O, E, P>
+where
+    O: Clone + ParserOutput,
+    E: Clone,
+    P: Parser<&'a str, O, E>,
+{
+    fn parse(&self, input: &'a str) -> Result<(&'a str, O), (&'a str, E)> {
+        let Some(offset) = self.cache.locate(input) else {
+            return self.parser.parse(input);
+        };
+        let key = PersistKey {
+            rule: self.rule.clone(),
+            offset,
+        };
+
+        // Stale or corrupt entries, from a changed document or a damaged file, are parsed again
+        let cached = self.cache.state.borrow().entries.get(&key).cloned();
+        if let Some(entry) = cached {
+            let unchanged = region_hash(input, entry.checked) == Some(entry.hash);
+            let rest = entry.end.checked_sub(offset).and_then(|consumed| input.get(consumed..));
+            if let (true, Some(rest)) = (unchanged, rest) {
+                self.cache.state.borrow_mut().hits += 1;
+                return match entry.result {
+                    Ok(output) => Ok((rest, output)),
+                    Err(error) => Err((rest, error)),
+                };
+            }
+        }
+
+        let result = self.parser.parse(input);
+        let (rest, stored) = match &result {
+            Ok((rest, output)) => (rest, Ok(output.clone())),
+            Err((rest, error)) => (rest, Err(error.clone())),
+        };
+        let consumed = input.len().saturating_sub(rest.len());
+        let checked = match stored {
+            Ok(_) => consumed.saturating_add(self.lookahead).min(input.len()),
+            Err(_) => input.len(),
+        };
+        let entry = PersistEntry {
+            end: offset + consumed,
+            checked,
+            hash: region_hash(input, checked).unwrap_or_default(),
+            result: stored,
+        };
+        let mut state = self.cache.state.borrow_mut();
+        state.misses += 1;
+        state.entries.insert(key, entry);
+        drop(state);
+
+        result
+    }
+}
+
+/// Extension trait adding persistent memoization to parsers over `&str`.
+pub trait PersistentMemoizableParser<'a, O, E>: Parser<&'a str, O, E> + Sized
+where
+    O: Clone + ParserOutput,
+    E: Clone,
+    &'a str: Parsable<E>,
+{
+    /// Wraps the parser so that its results are stored in `cache` under `rule`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::persist::*;
+    ///
+    /// let cache = PersistentCache::new();
+    /// let parser = 'a'.make_character_matcher(()).memoize_persistent(&cache, "a");
+    ///
+    /// cache.begin("abc");
+    /// assert_eq!(parser.parse("abc"), Ok(("bc", 'a')));
+    /// assert_eq!(parser.parse("abc"), Ok(("bc", 'a')));
+    /// assert_eq!(cache.hits_and_misses(), (1, 1));
+    /// ```
+    fn memoize_persistent(
+        self,
+        cache: &PersistentCache<O, E>,
+        rule: impl Into<String>,
+    ) -> PersistentMemoizedParser<O, E, Self> {
+        PersistentMemoizedParser::new(self, cache, rule)
+    }
+}
+
+impl<'a, O, E, P> PersistentMemoizableParser<'a, O, E> for P
+where
+    O: Clone + ParserOutput,
+    E: Clone,
+    P: Parser<&'a str, O, E> + Sized,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persistent_cache_round_trip() {
+        let counter = Rc::new(RefCell::new(0));
+        let counter_clone = counter.clone();
+        let word = move |input: &'static str| {
+            *counter_clone.borrow_mut() += 1;
+            "word"
+                .make_literal_matcher("Expected word".to_string())
+                .map(|s| s.len())
+                .parse(input)
+        };
+
+        let cache = PersistentCache::new();
+        let parser = (&word).memoize_persistent(&cache, "word");
+        let source = "word word";
+        cache.begin(source);
+        assert_eq!(parser.parse(source), Ok((" word", 4)));
+        assert_eq!(parser.parse(&source[5..]), Ok(("", 4)));
+        assert_eq!(parser.parse(&source[4..]), Err((" word", "Expected word".to_string())));
+        assert_eq!(cache.len(), 3);
+
+        let path = std::env::temp_dir().join(format!("friss-persist-{}.json", std::process::id()));
+        cache.save(&path).unwrap();
+        let reloaded = PersistentCache::<usize, String>::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let parser = (&word).memoize_persistent(&reloaded, "word");
+        reloaded.begin(source);
+        let calls = *counter.borrow();
+        assert_eq!(parser.parse(&source[5..]), Ok(("", 4)));
+        assert_eq!(*counter.borrow(), calls);
+        assert_eq!(reloaded.hits_and_misses(), (1, 0));
+
+        // A changed lookahead byte invalidates the entry
+        reloaded.begin("word!");
+        assert_eq!(parser.parse("word!"), Ok(("!", 4)));
+        assert_eq!(*counter.borrow(), calls + 1);
+    }
+
+    #[test]
+    fn test_persistent_cache_survives_edits() {
+        let counter = Rc::new(RefCell::new(0));
+        let counter_clone = counter.clone();
+        let word = move |input: &'static str| {
+            *counter_clone.borrow_mut() += 1;
+            "word".make_literal_matcher("Expected word").map(|s| s.len()).parse(input)
+        };
+        let cache = PersistentCache::new();
+        let parser = (&word).memoize_persistent(&cache, "word");
+
+        let source = "word word word";
+        cache.begin(source);
+        for offset in [0, 5, 10] {
+            parser.parse(&source[offset..]).unwrap();
+        }
+        assert_eq!(*counter.borrow(), 3);
+
+        // Only the edited word is parsed again, the last one moves behind the change
+        let edited = "word words word";
+        cache.edit(5..9, 5);
+        cache.begin(edited);
+        assert_eq!(parser.parse(edited), Ok((&edited[4..], 4)));
+        assert_eq!(parser.parse(&edited[5..]), Ok((&edited[9..], 4)));
+        assert_eq!(parser.parse(&edited[11..]), Ok(("", 4)));
+        assert_eq!(*counter.borrow(), 4);
+
+        // Without a recorded edit, entries are reused where their text is unchanged; the
+        // last word no longer ends the document
+        let appended = "word words word word";
+        cache.begin(appended);
+        assert_eq!(parser.parse(appended), Ok((&appended[4..], 4)));
+        assert_eq!(parser.parse(&appended[11..]), Ok((&appended[15..], 4)));
+        assert_eq!(*counter.borrow(), 5);
+    }
+
+    #[test]
+    fn test_persistent_cache_reparses_corrupt_entries() {
+        let source = "ab";
+        let hash = region_hash(source, 2).unwrap();
+        let saved = format!(
+            r#"[[{{"rule":"a","offset":0}},{{"end":99,"checked":2,"hash":{hash},"result":{{"Ok":"a"}}}}]]"#
+        );
+        let cache = PersistentCache::<String, String>::load_from(saved.as_bytes()).unwrap();
+        let parser = "a"
+            .make_literal_matcher("Expected a".to_string())
+            .map(|s| s.to_string())
+            .memoize_persistent(&cache, "a");
+
+        cache.begin(source);
+        assert_eq!(parser.parse(source), Ok(("b", "a".to_string())));
+        assert_eq!(cache.hits_and_misses(), (0, 1));
+    }
+}
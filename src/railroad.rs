@@ -0,0 +1,305 @@
+//! # Railroad Diagrams
+//!
+//! Draws the rules of a `GrammarSpec` as SVG railroad diagrams, for syntax documentation
+//! generated from the same descriptions that `analysis` checks. Build the grammar with
+//! `analysis::described` to take the descriptions straight from the parser definitions.
+//!
+//! - `diagram(expr)` draws one right-hand side
+//! - `diagrams(grammar)` draws every rule, in definition order
+//! - `document(grammar)` writes an HTML page with a heading and a diagram per rule
+//!
+//! Terminals are drawn as rounded boxes and rule references as square boxes. `alt` stacks
+//! its choices, `optional` and `many` add a bypass below, and `many` and `many1` a loop
+//! back. The SVGs carry their own stylesheet and a `railroad` class to override it with.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::analysis::*;
+//! use friss::railroad::*;
+//!
+//! let grammar = GrammarSpec::new()
+//!     .rule("list", seq([term("["), optional(seq([rule("item"), many(seq([term(","), rule("item")]))])), term("]")]))
+//!     .rule("item", alt([term("number"), rule("list")]));
+//!
+//! let svgs = diagrams(&grammar);
+//! assert_eq!(svgs.len(), 2);
+//! assert_eq!(svgs[1].0, "item");
+//! assert!(svgs[0].1.starts_with("<svg"));
+//! assert!(svgs[0].1.contains(">item</text>"));
+//! assert!(document(&grammar).contains("<h2 id=\"list\">list</h2>"));
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::analysis::{Expr, GrammarSpec};
+
+/// The radius of the curves
+const ARC: i64 = 10;
+/// The horizontal line between the parts of a sequence
+const GAP: i64 = 10;
+/// The vertical space between stacked lines
+const SPACE: i64 = 8;
+/// Half the height of a box
+const HALF_BOX: i64 = 11;
+/// The width of a character of box text
+const CHAR_WIDTH: i64 = 9;
+/// The space around the diagram
+const MARGIN: i64 = 10;
+
+const STYLE: &str = "path{fill:none;stroke:#333;stroke-width:2}\
+rect{fill:#f4f4ff;stroke:#333;stroke-width:2}\
+text{font:14px monospace;text-anchor:middle;dominant-baseline:central}";
+
+/// The extent of a drawn expression around the point it is entered at.
+#[derive(Debug, Clone, Copy)]
+struct Size {
+    width: i64,
+    /// Extent above the line
+    up: i64,
+    /// Extent below the line
+    down: i64,
+}
+
+/// How an expression is drawn. Alternatives and repetitions are choices stacked from top to
+/// bottom.
+enum Shape<'a> {
+    Box { text: String, rounded: bool },
+    Line,
+    Seq(&'a [Expr]),
+    Choice(Vec<Choice<'a>>),
+}
+
+enum Choice<'a> {
+    Expr(&'a Expr),
+    Loop(&'a Expr),
+    Skip,
+}
+
+fn shape(expr: &Expr) -> Shape<'_> {
+    match expr {
+        Expr::Empty => Shape::Line,
+        Expr::Terminal(name) => Shape::Box { text: format!("{:?}", name), rounded: true },
+        Expr::Rule(name) => Shape::Box { text: name.clone(), rounded: false },
+        Expr::Seq(parts) => Shape::Seq(parts),
+        Expr::Alt(choices) => Shape::Choice(choices.iter().map(Choice::Expr).collect()),
+        Expr::Many(inner) => Shape::Choice(alloc::vec![Choice::Loop(inner), Choice::Skip]),
+        Expr::Many1(inner) => Shape::Choice(alloc::vec![Choice::Loop(inner)]),
+        Expr::Optional(inner) => Shape::Choice(alloc::vec![Choice::Expr(inner), Choice::Skip]),
+    }
+}
+
+fn box_width(text: &str) -> i64 {
+    text.chars().count() as i64 * CHAR_WIDTH + 2 * GAP
+}
+
+fn size(expr: &Expr) -> Size {
+    match shape(expr) {
+        Shape::Line => Size { width: 0, up: 0, down: 0 },
+        Shape::Box { text, .. } => Size { width: box_width(&text), up: HALF_BOX, down: HALF_BOX },
+        Shape::Seq(parts) => {
+            let sizes: Vec<Size> = parts.iter().map(size).filter(|size| size.width > 0).collect();
+            Size {
+                width: sizes.iter().map(|size| size.width).sum::<i64>() + GAP * (sizes.len() as i64 - 1).max(0),
+                up: sizes.iter().map(|size| size.up).max().unwrap_or(0),
+                down: sizes.iter().map(|size| size.down).max().unwrap_or(0),
+            }
+        }
+        Shape::Choice(choices) => {
+            let sizes: Vec<Size> = choices.iter().map(choice_size).collect();
+            let offsets = offsets(&sizes);
+            Size {
+                width: sizes.iter().map(|size| size.width).max().unwrap_or(0) + 4 * ARC,
+                up: sizes.first().map_or(0, |size| size.up),
+                down: offsets.last().zip(sizes.last()).map_or(0, |(offset, size)| offset + size.down),
+            }
+        }
+    }
+}
+
+fn choice_size(choice: &Choice<'_>) -> Size {
+    match choice {
+        Choice::Expr(expr) => size(expr),
+        Choice::Loop(expr) => {
+            let inner = size(expr);
+            Size { down: loop_depth(inner), ..inner }
+        }
+        Choice::Skip => Size { width: 0, up: 0, down: 0 },
+    }
+}
+
+/// The depth below the line of the loop back around a repeated expression.
+fn loop_depth(inner: Size) -> i64 {
+    (inner.down + SPACE).max(2 * ARC)
+}
+
+/// The offsets below the first choice's line of each choice's line.
+fn offsets(sizes: &[Size]) -> Vec<i64> {
+    let mut offsets = Vec::with_capacity(sizes.len());
+    let mut previous: Option<(i64, Size)> = None;
+    for &size in sizes {
+        let offset = match previous {
+            None => 0,
+            Some((offset, above)) => (offset + above.down + SPACE + size.up).max(offset + 2 * ARC),
+        };
+        offsets.push(offset);
+        previous = Some((offset, size));
+    }
+    offsets
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Draws `expr` entered at `(x, y)` into `out`.
+fn draw(expr: &Expr, x: i64, y: i64, out: &mut String) {
+    match shape(expr) {
+        Shape::Line => {}
+        Shape::Box { text, rounded } => {
+            let width = box_width(&text);
+            let radius = if rounded { HALF_BOX } else { 0 };
+            out.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"/><text x=\"{}\" y=\"{}\">{}</text>",
+                x,
+                y - HALF_BOX,
+                width,
+                2 * HALF_BOX,
+                radius,
+                x + width / 2,
+                y,
+                escape(&text)
+            ));
+        }
+        Shape::Seq(parts) => {
+            let mut x = x;
+            for (i, part) in parts.iter().filter(|part| size(part).width > 0).enumerate() {
+                if i > 0 {
+                    out.push_str(&format!("<path d=\"M{} {}h{}\"/>", x, y, GAP));
+                    x += GAP;
+                }
+                draw(part, x, y, out);
+                x += size(part).width;
+            }
+        }
+        Shape::Choice(choices) => {
+            let sizes: Vec<Size> = choices.iter().map(choice_size).collect();
+            let inner = sizes.iter().map(|size| size.width).max().unwrap_or(0);
+            let offsets = offsets(&sizes);
+            for ((choice, size), offset) in choices.iter().zip(&sizes).zip(offsets) {
+                let (start, end) = (x + 2 * ARC, x + 2 * ARC + inner);
+                if offset == 0 {
+                    out.push_str(&format!("<path d=\"M{} {}h{}\"/>", x, y, 2 * ARC));
+                    out.push_str(&format!("<path d=\"M{} {}H{}h{}\"/>", start + size.width, y, end, 2 * ARC));
+                } else {
+                    let drop = offset - 2 * ARC;
+                    out.push_str(&format!(
+                        "<path d=\"M{} {}a{r} {r} 0 0 1 {r} {r}v{}a{r} {r} 0 0 0 {r} {r}\"/>",
+                        x,
+                        y,
+                        drop,
+                        r = ARC
+                    ));
+                    out.push_str(&format!(
+                        "<path d=\"M{} {}H{}a{r} {r} 0 0 0 {r} -{r}v-{}a{r} {r} 0 0 1 {r} -{r}\"/>",
+                        start + size.width,
+                        y + offset,
+                        end,
+                        drop,
+                        r = ARC
+                    ));
+                }
+                match choice {
+                    Choice::Expr(expr) => draw(expr, start, y + offset, out),
+                    Choice::Loop(expr) => {
+                        let width = size.width;
+                        let rise = size.down - 2 * ARC;
+                        draw(expr, start, y + offset, out);
+                        out.push_str(&format!(
+                            "<path d=\"M{} {}a{r} {r} 0 0 1 {r} {r}v{}a{r} {r} 0 0 1 -{r} {r}h-{}a{r} {r} 0 0 1 -{r} -{r}v-{}a{r} {r} 0 0 1 {r} -{r}\"/>",
+                            start + width,
+                            y + offset,
+                            rise,
+                            width,
+                            rise,
+                            r = ARC
+                        ));
+                    }
+                    Choice::Skip => {}
+                }
+            }
+        }
+    }
+}
+
+/// Returns an SVG railroad diagram of `expr`.
+pub fn diagram(expr: &Expr) -> String {
+    let size = size(expr);
+    let width = size.width + 2 * MARGIN + 2 * GAP;
+    let height = size.up + size.down + 2 * MARGIN;
+    let y = MARGIN + size.up;
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"railroad\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\"><style>{}</style>",
+        STYLE,
+        w = width,
+        h = height
+    );
+    // The line starts and ends at a short vertical bar
+    out.push_str(&format!("<path d=\"M{m} {}v{}m0 -{}h{}\"/>", y - HALF_BOX / 2, HALF_BOX, HALF_BOX / 2, GAP, m = MARGIN));
+    draw(expr, MARGIN + GAP, y, &mut out);
+    out.push_str(&format!(
+        "<path d=\"M{} {}h{}m0 -{}v{}\"/>",
+        MARGIN + GAP + size.width,
+        y,
+        GAP,
+        HALF_BOX / 2,
+        HALF_BOX
+    ));
+    out.push_str("</svg>");
+    out
+}
+
+/// Returns the name and SVG railroad diagram of every rule of `grammar`, in definition order.
+pub fn diagrams(grammar: &GrammarSpec) -> Vec<(String, String)> {
+    grammar.rules().map(|(name, expr)| (String::from(name), diagram(expr))).collect()
+}
+
+/// Returns an HTML page with a heading and a railroad diagram for every rule of `grammar`.
+/// Each heading's `id` is the rule's name, so rules can be linked to.
+pub fn document(grammar: &GrammarSpec) -> String {
+    let mut out = String::from("<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Grammar</title></head>\n<body>\n");
+    for (name, svg) in diagrams(grammar) {
+        let name = escape(&name);
+        out.push_str(&format!("<h2 id=\"{}\">{}</h2>\n{}\n", name, name, svg));
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{many, many1, optional, rule, seq, term};
+
+    #[test]
+    fn test_diagram_layout() {
+        // Boxes are 9 pixels per character plus 20; sequences add a 10 pixel gap
+        assert_eq!(size(&term("ab")).width, 4 * CHAR_WIDTH + 2 * GAP);
+        let pair = seq([rule("a"), Expr::Empty, rule("b")]);
+        assert_eq!(size(&pair).width, 2 * (CHAR_WIDTH + 2 * GAP) + GAP);
+
+        // A bypass sits below the optional part; a loop below the repeated part
+        // which must be deep enough for the curves
+        let optional = size(&optional(rule("a")));
+        assert_eq!((optional.width, optional.up, optional.down), (69, 11, 20));
+        assert_eq!(size(&many1(rule("a"))).down, 20);
+        assert_eq!(size(&many(rule("a"))).down, 28);
+
+        let svg = diagram(&seq([term("<&>"), rule("a")]));
+        assert!(svg.contains("<text x=\"52\" y=\"21\">&quot;&lt;&amp;&gt;&quot;</text>"));
+        assert_eq!(svg.matches("<rect").count(), 2);
+        assert!(svg.ends_with("</svg>"));
+    }
+}
@@ -0,0 +1,494 @@
+//! # Parsing From Readers
+//!
+//! This module runs parsers over any `std::io::Read` source without reading it into memory
+//! first. A `ReaderInput` is a position in a buffer shared by all its clones; matchers read
+//! more from the reader whenever they need bytes that are not buffered yet, so a parse can
+//! span any number of partial reads. Items are single bytes. A reader failing with
+//! `ErrorKind::WouldBlock` is treated as having no more data for now, without ending the
+//! stream: `parse_from_reader` then reports `ReadError::WouldBlock` and parses the record
+//! again from its start on the next call.
+//!
+//! `parse_from_reader` parses consecutive records, such as the lines of a log or the
+//! messages of a protocol, and drops each record's bytes from the buffer once it is parsed.
+//! Memory then stays bounded by the largest record, provided the outputs do not keep
+//! `ReaderInput` spans alive.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::reader::*;
+//!
+//! let source = "GET a\nGET bc\n".as_bytes();
+//! let get = ReaderInput::from_bytes(b"GET ").make_literal_matcher("Expected GET");
+//! let byte = <ReaderInput<&[u8]> as Parsable<&str>>::make_anything_matcher("Expected path");
+//! let newline = b'\n'.make_character_matcher("Expected newline");
+//! let path = byte.validate(|b: &u8| *b != b'\n', "Expected path").many();
+//! let request = path.preceded_by(get).map_err(Foldable::fold).skip(newline);
+//!
+//! let paths: Vec<_> = parse_from_reader(request, source).collect::<Result<_, _>>().unwrap();
+//! assert_eq!(paths, vec![b"a".to_vec(), b"bc".to_vec()]);
+//! ```
+
+use std::cell::RefCell;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io::{self, ErrorKind, Read};
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::core::{Checkpoint, Parsable, Parser};
+
+/// Number of bytes requested from the reader at a time.
+pub const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+struct Buffer<R> {
+    /// `None` once the reader is exhausted or failed
+    reader: Option<R>,
+    data: Vec<u8>,
+    /// Offset in the stream of `data[0]`
+    base: usize,
+    error: Option<io::Error>,
+    /// Set when a read failed with `ErrorKind::WouldBlock`
+    would_block: bool,
+}
+
+impl<R: Read> Buffer<R> {
+    /// Reads until the stream offset `end` is buffered or the reader is exhausted.
+    fn fill_to(&mut self, end: usize) {
+        while self.base + self.data.len() < end {
+            let Some(reader) = self.reader.as_mut() else {
+                return;
+            };
+            let len = self.data.len();
+            self.data.resize(len + READ_CHUNK_SIZE, 0);
+            #[allow(clippy::indexing_slicing)] // The buffer was just resized past `len`
+            let result = reader.read(&mut self.data[len..]);
+            match result {
+                Ok(0) => {
+                    self.data.truncate(len);
+                    self.reader = None;
+                }
+                Ok(n) => self.data.truncate(len + n),
+                Err(e) if e.kind() == ErrorKind::Interrupted => self.data.truncate(len),
+                // A non-blocking reader has nothing yet, but may have more later
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    self.data.truncate(len);
+                    self.would_block = true;
+                    return;
+                }
+                Err(e) => {
+                    self.data.truncate(len);
+                    self.reader = None;
+                    self.error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Returns the buffered bytes between the stream offsets `start` and `end`.
+    fn slice(&self, start: usize, end: usize) -> &[u8] {
+        assert!(start >= self.base, "ReaderInput used after its bytes were dropped");
+        let end = (end - self.base).min(self.data.len());
+        self.data.get((start - self.base).min(end)..end).unwrap_or_default()
+    }
+}
+
+/// A position in a stream read from `R`, optionally bounded to a span.
+pub struct ReaderInput<R> {
+    buffer: Rc<RefCell<Buffer<R>>>,
+    pos: usize,
+    end: Option<usize>,
+}
+
+impl<R> Clone for ReaderInput<R> {
+    fn clone(&self) -> Self {
+        ReaderInput {
+            buffer: self.buffer.clone(),
+            pos: self.pos,
+            end: self.end,
+        }
+    }
+}
+
+/// Inputs are equal if they are the same position in the same stream.
+impl<R> PartialEq for ReaderInput<R> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.buffer, &other.buffer) && self.pos == other.pos && self.end == other.end
+    }
+}
+
+impl<R> Debug for ReaderInput<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReaderInput").field("pos", &self.pos).field("end", &self.end).finish()
+    }
+}
+
+impl<R: Read> ReaderInput<R> {
+    /// Starts reading at the beginning of `reader`.
+    pub fn new(reader: R) -> Self {
+        ReaderInput {
+            buffer: Rc::new(RefCell::new(Buffer {
+                reader: Some(reader),
+                data: Vec::new(),
+                base: 0,
+                error: None,
+                would_block: false,
+            })),
+            pos: 0,
+            end: None,
+        }
+    }
+
+    /// Creates an input over `bytes` without a reader, for example the pattern of a literal
+    /// matcher.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        ReaderInput {
+            buffer: Rc::new(RefCell::new(Buffer {
+                reader: None,
+                data: bytes.to_vec(),
+                base: 0,
+                error: None,
+                would_block: false,
+            })),
+            pos: 0,
+            end: None,
+        }
+    }
+
+    /// Starts at the stream `offset` with `bytes` already buffered, reading what follows
+    /// them from `reader`.
+    #[cfg(feature = "async")]
+    pub(crate) fn with_buffered(reader: R, offset: usize, bytes: Vec<u8>) -> Self {
+        ReaderInput {
+            buffer: Rc::new(RefCell::new(Buffer {
+                reader: Some(reader),
+                data: bytes,
+                base: offset,
+                error: None,
+                would_block: false,
+            })),
+            pos: offset,
+            end: None,
+        }
+    }
+
+    /// Returns the offset of this position in the stream.
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns true if no bytes are left, reading more if necessary.
+    pub fn is_empty(&self) -> bool {
+        self.byte_at(self.pos).is_none()
+    }
+
+    /// Returns the remaining bytes of the span, reading the stream to its end for an
+    /// unbounded input.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let end = self.end.unwrap_or(usize::MAX);
+        let mut buffer = self.buffer.borrow_mut();
+        buffer.fill_to(end);
+        buffer.slice(self.pos, end).to_vec()
+    }
+
+    /// Takes the error that ended reading, if the reader failed.
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.buffer.borrow_mut().error.take()
+    }
+
+    /// Returns whether a read failed with `ErrorKind::WouldBlock` since the last call.
+    pub(crate) fn take_would_block(&self) -> bool {
+        std::mem::take(&mut self.buffer.borrow_mut().would_block)
+    }
+
+    fn byte_at(&self, offset: usize) -> Option<u8> {
+        if self.end.is_some_and(|end| offset >= end) {
+            return None;
+        }
+        let mut buffer = self.buffer.borrow_mut();
+        buffer.fill_to(offset + 1);
+        buffer.slice(offset, offset + 1).first().copied()
+    }
+
+    fn at(&self, pos: usize) -> Self {
+        ReaderInput {
+            buffer: self.buffer.clone(),
+            pos,
+            end: self.end,
+        }
+    }
+
+    fn span(&self, start: usize, end: usize) -> Self {
+        ReaderInput {
+            buffer: self.buffer.clone(),
+            pos: start,
+            end: Some(end),
+        }
+    }
+
+    /// Drops the buffered bytes before this position if no other handle can reach them.
+    pub(crate) fn compact(&self) {
+        if Rc::strong_count(&self.buffer) == 1 {
+            let mut buffer = self.buffer.borrow_mut();
+            let consumed = (self.pos - buffer.base).min(buffer.data.len());
+            buffer.data.drain(..consumed);
+            buffer.base += consumed;
+        }
+    }
+}
+
+/// A checkpoint shares the buffer, so rewinding never reads again.
+impl<R> Checkpoint for ReaderInput<R> {
+    type Checkpoint = Self;
+
+    fn checkpoint(&self) -> Self {
+        self.clone()
+    }
+
+    fn rewind(checkpoint: Self) -> Self {
+        checkpoint
+    }
+}
+
+impl<R: Read, Error: Clone> Parsable<Error> for ReaderInput<R> {
+    type Item = u8;
+
+    fn make_literal_matcher(self, err: Error) -> impl Parser<Self, Self, Error> {
+        let literal = self.to_vec();
+        move |input: Self| {
+            let end = input.pos + literal.len();
+            if input.end.is_some_and(|limit| end > limit) {
+                return Err((input, err.clone()));
+            }
+            let matched = {
+                let mut buffer = input.buffer.borrow_mut();
+                buffer.fill_to(end);
+                buffer.slice(input.pos, end) == literal.as_slice()
+            };
+            if matched {
+                Ok((input.at(end), input.span(input.pos, end)))
+            } else {
+                Err((input, err.clone()))
+            }
+        }
+    }
+
+    fn make_anything_matcher(err: Error) -> impl Parser<Self, Self::Item, Error> {
+        move |input: Self| match input.byte_at(input.pos) {
+            Some(byte) => Ok((input.at(input.pos + 1), byte)),
+            None => Err((input, err.clone())),
+        }
+    }
+
+    fn make_item_matcher(character: Self::Item, err: Error) -> impl Parser<Self, Self::Item, Error> {
+        move |input: Self| match input.byte_at(input.pos) {
+            Some(byte) if byte == character => Ok((input.at(input.pos + 1), byte)),
+            _ => Err((input, err.clone())),
+        }
+    }
+
+    fn make_empty_matcher(err: Error) -> impl Parser<Self, (), Error> {
+        move |input: Self| {
+            if input.is_empty() {
+                Ok((input, ()))
+            } else {
+                Err((input, err.clone()))
+            }
+        }
+    }
+}
+
+/// Errors of `parse_from_reader`.
+#[derive(Debug)]
+pub enum ReadError<E> {
+    /// The reader failed
+    Io(io::Error),
+    /// A record failed to parse at the stream `offset`
+    Parse { offset: usize, error: E },
+    /// The reader has no data for now; no input was consumed and the record is parsed
+    /// again by the next call
+    WouldBlock,
+}
+
+impl<E: Display> Display for ReadError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "read error: {}", e),
+            ReadError::Parse { offset, error } => write!(f, "{}: {}", offset, error),
+            ReadError::WouldBlock => write!(f, "reader would block"),
+        }
+    }
+}
+
+impl<E: Debug + Display> std::error::Error for ReadError<E> {}
+
+/// Iterator over the records parsed from a reader, created by `parse_from_reader`.
+pub struct ReaderRecords<R, P, O, E> {
+    parser: P,
+    input: Option<ReaderInput<R>>,
+    _marker: PhantomData<(O, E)>,
+}
+
+/// Parses consecutive records with `parser` until `reader` is exhausted.
+///
+/// The iterator ends after the first error. A failing reader is reported as
+/// `ReadError::Io` in place of the record it interrupted. A record that consumes no input
+/// ends the iteration as well, since parsing would never advance.
+///
+/// The exception is `ReadError::WouldBlock`, yielded when a non-blocking reader has no data
+/// while a record is parsed. Whatever the parser made of the bytes so far is discarded, and
+/// calling `next` again once the reader is ready parses the record from its start.
+pub fn parse_from_reader<R, P, O, E>(parser: P, reader: R) -> ReaderRecords<R, P, O, E>
+where
+    R: Read,
+    P: Parser<ReaderInput<R>, O, E>,
+    E: Clone,
+{
+    ReaderRecords {
+        parser,
+        input: Some(ReaderInput::new(reader)),
+        _marker: PhantomData,
+    }
+}
+
+impl<R, P, O, E> Iterator for ReaderRecords<R, P, O, E>
+where
+    R: Read,
+    P: Parser<ReaderInput<R>, O, E>,
+    E: Clone,
+{
+    type Item = Result<O, ReadError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = self.input.take()?;
+        input.compact();
+        input.take_would_block();
+        let start = input.clone();
+        let result = if input.is_empty() { None } else { Some(self.parser.parse(input)) };
+        // The parser may have seen the end of the buffered bytes instead of more data
+        if start.take_would_block() {
+            drop(result);
+            self.input = Some(start);
+            return Some(Err(ReadError::WouldBlock));
+        }
+        let Some(result) = result else {
+            return start.take_error().map(|e| Err(ReadError::Io(e)));
+        };
+        let start = start.offset();
+        match result {
+            Ok((rest, output)) => {
+                // The record may have been cut short by the failure
+                if let Some(e) = rest.take_error() {
+                    return Some(Err(ReadError::Io(e)));
+                }
+                if rest.offset() > start {
+                    self.input = Some(rest);
+                }
+                Some(Ok(output))
+            }
+            Err((rest, error)) => Some(Err(match rest.take_error() {
+                Some(e) => ReadError::Io(e),
+                None => ReadError::Parse {
+                    offset: rest.offset(),
+                    error,
+                },
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    /// Hands out one byte per read, then fails if `fail` is set.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        fail: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.data.split_first() {
+                Some((&byte, rest)) => {
+                    buf[0] = byte;
+                    self.data = rest;
+                    Ok(1)
+                }
+                None if self.fail => Err(io::Error::other("connection reset")),
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_records_across_partial_reads() {
+        let record = || {
+            let open = ReaderInput::from_bytes(b"<<").make_literal_matcher("Expected <<");
+            let digit = <ReaderInput<Trickle> as Parsable<&str>>::make_anything_matcher("Expected digit")
+                .validate(|b: &u8| b.is_ascii_digit(), "Expected digit");
+            digit.many().preceded_by(open).map_err(Foldable::fold)
+        };
+
+        let trickle = Trickle { data: b"<<12<<3<<x", fail: false };
+        let results: Vec<_> = parse_from_reader(record(), trickle).collect();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap(), b"12");
+        assert_eq!(results[1].as_ref().unwrap(), b"3");
+        assert!(results[2].as_ref().unwrap().is_empty());
+        assert!(matches!(results[3], Err(ReadError::Parse { offset: 9, error: "Expected <<" })));
+
+        let trickle = Trickle { data: b"<<1<<", fail: true };
+        let results: Vec<_> = parse_from_reader(record(), trickle).collect();
+        assert_eq!(results.len(), 2);
+        assert!(matches!(&results[1], Err(ReadError::Io(e)) if e.to_string() == "connection reset"));
+    }
+
+    /// Hands out its chunks in turn, failing with `WouldBlock` for each empty one.
+    struct Stutter(Vec<&'static [u8]>);
+
+    impl Read for Stutter {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Ok(0);
+            }
+            match self.0.remove(0) {
+                [] => Err(ErrorKind::WouldBlock.into()),
+                chunk => {
+                    buf[..chunk.len()].copy_from_slice(chunk);
+                    Ok(chunk.len())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_would_block_is_retryable() {
+        let digit = <ReaderInput<Stutter> as Parsable<&str>>::make_anything_matcher("Expected digit")
+            .validate(|b: &u8| b.is_ascii_digit(), "Expected digit");
+        let record = digit.many().skip(b';'.make_character_matcher("Expected ;"));
+
+        let source = Stutter(vec![b"", b"12", b"", b"3;4", b"", b";"]);
+        let mut records = parse_from_reader(record, source);
+        assert!(matches!(records.next(), Some(Err(ReadError::WouldBlock))));
+        // A record cut short by the reader is neither a parse error nor lost
+        assert!(matches!(records.next(), Some(Err(ReadError::WouldBlock))));
+        assert_eq!(records.next().unwrap().unwrap(), b"123");
+        assert!(matches!(records.next(), Some(Err(ReadError::WouldBlock))));
+        assert_eq!(records.next().unwrap().unwrap(), b"4");
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_literal_spans_keep_their_bytes() {
+        let input = ReaderInput::new(&b"keyword rest"[..]);
+        let keyword = ReaderInput::from_bytes(b"keyword").make_literal_matcher("Expected keyword");
+        let (rest, matched) = keyword.parse(input).unwrap();
+        rest.compact();
+        assert_eq!(matched.to_vec(), b"keyword");
+        assert_eq!(rest.to_vec(), b" rest");
+        assert_eq!(rest.offset(), 7);
+        assert!(keyword.parse(rest).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! # Error Recovery
+//!
+//! This module lets a parse continue after an error, as IDE-style tools need to. A failing
+//! parser wrapped with `recover_to` records its error in a shared `Errors` accumulator,
+//! skips ahead to a synchronization point such as `;` or `}` and produces a placeholder
+//! instead, so a single parse yields both a partial result and every error it hit.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::recovery::*;
+//!
+//! let errors = Errors::new();
+//! let statement = "ok".make_literal_matcher("Expected ok")
+//!     .recover_to(";".make_literal_matcher(()), "<error>", &errors)
+//!     .skip(";".make_literal_matcher("Expected ;"))
+//!     .many();
+//!
+//! assert_eq!(statement.parse("ok;bad;ok;"), Ok(("", vec!["ok", "<error>", "ok"])));
+//! assert_eq!(errors.take(), vec![("bad;ok;", "Expected ok")]);
+//! ```
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::core::{Parsable, Parser, ParserOutput};
+
+/// A shared list of errors recorded by recovering parsers.
+///
+/// Clones share the same list, so one accumulator can be handed to many parsers.
+#[derive(Debug)]
+pub struct Errors<I, E> {
+    errors: Rc<RefCell<Vec<(I, E)>>>,
+}
+
+impl<I, E> Clone for Errors<I, E> {
+    fn clone(&self) -> Self {
+        Errors {
+            errors: self.errors.clone(),
+        }
+    }
+}
+
+impl<I, E> Default for Errors<I, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, E> Errors<I, E> {
+    /// Creates an empty accumulator.
+    pub fn new() -> Self {
+        Errors {
+            errors: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Records an error with the input at which it occurred.
+    pub fn push(&self, input: I, error: E) {
+        self.errors.borrow_mut().push((input, error));
+    }
+
+    /// Returns the number of recorded errors.
+    pub fn len(&self) -> usize {
+        self.errors.borrow().len()
+    }
+
+    /// Returns true if no errors were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.errors.borrow().is_empty()
+    }
+
+    /// Removes and returns all recorded errors in the order they occurred.
+    pub fn take(&self) -> Vec<(I, E)> {
+        core::mem::take(&mut *self.errors.borrow_mut())
+    }
+}
+
+impl<I: Clone, E: Clone> Errors<I, E> {
+    /// Returns a copy of the recorded errors.
+    pub fn to_vec(&self) -> Vec<(I, E)> {
+        self.errors.borrow().clone()
+    }
+}
+
+/// Extension trait adding error recovery to parsers.
+pub trait RecoveryParser<Input, Output, Error>: Parser<Input, Output, Error>
+where
+    Input: Parsable<Error>,
+    Output: ParserOutput,
+    Error: Clone,
+{
+    /// On failure, records the error in `errors`, skips input up to the next position where
+    /// `sync` matches and returns `placeholder`. The synchronization token itself is not
+    /// consumed.
+    ///
+    /// If `sync` matches nowhere in the rest of the input, the original error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::recovery::*;
+    ///
+    /// let errors = Errors::new();
+    /// let body = "x".make_literal_matcher("Expected x")
+    ///     .recover_to("}".make_literal_matcher(()), "", &errors);
+    ///
+    /// assert_eq!(body.parse("x}"), Ok(("}", "x")));
+    /// assert_eq!(body.parse("y y}"), Ok(("}", "")));
+    /// assert_eq!(body.parse("y"), Err(("y", "Expected x")));
+    /// assert_eq!(errors.len(), 1);
+    /// ```
+    fn recover_to<SyncOutput, SyncError>(
+        self,
+        sync: impl Parser<Input, SyncOutput, SyncError>,
+        placeholder: Output,
+        errors: &Errors<Input, Error>,
+    ) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+        Input: Parsable<SyncError>,
+        Output: Clone,
+        SyncError: Clone,
+    {
+        let errors = errors.clone();
+        move |input: Input| {
+            let (failed_at, err) = match self.parse(input) {
+                Ok(ok) => return Ok(ok),
+                Err(failure) => failure,
+            };
+
+            let anything = Input::make_anything_matcher(err.clone());
+            let mut rest = failed_at.clone();
+            while sync.parse(rest.clone()).is_err() {
+                match anything.parse(rest) {
+                    Ok((next, _)) => rest = next,
+                    Err(_) => return Err((failed_at, err)),
+                }
+            }
+
+            errors.push(failed_at, err);
+            Ok((rest, placeholder.clone()))
+        }
+    }
+}
+
+impl<Input, Output, Error, P> RecoveryParser<Input, Output, Error> for P
+where
+    Input: Parsable<Error>,
+    Output: ParserOutput,
+    Error: Clone,
+    P: Parser<Input, Output, Error>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_recover_to_collects_all_errors() {
+        let errors = Errors::new();
+        let statements = '1'
+            .make_character_matcher("Expected 1")
+            .map(|_| Some(1))
+            .recover_to(';'.make_character_matcher(()), None, &errors)
+            .skip(';'.make_character_matcher("Expected ;"))
+            .many();
+
+        assert_eq!(statements.parse("1;x;1;zz;"), Ok(("", vec![Some(1), None, Some(1), None])));
+        let recorded = errors.to_vec();
+        assert_eq!(recorded, vec![("x;1;zz;", "Expected 1"), ("zz;", "Expected 1")]);
+
+        assert_eq!(errors.take().len(), 2);
+        assert!(errors.is_empty());
+    }
+}
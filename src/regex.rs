@@ -0,0 +1,116 @@
+//! # Regex Parsers
+//!
+//! This module builds parsers from regular expressions, for tokens that are simply easiest
+//! to express as a regex. It is available with the `regex` feature.
+//!
+//! Patterns are anchored at the current position: a regex parser only matches a prefix of
+//! the input, it never skips ahead.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::regex::*;
+//!
+//! let number = regex_matcher(r"[0-9]+(\.[0-9]+)?", "Expected number");
+//!
+//! assert_eq!(number.parse("3.14 rest"), Ok((" rest", "3.14")));
+//! assert_eq!(number.parse("x3"), Err(("x3", "Expected number")));
+//!
+//! let pair = regex_captures(r"(\w+)=(\w+)?", "Expected pair");
+//! assert_eq!(pair.parse("key=;"), Ok((";", vec![Some("key="), Some("key"), None])));
+//! ```
+
+use ::regex::Regex;
+
+use crate::core::Parser;
+
+#[allow(clippy::panic)] // Depends on the pattern, not on the input
+fn anchored(pattern: &str) -> Regex {
+    Regex::new(&format!(r"\A(?:{})", pattern))
+        .unwrap_or_else(|e| panic!("Invalid regex pattern `{}`: {}", pattern, e))
+}
+
+/// Creates a parser returning the text matched by `pattern` at the current position.
+///
+/// Panics if `pattern` is not a valid regex.
+pub fn regex_matcher<'a, Error: Clone>(pattern: &str, err: Error) -> impl Parser<&'a str, &'a str, Error> {
+    let regex = anchored(pattern);
+    move |input: &'a str| match regex.find(input) {
+        Some(m) => Ok((input.get(m.end()..).unwrap_or_default(), m.as_str())),
+        None => Err((input, err.clone())),
+    }
+}
+
+/// Creates a parser returning all capture groups of `pattern` at the current position.
+///
+/// The first element is the whole match; groups that did not participate are `None`.
+/// Panics if `pattern` is not a valid regex.
+pub fn regex_captures<'a, Error: Clone>(
+    pattern: &str,
+    err: Error,
+) -> impl Parser<&'a str, Vec<Option<&'a str>>, Error> {
+    let regex = anchored(pattern);
+    move |input: &'a str| match regex.captures(input) {
+        Some(caps) => {
+            let end = caps.get(0).map_or(0, |m| m.end());
+            let groups = caps.iter().map(|group| group.map(|m| m.as_str())).collect();
+            Ok((input.get(end..).unwrap_or_default(), groups))
+        }
+        None => Err((input, err.clone())),
+    }
+}
+
+/// Creates a parser returning the named capture groups of `pattern` as `(name, text)` pairs,
+/// in the order the groups appear in the pattern.
+///
+/// Panics if `pattern` is not a valid regex.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::regex::*;
+///
+/// let version = regex_named_captures(r"v(?P<major>\d+)\.(?P<minor>\d+)", "Expected version");
+///
+/// let (_, groups) = version.parse("v1.22").unwrap();
+/// assert_eq!(groups[0], ("major".to_string(), Some("1")));
+/// assert_eq!(groups[1], ("minor".to_string(), Some("22")));
+/// ```
+pub fn regex_named_captures<'a, Error: Clone>(
+    pattern: &str,
+    err: Error,
+) -> impl Parser<&'a str, Vec<(String, Option<&'a str>)>, Error> {
+    let regex = anchored(pattern);
+    let names: Vec<Option<String>> = regex.capture_names().map(|n| n.map(String::from)).collect();
+    move |input: &'a str| match regex.captures(input) {
+        Some(caps) => {
+            let end = caps.get(0).map_or(0, |m| m.end());
+            let groups = names
+                .iter()
+                .enumerate()
+                .filter_map(|(i, name)| Some((name.clone()?, caps.get(i).map(|m| m.as_str()))))
+                .collect();
+            Ok((input.get(end..).unwrap_or_default(), groups))
+        }
+        None => Err((input, err.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_is_anchored() {
+        // Every alternative must match at the current position
+        let keyword = regex_matcher("let|fn", "Expected keyword");
+        assert_eq!(keyword.parse("fn main"), Ok((" main", "fn")));
+        assert_eq!(keyword.parse(" let"), Err((" let", "Expected keyword")));
+
+        let ident = regex_matcher(r"[a-z]+", "Expected identifier")
+            .sep_by(regex_matcher(r"\s*,\s*", "Expected comma"));
+        assert_eq!(ident.parse("a , bc,d!"), Ok(("!", vec!["a", "bc", "d"])));
+    }
+}
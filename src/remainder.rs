@@ -0,0 +1,239 @@
+//! # Remainder Classification
+//!
+//! This module classifies the input left over after a successful prefix parse, so callers
+//! can tell "ok with trailing whitespace" from "hard error" without writing their own
+//! trailing-input logic. A `RestClassifier` is configured with parsers recognizing
+//! whitespace, comments and the start of the next record; everything else is garbage.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::remainder::*;
+//!
+//! let classifier = RestClassifier::new()
+//!     .whitespace(' '.make_character_matcher(()))
+//!     .comment("#".make_literal_matcher(()))
+//!     .record_boundary("---".make_literal_matcher(()));
+//!
+//! let document = "key".make_literal_matcher("Expected key").classify_rest(&classifier);
+//!
+//! assert_eq!(document.parse("key"), Ok(("", ("key", RestKind::Empty))));
+//! assert_eq!(document.parse("key  "), Ok(("  ", ("key", RestKind::Whitespace))));
+//! assert_eq!(document.parse("key #"), Ok((" #", ("key", RestKind::Comment))));
+//! assert_eq!(document.parse("key ---"), Ok((" ---", ("key", RestKind::RecordBoundary("---")))));
+//! assert_eq!(document.parse("key x"), Ok((" x", ("key", RestKind::Garbage("x")))));
+//! ```
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+
+use crate::core::{Parsable, Parser, ParserOutput};
+
+/// The kind of input left over after a parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RestKind<I> {
+    /// Nothing is left
+    Empty,
+    /// Only whitespace is left
+    Whitespace,
+    /// Only whitespace and at least one comment are left
+    Comment,
+    /// The next record starts after optional trivia, at the given input
+    RecordBoundary(I),
+    /// Unrecognized input starts after optional trivia, at the given input
+    Garbage(I),
+}
+
+impl<I> RestKind<I> {
+    /// Returns true if the remainder holds nothing but trivia.
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, RestKind::Empty | RestKind::Whitespace | RestKind::Comment)
+    }
+}
+
+type Recognizer<I> = Rc<dyn Fn(I) -> Option<I>>;
+
+fn recognizer<I, O, E>(parser: impl Parser<I, O, E> + 'static) -> Recognizer<I>
+where
+    I: Parsable<E>,
+    E: Clone,
+{
+    Rc::new(move |input| parser.parse(input).ok().map(|(rest, _)| rest))
+}
+
+/// Configurable classifier for leftover input.
+pub struct RestClassifier<I> {
+    whitespace: Vec<Recognizer<I>>,
+    comments: Vec<Recognizer<I>>,
+    boundaries: Vec<Recognizer<I>>,
+}
+
+impl<I> Clone for RestClassifier<I> {
+    fn clone(&self) -> Self {
+        RestClassifier {
+            whitespace: self.whitespace.clone(),
+            comments: self.comments.clone(),
+            boundaries: self.boundaries.clone(),
+        }
+    }
+}
+
+impl<I> Default for RestClassifier<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I> RestClassifier<I> {
+    /// Creates a classifier that recognizes only empty remainders.
+    pub fn new() -> Self {
+        RestClassifier {
+            whitespace: Vec::new(),
+            comments: Vec::new(),
+            boundaries: Vec::new(),
+        }
+    }
+
+    /// Adds a parser recognizing whitespace.
+    pub fn whitespace<O, E: Clone>(mut self, parser: impl Parser<I, O, E> + 'static) -> Self
+    where
+        I: Parsable<E>,
+    {
+        self.whitespace.push(recognizer(parser));
+        self
+    }
+
+    /// Adds a parser recognizing a comment.
+    pub fn comment<O, E: Clone>(mut self, parser: impl Parser<I, O, E> + 'static) -> Self
+    where
+        I: Parsable<E>,
+    {
+        self.comments.push(recognizer(parser));
+        self
+    }
+
+    /// Adds a parser recognizing the start of the next record.
+    pub fn record_boundary<O, E: Clone>(mut self, parser: impl Parser<I, O, E> + 'static) -> Self
+    where
+        I: Parsable<E>,
+    {
+        self.boundaries.push(recognizer(parser));
+        self
+    }
+}
+
+impl<I: Parsable<()> + PartialEq> RestClassifier<I> {
+    /// Classifies the leftover input `rest`.
+    ///
+    /// Whitespace and comments are skipped first; the remainder is trivia if they reach
+    /// the end, a record boundary if a boundary parser matches next, and garbage otherwise.
+    pub fn classify(&self, rest: I) -> RestKind<I> {
+        let empty = I::make_empty_matcher(());
+        let is_empty = |input: &I| empty.parse(input.clone()).is_ok();
+        if is_empty(&rest) {
+            return RestKind::Empty;
+        }
+
+        let mut current = rest;
+        let mut saw_comment = false;
+        'trivia: loop {
+            for (recognizers, is_comment) in [(&self.whitespace, false), (&self.comments, true)] {
+                for recognize in recognizers {
+                    if let Some(next) = recognize(current.clone()) {
+                        if next != current {
+                            saw_comment |= is_comment;
+                            current = next;
+                            continue 'trivia;
+                        }
+                    }
+                }
+            }
+            break;
+        }
+
+        if is_empty(&current) {
+            if saw_comment {
+                RestKind::Comment
+            } else {
+                RestKind::Whitespace
+            }
+        } else if self.boundaries.iter().any(|recognize| recognize(current.clone()).is_some()) {
+            RestKind::RecordBoundary(current)
+        } else {
+            RestKind::Garbage(current)
+        }
+    }
+}
+
+/// Extension trait adding remainder classification to parsers.
+pub trait ClassifyRestParser<Input, Output, Error>: Parser<Input, Output, Error>
+where
+    Input: Parsable<Error> + Parsable<()> + PartialEq,
+    Output: ParserOutput,
+    Error: Clone,
+{
+    /// Runs the parser and pairs its output with the classification of the leftover input.
+    /// The leftover input is not consumed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::remainder::*;
+    ///
+    /// let classifier = RestClassifier::new().whitespace('\n'.make_character_matcher(()));
+    /// let line = "row".make_literal_matcher("Expected row").classify_rest(&classifier);
+    ///
+    /// let (_, (_, kind)) = line.parse("row\n\n").unwrap();
+    /// assert!(kind.is_trivia());
+    /// ```
+    fn classify_rest(self, classifier: &RestClassifier<Input>) -> impl Parser<Input, (Output, RestKind<Input>), Error>
+    where
+        Self: Sized,
+    {
+        let classifier = classifier.clone();
+        move |input: Input| {
+            let (rest, output) = self.parse(input)?;
+            let kind = classifier.classify(rest.clone());
+            Ok((rest, (output, kind)))
+        }
+    }
+}
+
+impl<Input, Output, Error, P> ClassifyRestParser<Input, Output, Error> for P
+where
+    Input: Parsable<Error> + Parsable<()> + PartialEq,
+    Output: ParserOutput,
+    Error: Clone,
+    P: Parser<Input, Output, Error>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_classify_mixed_trivia() {
+        let line_comment = "//"
+            .make_literal_matcher(())
+            .seq('x'.make_character_matcher(()).many());
+        let classifier = RestClassifier::new()
+            .whitespace(' '.make_character_matcher(()).many().non_empty(()))
+            .comment(line_comment)
+            .record_boundary(';'.make_character_matcher(()));
+
+        assert_eq!(classifier.classify("  //xx  //x "), RestKind::Comment);
+        assert_eq!(classifier.classify("   "), RestKind::Whitespace);
+        assert_eq!(classifier.classify(" //x ;next"), RestKind::RecordBoundary(";next"));
+        assert_eq!(classifier.classify(" //x y"), RestKind::Garbage("y"));
+        assert!(!classifier.classify("y").is_trivia());
+
+        // Without recognizers everything non-empty is garbage
+        let strict = RestClassifier::<&str>::new();
+        assert_eq!(strict.classify(" "), RestKind::Garbage(" "));
+        assert_eq!(strict.classify(""), RestKind::Empty);
+    }
+}
@@ -0,0 +1,261 @@
+//! # Read-Parse-Print Loop
+//!
+//! This module drives an interactive loop for any grammar implementing `ReplGrammar`. Each
+//! entry is parsed and its output printed; errors are reported with the offending line and
+//! a caret under the failure. Input that stops in the middle of a construct is continued
+//! on the next line, entries are kept in a history, and the grammar can offer completion
+//! hints. Run `cargo run --example repl` for a calculator built on top of it.
+//!
+//! Lines starting with `:` are commands:
+//!
+//! - `:history` lists the previous entries
+//! - `:complete <prefix>` lists the completions of `prefix`
+//! - `:quit` ends the loop
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::grammar::*;
+//! use friss::repl::*;
+//!
+//! struct Words;
+//!
+//! impl ReplGrammar for Words {
+//!     type Output = Vec<String>;
+//!
+//!     fn parse(&self, source: &str) -> Result<Self::Output, GrammarError> {
+//!         GrammarBuilder::new()
+//!             .build(|g| g.identifier().map(String::from).many())
+//!             .parse_source(source)
+//!     }
+//! }
+//!
+//! let mut repl = Repl::new(Words);
+//! let mut output = Vec::new();
+//! repl.run("hello world\n".as_bytes(), &mut output).unwrap();
+//!
+//! assert!(String::from_utf8(output).unwrap().contains(r#"["hello", "world"]"#));
+//! assert_eq!(repl.history(), ["hello world"]);
+//! ```
+
+use std::fmt::Debug;
+use std::io::{self, BufRead, Write};
+
+use crate::grammar::GrammarError;
+
+/// A grammar that can be driven by a `Repl`.
+pub trait ReplGrammar {
+    /// The value printed for a successfully parsed entry
+    type Output: Debug;
+
+    /// Parses one complete entry.
+    fn parse(&self, source: &str) -> Result<Self::Output, GrammarError>;
+
+    /// Returns true if `error` means the entry is unfinished rather than wrong, so the
+    /// next line should be appended to it. By default this is any error at the end of the
+    /// input.
+    fn is_incomplete(&self, source: &str, error: &GrammarError) -> bool {
+        error.offset >= source.trim_end().len()
+    }
+
+    /// Returns the completions of the word `prefix`.
+    fn completions(&self, _prefix: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The prompt shown before a new entry.
+    fn prompt(&self) -> &str {
+        "> "
+    }
+
+    /// The prompt shown before a continuation line.
+    fn continuation_prompt(&self) -> &str {
+        ". "
+    }
+}
+
+/// An interactive loop over a `ReplGrammar`.
+pub struct Repl<G> {
+    grammar: G,
+    history: Vec<String>,
+}
+
+impl<G: ReplGrammar> Repl<G> {
+    /// Creates a loop with an empty history.
+    pub fn new(grammar: G) -> Self {
+        Repl {
+            grammar,
+            history: Vec::new(),
+        }
+    }
+
+    /// Returns the entries evaluated so far, oldest first.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Reads entries from `input` and writes prompts and results to `output` until the
+    /// input ends or `:quit` is entered.
+    ///
+    /// An empty line ends an unfinished entry, reporting its error.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        let mut entry = String::new();
+        let mut lines = input.lines();
+        loop {
+            let prompt = if entry.is_empty() {
+                self.grammar.prompt()
+            } else {
+                self.grammar.continuation_prompt()
+            };
+            write!(output, "{}", prompt)?;
+            output.flush()?;
+
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => break,
+            };
+
+            if entry.is_empty() {
+                if let Some(command) = line.trim().strip_prefix(':') {
+                    if !self.command(command, &mut output)? {
+                        break;
+                    }
+                    continue;
+                }
+                if line.trim().is_empty() {
+                    continue;
+                }
+            }
+
+            let force = !entry.is_empty() && line.trim().is_empty();
+            if !entry.is_empty() {
+                entry.push('\n');
+            }
+            entry.push_str(&line);
+
+            match self.grammar.parse(&entry) {
+                Ok(value) => writeln!(output, "{:?}", value)?,
+                Err(error) if !force && self.grammar.is_incomplete(&entry, &error) => continue,
+                Err(error) => {
+                    writeln!(output, "{}", error.report(&entry))?;
+                    let word = word_at(&entry, error.offset);
+                    let hints = self.grammar.completions(word);
+                    if !word.is_empty() && !hints.is_empty() {
+                        writeln!(output, "hint: {}", hints.join(", "))?;
+                    }
+                }
+            }
+            self.history.push(std::mem::take(&mut entry).trim_end().to_string());
+        }
+        Ok(())
+    }
+
+    /// Runs a `:` command, returning false if the loop should end.
+    fn command(&mut self, command: &str, output: &mut impl Write) -> io::Result<bool> {
+        let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+        match name {
+            "quit" | "q" => return Ok(false),
+            "history" => {
+                for (i, entry) in self.history.iter().enumerate() {
+                    writeln!(output, "{:>4}  {}", i + 1, entry)?;
+                }
+            }
+            "complete" => writeln!(output, "{}", self.grammar.completions(argument.trim()).join(" "))?,
+            _ => writeln!(output, "unknown command :{}", name)?,
+        }
+        Ok(true)
+    }
+}
+
+/// Returns the word of `text` touching byte `offset`, preferring the one starting there.
+fn word_at(text: &str, offset: usize) -> &str {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let (before, after) = text.split_at_checked(offset).unwrap_or((text, ""));
+    let word = after.split(|c: char| !is_word(c)).next().unwrap_or_default();
+    if !word.is_empty() {
+        return word;
+    }
+    let start = before
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word(c))
+        .last()
+        .map_or(before.len(), |(i, _)| i);
+    before.get(start..).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::*;
+    use crate::*;
+
+    struct Sum;
+
+    impl ReplGrammar for Sum {
+        type Output = f64;
+
+        fn parse(&self, source: &str) -> Result<f64, GrammarError> {
+            GrammarBuilder::new()
+                .keywords(["pi"])
+                .build(|g| {
+                    let number = g.number();
+                    let pi = g.keyword("pi").map(|_| std::f64::consts::PI);
+                    let term = move |i| number.parse(i).or_else(|_| pi.parse(i));
+                    let plus = g.symbol("+");
+                    move |input| {
+                        let (mut rest, mut sum) = term.parse(input)?;
+                        while let Ok((after, _)) = plus.parse(rest) {
+                            let (after, value) = term.parse(after)?;
+                            sum += value;
+                            rest = after;
+                        }
+                        Ok((rest, sum))
+                    }
+                })
+                .parse_source(source)
+        }
+
+        fn completions(&self, prefix: &str) -> Vec<String> {
+            ["pi", "print"]
+                .iter()
+                .filter(|word| word.starts_with(prefix))
+                .map(|word| word.to_string())
+                .collect()
+        }
+    }
+
+    fn session(input: &str) -> (String, Vec<String>) {
+        let mut repl = Repl::new(Sum);
+        let mut output = Vec::new();
+        repl.run(input.as_bytes(), &mut output).unwrap();
+        (String::from_utf8(output).unwrap(), repl.history().to_vec())
+    }
+
+    #[test]
+    fn test_repl_session() {
+        let (output, history) = session("1 + 2 +\n3\n1 ? 2\n:complete p\n:history\n:quit\n4\n");
+        assert!(output.contains("> . 6.0\n"));
+        assert!(output.contains("0:2: expected end of input\n1 ? 2\n  ^\n"));
+        assert!(output.contains("pi print\n"));
+        assert!(output.contains("   1  1 + 2 +\n3\n   2  1 ? 2\n"));
+        assert!(!output.contains("4\n> "));
+        assert_eq!(history, vec!["1 + 2 +\n3", "1 ? 2"]);
+
+        // An empty line ends an unfinished entry
+        let (output, _) = session("1 +\n\n");
+        assert!(output.contains("expected"));
+
+        let (output, _) = session("1 + pr\n");
+        assert!(output.contains("hint: print\n"));
+    }
+
+    #[test]
+    fn test_word_at() {
+        assert_eq!(word_at("1 + pr", 4), "pr");
+        assert_eq!(word_at("1 + pr", 6), "pr");
+        assert_eq!(word_at("a größe", 9), "größe");
+        assert_eq!(word_at("1 +", 3), "");
+    }
+}
@@ -0,0 +1,492 @@
+//! # Scanning
+//!
+//! This module finds the first position in a buffer where a parser matches, for example to
+//! locate the next frame header after a corrupted region, and provides `take_until`
+//! primitives that consume everything up to a delimiter. The parser is tried at every
+//! start position in turn; when the parser is known to begin with a literal, candidate
+//! positions are found with a substring search instead. With the `memchr` feature the
+//! substring search over `&str` and `&[u8]` uses the vectorized `memchr::memmem`
+//! searcher; run `cargo bench --bench literal_search --features memchr` to compare.
+//!
+//! For `&str` and `&[u8]`, `take_until_byte` searches for a single delimiter byte with
+//! `memchr`, and `take_while_ascii` consumes bytes by table lookup without decoding
+//! characters. Without the feature both fall back to plain loops.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::scan::*;
+//!
+//! let header = '1'
+//!     .make_character_matcher("Expected 1")
+//!     .preceded_by("#".make_literal_matcher("Expected #"));
+//!
+//! assert_eq!(header.first_match_in("xx#2#1rest"), Some((4, '1', "rest")));
+//! assert_eq!(header.first_match_with_prefix("xx#2#1rest", "#"), Some((4, '1', "rest")));
+//! assert_eq!(header.first_match_in("no header"), None);
+//! ```
+
+use core::any::TypeId;
+
+use crate::core::{Parsable, Parser, ParserOutput};
+
+/// Returns the offset of the first occurrence of `needle` in `haystack`.
+///
+/// Uses `memchr::memmem` with the `memchr` feature. Otherwise UTF-8 text is searched with
+/// `str::find`, and other bytes with a first-byte search.
+pub fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    #[cfg(feature = "memchr")]
+    {
+        memchr::memmem::find(haystack, needle)
+    }
+    #[cfg(not(feature = "memchr"))]
+    {
+        if let (Ok(haystack), Ok(needle)) = (core::str::from_utf8(haystack), core::str::from_utf8(needle)) {
+            return haystack.find(needle);
+        }
+        if needle.is_empty() {
+            return Some(0);
+        }
+        haystack.windows(needle.len()).position(|window| window == needle)
+    }
+}
+
+/// Inputs that can be split at arbitrary start positions for scanning.
+///
+/// Offsets are measured in the input's units: bytes for `&str`, items for slices.
+pub trait Scannable: Sized + Clone {
+    /// Returns the length of the input.
+    fn scan_len(&self) -> usize;
+
+    /// Returns the input starting at `offset`.
+    fn suffix(&self, offset: usize) -> Self;
+
+    /// Returns the input up to `offset`.
+    fn prefix(&self, offset: usize) -> Self;
+
+    /// Returns the offset of the next start position after `offset`.
+    fn next_start(&self, offset: usize) -> usize;
+
+    /// Returns the offset of the first occurrence of `needle` at or after `from`.
+    fn find_from(&self, from: usize, needle: &Self) -> Option<usize>;
+}
+
+impl Scannable for &str {
+    fn scan_len(&self) -> usize {
+        self.len()
+    }
+
+    fn suffix(&self, offset: usize) -> Self {
+        self.get(offset..).unwrap_or_default()
+    }
+
+    fn prefix(&self, offset: usize) -> Self {
+        self.get(..offset).unwrap_or(self)
+    }
+
+    fn next_start(&self, offset: usize) -> usize {
+        offset + self.get(offset..).and_then(|rest| rest.chars().next()).map_or(1, char::len_utf8)
+    }
+
+    fn find_from(&self, from: usize, needle: &Self) -> Option<usize> {
+        #[cfg(feature = "memchr")]
+        {
+            find_bytes(self.as_bytes().get(from..)?, needle.as_bytes()).map(|i| from + i)
+        }
+        #[cfg(not(feature = "memchr"))]
+        {
+            self.get(from..)?.find(needle).map(|i| from + i)
+        }
+    }
+}
+
+/// Byte slices are searched with `find_bytes`, other slices item by item.
+impl<T: Eq + 'static> Scannable for &[T] {
+    fn scan_len(&self) -> usize {
+        self.len()
+    }
+
+    fn suffix(&self, offset: usize) -> Self {
+        self.get(offset..).unwrap_or_default()
+    }
+
+    fn prefix(&self, offset: usize) -> Self {
+        self.get(..offset).unwrap_or(self)
+    }
+
+    fn next_start(&self, offset: usize) -> usize {
+        offset + 1
+    }
+
+    fn find_from(&self, from: usize, needle: &Self) -> Option<usize> {
+        if TypeId::of::<T>() == TypeId::of::<u8>() {
+            // SAFETY: `T` is `u8`, so both slices already are byte slices
+            let (haystack, needle) = unsafe {
+                (
+                    core::slice::from_raw_parts(self.as_ptr().cast::<u8>(), self.len()),
+                    core::slice::from_raw_parts(needle.as_ptr().cast::<u8>(), needle.len()),
+                )
+            };
+            return find_bytes(haystack.get(from..)?, needle).map(|i| from + i);
+        }
+        if needle.is_empty() {
+            return Some(from);
+        }
+        let haystack = self.get(from..)?;
+        haystack.windows(needle.len()).position(|window| window == *needle).map(|i| from + i)
+    }
+}
+
+/// Extension trait adding scanning to parsers.
+pub trait ScanningParser<Input, Output, Error>: Parser<Input, Output, Error>
+where
+    Input: Parsable<Error> + Scannable,
+    Output: ParserOutput,
+    Error: Clone,
+{
+    /// Tries the parser at every start position of `haystack` and returns the offset,
+    /// output and remaining input of the first match.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::scan::*;
+    ///
+    /// let marker = [0xFFu8, 0xD8].as_slice().make_literal_matcher("Expected marker");
+    /// let data: &[u8] = &[0x00, 0xFF, 0x00, 0xFF, 0xD8, 0x01];
+    ///
+    /// let (offset, _, rest) = marker.first_match_in(data).unwrap();
+    /// assert_eq!(offset, 3);
+    /// assert_eq!(rest, &[0x01]);
+    /// ```
+    fn first_match_in(&self, haystack: Input) -> Option<(usize, Output, Input)> {
+        let mut offset = 0;
+        loop {
+            if let Ok((rest, output)) = self.parse(haystack.suffix(offset)) {
+                return Some((offset, output, rest));
+            }
+            if offset >= haystack.scan_len() {
+                return None;
+            }
+            offset = haystack.next_start(offset);
+        }
+    }
+
+    /// Like `first_match_in`, but only tries start positions where `prefix` occurs.
+    ///
+    /// The parser must begin by matching `prefix`; candidate positions are found with a
+    /// substring search, which is much faster than trying the parser everywhere.
+    fn first_match_with_prefix(&self, haystack: Input, prefix: Input) -> Option<(usize, Output, Input)> {
+        let mut from = 0;
+        while from <= haystack.scan_len() {
+            let offset = haystack.find_from(from, &prefix)?;
+            if let Ok((rest, output)) = self.parse(haystack.suffix(offset)) {
+                return Some((offset, output, rest));
+            }
+            if offset >= haystack.scan_len() {
+                return None;
+            }
+            from = haystack.next_start(offset);
+        }
+        None
+    }
+}
+
+impl<Input, Output, Error, P> ScanningParser<Input, Output, Error> for P
+where
+    Input: Parsable<Error> + Scannable,
+    Output: ParserOutput,
+    Error: Clone,
+    P: Parser<Input, Output, Error>,
+{
+}
+
+/// Consumes everything up to the first occurrence of `delimiter` and returns the consumed
+/// input. The delimiter itself is left in the remaining input.
+///
+/// Fails with `err`, consuming nothing, if the delimiter does not occur.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::scan::*;
+///
+/// let body = take_until("-->", "Unterminated comment");
+///
+/// assert_eq!(body.parse(" note -->rest"), Ok(("-->rest", " note ")));
+/// assert_eq!(body.parse(" note"), Err((" note", "Unterminated comment")));
+/// ```
+pub fn take_until<Input, Error>(delimiter: Input, err: Error) -> impl Parser<Input, Input, Error>
+where
+    Input: Parsable<Error> + Scannable,
+    Error: Clone,
+{
+    move |input: Input| match input.find_from(0, &delimiter) {
+        Some(offset) => Ok((input.suffix(offset), input.prefix(offset))),
+        None => Err((input, err.clone())),
+    }
+}
+
+/// Like `take_until`, but also consumes the delimiter and includes it in the output.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::scan::*;
+///
+/// let cdata = take_until_included("]]>", "Unterminated CDATA");
+///
+/// assert_eq!(cdata.parse("x < y]]></a>"), Ok(("</a>", "x < y]]>")));
+/// ```
+pub fn take_until_included<Input, Error>(delimiter: Input, err: Error) -> impl Parser<Input, Input, Error>
+where
+    Input: Parsable<Error> + Scannable,
+    Error: Clone,
+{
+    move |input: Input| match input.find_from(0, &delimiter) {
+        Some(offset) => {
+            let end = offset + delimiter.scan_len();
+            Ok((input.suffix(end), input.prefix(end)))
+        }
+        None => Err((input, err.clone())),
+    }
+}
+
+/// Consumes everything up to the first position where `delimiter` matches and returns the
+/// consumed input. The delimiter match is left in the remaining input.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::scan::*;
+///
+/// let level = "WARN".make_literal_matcher(()).or("ERROR".make_literal_matcher(()));
+/// let message = take_until_match(level, "No next entry");
+///
+/// assert_eq!(message.parse("disk full\nERROR io"), Ok(("ERROR io", "disk full\n")));
+/// ```
+pub fn take_until_match<Input, Output, Error, Error2>(
+    delimiter: impl Parser<Input, Output, Error2>,
+    err: Error,
+) -> impl Parser<Input, Input, Error>
+where
+    Input: Parsable<Error> + Parsable<Error2> + Scannable,
+    Error: Clone,
+    Error2: Clone,
+{
+    move |input: Input| match delimiter.first_match_in(input.clone()) {
+        Some((offset, _, _)) => Ok((input.suffix(offset), input.prefix(offset))),
+        None => Err((input, err.clone())),
+    }
+}
+
+/// Like `take_until_match`, but also consumes the delimiter match and returns it along
+/// with the consumed input before it.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::scan::*;
+///
+/// let end = '\n'.make_character_matcher(());
+/// let line = take_until_match_included(end, "Unterminated line");
+///
+/// assert_eq!(line.parse("first\nsecond"), Ok(("second", ("first", '\n'))));
+/// ```
+pub fn take_until_match_included<Input, Output, Error, Error2>(
+    delimiter: impl Parser<Input, Output, Error2>,
+    err: Error,
+) -> impl Parser<Input, (Input, Output), Error>
+where
+    Input: Parsable<Error> + Parsable<Error2> + Scannable,
+    Error: Clone,
+    Error2: Clone,
+{
+    move |input: Input| match delimiter.first_match_in(input.clone()) {
+        Some((offset, output, rest)) => Ok((rest, (input.prefix(offset), output))),
+        None => Err((input, err.clone())),
+    }
+}
+
+/// Returns the offset of the first `byte` in `haystack`.
+///
+/// Uses the vectorized `memchr::memchr` with the `memchr` feature, and a linear search
+/// otherwise.
+pub fn find_byte(haystack: &[u8], byte: u8) -> Option<usize> {
+    #[cfg(feature = "memchr")]
+    {
+        memchr::memchr(byte, haystack)
+    }
+    #[cfg(not(feature = "memchr"))]
+    {
+        haystack.iter().position(|&b| b == byte)
+    }
+}
+
+/// Inputs made of bytes, which the byte-level scanners below work on.
+pub trait ByteInput: Sized + Clone {
+    /// Returns the bytes of the input.
+    fn input_bytes(&self) -> &[u8];
+
+    /// Splits the input at the byte `offset`, or returns `None` if the offset is not a
+    /// valid split point.
+    fn split_bytes(&self, offset: usize) -> Option<(Self, Self)>;
+}
+
+impl ByteInput for &str {
+    fn input_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+
+    fn split_bytes(&self, offset: usize) -> Option<(Self, Self)> {
+        Some((self.get(..offset)?, self.get(offset..)?))
+    }
+}
+
+impl ByteInput for &[u8] {
+    fn input_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn split_bytes(&self, offset: usize) -> Option<(Self, Self)> {
+        (offset <= self.len()).then(|| self.split_at(offset))
+    }
+}
+
+/// Consumes everything up to the first `delimiter` byte and returns the consumed input,
+/// leaving the delimiter in the remaining input. This is `take_until` for a single byte,
+/// searched with `memchr` when the `memchr` feature is enabled.
+///
+/// Fails with `err`, consuming nothing, if the delimiter does not occur. On `&str` the
+/// delimiter must be an ASCII byte.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::scan::*;
+///
+/// let field = take_until_byte(b'|', "Expected |");
+/// assert_eq!(field.parse("GET /index|200"), Ok(("|200", "GET /index")));
+/// assert_eq!(field.parse("no delimiter"), Err(("no delimiter", "Expected |")));
+///
+/// let record = take_until_byte(0x00, "Expected NUL");
+/// assert_eq!(record.parse(b"\x01\x02\x00".as_slice()), Ok((b"\x00".as_slice(), b"\x01\x02".as_slice())));
+/// ```
+pub fn take_until_byte<Input, Error>(delimiter: u8, err: Error) -> impl Parser<Input, Input, Error>
+where
+    Input: Parsable<Error> + ByteInput,
+    Error: Clone,
+{
+    move |input: Input| {
+        let split = find_byte(input.input_bytes(), delimiter).and_then(|offset| input.split_bytes(offset));
+        match split {
+            Some((consumed, rest)) => Ok((rest, consumed)),
+            None => Err((input, err.clone())),
+        }
+    }
+}
+
+/// Consumes the longest prefix of ASCII bytes satisfying `predicate`, such as
+/// `u8::is_ascii_digit`, and returns it. Never fails; the prefix may be empty.
+///
+/// The predicate is evaluated once per byte value when the parser is created, and the
+/// input is scanned with a table lookup per byte instead of decoding characters. Non-ASCII
+/// bytes never match, so on `&str` the prefix always ends at a character boundary.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::scan::*;
+///
+/// let digits = take_while_ascii::<&str, ()>(u8::is_ascii_digit);
+///
+/// assert_eq!(digits.parse("2024-01-01"), Ok(("-01-01", "2024")));
+/// assert_eq!(digits.parse("٣"), Ok(("٣", "")));
+/// ```
+pub fn take_while_ascii<Input, Error>(predicate: impl Fn(&u8) -> bool) -> impl Parser<Input, Input, Error>
+where
+    Input: Parsable<Error> + ByteInput + Scannable,
+    Error: Clone,
+{
+    let mut table = [false; 256];
+    for (byte, slot) in (0..=127u8).zip(table.iter_mut()) {
+        *slot = predicate(&byte);
+    }
+    move |input: Input| {
+        let bytes = input.input_bytes();
+        #[allow(clippy::indexing_slicing)] // A `u8` index is always within the 256 table entries
+        let end = bytes.iter().position(|&b| !table[usize::from(b)]).unwrap_or(bytes.len());
+        // Only ASCII bytes match, so `end` is always a character boundary
+        Ok((input.suffix(end), input.prefix(end)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_first_match_in() {
+        let word = "ab".make_literal_matcher("Expected ab");
+
+        assert_eq!(word.first_match_in("ab"), Some((0, "ab", "")));
+        assert_eq!(word.first_match_in("äaab!"), Some((3, "ab", "!")));
+        assert_eq!(word.first_match_in("aaa"), None);
+        assert_eq!(word.first_match_in(""), None);
+
+        // A parser matching empty input matches at the very end
+        let end = <&str as Parsable<&str>>::make_empty_matcher("Expected end");
+        assert_eq!(end.first_match_in("abc"), Some((3, (), "")));
+    }
+
+    #[test]
+    fn test_first_match_with_prefix() {
+        let header = '1'
+            .make_character_matcher("Expected 1")
+            .preceded_by("<h".make_literal_matcher("Expected <h"));
+        let text = "<p><h2><h1>title";
+
+        assert_eq!(header.first_match_with_prefix(text, "<h"), header.first_match_in(text));
+        assert_eq!(header.first_match_with_prefix(text, "<h"), Some((7, '1', ">title")));
+        assert_eq!(header.first_match_with_prefix("<h2<h", "<h"), None);
+
+        let bytes: &[u8] = b"..GIF87a..GIF89a";
+        let gif = b"GIF89a".as_slice().make_literal_matcher("Expected GIF89a");
+        assert_eq!(gif.first_match_with_prefix(bytes, b"GIF"), Some((10, b"GIF89a".as_slice(), b"".as_slice())));
+    }
+
+    #[test]
+    fn test_take_until() {
+        let bytes: &[u8] = b"payload\r\nnext";
+        let line = take_until(b"\r\n".as_slice(), "No line end");
+        assert_eq!(line.parse(bytes), Ok((b"\r\nnext".as_slice(), b"payload".as_slice())));
+        // Byte slices that are not UTF-8 are searched too, and other slices item by item
+        assert_eq!(line.parse(b"\xFF\r\r\n".as_slice()), Ok((b"\r\n".as_slice(), b"\xFF\r".as_slice())));
+        let words: &[&str] = &["a", "b", "c"];
+        assert_eq!(take_until(["b", "c"].as_slice(), ()).parse(words), Ok((&words[1..], &words[..1])));
+
+        let included = take_until_included("*/", "Unterminated comment");
+        assert_eq!(included.parse("*/x"), Ok(("x", "*/")));
+        assert_eq!(included.parse("a * / b"), Err(("a * / b", "Unterminated comment")));
+
+        // A non-ASCII delimiter byte cannot split a `&str`
+        assert_eq!(take_until_byte(0xA9, ()).parse("©"), Err(("©", ())));
+        let spaces = take_while_ascii::<&[u8], ()>(u8::is_ascii_whitespace);
+        assert_eq!(spaces.parse(b" \t\xA0x".as_slice()), Ok((b"\xA0x".as_slice(), b" \t".as_slice())));
+        assert_eq!(spaces.parse(b"".as_slice()), Ok((b"".as_slice(), b"".as_slice())));
+
+        let digit = '1'.make_character_matcher(()).or('2'.make_character_matcher(()));
+        let prefix = take_until_match(digit, "No digit");
+        assert_eq!(prefix.parse("abc2d"), Ok(("2d", "abc")));
+        assert_eq!(prefix.parse("abc"), Err(("abc", "No digit")));
+    }
+}
@@ -1,6 +1,7 @@
-use std::{cell::RefCell, marker::PhantomData};
+use alloc::vec::Vec;
+use core::{cell::RefCell, marker::PhantomData};
 
-use crate::{core::ParserOutput, Parsable, Parser};
+use crate::{core::{Checkpoint, ParserOutput}, types::Either, Parsable, Parser};
 
 /// A container that carries both parser state and input.
 ///
@@ -9,6 +10,9 @@ use crate::{core::ParserOutput, Parsable, Parser};
 /// capabilities like tracking line/column positions, indentation levels, and other
 /// contextual information.
 ///
+/// Carriers compare and hash by both state and input, so memoized and packrat parsers over
+/// a carrier cache one result per position and state.
+///
 /// # Type Parameters
 ///
 /// * `State`: The type representing the state to be carried (e.g., `Position`, `Offset`)
@@ -27,12 +31,29 @@ use crate::{core::ParserOutput, Parsable, Parser};
 /// println!("Line: {}, Column: {}", carrier.state.line, carrier.state.column);
 /// println!("Input: {}", carrier.input);
 /// ```
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd)]
 pub struct StateCarrier<State, Input> {
     pub state: State,
     pub input: Input,
 }
 
+/// A carrier is restored with both its state and its input.
+impl<State: Clone, Input: Checkpoint> Checkpoint for StateCarrier<State, Input> {
+    type Checkpoint = (State, Input::Checkpoint);
+
+    fn checkpoint(&self) -> Self::Checkpoint {
+        (self.state.clone(), self.input.checkpoint())
+    }
+
+    fn rewind((state, input): Self::Checkpoint) -> Self {
+        StateCarrier::new(state, Input::rewind(input))
+    }
+}
+
+/// The result of running a parser on a `StateCarrier` input.
+pub(crate) type StatefulResult<State, Input, Output, Error> =
+    Result<(StateCarrier<State, Input>, Output), (StateCarrier<State, Input>, Error)>;
+
 impl<State, Input> StateCarrier<State, Input> {
     /// Create a new state carrier
     pub fn new(state: State, input: Input) -> Self {
@@ -157,7 +178,7 @@ impl<State, Input, Output, Error, P, SuccessT, ErrorT>
         input: Input,
         output: Output,
         input_ref: Input,
-    ) -> Result<(StateCarrier<State, Input>, Output), (StateCarrier<State, Input>, Error)>
+    ) -> StatefulResult<State, Input, Output, Error>
     where
         SuccessT: FnMut(State, Input, Output, Input) -> (State, Input, Output),
     {
@@ -176,7 +197,7 @@ impl<State, Input, Output, Error, P, SuccessT, ErrorT>
         input: Input,
         error: Error,
         input_ref: Input,
-    ) -> Result<(StateCarrier<State, Input>, Output), (StateCarrier<State, Input>, Error)>
+    ) -> StatefulResult<State, Input, Output, Error>
     where
         ErrorT: FnMut(State, Input, Error, Input) -> (State, Input, Error),
     {
@@ -205,7 +226,7 @@ where
     fn parse(
         &self,
         StateCarrier { state, input }: StateCarrier<State, Input>,
-    ) -> Result<(StateCarrier<State, Input>, Output), (StateCarrier<State, Input>, Error)> {
+    ) -> StatefulResult<State, Input, Output, Error> {
         let input_ref = input.clone();
         match self.parser.parse(input) {
             Ok((input, output)) => self.succes_helper(state, input, output, input_ref),
@@ -278,7 +299,7 @@ where
         &self,
         input: Input,
         state: State,
-    ) -> Result<(StateCarrier<State, Input>, Output), (StateCarrier<State, Input>, Error)> {
+    ) -> StatefulResult<State, Input, Output, Error> {
         self.parse(StateCarrier::new(state, input))
     }
 
@@ -349,6 +370,7 @@ where
     /// # Returns
     ///
     /// A parser with state information injected into all results
+    #[allow(clippy::type_complexity)]
     fn inject_last_and_current_state(
         self,
     ) -> impl Parser<StateCarrier<State, Input>, ((State, State), Output), ((State, State), Error)>
@@ -481,7 +503,6 @@ where
     /// # Returns
     ///
     /// A parser that includes current state information in the output
-
     fn inject_current_state_to_output(
         self,
     ) -> impl Parser<StateCarrier<State, Input>, (State, Output), Error>
@@ -626,7 +647,7 @@ where
     ///         }
     ///  );
     ///  let r1 = p.parse_with_state("digithaha",Position::new(0, 0));
-    ///  assert_eq!(r1,Ok((StateCarrier::new(Position{column:9,line:0}, ""),StateCarrier::new(Position{column:1,line:1}, "haha"))))
+    ///  assert_eq!(r1,Ok((StateCarrier::new(Position::new(0, 9), ""),StateCarrier::new(Position::new(1, 1), "haha"))))
     ///
     ///
     /// ```
@@ -654,6 +675,200 @@ where
             }
         }
     }
+
+    /// A state-aware version of `recover_with`.
+    ///
+    /// When the parser fails, `recovery` receives the state at the point of failure and the
+    /// error, and returns the parser to try instead. Like `recover_with`, the recovery
+    /// parser restarts at the original input, and it runs with the state as it was before
+    /// the failed attempt, so partial state changes of the failed branch are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::state::*;
+    /// use friss::parsers::*;
+    ///
+    /// let keyword = "if".with_state(Offset(0)).make_literal_matcher("Expected if");
+    /// let parser = keyword.recover_with_state(|state: Offset, _err| {
+    ///     // Only accept the short form at the start of the input
+    ///     let fallback = if state.0 == 0 { "i" } else { "if" };
+    ///     fallback.with_state(Offset(0)).make_literal_matcher("Expected keyword")
+    /// });
+    ///
+    /// let (rest, out) = parser.parse_with_state("ix", Offset(0)).unwrap();
+    /// assert_eq!((rest.state, out.input), (Offset(1), "i"));
+    /// assert!(parser.parse_with_state("ix", Offset(3)).is_err());
+    /// ```
+    fn recover_with_state<P, F>(self, recovery: F) -> impl StatefulParser<State, Input, Output, Error>
+    where
+        Self: Sized,
+        P: Parser<StateCarrier<State, Input>, Output, Error>,
+        F: Fn(State, Error) -> P,
+        StateCarrier<State, Input>: Clone,
+    {
+        move |input: StateCarrier<State, Input>| {
+            self.parse(input.clone())
+                .or_else(|(failed, err)| recovery(failed.state, err).parse(input))
+        }
+    }
+
+    /// A state-aware version of `seq`: runs `next` on the state and input left by this
+    /// parser, and stays a `StatefulParser`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::state::*;
+    /// use friss::parsers::*;
+    ///
+    /// let matcher = |s: &'static str| s.with_state(Offset(0)).make_literal_matcher(s);
+    /// let parser = matcher("let").seq_stateful(matcher(" x"));
+    ///
+    /// let (rest, _) = parser.parse_with_state("let x;", Offset(0)).unwrap();
+    /// assert_eq!(rest.state, Offset(5));
+    /// assert_eq!(parser.parse_with_state("let y", Offset(0)).unwrap_err().1, Either::Right(" x"));
+    /// ```
+    fn seq_stateful<O2, E2>(
+        self,
+        next: impl StatefulParser<State, Input, O2, E2>,
+    ) -> impl StatefulParser<State, Input, (Output, O2), Either<Error, E2>>
+    where
+        Self: Sized,
+        E2: Clone,
+        Input: Parsable<E2> + Parsable<Either<Error, E2>> + Clone,
+        StateCarrier<State, Input>: Parsable<E2> + Parsable<Either<Error, E2>>,
+    {
+        move |input: StateCarrier<State, Input>| {
+            let (rest, first) = self.parse(input).map_err(|(rest, err)| (rest, Either::Left(err)))?;
+            let (rest, second) = next.parse(rest).map_err(|(rest, err)| (rest, Either::Right(err)))?;
+            Ok((rest, (first, second)))
+        }
+    }
+
+    /// A state-aware version of `alt`. The alternative runs on the input and state as they
+    /// were before this parser, so state changes of a failed first branch are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::state::*;
+    /// use friss::parsers::*;
+    ///
+    /// let matcher = |s: &'static str| s.with_state(Offset(0)).make_literal_matcher(s);
+    /// let parser = matcher("a").seq_stateful(matcher("b")).alt_stateful(matcher("ac"));
+    ///
+    /// let (rest, _) = parser.parse_with_state("ac", Offset(0)).unwrap();
+    /// assert_eq!(rest.state, Offset(2));
+    /// ```
+    fn alt_stateful<O2, E2>(
+        self,
+        other: impl StatefulParser<State, Input, O2, E2>,
+    ) -> impl StatefulParser<State, Input, Either<Output, O2>, (Error, E2)>
+    where
+        Self: Sized,
+        E2: Clone,
+        State: Clone,
+        Input: Parsable<E2> + Parsable<(Error, E2)> + Clone,
+        StateCarrier<State, Input>: Parsable<E2> + Parsable<(Error, E2)>,
+    {
+        move |input: StateCarrier<State, Input>| match self.parse(input.clone()) {
+            Ok((rest, out)) => Ok((rest, Either::Left(out))),
+            Err((_, e1)) => match other.parse(input) {
+                Ok((rest, out)) => Ok((rest, Either::Right(out))),
+                Err((rest, e2)) => Err((rest, (e1, e2))),
+            },
+        }
+    }
+
+    /// A state-aware version of `maybe`. On failure the input and state are left as they
+    /// were before this parser.
+    fn maybe_stateful(self) -> impl StatefulParser<State, Input, Option<Output>, Error>
+    where
+        Self: Sized,
+        State: Clone,
+        Input: Clone,
+    {
+        move |input: StateCarrier<State, Input>| match self.parse(input.clone()) {
+            Ok((rest, out)) => Ok((rest, Some(out))),
+            Err(_) => Ok((input, None)),
+        }
+    }
+
+    /// A state-aware version of `many`. Stops at the input and state left by the last
+    /// successful match, or after a match that consumed no input.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::state::*;
+    /// use friss::parsers::*;
+    ///
+    /// let line = "ok\n".with_state(Position::default()).make_literal_matcher("Expected ok");
+    /// let (rest, lines) = line.many_stateful().parse_with_state("ok\nok\nno", Position::default()).unwrap();
+    ///
+    /// assert_eq!(lines.len(), 2);
+    /// assert_eq!(rest.state, Position::new(2, 0));
+    /// ```
+    fn many_stateful(self) -> impl StatefulParser<State, Input, Vec<Output>, Error>
+    where
+        Self: Sized,
+        State: Clone,
+        Input: Clone + PartialEq,
+    {
+        move |input: StateCarrier<State, Input>| {
+            let mut results = Vec::new();
+            let mut rest = input;
+            while let Ok((next, out)) = self.parse(rest.clone()) {
+                results.push(out);
+                if next.input == rest.input {
+                    rest = next;
+                    break;
+                }
+                rest = next;
+            }
+            Ok((rest, results))
+        }
+    }
+
+    /// A state-aware version of `sep_by`. A separator not followed by an item is left
+    /// unconsumed, together with its state changes.
+    fn sep_by_stateful<O2, E2>(
+        self,
+        sep: impl StatefulParser<State, Input, O2, E2>,
+    ) -> impl StatefulParser<State, Input, Vec<Output>, Error>
+    where
+        Self: Sized,
+        E2: Clone,
+        State: Clone,
+        Input: Parsable<E2> + Clone,
+        StateCarrier<State, Input>: Parsable<E2>,
+    {
+        move |input: StateCarrier<State, Input>| {
+            let mut results = Vec::new();
+            let mut rest = match self.parse(input.clone()) {
+                Ok((rest, out)) => {
+                    results.push(out);
+                    rest
+                }
+                Err(_) => return Ok((input, results)),
+            };
+            while let Ok((after_sep, _)) = sep.parse(rest.clone()) {
+                match self.parse(after_sep) {
+                    Ok((next, out)) => {
+                        results.push(out);
+                        rest = next;
+                    }
+                    Err(_) => break,
+                }
+            }
+            Ok((rest, results))
+        }
+    }
 }
 
 impl<State, Input, Output, Error, P, SuccesT, ErrorT> StatefulParser<State, Input, Output, Error>
@@ -693,23 +908,8 @@ where
     }
 }
 
-/*
-pub fn lift_parser<State: Default, Input, Output, Error>(
-    parser: impl Parser<Input, Output, Error>,
-) -> impl StatefulParser<State, Input, Output, Error>
-where
-    Input: Clone + Parsable<Error>,
-StateCarrier<State, Input>: Parsable<Error>,
-    Error: Clone,
-{
-    move |carrier: StateCarrier<State, Input>| {
-        let state = carrier.state;
-        let input = carrier.input;
-
-        match parser.parse(input) {
-            Ok((rest, output)) => Ok((StateCarrier { state, input: rest }, output)),
-            Err((rest, error)) => Err((StateCarrier { state, input: rest }, error)),
-        }
-    }
+/// States that follow the input consumed by a plain parser, used by `Parser::lift`.
+pub trait StateUpdate<Input> {
+    /// Updates the state after a parser moved from `before` to `after`.
+    fn update(&mut self, before: &Input, after: &Input);
 }
-*/
@@ -0,0 +1,233 @@
+//! # Static Grammars
+//!
+//! The matcher constructors return closures, which cannot be built in a `static`. Grammars
+//! used throughout a program are therefore usually built on every call or behind a lazy
+//! initializer. This module provides parsers that are plain data, built by `const fn`:
+//!
+//! - `literal(text, err)` matches fixed text in a `&str`
+//! - `byte_literal(bytes, err)` matches fixed bytes in a `&[u8]`
+//! - `char_class(ranges, err)` matches one character from inclusive ranges
+//!
+//! They are `Send + Sync` when their error is, so they can be stored in `static`s and
+//! `const`s directly. Parsers built from combinators are stored in a `StaticParser`, which
+//! builds them on first use. The `static_parser!` macro declares one; a rule refers to other
+//! rules, or to itself, by name, so recursive grammars need no forward declarations.
+//! `StaticParser` and the macro require the `std` feature.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::statics::*;
+//!
+//! const HEX_DIGIT: CharClass<&str> = char_class(&[('0', '9'), ('a', 'f')], "Expected hex digit");
+//! static PREFIX: Literal<&str> = literal("0x", "Expected 0x");
+//!
+//! let hex = |input| {
+//!     let (rest, _) = PREFIX.parse(input)?;
+//!     HEX_DIGIT.many().parse(rest)
+//! };
+//! assert_eq!(hex.parse("0x1f;"), Ok((";", vec!['1', 'f'])));
+//! assert_eq!(hex.parse("1f"), Err(("1f", "Expected 0x")));
+//! ```
+
+use crate::core::Parser;
+
+/// Matches fixed text, see `literal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Literal<E> {
+    text: &'static str,
+    err: E,
+}
+
+/// Matches `text` and returns the matched part of the input.
+pub const fn literal<E>(text: &'static str, err: E) -> Literal<E> {
+    Literal { text, err }
+}
+
+impl<'a, E: Clone> Parser<&'a str, &'a str, E> for Literal<E> {
+    fn parse(&self, input: &'a str) -> Result<(&'a str, &'a str), (&'a str, E)> {
+        match input.split_at_checked(self.text.len()) {
+            Some((matched, rest)) if matched == self.text => Ok((rest, matched)),
+            _ => Err((input, self.err.clone())),
+        }
+    }
+}
+
+/// Matches fixed bytes, see `byte_literal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteLiteral<E> {
+    bytes: &'static [u8],
+    err: E,
+}
+
+/// Matches `bytes` and returns the matched part of the input.
+pub const fn byte_literal<E>(bytes: &'static [u8], err: E) -> ByteLiteral<E> {
+    ByteLiteral { bytes, err }
+}
+
+impl<'a, E: Clone> Parser<&'a [u8], &'a [u8], E> for ByteLiteral<E> {
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], &'a [u8]), (&'a [u8], E)> {
+        match input.split_at_checked(self.bytes.len()) {
+            Some((matched, rest)) if matched == self.bytes => Ok((rest, matched)),
+            _ => Err((input, self.err.clone())),
+        }
+    }
+}
+
+/// Matches one character from a set of ranges, see `char_class`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharClass<E> {
+    ranges: &'static [(char, char)],
+    err: E,
+}
+
+/// Matches one character from any of the inclusive `ranges` and returns it.
+pub const fn char_class<E>(ranges: &'static [(char, char)], err: E) -> CharClass<E> {
+    CharClass { ranges, err }
+}
+
+impl<E> CharClass<E> {
+    /// Returns true if `c` lies in one of the ranges.
+    pub fn contains(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&c))
+    }
+}
+
+impl<'a, E: Clone> Parser<&'a str, char, E> for CharClass<E> {
+    fn parse(&self, input: &'a str) -> Result<(&'a str, char), (&'a str, E)> {
+        let mut chars = input.chars();
+        match chars.next() {
+            Some(c) if self.contains(c) => Ok((chars.as_str(), c)),
+            _ => Err((input, self.err.clone())),
+        }
+    }
+}
+
+/// A parser stored in a `static`, built by `init` on first use.
+///
+/// Usually declared with `static_parser!`. Building is thread-safe; concurrent first uses
+/// build the parser once.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::statics::*;
+///
+/// const DIGIT: CharClass<&str> = char_class(&[('0', '9')], "Expected digit");
+/// static COMMA: Literal<&str> = literal(",", "Expected ,");
+///
+/// static_parser! {
+///     /// A number, or a parenthesized list of lists and numbers, returning the digit count
+///     pub static ITEM: Parser<&'static str, usize, &'static str> = |input: &'static str| {
+///         match '('.make_character_matcher("Expected (").parse(input) {
+///             Ok((rest, _)) => {
+///                 let (rest, items) = LIST.parse(rest)?;
+///                 let (rest, _) = ')'.make_character_matcher("Expected )").parse(rest)?;
+///                 Ok((rest, items.into_iter().sum()))
+///             }
+///             Err(_) => {
+///                 let (rest, digits) = DIGIT.many().parse(input)?;
+///                 if digits.is_empty() {
+///                     return Err((input, "Expected digit"));
+///                 }
+///                 Ok((rest, digits.len()))
+///             }
+///         }
+///     };
+///     static LIST: Parser<&'static str, Vec<usize>, &'static str> =
+///         (|input: &'static str| ITEM.parse(input)).sep_by(COMMA.map(|_| 0));
+/// }
+///
+/// assert_eq!(ITEM.parse("(12,(3,45))"), Ok(("", 5)));
+/// assert_eq!(std::thread::spawn(|| ITEM.parse("(1,x)")).join().unwrap(), Err((",x)", "Expected )")));
+/// ```
+#[cfg(feature = "std")]
+pub struct StaticParser<Input, Output, Error> {
+    parser: std::sync::OnceLock<crate::core::SyncParser<Input, Output, Error>>,
+    init: fn() -> crate::core::SyncParser<Input, Output, Error>,
+}
+
+#[cfg(feature = "std")]
+impl<Input, Output, Error> StaticParser<Input, Output, Error> {
+    /// Creates a parser built by `init` when it is first used.
+    pub const fn new(init: fn() -> crate::core::SyncParser<Input, Output, Error>) -> Self {
+        StaticParser {
+            parser: std::sync::OnceLock::new(),
+            init,
+        }
+    }
+
+    /// Returns true if the parser has been built.
+    pub fn is_built(&self) -> bool {
+        self.parser.get().is_some()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Input, Output, Error> Parser<Input, Output, Error> for StaticParser<Input, Output, Error>
+where
+    Input: crate::core::Parsable<Error>,
+    Error: Clone,
+{
+    fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+        self.parser.get_or_init(self.init).parse(input)
+    }
+}
+
+/// Declares `static` parsers built on first use.
+///
+/// Each item reads `static NAME: Parser<Input, Output, Error> = expression;`, with optional
+/// attributes and visibility. The expression must be `Send + Sync` and may refer to any
+/// `static`, including the one being declared.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+///
+/// static_parser! {
+///     static NESTING: Parser<&'static str, usize, &'static str> = |input: &'static str| {
+///         let Ok((rest, _)) = '('.make_character_matcher("Expected (").parse(input) else {
+///             return Ok((input, 0));
+///         };
+///         let (rest, depth) = NESTING.parse(rest)?;
+///         let (rest, _) = ')'.make_character_matcher("Expected )").parse(rest)?;
+///         Ok((rest, depth + 1))
+///     };
+/// }
+///
+/// assert_eq!(NESTING.parse("(())"), Ok(("", 2)));
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! static_parser {
+    ($($(#[$meta:meta])* $vis:vis static $name:ident: Parser<$input:ty, $output:ty, $error:ty> = $parser:expr;)*) => {
+        $(
+            $(#[$meta])*
+            $vis static $name: $crate::statics::StaticParser<$input, $output, $error> =
+                $crate::statics::StaticParser::new(|| ::std::boxed::Box::new($parser));
+        )*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_const_parsers() {
+        const HEX: CharClass<()> = char_class(&[('0', '9'), ('a', 'f'), ('A', 'F')], ());
+        const MAGIC: ByteLiteral<&str> = byte_literal(b"\x7fELF", "Expected ELF magic");
+        static ARROW: Literal<&str> = literal("->", "Expected ->");
+
+        assert_eq!(HEX.parse("Fg"), Ok(("g", 'F')));
+        assert_eq!(HEX.parse("g"), Err(("g", ())));
+        assert_eq!(HEX.parse(""), Err(("", ())));
+        assert_eq!(MAGIC.parse(b"\x7fELF\x02".as_slice()), Ok((b"\x02".as_slice(), b"\x7fELF".as_slice())));
+        assert_eq!(MAGIC.parse(b"\x7fEL".as_slice()), Err((b"\x7fEL".as_slice(), "Expected ELF magic")));
+        assert_eq!(ARROW.parse("->é"), Ok(("é", "->")));
+        assert_eq!(ARROW.parse("=>"), Err(("=>", "Expected ->")));
+    }
+}
@@ -0,0 +1,406 @@
+//! # Stress Input Generation
+//!
+//! This module generates deterministic pseudo-random inputs for stress testing grammars:
+//! deeply nested structures, long repetition runs and near-miss tokens that almost match.
+//! Generation is guided by a `Shape`, a lightweight description of the grammar's rule
+//! structure, and seeded so that every failure can be reproduced.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::stress::*;
+//!
+//! // list := "[" (list | "x")* "]"
+//! let shape = Shape::seq([
+//!     Shape::literal("["),
+//!     Shape::repeat(Shape::one_of([Shape::Recurse, Shape::literal("x")])),
+//!     Shape::literal("]"),
+//! ]);
+//!
+//! let mut generator = StressGenerator::new(42);
+//! let inputs = generator.adversarial(&shape, 10);
+//!
+//! assert_eq!(inputs, StressGenerator::new(42).adversarial(&shape, 10));
+//! assert!(inputs.iter().any(|input| input.starts_with("[[[[")));
+//! ```
+
+/// A description of a grammar's structure used to guide input generation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    /// A fixed token
+    Literal(String),
+    /// Any one character of the set
+    Chars(Vec<char>),
+    /// All shapes in order
+    Seq(Vec<Shape>),
+    /// One of the alternatives
+    OneOf(Vec<Shape>),
+    /// Zero or more repetitions
+    Repeat(Box<Shape>),
+    /// Optionally the shape
+    Optional(Box<Shape>),
+    /// A recursive reference to the root shape
+    Recurse,
+}
+
+impl Shape {
+    /// Creates a literal shape.
+    pub fn literal(text: impl Into<String>) -> Self {
+        Shape::Literal(text.into())
+    }
+
+    /// Creates a shape matching any character of `chars`.
+    pub fn chars(chars: impl IntoIterator<Item = char>) -> Self {
+        Shape::Chars(chars.into_iter().collect())
+    }
+
+    /// Creates a sequence shape.
+    pub fn seq(shapes: impl IntoIterator<Item = Shape>) -> Self {
+        Shape::Seq(shapes.into_iter().collect())
+    }
+
+    /// Creates an alternative shape.
+    pub fn one_of(shapes: impl IntoIterator<Item = Shape>) -> Self {
+        Shape::OneOf(shapes.into_iter().collect())
+    }
+
+    /// Creates a repetition shape.
+    pub fn repeat(shape: Shape) -> Self {
+        Shape::Repeat(Box::new(shape))
+    }
+
+    /// Creates an optional shape.
+    pub fn optional(shape: Shape) -> Self {
+        Shape::Optional(Box::new(shape))
+    }
+
+    /// Returns true if the shape contains a `Recurse` reference.
+    pub fn recurses(&self) -> bool {
+        match self {
+            Shape::Recurse => true,
+            Shape::Seq(shapes) | Shape::OneOf(shapes) => shapes.iter().any(Shape::recurses),
+            Shape::Repeat(shape) | Shape::Optional(shape) => shape.recurses(),
+            Shape::Literal(_) | Shape::Chars(_) => false,
+        }
+    }
+
+    /// Collects every literal token of the shape.
+    pub fn literals(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        self.collect_literals(&mut out);
+        out
+    }
+
+    fn collect_literals<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Shape::Literal(text) => out.push(text),
+            Shape::Seq(shapes) | Shape::OneOf(shapes) => {
+                shapes.iter().for_each(|s| s.collect_literals(out))
+            }
+            Shape::Repeat(shape) | Shape::Optional(shape) => shape.collect_literals(out),
+            Shape::Chars(_) | Shape::Recurse => {}
+        }
+    }
+}
+
+/// Limits applied while generating inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StressConfig {
+    /// Maximum recursion depth of random samples
+    pub max_depth: usize,
+    /// Maximum repetitions of random samples
+    pub max_repeat: usize,
+    /// Length of generated long runs
+    pub run_length: usize,
+    /// Nesting depth of generated deep inputs
+    pub nesting_depth: usize,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        StressConfig {
+            max_depth: 8,
+            max_repeat: 4,
+            run_length: 10_000,
+            nesting_depth: 1_000,
+        }
+    }
+}
+
+/// Scrambles a seed with a splitmix64 step, so that nearby seeds start far apart.
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Deterministic generator of stress inputs.
+#[derive(Debug, Clone)]
+pub struct StressGenerator {
+    state: u64,
+    config: StressConfig,
+}
+
+impl StressGenerator {
+    /// Creates a generator with the default configuration.
+    pub fn new(seed: u64) -> Self {
+        Self::with_config(seed, StressConfig::default())
+    }
+
+    /// Creates a generator with the given configuration.
+    pub fn with_config(seed: u64, config: StressConfig) -> Self {
+        StressGenerator {
+            // xorshift stays at zero once there, and one seed scrambles to zero
+            state: splitmix64(seed).max(1),
+            config,
+        }
+    }
+
+    /// Returns the next pseudo-random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Returns a pseudo-random number below `bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            0
+        } else {
+            (self.next_u64() % bound as u64) as usize
+        }
+    }
+
+    /// Returns a random item of `items`, or `None` without drawing when it is empty.
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            items.get(self.below(items.len()))
+        }
+    }
+
+    /// Generates a random input matching `shape`.
+    pub fn sample(&mut self, shape: &Shape) -> String {
+        let mut out = String::new();
+        self.sample_into(shape, shape, 0, &mut out);
+        out
+    }
+
+    fn sample_into(&mut self, root: &Shape, shape: &Shape, depth: usize, out: &mut String) {
+        match shape {
+            Shape::Literal(text) => out.push_str(text),
+            Shape::Chars(chars) => {
+                out.extend(self.pick(chars));
+            }
+            Shape::Seq(shapes) => {
+                for s in shapes {
+                    self.sample_into(root, s, depth, out);
+                }
+            }
+            Shape::OneOf(shapes) => {
+                // Past the depth limit prefer alternatives that do not recurse
+                let candidates: Vec<&Shape> = if depth >= self.config.max_depth {
+                    shapes.iter().filter(|s| !s.recurses()).collect()
+                } else {
+                    shapes.iter().collect()
+                };
+                if let Some(&choice) = self.pick(&candidates) {
+                    self.sample_into(root, choice, depth, out);
+                }
+            }
+            Shape::Repeat(shape) => {
+                let limit = if depth >= self.config.max_depth { 0 } else { self.config.max_repeat };
+                for _ in 0..self.below(limit + 1) {
+                    self.sample_into(root, shape, depth, out);
+                }
+            }
+            Shape::Optional(shape) => {
+                if depth < self.config.max_depth && self.below(2) == 1 {
+                    self.sample_into(root, shape, depth, out);
+                }
+            }
+            Shape::Recurse => {
+                if depth < self.config.max_depth {
+                    self.sample_into(root, root, depth + 1, out);
+                }
+            }
+        }
+    }
+
+    /// Generates an input nesting `shape` into itself as deeply as configured.
+    ///
+    /// Every `Recurse` reference along the first recursive path is expanded, all other
+    /// choices take their shortest form.
+    pub fn deep_nesting(&mut self, shape: &Shape) -> String {
+        let mut out = String::new();
+        Self::nest_into(shape, shape, self.config.nesting_depth, &mut out);
+        out
+    }
+
+    fn nest_into(root: &Shape, shape: &Shape, depth: usize, out: &mut String) {
+        match shape {
+            Shape::Literal(text) => out.push_str(text),
+            Shape::Chars(chars) => out.extend(chars.first()),
+            Shape::Seq(shapes) => shapes.iter().for_each(|s| Self::nest_into(root, s, depth, out)),
+            Shape::OneOf(shapes) => {
+                let choice = if depth > 0 {
+                    shapes.iter().find(|s| s.recurses()).or(shapes.first())
+                } else {
+                    shapes.iter().find(|s| !s.recurses())
+                };
+                if let Some(choice) = choice {
+                    Self::nest_into(root, choice, depth, out);
+                }
+            }
+            Shape::Repeat(shape) | Shape::Optional(shape) => {
+                if depth > 0 && shape.recurses() {
+                    Self::nest_into(root, shape, depth, out);
+                }
+            }
+            Shape::Recurse => {
+                if depth > 0 {
+                    Self::nest_into(root, root, depth - 1, out);
+                }
+            }
+        }
+    }
+
+    /// Generates a long run of samples of `shape`, stressing repetition combinators.
+    pub fn long_run(&mut self, shape: &Shape) -> String {
+        let mut out = String::new();
+        let mut shallow = self.clone();
+        shallow.config.max_depth = 0;
+        while out.len() < self.config.run_length {
+            let before = out.len();
+            out.push_str(&shallow.sample(shape));
+            if out.len() == before {
+                break;
+            }
+        }
+        self.state = shallow.state;
+        out
+    }
+
+    /// Generates tokens that almost match `literal`: truncated, extended, with one
+    /// character replaced or with two characters swapped.
+    pub fn near_misses(&mut self, literal: &str) -> Vec<String> {
+        let chars: Vec<char> = literal.chars().collect();
+        let mut out = Vec::new();
+        let Some((_, truncated)) = chars.split_last() else {
+            return out;
+        };
+
+        out.push(truncated.iter().collect());
+        out.extend(self.pick(&chars).map(|c| format!("{}{}", literal, c)));
+
+        let i = self.below(chars.len());
+        let mut replaced = chars.clone();
+        if let Some(c) = replaced.get_mut(i) {
+            *c = if *c == '\u{0}' { '\u{1}' } else { char::from_u32(*c as u32 ^ 1).unwrap_or('?') };
+        }
+        out.push(replaced.into_iter().collect());
+
+        if chars.len() > 1 {
+            let i = self.below(chars.len() - 1);
+            let mut swapped = chars.clone();
+            swapped.swap(i, i + 1);
+            if swapped != chars {
+                out.push(swapped.into_iter().collect());
+            }
+        }
+        out
+    }
+
+    /// Generates `count` adversarial inputs for `shape`, cycling through random samples,
+    /// deep nesting, long runs and samples with a near-miss token spliced in.
+    pub fn adversarial(&mut self, shape: &Shape, count: usize) -> Vec<String> {
+        let literals: Vec<String> = shape.literals().into_iter().map(String::from).collect();
+        (0..count)
+            .map(|i| match i % 4 {
+                0 => self.sample(shape),
+                1 => self.deep_nesting(shape),
+                2 => self.long_run(shape),
+                _ => {
+                    let valid = self.sample(shape);
+                    let Some(literal) = literals.get(self.below(literals.len())) else {
+                        return valid;
+                    };
+                    let misses = self.near_misses(literal);
+                    let Some(miss) = self.pick(&misses) else {
+                        return valid;
+                    };
+                    match valid.split_once(literal.as_str()) {
+                        Some((before, after)) => format!("{}{}{}", before, miss, after),
+                        None => format!("{}{}", valid, miss),
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn parens() -> Shape {
+        Shape::seq([
+            Shape::literal("("),
+            Shape::optional(Shape::Recurse),
+            Shape::literal(")"),
+        ])
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let shape = Shape::repeat(Shape::one_of([parens(), Shape::chars(['a', 'b'])]));
+        let a = StressGenerator::new(7).adversarial(&shape, 12);
+        let b = StressGenerator::new(7).adversarial(&shape, 12);
+        let c = StressGenerator::new(8).adversarial(&shape, 12);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_no_seed_gets_stuck_at_zero() {
+        for seed in [0, 0x9E37_79B9_7F4A_7C15, 0u64.wrapping_sub(0x9E37_79B9_7F4A_7C15)] {
+            let mut generator = StressGenerator::new(seed);
+            let draws: Vec<u64> = (0..4).map(|_| generator.next_u64()).collect();
+            assert!(draws.iter().all(|&n| n != 0), "seed {:#x} produced {:?}", seed, draws);
+        }
+    }
+
+    #[test]
+    fn test_deep_nesting_and_long_runs() {
+        let config = StressConfig { nesting_depth: 50, run_length: 100, ..StressConfig::default() };
+        let mut generator = StressGenerator::with_config(1, config);
+
+        let deep = generator.deep_nesting(&parens());
+        assert_eq!(deep, format!("{}{}", "(".repeat(51), ")".repeat(51)));
+
+        let run = generator.long_run(&Shape::chars(['a']));
+        assert_eq!(run.len(), 100);
+        let parser = 'a'.make_character_matcher("Expected a").many();
+        assert_eq!(parser.parse(run.as_str()).map(|(rest, v)| (rest, v.len())), Ok(("", 100)));
+    }
+
+    #[test]
+    fn test_near_misses() {
+        let mut generator = StressGenerator::new(3);
+        let misses = generator.near_misses("while");
+
+        assert_eq!(misses[0], "whil");
+        assert!(misses[1].starts_with("while") && misses[1].len() == 6);
+        assert!(misses.iter().all(|m| m != "while"));
+
+        let keyword = "while".make_literal_matcher("Expected while");
+        assert!(misses.iter().all(|m| keyword.parse(m).map(|(rest, _)| rest.is_empty()) != Ok(true)));
+    }
+}
@@ -1,11 +1,235 @@
 //! # Parser Sugar for N-ary Operations
 //!
 //! This module provides syntactic sugar for working with tuple-based parsers,
-//! allowing for convenient sequencing and alternation of multiple parsers.
+//! allowing for convenient sequencing, alternation and permutation of multiple parsers.
+//! `alt_longest` is the longest-match counterpart of `alt`, `alt_last` prefers the later
+//! alternatives, and `alt_farthest` reports the error of the alternative that got furthest
+//! when all of them fail.
+//!
+//! The tuple traits are implemented for tuples of up to 10 parsers, or up to 16 with the
+//! `wide-tuples` feature, which also adds `Either11` to `Either16`.
+//!
+//! It also provides the `P` wrapper, which enables infix operators on parsers:
+//!
+//! - `P(a) >> P(b)` sequences both parsers and keeps both outputs, like `a.seq(b)`
+//! - `P(a) | P(b)` tries `a` and then `b`, like `a.alt(b)`
+//! - `P(a) << P(b)` sequences both parsers and keeps the output of `a`
+//! - `P(a) * P(b)` sequences both parsers and keeps the output of `b`
+//!
+//! The operators produce the same `Either` and tuple structures as the method forms.
+//! For longer sequences full of punctuation, the `seq_keep!` macro sequences a list of
+//! parsers and keeps only the outputs of those marked with `keep(...)`.
+//!
+//! ```rust
+//! use friss::*;
+//!
+//! let key = P("key".make_literal_matcher("Expected key"));
+//! let eq = P("=".make_literal_matcher("Expected ="));
+//! let value = P("yes".make_literal_matcher("Expected yes")) | P("no".make_literal_matcher("Expected no"));
+//!
+//! let parser = key << eq >> value;
+//!
+//! assert_eq!(parser.parse("key=no"), Ok(("", ("key", Either::Right("no")))));
+//! assert_eq!(
+//!     parser.parse("key=maybe"),
+//!     Err(("maybe", Either::Right(("Expected yes", "Expected no"))))
+//! );
+//! ```
+
+use core::marker::PhantomData;
+use core::ops::{BitOr, Mul, Shl, Shr};
 
 use crate::core::{Parsable, Parser};
 use crate::types::*;
 
+/// Wrapper enabling operator syntax on parsers. See the module documentation.
+///
+/// Construct it with the `P` function: `P(parser)`.
+pub struct P<T, Input, Output, Error> {
+    parser: T,
+    _phantom: PhantomData<fn(Input) -> (Output, Error)>,
+}
+
+/// Wraps a parser to enable operator syntax.
+#[allow(non_snake_case)]
+pub fn P<T, Input, Output, Error>(parser: T) -> P<T, Input, Output, Error>
+where
+    T: Parser<Input, Output, Error>,
+    Input: Parsable<Error>,
+    Error: Clone,
+{
+    P {
+        parser,
+        _phantom: PhantomData,
+    }
+}
+
+impl<T, Input, Output, Error> P<T, Input, Output, Error> {
+    /// Returns the wrapped parser.
+    pub fn into_inner(self) -> T {
+        self.parser
+    }
+}
+
+impl<T, Input, Output, Error> Parser<Input, Output, Error> for P<T, Input, Output, Error>
+where
+    T: Parser<Input, Output, Error>,
+    Input: Parsable<Error>,
+    Error: Clone,
+{
+    fn parse(&self, input: Input) -> Result<(Input, Output), (Input, Error)> {
+        self.parser.parse(input)
+    }
+}
+
+/// Parser produced by `>>` on `P`, keeping both outputs.
+pub struct SeqOp<A, B>(A, B);
+
+/// Parser produced by `|` on `P`.
+pub struct AltOp<A, B>(A, B);
+
+/// Parser produced by `<<` on `P`, keeping the left output.
+pub struct KeepLeftOp<A, B>(A, B);
+
+/// Parser produced by `*` on `P`, keeping the right output.
+pub struct KeepRightOp<A, B>(A, B);
+
+impl<T1, T2, In, Out1, Out2, Error1, Error2>
+    Parser<In, (Out1, Out2), Either<Error1, Error2>>
+    for SeqOp<P<T1, In, Out1, Error1>, P<T2, In, Out2, Error2>>
+where
+    T1: Parser<In, Out1, Error1>,
+    T2: Parser<In, Out2, Error2>,
+    In: Parsable<Error1> + Parsable<Error2> + Parsable<Either<Error1, Error2>>,
+    Error1: Clone,
+    Error2: Clone,
+{
+    fn parse(&self, input: In) -> Result<(In, (Out1, Out2)), (In, Either<Error1, Error2>)> {
+        (|i| self.0.parse(i)).seq(|i| self.1.parse(i)).parse(input)
+    }
+}
+
+impl<T1, T2, In, Out1, Out2, Error1, Error2>
+    Parser<In, Either<Out1, Out2>, (Error1, Error2)>
+    for AltOp<P<T1, In, Out1, Error1>, P<T2, In, Out2, Error2>>
+where
+    T1: Parser<In, Out1, Error1>,
+    T2: Parser<In, Out2, Error2>,
+    In: Parsable<Error1> + Parsable<Error2> + Parsable<(Error1, Error2)>,
+    Error1: Clone,
+    Error2: Clone,
+{
+    fn parse(&self, input: In) -> Result<(In, Either<Out1, Out2>), (In, (Error1, Error2))> {
+        (|i| self.0.parse(i)).alt(|i| self.1.parse(i)).parse(input)
+    }
+}
+
+impl<T1, T2, In, Out1, Out2, Error1, Error2> Parser<In, Out1, Either<Error1, Error2>>
+    for KeepLeftOp<P<T1, In, Out1, Error1>, P<T2, In, Out2, Error2>>
+where
+    T1: Parser<In, Out1, Error1>,
+    T2: Parser<In, Out2, Error2>,
+    In: Parsable<Error1> + Parsable<Error2> + Parsable<Either<Error1, Error2>>,
+    Error1: Clone,
+    Error2: Clone,
+{
+    fn parse(&self, input: In) -> Result<(In, Out1), (In, Either<Error1, Error2>)> {
+        (|i| self.0.parse(i))
+            .seq(|i| self.1.parse(i))
+            .map(|(out, _)| out)
+            .parse(input)
+    }
+}
+
+impl<T1, T2, In, Out1, Out2, Error1, Error2> Parser<In, Out2, Either<Error1, Error2>>
+    for KeepRightOp<P<T1, In, Out1, Error1>, P<T2, In, Out2, Error2>>
+where
+    T1: Parser<In, Out1, Error1>,
+    T2: Parser<In, Out2, Error2>,
+    In: Parsable<Error1> + Parsable<Error2> + Parsable<Either<Error1, Error2>>,
+    Error1: Clone,
+    Error2: Clone,
+{
+    fn parse(&self, input: In) -> Result<(In, Out2), (In, Either<Error1, Error2>)> {
+        (|i| self.0.parse(i))
+            .seq(|i| self.1.parse(i))
+            .map(|(_, out)| out)
+            .parse(input)
+    }
+}
+
+impl<T1, T2, In, Out1, Out2, Error1, Error2> Shr<P<T2, In, Out2, Error2>>
+    for P<T1, In, Out1, Error1>
+{
+    type Output = P<
+        SeqOp<P<T1, In, Out1, Error1>, P<T2, In, Out2, Error2>>,
+        In,
+        (Out1, Out2),
+        Either<Error1, Error2>,
+    >;
+
+    fn shr(self, rhs: P<T2, In, Out2, Error2>) -> Self::Output {
+        P {
+            parser: SeqOp(self, rhs),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T1, T2, In, Out1, Out2, Error1, Error2> BitOr<P<T2, In, Out2, Error2>>
+    for P<T1, In, Out1, Error1>
+{
+    type Output = P<
+        AltOp<P<T1, In, Out1, Error1>, P<T2, In, Out2, Error2>>,
+        In,
+        Either<Out1, Out2>,
+        (Error1, Error2),
+    >;
+
+    fn bitor(self, rhs: P<T2, In, Out2, Error2>) -> Self::Output {
+        P {
+            parser: AltOp(self, rhs),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T1, T2, In, Out1, Out2, Error1, Error2> Shl<P<T2, In, Out2, Error2>>
+    for P<T1, In, Out1, Error1>
+{
+    type Output = P<
+        KeepLeftOp<P<T1, In, Out1, Error1>, P<T2, In, Out2, Error2>>,
+        In,
+        Out1,
+        Either<Error1, Error2>,
+    >;
+
+    fn shl(self, rhs: P<T2, In, Out2, Error2>) -> Self::Output {
+        P {
+            parser: KeepLeftOp(self, rhs),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T1, T2, In, Out1, Out2, Error1, Error2> Mul<P<T2, In, Out2, Error2>>
+    for P<T1, In, Out1, Error1>
+{
+    type Output = P<
+        KeepRightOp<P<T1, In, Out1, Error1>, P<T2, In, Out2, Error2>>,
+        In,
+        Out2,
+        Either<Error1, Error2>,
+    >;
+
+    fn mul(self, rhs: P<T2, In, Out2, Error2>) -> Self::Output {
+        P {
+            parser: KeepRightOp(self, rhs),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 /// Trait for parser sugar, providing convenient methods for parser combinators.
 ///
 /// This trait simplifies the creation of complex parsers by enabling the
@@ -28,6 +252,15 @@ pub trait ParserSugar<
     fn or(self) -> impl Parser<In, <SeqOut as ToOrOutput<In>>::OrOutput, AltError>
     where
         In: Clone;
+
+    /// The same as `alt`, spelled out next to `alt_last` and `alt_longest` when the
+    /// policy matters to the reader.
+    fn alt_first(self) -> impl Parser<In, AltOut, AltError>
+    where
+        Self: Sized,
+    {
+        self.alt()
+    }
 }
 
 /// Type family for converting from sequence output to or output
@@ -1683,3 +1916,467 @@ where
             })
     }
 }
+
+// Implements `ToOrOutput` and `ParserSugar` for a tuple of parsers, running the parsers
+// directly instead of nesting binary combinators. Used for the arities of the
+// `wide-tuples` feature; a downstream crate cannot add these impls itself because of the
+// orphan rules.
+#[cfg(feature = "wide-tuples")]
+macro_rules! define_parser_tuple {
+    ($Either:ident; $($P:ident $O:ident $E:ident $idx:tt $Variant:ident),+) => {
+        impl<In, $($O),+> ToOrOutput<In> for ($($O,)+) {
+            type OrOutput = ($(Option<(In, $O)>,)+);
+        }
+
+        impl<In, $($P, $O, $E),+> ParserSugar<In, ($($O,)+), $Either<$($E),+>, $Either<$($O),+>, ($($E,)+)>
+            for ($($P,)+)
+        where
+            $($P: Parser<In, $O, $E>, $E: Clone, In: Parsable<$E>,)+
+            In: Parsable<$Either<$($E),+>> + Parsable<($($E,)+)>,
+        {
+            fn seq(self) -> impl Parser<In, ($($O,)+), $Either<$($E),+>> {
+                #[allow(non_snake_case)]
+                move |input: In| {
+                    let rest = input;
+                    $(
+                        let (rest, $O) = match self.$idx.parse(rest) {
+                            Ok(ok) => ok,
+                            Err((rest, err)) => return Err((rest, $Either::$Variant(err))),
+                        };
+                    )+
+                    Ok((rest, ($($O,)+)))
+                }
+            }
+
+            fn alt(self) -> impl Parser<In, $Either<$($O),+>, ($($E,)+)> {
+                #[allow(non_snake_case)]
+                move |input: In| {
+                    let rest = input;
+                    $(
+                        let (rest, $E) = match self.$idx.parse(rest) {
+                            Ok((rest, output)) => return Ok((rest, $Either::$Variant(output))),
+                            Err(err) => err,
+                        };
+                    )+
+                    Err((rest, ($($E,)+)))
+                }
+            }
+
+            fn or(self) -> impl Parser<In, ($(Option<(In, $O)>,)+), ($($E,)+)>
+            where
+                In: Clone,
+            {
+                #[allow(non_snake_case)]
+                move |input: In| match ($(self.$idx.parse(input.clone()),)+) {
+                    ($(Err((_, $E)),)+) => Err((input, ($($E,)+))),
+                    results => Ok((input, ($(results.$idx.ok(),)+))),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "wide-tuples")]
+define_parser_tuple!(Either11; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11);
+#[cfg(feature = "wide-tuples")]
+define_parser_tuple!(Either12; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12);
+#[cfg(feature = "wide-tuples")]
+define_parser_tuple!(Either13; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13);
+#[cfg(feature = "wide-tuples")]
+define_parser_tuple!(Either14; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14);
+#[cfg(feature = "wide-tuples")]
+define_parser_tuple!(Either15; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14, P15 O15 E15 14 _15);
+#[cfg(feature = "wide-tuples")]
+define_parser_tuple!(Either16; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14, P15 O15 E15 14 _15, P16 O16 E16 15 _16);
+
+/// Parses the parsers of a tuple in any order.
+pub trait PermutationSugar<In: Parsable<Error>, Out, Error: Clone> {
+    /// Creates a parser that matches every parser of this tuple exactly once, in any order,
+    /// returning their outputs in declaration order.
+    ///
+    /// At each position the first parser not matched yet that succeeds is taken. If none of
+    /// the remaining parsers matches, the error holds their errors, with `None` for the
+    /// parsers that already matched.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let flags = (
+    ///     "-a".make_literal_matcher("Expected -a"),
+    ///     "-b".make_literal_matcher("Expected -b"),
+    ///     "-c".make_literal_matcher("Expected -c"),
+    /// )
+    ///     .permutation();
+    ///
+    /// assert_eq!(flags.parse("-c-a-b"), Ok(("", ("-a", "-b", "-c"))));
+    /// assert_eq!(flags.parse("-b-a-x"), Err(("-x", (None, None, Some("Expected -c")))));
+    /// ```
+    fn permutation(self) -> impl Parser<In, Out, Error>;
+}
+
+macro_rules! impl_permutation {
+    ($($P:ident $O:ident $E:ident $idx:tt),+) => {
+        impl<In, $($P, $O, $E),+> PermutationSugar<In, ($($O,)+), ($(Option<$E>,)+)> for ($($P,)+)
+        where
+            $($P: Parser<In, $O, $E>, $E: Clone, In: Parsable<$E>,)+
+            In: Parsable<($(Option<$E>,)+)>,
+        {
+            #[allow(non_snake_case)]
+            fn permutation(self) -> impl Parser<In, ($($O,)+), ($(Option<$E>,)+)> {
+                move |input: In| {
+                    let mut outputs = ($(None::<$O>,)+);
+                    let mut errors = ($(None::<$E>,)+);
+                    let mut rest = input;
+                    loop {
+                        let mut matched = false;
+                        $(
+                            if !matched && outputs.$idx.is_none() {
+                                match self.$idx.parse(rest.clone()) {
+                                    Ok((next, output)) => {
+                                        outputs.$idx = Some(output);
+                                        errors.$idx = None;
+                                        rest = next;
+                                        matched = true;
+                                    }
+                                    Err((_, err)) => errors.$idx = Some(err),
+                                }
+                            }
+                        )+
+                        if !matched {
+                            return Err((rest, errors));
+                        }
+                        if let ($(Some($O),)+) = outputs {
+                            return Ok((rest, ($($O,)+)));
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
+
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1);
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2);
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3);
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4);
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5);
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6);
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6, P8 O8 E8 7);
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6, P8 O8 E8 7, P9 O9 E9 8);
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6, P8 O8 E8 7, P9 O9 E9 8, P10 O10 E10 9);
+#[cfg(feature = "wide-tuples")]
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6, P8 O8 E8 7, P9 O9 E9 8, P10 O10 E10 9, P11 O11 E11 10);
+#[cfg(feature = "wide-tuples")]
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6, P8 O8 E8 7, P9 O9 E9 8, P10 O10 E10 9, P11 O11 E11 10, P12 O12 E12 11);
+#[cfg(feature = "wide-tuples")]
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6, P8 O8 E8 7, P9 O9 E9 8, P10 O10 E10 9, P11 O11 E11 10, P12 O12 E12 11, P13 O13 E13 12);
+#[cfg(feature = "wide-tuples")]
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6, P8 O8 E8 7, P9 O9 E9 8, P10 O10 E10 9, P11 O11 E11 10, P12 O12 E12 11, P13 O13 E13 12, P14 O14 E14 13);
+#[cfg(feature = "wide-tuples")]
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6, P8 O8 E8 7, P9 O9 E9 8, P10 O10 E10 9, P11 O11 E11 10, P12 O12 E12 11, P13 O13 E13 12, P14 O14 E14 13, P15 O15 E15 14);
+#[cfg(feature = "wide-tuples")]
+impl_permutation!(P1 O1 E1 0, P2 O2 E2 1, P3 O3 E3 2, P4 O4 E4 3, P5 O5 E5 4, P6 O6 E6 5, P7 O7 E7 6, P8 O8 E8 7, P9 O9 E9 8, P10 O10 E10 9, P11 O11 E11 10, P12 O12 E12 11, P13 O13 E13 12, P14 O14 E14 13, P15 O15 E15 14, P16 O16 E16 15);
+
+/// Longest-match alternation over the parsers of a tuple.
+pub trait LongestSugar<In: Parsable<Error>, Out, Error: Clone> {
+    /// Creates a parser that tries every parser of this tuple on the same input and commits
+    /// to the one consuming the most input. Ties are resolved in declaration order.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let operator = (
+    ///     "=".make_literal_matcher("Expected ="),
+    ///     "==".make_literal_matcher("Expected =="),
+    ///     "===".make_literal_matcher("Expected ==="),
+    /// )
+    ///     .alt_longest();
+    ///
+    /// assert_eq!(operator.parse("== b"), Ok((" b", Either3::Middle("=="))));
+    /// assert_eq!(operator.parse("=== b"), Ok((" b", Either3::Right("==="))));
+    /// ```
+    fn alt_longest(self) -> impl Parser<In, Out, Error>;
+}
+
+/// Expands to the body of `alt_longest`: alternatives are tried in order, collecting their
+/// errors, until one matches; that match is then kept as the best and the remaining
+/// alternatives only replace it with a longer one.
+macro_rules! alt_longest_body {
+    (@failed $self:ident, $input:ident, $Either:ident, [$($failed:ident)*],) => {
+        Err(($input, ($($failed,)*)))
+    };
+    (@failed $self:ident, $input:ident, $Either:ident, [$($failed:ident)*],
+        $E:ident $idx:tt $Variant:ident, $($rest:tt)*) => {
+        match $self.$idx.parse($input.clone()) {
+            Ok((rest, output)) => {
+                #[allow(unused_mut)] // Never replaced when the last alternative matched first
+                let mut best = (rest.remaining(), rest, $Either::$Variant(output));
+                alt_longest_body!(@matched $self, $input, $Either, best, $($rest)*);
+                Ok((best.1, best.2))
+            }
+            Err((_, $E)) => alt_longest_body!(@failed $self, $input, $Either, [$($failed)* $E], $($rest)*),
+        }
+    };
+    (@matched $self:ident, $input:ident, $Either:ident, $best:ident, $($E:ident $idx:tt $Variant:ident,)*) => {
+        $(
+            if let Ok((rest, output)) = $self.$idx.parse($input.clone()) {
+                let remaining = rest.remaining();
+                if remaining < $best.0 {
+                    $best = (remaining, rest, $Either::$Variant(output));
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_alt_longest {
+    ($Either:ident; $($P:ident $O:ident $E:ident $idx:tt $Variant:ident),+) => {
+        impl<In, $($P, $O, $E),+> LongestSugar<In, $Either<$($O),+>, ($($E,)+)> for ($($P,)+)
+        where
+            $($P: Parser<In, $O, $E>, $E: Clone, In: Parsable<$E>,)+
+            In: Parsable<($($E,)+)> + Locate,
+        {
+            fn alt_longest(self) -> impl Parser<In, $Either<$($O),+>, ($($E,)+)> {
+                #[allow(non_snake_case)]
+                move |input: In| alt_longest_body!(@failed self, input, $Either, [], $($E $idx $Variant,)+)
+            }
+        }
+    };
+}
+
+impl_alt_longest!(Either; P1 O1 E1 0 Left, P2 O2 E2 1 Right);
+impl_alt_longest!(Either3; P1 O1 E1 0 Left, P2 O2 E2 1 Middle, P3 O3 E3 2 Right);
+impl_alt_longest!(Either4; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4);
+impl_alt_longest!(Either5; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5);
+impl_alt_longest!(Either6; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6);
+impl_alt_longest!(Either7; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7);
+impl_alt_longest!(Either8; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8);
+impl_alt_longest!(Either9; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9);
+impl_alt_longest!(Either10; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10);
+#[cfg(feature = "wide-tuples")]
+impl_alt_longest!(Either11; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11);
+#[cfg(feature = "wide-tuples")]
+impl_alt_longest!(Either12; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12);
+#[cfg(feature = "wide-tuples")]
+impl_alt_longest!(Either13; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13);
+#[cfg(feature = "wide-tuples")]
+impl_alt_longest!(Either14; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14);
+#[cfg(feature = "wide-tuples")]
+impl_alt_longest!(Either15; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14, P15 O15 E15 14 _15);
+#[cfg(feature = "wide-tuples")]
+impl_alt_longest!(Either16; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14, P15 O15 E15 14 _15, P16 O16 E16 15 _16);
+
+/// Alternation over the parsers of a tuple preferring the later alternatives.
+pub trait LastSugar<In: Parsable<Error>, Out, Error: Clone> {
+    /// Creates a parser that tries the parsers of this tuple from the last to the first,
+    /// returning the first success, so a later alternative wins over an earlier one that
+    /// would also match. If all of them fail, the errors are reported at the start of the
+    /// input in declaration order.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let setting = |value: &'static str| "color".make_literal_matcher("Expected color").value(value);
+    /// let color = (setting("default"), setting("system"), setting("user")).alt_last();
+    ///
+    /// assert_eq!(color.parse("color"), Ok(("", Either3::Right("user"))));
+    /// assert_eq!(color.parse("size").unwrap_err().1, ("Expected color", "Expected color", "Expected color"));
+    /// ```
+    fn alt_last(self) -> impl Parser<In, Out, Error>;
+}
+
+// The parsers are passed in declaration order and again reversed, for the order of the
+// attempts; the reversal is done by the `@reverse` rules.
+macro_rules! impl_alt_last {
+    ($Either:ident; $($P:ident $O:ident $E:ident $idx:tt $Variant:ident),+) => {
+        impl_alt_last!(@reverse $Either; [$(($P $O $E $idx $Variant))+] [$(($P $O $E $idx $Variant))+] []);
+    };
+    (@reverse $Either:ident; $forward:tt [$head:tt $($tail:tt)*] [$($reversed:tt)*]) => {
+        impl_alt_last!(@reverse $Either; $forward [$($tail)*] [$head $($reversed)*]);
+    };
+    (@reverse $Either:ident; [$(($P:ident $O:ident $E:ident $idx:tt $Variant:ident))+] [] [$(($RP:ident $RO:ident $RE:ident $ridx:tt $RVariant:ident))+]) => {
+        impl<In, $($P, $O, $E),+> LastSugar<In, $Either<$($O),+>, ($($E,)+)> for ($($P,)+)
+        where
+            $($P: Parser<In, $O, $E>, $E: Clone, In: Parsable<$E>,)+
+            In: Parsable<($($E,)+)>,
+        {
+            fn alt_last(self) -> impl Parser<In, $Either<$($O),+>, ($($E,)+)> {
+                #[allow(non_snake_case)]
+                move |input: In| {
+                    let checkpoint = input.checkpoint();
+                    $(
+                        let $RE = match self.$ridx.parse(In::rewind(checkpoint.clone())) {
+                            Ok((rest, output)) => return Ok((rest, $Either::$RVariant(output))),
+                            Err((_, err)) => err,
+                        };
+                    )+
+                    Err((In::rewind(checkpoint), ($($E,)+)))
+                }
+            }
+        }
+    };
+}
+
+impl_alt_last!(Either; P1 O1 E1 0 Left, P2 O2 E2 1 Right);
+impl_alt_last!(Either3; P1 O1 E1 0 Left, P2 O2 E2 1 Middle, P3 O3 E3 2 Right);
+impl_alt_last!(Either4; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4);
+impl_alt_last!(Either5; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5);
+impl_alt_last!(Either6; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6);
+impl_alt_last!(Either7; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7);
+impl_alt_last!(Either8; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8);
+impl_alt_last!(Either9; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9);
+impl_alt_last!(Either10; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10);
+#[cfg(feature = "wide-tuples")]
+impl_alt_last!(Either11; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11);
+#[cfg(feature = "wide-tuples")]
+impl_alt_last!(Either12; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12);
+#[cfg(feature = "wide-tuples")]
+impl_alt_last!(Either13; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13);
+#[cfg(feature = "wide-tuples")]
+impl_alt_last!(Either14; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14);
+#[cfg(feature = "wide-tuples")]
+impl_alt_last!(Either15; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14, P15 O15 E15 14 _15);
+#[cfg(feature = "wide-tuples")]
+impl_alt_last!(Either16; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14, P15 O15 E15 14 _15, P16 O16 E16 15 _16);
+
+/// Alternation over the parsers of a tuple reporting the failure that got furthest.
+pub trait FarthestSugar<In: Parsable<Error>, Out, Error: Clone> {
+    /// Creates a parser that tries every parser of this tuple on the same input, returning
+    /// the first success like `alt`. If all of them fail, the error is the one of the parser
+    /// that got furthest into the input, reported where it failed, with the errors of all
+    /// parsers in the notes. Ties are resolved in declaration order.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let lit = |s: &'static str| s.make_literal_matcher(s);
+    /// let statement = (
+    ///     lit("let ").seq(lit("x")),
+    ///     lit("if ").seq(lit("(")),
+    ///     lit("loop").map(|_| ("loop", "")),
+    /// )
+    ///     .alt_farthest();
+    ///
+    /// let (rest, err) = statement.parse("if x").unwrap_err();
+    /// assert_eq!(rest, "x");
+    /// assert_eq!(err.error, Either3::Middle(Either::Right("(")));
+    /// assert_eq!(err.notes.2, ("loop", 4));
+    /// ```
+    fn alt_farthest(self) -> impl Parser<In, Out, Error>;
+}
+
+macro_rules! impl_alt_farthest {
+    ($Either:ident; $P1:ident $O1:ident $E1:ident $idx1:tt $Variant1:ident
+        $(, $P:ident $O:ident $E:ident $idx:tt $Variant:ident)+) => {
+        impl<In, $P1, $O1, $E1, $($P, $O, $E),+>
+            FarthestSugar<In, $Either<$O1, $($O),+>, Farthest<$Either<$E1, $($E),+>, (($E1, usize), $(($E, usize),)+)>>
+            for ($P1, $($P,)+)
+        where
+            $P1: Parser<In, $O1, $E1>, $E1: Clone, In: Parsable<$E1>,
+            $($P: Parser<In, $O, $E>, $E: Clone, In: Parsable<$E>,)+
+            In: Parsable<Farthest<$Either<$E1, $($E),+>, (($E1, usize), $(($E, usize),)+)>> + Locate,
+        {
+            fn alt_farthest(
+                self,
+            ) -> impl Parser<In, $Either<$O1, $($O),+>, Farthest<$Either<$E1, $($E),+>, (($E1, usize), $(($E, usize),)+)>> {
+                #[allow(non_snake_case, clippy::result_large_err)]
+                move |input: In| {
+                    let $E1 = match self.$idx1.parse(input.clone()) {
+                        Ok((rest, output)) => return Ok((rest, $Either::$Variant1(output))),
+                        Err((rest, err)) => (rest.remaining(), rest, err),
+                    };
+                    $(
+                        let $E = match self.$idx.parse(input.clone()) {
+                            Ok((rest, output)) => return Ok((rest, $Either::$Variant(output))),
+                            Err((rest, err)) => (rest.remaining(), rest, err),
+                        };
+                    )+
+                    let notes = (($E1.2.clone(), $E1.0), $(($E.2.clone(), $E.0),)+);
+                    // Ties go to the earlier alternative
+                    let mut farthest = ($E1.0, $E1.1, $Either::$Variant1($E1.2));
+                    $(
+                        if $E.0 < farthest.0 {
+                            farthest = ($E.0, $E.1, $Either::$Variant($E.2));
+                        }
+                    )+
+                    let (remaining, rest, error) = farthest;
+                    Err((rest, Farthest { error, remaining, notes }))
+                }
+            }
+        }
+    };
+}
+
+impl_alt_farthest!(Either; P1 O1 E1 0 Left, P2 O2 E2 1 Right);
+impl_alt_farthest!(Either3; P1 O1 E1 0 Left, P2 O2 E2 1 Middle, P3 O3 E3 2 Right);
+impl_alt_farthest!(Either4; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4);
+impl_alt_farthest!(Either5; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5);
+impl_alt_farthest!(Either6; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6);
+impl_alt_farthest!(Either7; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7);
+impl_alt_farthest!(Either8; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8);
+impl_alt_farthest!(Either9; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9);
+impl_alt_farthest!(Either10; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10);
+#[cfg(feature = "wide-tuples")]
+impl_alt_farthest!(Either11; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11);
+#[cfg(feature = "wide-tuples")]
+impl_alt_farthest!(Either12; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12);
+#[cfg(feature = "wide-tuples")]
+impl_alt_farthest!(Either13; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13);
+#[cfg(feature = "wide-tuples")]
+impl_alt_farthest!(Either14; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14);
+#[cfg(feature = "wide-tuples")]
+impl_alt_farthest!(Either15; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14, P15 O15 E15 14 _15);
+#[cfg(feature = "wide-tuples")]
+impl_alt_farthest!(Either16; P1 O1 E1 0 _1, P2 O2 E2 1 _2, P3 O3 E3 2 _3, P4 O4 E4 3 _4, P5 O5 E5 4 _5, P6 O6 E6 5 _6, P7 O7 E7 6 _7, P8 O8 E8 7 _8, P9 O9 E9 8 _9, P10 O10 E10 9 _10, P11 O11 E11 10 _11, P12 O12 E12 11 _12, P13 O13 E13 12 _13, P14 O14 E14 13 _14, P15 O15 E15 14 _15, P16 O16 E16 15 _16);
+
+/// Sequences parsers like the tuple `seq`, keeping only the outputs of the parsers marked
+/// with `keep(...)`.
+///
+/// A single kept output is returned as is, several as a tuple in order, and none as `()`.
+/// The error is the `Either` of the tuple `seq`, with a variant for every parser, kept or
+/// not. Takes 2 to 10 parsers, or up to 16 with the `wide-tuples` feature.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+///
+/// let word = |w: &'static str| w.make_literal_matcher("Expected word");
+/// let punct = |p: &'static str| p.make_literal_matcher("Expected punctuation");
+///
+/// let pair = seq_keep!(punct("("), keep(word("a")), punct(","), keep(word("b")), punct(")"));
+/// let inner = seq_keep!(punct("["), keep(word("a")), punct("]"));
+///
+/// assert_eq!(pair.parse("(a,b)!"), Ok(("!", ("a", "b"))));
+/// assert_eq!(inner.parse("[a]"), Ok(("", "a")));
+/// assert_eq!(pair.parse("(a;b)"), Err((";b)", Either5::_3("Expected punctuation"))));
+/// ```
+#[macro_export]
+macro_rules! seq_keep {
+    (@munch [$($parser:expr,)*] [$($pattern:tt)*] [$($kept:tt)*] keep($next:expr) $(, $($rest:tt)*)?) => {
+        $crate::seq_keep!(@munch [$($parser,)* $next,] [$($pattern)* kept,] [$($kept)* kept,] $($($rest)*)?)
+    };
+    (@munch [$($parser:expr,)*] [$($pattern:tt)*] [$($kept:tt)*] $next:expr $(, $($rest:tt)*)?) => {
+        $crate::seq_keep!(@munch [$($parser,)* $next,] [$($pattern)* _,] [$($kept)*] $($($rest)*)?)
+    };
+    (@munch [$($parser:expr,)*] [$($pattern:tt)*] []) => {
+        $crate::Parser::map($crate::ParserSugar::seq(($($parser,)*)), |($($pattern)*)| ())
+    };
+    (@munch [$($parser:expr,)*] [$($pattern:tt)*] [$only:tt,]) => {
+        $crate::Parser::map($crate::ParserSugar::seq(($($parser,)*)), |($($pattern)*)| $only)
+    };
+    (@munch [$($parser:expr,)*] [$($pattern:tt)*] [$($kept:tt)*]) => {
+        $crate::Parser::map($crate::ParserSugar::seq(($($parser,)*)), |($($pattern)*)| ($($kept)*))
+    };
+    ($($parsers:tt)+) => {
+        $crate::seq_keep!(@munch [] [] [] $($parsers)+)
+    };
+}
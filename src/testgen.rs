@@ -0,0 +1,260 @@
+//! # Test Input Generation
+//!
+//! Property tests need many inputs that a grammar accepts. A `Syntax` describes a grammar
+//! with the literal, item, seq, alt and many combinators, and is at once a parser for it
+//! and a generator of random inputs:
+//!
+//! - `literal(text)` matches fixed text
+//! - `item(ranges)` matches one character from inclusive ranges
+//! - `seq(parts)` and `alt(choices)` mirror `Parser::seq` and `Parser::alt`
+//! - `many(syntax)` and `many1(syntax)` mirror `Parser::many`, generating at most
+//!   `max_repeat` repetitions
+//!
+//! As a parser, a `Syntax` returns the slice it recognized. Because `alt` commits to the
+//! first choice that matches and `many` is greedy, a randomly built string is not always
+//! accepted; `generate` therefore draws samples until the parser accepts one completely.
+//!
+//! Generation is deterministic for a seed, so a failing case is reproduced by its seed. With
+//! proptest or quickcheck, generate a `u64` and call `generate(seed)` in the property.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::testgen::*;
+//!
+//! let digit = item(&[('0', '9')]);
+//! let number = seq([item(&[('1', '9')]), many(digit)]);
+//! let list = seq([literal("["), number.clone(), many(seq([literal(","), number])), literal("]")]);
+//!
+//! for seed in 0..100 {
+//!     let input = list.generate(seed).unwrap();
+//!     assert_eq!(list.parse(input.as_str()), Ok(("", input.as_str())));
+//! }
+//! assert_eq!(list.generate(7), list.generate(7));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use crate::core::Parser;
+use crate::parsers::consumed;
+
+/// How many samples `Syntax::generate` draws before giving up.
+const MAX_SAMPLES: usize = 100;
+
+/// A grammar description that parses and generates its inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Syntax {
+    Literal(String),
+    /// One character from any of the inclusive ranges
+    Item(Vec<(char, char)>),
+    Seq(Vec<Syntax>),
+    Alt(Vec<Syntax>),
+    /// At least `min` repetitions, and at most `min + max_repeat` when generating
+    Many {
+        syntax: Box<Syntax>,
+        min: usize,
+        max_repeat: usize,
+    },
+}
+
+/// Matches `text`.
+pub fn literal(text: &str) -> Syntax {
+    Syntax::Literal(String::from(text))
+}
+
+/// Matches one character from the inclusive `ranges`.
+pub fn item(ranges: &[(char, char)]) -> Syntax {
+    Syntax::Item(ranges.to_vec())
+}
+
+/// Matches all `parts` in order.
+pub fn seq(parts: impl IntoIterator<Item = Syntax>) -> Syntax {
+    Syntax::Seq(parts.into_iter().collect())
+}
+
+/// Matches the first of `choices` that matches.
+pub fn alt(choices: impl IntoIterator<Item = Syntax>) -> Syntax {
+    Syntax::Alt(choices.into_iter().collect())
+}
+
+/// Matches `syntax` zero or more times.
+pub fn many(syntax: Syntax) -> Syntax {
+    Syntax::Many {
+        syntax: Box::new(syntax),
+        min: 0,
+        max_repeat: 3,
+    }
+}
+
+/// Matches `syntax` one or more times.
+pub fn many1(syntax: Syntax) -> Syntax {
+    Syntax::Many {
+        syntax: Box::new(syntax),
+        min: 1,
+        max_repeat: 3,
+    }
+}
+
+/// The input does not match a `Syntax`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoMatch;
+
+impl Display for NoMatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "input does not match the syntax")
+    }
+}
+
+impl core::error::Error for NoMatch {}
+
+/// A small deterministic random number generator (SplitMix64).
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub const fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number below `n`, which must not be zero.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+impl Syntax {
+    /// Sets the number of repetitions a `many` may generate beyond its minimum.
+    pub fn max_repeat(self, max_repeat: usize) -> Self {
+        match self {
+            Syntax::Many { syntax, min, .. } => Syntax::Many { syntax, min, max_repeat },
+            other => other,
+        }
+    }
+
+    /// Appends a random string built from the description to `out`. The parser may reject
+    /// it, see the module documentation.
+    pub fn sample(&self, rng: &mut Rng, out: &mut String) {
+        match self {
+            Syntax::Literal(text) => out.push_str(text),
+            Syntax::Item(ranges) => {
+                let sizes = ranges.iter().map(|&(lo, hi)| (hi as u64 + 1).saturating_sub(lo as u64));
+                let mut pick = rng.below(sizes.clone().sum::<u64>().max(1));
+                for (size, &(lo, _)) in sizes.zip(ranges) {
+                    if pick < size {
+                        // Skip the surrogate gap, which no `char` lies in
+                        if let Some(c) = (0..size - pick).find_map(|i| char::from_u32((lo as u64 + pick + i) as u32)) {
+                            out.push(c);
+                        }
+                        return;
+                    }
+                    pick -= size;
+                }
+            }
+            Syntax::Seq(parts) => parts.iter().for_each(|part| part.sample(rng, out)),
+            Syntax::Alt(choices) => {
+                if !choices.is_empty() {
+                    let pick = rng.below(choices.len() as u64) as usize;
+                    if let Some(choice) = choices.get(pick) {
+                        choice.sample(rng, out);
+                    }
+                }
+            }
+            Syntax::Many { syntax, min, max_repeat } => {
+                let count = min + rng.below(*max_repeat as u64 + 1) as usize;
+                (0..count).for_each(|_| syntax.sample(rng, out));
+            }
+        }
+    }
+
+    /// Returns a random input that this syntax accepts completely, or `None` if no sample
+    /// out of a hundred was accepted.
+    pub fn generate(&self, seed: u64) -> Option<String> {
+        let mut rng = Rng::new(seed);
+        (0..MAX_SAMPLES).find_map(|_| {
+            let mut out = String::new();
+            self.sample(&mut rng, &mut out);
+            matches!(self.parse(out.as_str()), Ok(("", _))).then_some(out)
+        })
+    }
+
+    /// Returns the input after a match, or the input at which matching failed.
+    fn recognize<'a>(&self, input: &'a str) -> Result<&'a str, &'a str> {
+        match self {
+            Syntax::Literal(text) => input.strip_prefix(text.as_str()).ok_or(input),
+            Syntax::Item(ranges) => {
+                let mut chars = input.chars();
+                match chars.next() {
+                    Some(c) if ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&c)) => Ok(chars.as_str()),
+                    _ => Err(input),
+                }
+            }
+            Syntax::Seq(parts) => parts.iter().try_fold(input, |rest, part| part.recognize(rest)),
+            Syntax::Alt(choices) => choices
+                .iter()
+                .find_map(|choice| choice.recognize(input).ok())
+                .ok_or(input),
+            Syntax::Many { syntax, min, .. } => {
+                let mut rest = input;
+                let mut count = 0;
+                while let Ok(next) = syntax.recognize(rest) {
+                    count += 1;
+                    if next.len() == rest.len() {
+                        break;
+                    }
+                    rest = next;
+                }
+                if count < *min {
+                    return Err(rest);
+                }
+                Ok(rest)
+            }
+        }
+    }
+}
+
+impl<'a> Parser<&'a str, &'a str, NoMatch> for Syntax {
+    fn parse(&self, input: &'a str) -> Result<(&'a str, &'a str), (&'a str, NoMatch)> {
+        match self.recognize(input) {
+            Ok(rest) => Ok((rest, consumed(input, rest))),
+            Err(rest) => Err((rest, NoMatch)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_inputs_are_accepted() {
+        // Ordered choice: "a" shadows "ab" unless followed by "c"
+        let shadowed = seq([alt([literal("a"), literal("ab")]), literal("c")]);
+        for seed in 0..20 {
+            assert_eq!(shadowed.generate(seed).as_deref(), Some("ac"));
+        }
+
+        let word = many1(item(&[('a', 'z'), ('é', 'é')])).max_repeat(10);
+        let words = seq([word.clone(), many(seq([literal(" "), word]))]);
+        for seed in 0..50 {
+            let input = words.generate(seed).unwrap();
+            assert!(input.chars().all(|c| c == ' ' || c == 'é' || c.is_ascii_lowercase()));
+        }
+
+        // Greedy repetition leaves nothing for the last `x`
+        assert_eq!(seq([many(literal("x")), literal("x")]).generate(1), None);
+        assert_eq!(many1(literal("ab")).parse("ababa"), Ok(("a", "abab")));
+        assert_eq!(many1(literal("ab")).parse("ba"), Err(("ba", NoMatch)));
+    }
+}
@@ -3,12 +3,34 @@
 //! This module contains tests for the parser combinator library.
 
 use crate::core::*;
-use crate::parsers::{Indentation, Offset, Position, WithState};
+use crate::parsers::{parse_str, Indentation, KeywordMatcher, Offset, Position, WithState};
 use crate::state::{ StateCarrier, StatefulParser};
 use crate::types::*;
 use core::str;
 
-use crate::sugar::ParserSugar;
+use crate::sugar::{FarthestSugar, LastSugar, LongestSugar, ParserSugar, PermutationSugar, P};
+use crate::patterns::PatternParser;
+use crate::formats::json::{json_value, JsonValue};
+
+/// Test folding and widening Eithers with different variant types
+#[test]
+fn test_either_heterogeneous_utilities() {
+    let value: Either3<i32, &str, bool> = Either3::Middle("abc");
+    assert_eq!(value.m_fold(|n| n as usize, str::len, usize::from), 3);
+
+    let widened: Either4<i32, &str, bool, char> = value.into();
+    assert_eq!(widened, Either4::_2("abc"));
+    let widened: Either3<i32, bool, ()> = Either::Right(true).into();
+    assert_eq!(widened, Either3::Middle(true));
+
+    let sizes = [Either4::_1(2u8), Either4::_2("four"), Either4::_3(3u8), Either4::_4("xy")].map(|value| {
+        crate::collapse!(value, Either4 {
+            _1 | _3 => |n: u8| n as usize,
+            _2 | _4 => str::len,
+        })
+    });
+    assert_eq!(sizes, [2, 4, 3, 2]);
+}
 
 #[test]
 fn test_either_simple_fold() {
@@ -74,6 +96,7 @@ fn test_recursive_parser() {
 }
 /// Test reverse application
 #[test]
+#[allow(clippy::extra_unused_lifetimes, clippy::assertions_on_constants)]
 fn test_rev_apply<'a>() {
     let parser = (
         [1].make_literal_matcher("1 failed"),
@@ -102,11 +125,16 @@ fn test_and_then() {
 fn test_exactly_n() {
     let parser = "a"
         .make_literal_matcher("No a")
-        .exactly_n::<3>("Need exactly 3");
+        .exactly_n::<3>();
 
     assert_eq!(parser.parse("aaa"), Ok(("", Box::new(["a", "a", "a"]))));
-    assert_eq!(parser.parse("aa"), Err(("", "Need exactly 3")));
+    assert_eq!(parser.parse("aa"), Err(("", RepetitionError { expected: 3, found: 2, error: "No a" })));
     assert_eq!(parser.parse("aaaa"), Ok(("a", Box::new(["a", "a", "a"]))));
+    assert_eq!(parser.parse("b"), Err(("b", RepetitionError { expected: 3, found: 0, error: "No a" })));
+
+    // Zero repetitions always succeed without consuming input
+    let none = "a".make_literal_matcher("No a").exactly_n::<0>();
+    assert_eq!(none.parse("aa"), Ok(("aa", Box::new([]))));
 }
 
 /// Test at least N parser
@@ -121,6 +149,30 @@ fn test_at_least_n() {
     assert_eq!(parser.parse("a"), Err(("", "Need at least 2")));
 }
 
+/// Test repetitions keeping their partial results on failure
+#[test]
+fn test_partial_repetition_errors() {
+    let word = || "ab".make_literal_matcher("No ab").map(String::from);
+
+    let exactly = word().exactly_n_partial::<3>();
+    assert_eq!(exactly.parse("abab").unwrap_err(), ("", PartialRepetition {
+        expected: 3,
+        items: vec!["ab".to_string(), "ab".to_string()],
+        error: "No ab",
+    }));
+    assert_eq!(exactly.parse("ab").unwrap_err().1.to_string(), "expected 3 repetitions, found 1: No ab");
+
+    // Like `at_least_n`, the repetition stops after the required matches
+    let at_least = word().at_least_n_partial(2);
+    assert_eq!(at_least.parse("ababx"), Ok(("x", vec!["ab".to_string(), "ab".to_string()])));
+    assert_eq!(at_least.parse("ababab"), Ok(("ab", vec!["ab".to_string(), "ab".to_string()])));
+    assert_eq!(at_least.parse("x").unwrap_err().1.index(), 0);
+    assert_eq!(word().at_least_n_partial(0).parse("x"), Ok(("x", vec![])));
+
+    let nothing = "".make_literal_matcher("Never").at_least_n_partial(2);
+    assert_eq!(nothing.parse("a"), Ok(("a", vec!["", ""])));
+}
+
 /// Test many parser with zero matches
 #[test]
 fn test_many_zero_matches() {
@@ -153,6 +205,843 @@ fn test_maybe() {
     assert_eq!(parser.parse("b"), Ok(("b", None)));
 }
 
+/// Test non_empty guard
+#[test]
+fn test_non_empty() {
+    let spaces = ' '
+        .make_character_matcher("No space")
+        .many()
+        .non_empty("Expected whitespace");
+
+    assert_eq!(spaces.parse("  x"), Ok(("x", vec![' ', ' '])));
+    assert_eq!(spaces.parse("x"), Err(("x", "Expected whitespace")));
+
+    // Errors of the inner parser are passed through unchanged
+    let a = "a".make_literal_matcher("No a").non_empty("Empty");
+    assert_eq!(a.parse("b"), Err(("b", "No a")));
+}
+
+/// Test end of input, rest and all_consuming on slices
+#[test]
+fn test_end_of_input_and_rest() {
+    let bytes: &[u8] = b"ab";
+    let a = (&b"a"[..]).make_literal_matcher("Expected a");
+
+    assert_eq!(a.all_consuming("Trailing input").parse(bytes), Err((&b"b"[..], "Trailing input")));
+    assert_eq!(rest::<_, ()>().parse(bytes), Ok((&b""[..], bytes)));
+    assert_eq!(end_of_input("Not at end").parse(&bytes[2..]), Ok((&b""[..], ())));
+    assert_eq!(end_of_input("Not at end").parse(bytes), Err((bytes, "Not at end")));
+}
+
+/// Test value and ignore
+#[test]
+fn test_value_and_ignore() {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Bool {
+        True,
+        False,
+    }
+
+    let t = "true".make_literal_matcher("Expected bool").value(Bool::True);
+    let f = "false".make_literal_matcher("Expected bool").value(Bool::False);
+    let boolean = |input| t.parse(input).or_else(|_| f.parse(input));
+
+    assert_eq!(boolean.parse("false"), Ok(("", Bool::False)));
+    assert_eq!(boolean.parse("true!"), Ok(("!", Bool::True)));
+    assert_eq!(boolean.ignore().parse("x"), Err(("x", "Expected bool")));
+}
+
+/// Test try_map converting mapper errors into parse errors
+#[test]
+fn test_try_map() {
+    #[derive(Debug, Clone, PartialEq)]
+    enum Error {
+        Syntax,
+        Invalid(String),
+    }
+
+    let anything = <&str as Parsable<Error>>::make_anything_matcher(Error::Syntax);
+    let hex = anything
+        .many()
+        .try_map(
+            |cs| u32::from_str_radix(&cs.into_iter().collect::<String>(), 16),
+            |e| Error::Invalid(e.to_string()),
+        );
+
+    assert_eq!(hex.parse("ff"), Ok(("", 255)));
+    assert_eq!(
+        hex.parse("fg"),
+        Err(("fg", Error::Invalid("invalid digit found in string".to_string())))
+    );
+}
+
+/// Test left-recursive definitions associating to the left
+#[test]
+fn test_recursive_left_associativity() {
+    let digit = || <&str as Parsable<&str>>::make_anything_matcher("Expected digit")
+        .try_map(|c| c.to_digit(10).ok_or(()), |_| "Expected digit")
+        .map(|d| d as i32);
+
+    // diff := diff '-' digit | digit
+    let diff = recursive_left("Expected expression", move |diff| {
+        Box::new(move |input| {
+            let minus = |i| {
+                let (rest, lhs) = diff.parse(i)?;
+                let (rest, _) = '-'.make_character_matcher("Expected -").parse(rest)?;
+                let (rest, rhs) = digit().parse(rest)?;
+                Ok((rest, lhs - rhs))
+            };
+            minus(input).or_else(|_: (&str, &str)| digit().parse(input))
+        })
+    });
+
+    assert_eq!(diff.parse("9-3-2"), Ok(("", 4)));
+    assert_eq!(diff.parse("9-"), Ok(("-", 9)));
+    assert_eq!(diff.parse("7"), Ok(("", 7)));
+}
+
+/// Test boxed parsers stored in a struct and borrowing local data
+#[test]
+fn test_boxed_parser() {
+    struct Keywords<'k> {
+        rules: Vec<BoxedParser<'k, &'static str, &'static str, String>>,
+    }
+
+    let names = ["if".to_string(), "else".to_string()];
+    let mut keywords = Keywords {
+        rules: names
+            .iter()
+            .map(|name| {
+                (move |input: &'static str| match input.strip_prefix(name.as_str()) {
+                    Some(rest) => Ok((rest, &input[..name.len()])),
+                    None => Err((input, format!("Expected {}", name))),
+                })
+                .boxed()
+            })
+            .collect(),
+    };
+
+    assert_eq!(keywords.rules[1].parse("else {"), Ok((" {", "else")));
+    assert_eq!(keywords.rules[0].parse("else"), Err(("else", "Expected if".to_string())));
+
+    let many_else = keywords.rules.pop().unwrap().many();
+    assert_eq!(many_else.parse("elseelse"), Ok(("", vec!["else", "else"])));
+}
+
+/// Test parse_complete errors on text, slices and stateful input
+#[test]
+fn test_parse_complete() {
+    let line = "x".make_literal_matcher("Expected x").skip("\n".make_literal_matcher("Expected newline"));
+    let lines = line.many().seq("y".make_literal_matcher("Expected y")).map_err(Foldable::fold);
+    let err = lines.parse_complete("x\nx\nz").unwrap_err();
+    assert_eq!(err.kind, FrissErrorKind::Parse("Expected y"));
+    assert_eq!(err.position, Some(Position::new(2, 0)));
+    assert_eq!(err.to_string(), "2:0: Expected y");
+
+    let bytes: &[u8] = b"abc";
+    let ab = (&b"ab"[..]).make_literal_matcher("Expected ab");
+    let err = ab.parse_complete(bytes).unwrap_err();
+    assert_eq!((err.kind, err.offset, err.position), (FrissErrorKind::TrailingInput, 2, None));
+
+    let counted = "a".with_state(Offset::default()).make_literal_matcher("Expected a");
+    assert_eq!(counted.parse_complete("a".with_state(Offset::default())).map(|out| out.input), Ok("a"));
+}
+
+/// Test permutations of parsers with different output types
+#[test]
+fn test_permutation() {
+    let width = " width=1".make_literal_matcher("Expected width").map(|_| 1);
+    let hidden = " hidden".make_literal_matcher("Expected hidden").value(true);
+    let attributes = (width, hidden).permutation();
+
+    assert_eq!(attributes.parse(" hidden width=1>"), Ok((">", (1, true))));
+    assert_eq!(attributes.parse(" width=1 hidden>"), Ok((">", (1, true))));
+    assert_eq!(
+        attributes.parse(" hidden hidden"),
+        Err((" hidden", (Some("Expected width"), None)))
+    );
+}
+
+/// Test longest-match alternation against first-match alternation
+#[test]
+fn test_alt_longest() {
+    let lit = |s: &'static str| s.make_literal_matcher("Expected operator");
+    let first = lit("<").alt(lit("<=")).alt(lit("<<="));
+    assert_eq!(first.parse("<<= 1"), Ok(("<= 1", Either::Left(Either::Left("<")))));
+
+    let longest = (lit("<"), lit("<="), lit("<<"), lit("<<=")).alt_longest();
+    assert_eq!(longest.parse("<<= 1"), Ok((" 1", Either4::_4("<<="))));
+    assert_eq!(longest.parse("<= 1"), Ok((" 1", Either4::_2("<="))));
+    assert_eq!(longest.parse("< 1"), Ok((" 1", Either4::_1("<"))));
+    let expected = "Expected operator";
+    assert_eq!(longest.parse("> 1"), Err(("> 1", (expected, expected, expected, expected))));
+
+    // Ties go to the earlier alternative
+    let tied = (lit("ab"), lit("ab").map(|_| "second")).alt_longest();
+    assert_eq!(tied.parse("abc"), Ok(("c", Either::Left("ab"))));
+
+    let bytes: &[u8] = b"xyz";
+    let short = (&b"x"[..]).make_literal_matcher("Expected x");
+    let long = (&b"xy"[..]).make_literal_matcher("Expected xy");
+    assert_eq!(short.alt_longest(long).parse(bytes), Ok((&b"z"[..], Either::Right(&b"xy"[..]))));
+}
+
+/// Test keyword disambiguation with lookahead after the keyword
+#[test]
+fn test_followed_by_and_not_followed_by() {
+    let ident_char = || {
+        <&str as Parsable<&str>>::make_anything_matcher("Expected identifier")
+            .validate(|c: &char| c.is_alphanumeric() || *c == '_', "Expected identifier")
+    };
+    let keyword = "let".make_literal_matcher("Expected let").not_followed_by(ident_char(), "Expected let");
+    assert_eq!(keyword.parse("let x = 1"), Ok((" x = 1", "let")));
+    assert_eq!(keyword.parse("let"), Ok(("", "let")));
+    assert_eq!(keyword.parse("letter"), Err(("ter", "Expected let")));
+    assert_eq!(keyword.parse("le"), Err(("le", "Expected let")));
+
+    let call = "print".make_literal_matcher("Expected name").followed_by("(".make_literal_matcher("Expected ("));
+    assert_eq!(call.parse("print(1)"), Ok(("(1)", "print")));
+    assert_eq!(call.parse("print 1"), Err((" 1", Either::Right("Expected ("))));
+    assert_eq!(call.parse("x(1)"), Err(("x(1)", Either::Left("Expected name"))));
+}
+
+/// Test keywords only matching at word boundaries
+#[test]
+fn test_keyword_matcher() {
+    let keyword = "let".make_keyword_matcher("Expected let");
+    assert_eq!(keyword.parse("let"), Ok(("", "let")));
+    assert_eq!(keyword.parse("let\tx"), Ok(("\tx", "let")));
+    assert_eq!(keyword.parse("letter"), Err(("letter", "Expected let")));
+    assert_eq!(keyword.parse("let_"), Err(("let_", "Expected let")));
+    assert_eq!(keyword.parse("let1"), Err(("let1", "Expected let")));
+    assert_eq!(keyword.parse("letä"), Err(("letä", "Expected let")));
+    assert_eq!(keyword.parse("le"), Err(("le", "Expected let")));
+
+    let statement = keyword.seq(" x".make_literal_matcher("Expected name"));
+    assert_eq!(statement.parse("let x"), Ok(("", ("let", " x"))));
+}
+
+/// Test the tuple sugar beyond ten parsers
+#[cfg(feature = "wide-tuples")]
+#[test]
+fn test_wide_tuple_sugar() {
+    let c = |ch: char| ch.make_character_matcher(ch);
+    let letters = || (c('a'), c('b'), c('c'), c('d'), c('e'), c('f'), c('g'), c('h'), c('i'), c('j'), c('k'), c('l'));
+
+    assert_eq!(
+        letters().seq().parse("abcdefghijkl!"),
+        Ok(("!", ('a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l')))
+    );
+    assert_eq!(letters().seq().parse("abcdefghijx"), Err(("x", Either12::_11('k'))));
+
+    assert_eq!(letters().alt().parse("k!"), Ok(("!", Either12::_11('k'))));
+    assert_eq!(
+        letters().alt().parse("z"),
+        Err(("z", ('a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l')))
+    );
+    assert_eq!(letters().alt().map(Foldable::fold).parse("l"), Ok(("", 'l')));
+
+    let (_, options) = letters().or().parse("d").unwrap();
+    assert_eq!((options.2, options.3), (None, Some(("", 'd'))));
+    assert_eq!(letters().or().parse("z").map(|_| ()), Err(("z", ('a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l'))));
+    assert_eq!(letters().permutation().parse("lkjihgfedcba").map(|(_, out)| out.11), Ok('l'));
+}
+
+/// Test flattening the errors of chained binary combinators
+#[test]
+fn test_normalize_err() {
+    let digit = |err| <&str as Parsable<&str>>::make_anything_matcher(err).validate(|c: &char| c.is_ascii_digit(), err);
+    let date = digit("year")
+        .seq('-'.make_character_matcher('-'))
+        .seq(digit("month"))
+        .seq('-'.make_character_matcher('-'))
+        .normalize_err::<4>();
+
+    assert_eq!(date.parse("1-2-"), Ok(("", ((('1', '-'), '2'), '-'))));
+    assert_eq!(date.parse("1-x-"), Err(("x-", Either4::_3("month"))));
+    assert_eq!(date.parse("1-2+"), Err(("+", Either4::_4('-'))));
+
+    let sign = '+'.make_character_matcher("plus")
+        .alt('-'.make_character_matcher("minus"))
+        .alt('~'.make_character_matcher("tilde"))
+        .alt('!'.make_character_matcher("bang"))
+        .normalize_err::<4>();
+    assert_eq!(sign.parse("?"), Err(("?", ("plus", "minus", "tilde", "bang"))));
+}
+
+/// Test reporting the alternative that got furthest before failing
+#[test]
+fn test_alt_farthest() {
+    let lit = |s: &'static str| s.make_literal_matcher(s);
+    let keyword = |k: &'static str| lit(k).seq(lit(" ")).seq(lit("name")).map(move |_| k);
+    let item = (keyword("fn"), keyword("struct"), keyword("enum"), keyword("trait")).alt_farthest();
+
+    assert_eq!(item.parse("enum name"), Ok(("", Either4::_3("enum"))));
+
+    let (rest, err) = item.parse("struct x").unwrap_err();
+    assert_eq!(rest, "x");
+    assert_eq!(err.error, Either4::_2(Either::Right("name")));
+    assert_eq!(err.remaining, 1);
+    assert_eq!(err.notes.0, (Either::Left(Either::Left("fn")), 8));
+
+    // With no progress in any branch, the first error is reported
+    let (rest, err) = item.parse("mod x").unwrap_err();
+    assert_eq!(rest, "mod x");
+    assert_eq!(err.error, Either4::_1(Either::Left(Either::Left("fn"))));
+
+    let binary = keyword("fn").alt_farthest(keyword("for"));
+    assert_eq!(binary.parse("fox").unwrap_err().1.error, Either::Left(Either::Left(Either::Left("fn"))));
+}
+
+/// Test line and column reporting for plain string parsers
+#[test]
+fn test_parse_str_positions() {
+    let assignment = "let x = 1;\n".make_literal_matcher("Expected assignment");
+    let program = assignment.many().skip("return x;".make_literal_matcher("Expected return"));
+
+    assert_eq!(parse_str(&program, "let x = 1;\nreturn x;"), Ok(("", vec!["let x = 1;\n"])));
+
+    let err = parse_str(&program, "let x = 1;\nlet x = 1;\nreturn y;").unwrap_err();
+    assert_eq!(err.error, "Expected return");
+    assert_eq!(err.position, Position::new(2, 0));
+
+    // Failures report the position the parser failed at, inside a sequence too
+    let pair = "a\nb".make_literal_matcher("ab").seq("c".make_literal_matcher("c"));
+    assert_eq!(parse_str(&pair, "a\nbx").unwrap_err().position, Position::new(1, 1));
+}
+
+/// Test running plain parsers inside stateful parsing
+#[test]
+fn test_lift_plain_parsers() {
+    let digits = <&str as Parsable<&str>>::make_anything_matcher("Expected digit")
+        .validate(|c: &char| c.is_ascii_digit(), "Expected digit")
+        .many();
+
+    // A lifted parser sequences with the matchers of the state carrier
+    let number = digits.lift::<Offset>();
+    let plus = "+".with_state(Offset::default()).make_literal_matcher("Expected +");
+    let sum = number.seq(plus).map_err(Foldable::fold);
+    let (rest, _) = sum.parse("12+3".with_state(Offset::new(10))).unwrap();
+    assert_eq!((rest.state, rest.input), (Offset::new(13), "3"));
+
+    let word = "ab".make_literal_matcher("Expected ab").seq("cd".make_literal_matcher("Expected cd")).lift::<Position>();
+    let (rest, err) = word.parse("ab\nx".with_state(Position::new(3, 0))).unwrap_err();
+    assert_eq!((rest.state, err), (Position::new(3, 2), Either::Right("Expected cd")));
+
+    let bytes: &[u8] = b"\x01\x02\x03";
+    let (rest, _) = crate::parsers::binary::take(2).lift::<Offset>().parse(bytes.with_state(Offset::default())).unwrap();
+    assert_eq!(rest.state, Offset::new(2));
+}
+
+/// Test composing stateful parsers without losing the state API
+#[test]
+fn test_stateful_combinators() {
+    let matcher = |s: &'static str| s.with_state(Position::default()).make_literal_matcher(s);
+    let item = matcher("x").alt_stateful(matcher("y\n"));
+    let list = item.sep_by_stateful(matcher(",")).seq_stateful(matcher(";").maybe_stateful());
+
+    let (rest, (items, end)) = list.parse_with_state("x,y\n,x;", Position::default()).unwrap();
+    assert_eq!(items.len(), 3);
+    assert!(end.is_some());
+    assert_eq!(rest.state, Position::new(1, 3));
+
+    // A trailing separator is not consumed, and neither are its state changes
+    let (rest, (items, end)) = list.parse_with_state("x,\n", Position::default()).unwrap();
+    assert_eq!((items.len(), end, rest.input, rest.state), (1, None, ",\n", Position::new(0, 1)));
+
+    // The result keeps the state API
+    let lines = matcher("ok\n").many_stateful().get_current_state();
+    assert_eq!(lines.parse("ok\nok\n".with_state(Position::default())).unwrap().1, Position::new(2, 0));
+}
+
+/// Test that restoring combinators rewind state carriers through checkpoints
+#[test]
+fn test_checkpoint_rewinds_state_carriers() {
+    let start = "ab,ab,x".with_state(Position::default());
+    let ab = || "ab".with_state(Position::default()).make_literal_matcher("Expected ab");
+    let comma = || ",".with_state(Position::default()).make_literal_matcher("Expected comma");
+
+    let (rest, _) = ab().validate(|_| false, "Rejected").parse(start).unwrap_err();
+    assert_eq!(rest, start);
+
+    let (rest, _) = ab().seq(ab()).backtrack().parse(start).unwrap_err();
+    assert_eq!(rest, start);
+
+    // The trailing separator is given back with its position
+    let (rest, items) = ab().sep_by(comma()).parse(start).unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!((rest.input, rest.state), (",x", Position::new(0, 5)));
+
+    let (rest, _) = ab().validate(|_| false, "Rejected").recover_with(|_| ab()).parse(start).unwrap();
+    assert_eq!(rest.state, Position::new(0, 2));
+}
+
+/// Test that alt and maybe retry from a checkpoint with the original state
+#[test]
+fn test_alt_and_maybe_rewind_state_carriers() {
+    let start = "abd".with_state(Position::default());
+    let lit = |text: &'static str| text.with_state(Position::default()).make_literal_matcher("Expected literal");
+    let abc = || lit("a").seq(lit("bc")).map_err(Foldable::fold);
+
+    let (rest, out) = abc().alt(lit("ab")).parse(start).unwrap();
+    assert!(matches!(out, Either::Right(_)));
+    assert_eq!((rest.input, rest.state), ("d", Position::new(0, 2)));
+
+    let (rest, out) = abc().maybe().parse(start).unwrap();
+    assert!(out.is_none());
+    assert_eq!(rest, start);
+}
+
+/// Test that failed branches leave no state changes behind
+#[test]
+fn test_failed_branches_leave_state_untouched() {
+    use crate::context::modify_state;
+
+    // A line whose second half is missing must not advance the position of `many`
+    let lit = |text: &'static str| text.with_state(Position::default()).make_literal_matcher("Expected literal");
+    let line = lit("ok").seq(lit("\n")).map_err(Foldable::fold);
+    let (rest, lines) = line.many().parse("ok\nok".with_state(Position::default())).unwrap();
+    assert_eq!(lines.len(), 1);
+    assert_eq!((rest.input, rest.state), ("ok", Position::new(1, 0)));
+
+    // A branch pushing an indentation level before failing
+    let lit = |text: &'static str| text.with_state(Indentation::new()).make_literal_matcher("Expected literal");
+    let push = || {
+        modify_state::<_, _, &str, _>(|mut state: Indentation| {
+            state.push_level(4);
+            state
+        })
+    };
+    let nested = || push().seq(lit("x")).map_err(Foldable::fold);
+    let start = "y".with_state(Indentation::new());
+
+    let (rest, _) = nested().alt(lit("y")).parse(start.clone()).unwrap();
+    assert_eq!(rest.state, Indentation::new());
+
+    let (rest, _) = nested().maybe().parse(start.clone()).unwrap();
+    assert_eq!(rest, start);
+
+    let (rest, (left, right)) = nested().or(lit("y")).parse(start.clone()).unwrap();
+    assert!(left.is_none());
+    assert_eq!(right.unwrap().0.state, Indentation::new());
+    assert_eq!(rest, start);
+}
+
+/// Test lazily iterating over the outputs of a repeated parser
+#[test]
+fn test_parser_iter() {
+    let lit = |text: &'static str| text.with_state(Position::default()).make_literal_matcher("Expected literal");
+    let line = lit("ok").seq(lit("\n")).map_err(Foldable::fold);
+
+    let mut lines = line.iter("ok\nok\nok".with_state(Position::default()));
+    assert_eq!(lines.by_ref().count(), 2);
+    assert_eq!(lines.error(), Some(&"Expected literal"));
+    let (rest, _) = lines.finish();
+    assert_eq!((rest.input, rest.state), ("ok", Position::new(2, 0)));
+
+    // A parser matching nothing yields once and stops
+    let empty = "".make_literal_matcher("Expected nothing");
+    let mut outputs = empty.iter("ab");
+    assert_eq!(outputs.next(), Some(""));
+    assert_eq!(outputs.next(), None);
+    assert_eq!(outputs.finish(), ("ab", None));
+}
+
+/// Test that the `&str` matchers keep multi-byte characters whole
+#[test]
+fn test_str_matchers_on_multibyte_text() {
+    let anything = || <&str as Parsable<&str>>::make_anything_matcher("Expected character");
+    assert_eq!(anything().many().parse("日本🦀é"), Ok(("", vec!['日', '本', '🦀', 'é'])));
+    assert_eq!('é'.make_character_matcher("Expected é").parse("éa"), Ok(("a", 'é')));
+    assert_eq!('e'.make_character_matcher("Expected e").parse("é"), Err(("é", "Expected e")));
+    assert_eq!("🦀".make_literal_matcher("Expected crab").parse("🦀🦀"), Ok(("🦀", "🦀")));
+
+    // Offsets and spans count bytes, positions count characters
+    let any = |input: &'static str| {
+        let (rest, c) = anything().lift::<Offset>().parse(input.with_state(Offset::default())).unwrap();
+        (c, rest.input, rest.state)
+    };
+    assert_eq!(any("🦀x"), ('🦀', "x", Offset::new(4)));
+
+    let crab = <StateCarrier<Offset, &str> as Parsable<&str>>::make_item_matcher('🦀', "Expected crab");
+    let (rest, _) = crab.parse("🦀".with_state(Offset::new(1))).unwrap();
+    assert_eq!(rest.state, Offset::new(5));
+
+    let sun = <StateCarrier<Position, &str> as Parsable<&str>>::make_item_matcher('日', "Expected 日");
+    let (rest, _) = sun.many().parse("日日\n".with_state(Position::default())).unwrap();
+    assert_eq!((rest.input, rest.state), ("\n", Position::new(0, 2)));
+
+    let spanned = <StateCarrier<crate::parsers::Span, &str> as Parsable<&str>>::make_anything_matcher("Expected character");
+    let (rest, c) = spanned.parse("éa".with_state(crate::parsers::Span::default())).unwrap();
+    assert_eq!((c, rest.state.end), ('é', 2));
+
+    let indented = <StateCarrier<Indentation, &str> as Parsable<&str>>::make_anything_matcher("Expected character");
+    assert_eq!(indented.parse("本".with_state(Indentation::new())).map(|(rest, c)| (rest.input, c)), Ok(("", '本')));
+}
+
+/// Test the column policies of the `Position` state
+#[test]
+fn test_position_column_policies() {
+    use crate::parsers::ColumnPolicy;
+
+    let end = |policy: ColumnPolicy, input: &'static str| {
+        let any = <StateCarrier<Position, &str> as Parsable<&str>>::make_anything_matcher("Expected character");
+        let (rest, _) = any.many().parse(input.with_state(Position::with_policy(policy))).unwrap();
+        rest.state
+    };
+    assert_eq!(end(ColumnPolicy::bytes(), "日本\nab"), Position::new(1, 2));
+    assert_eq!(end(ColumnPolicy::bytes(), "日本"), Position::new(0, 6));
+    assert_eq!(end(ColumnPolicy::chars().tab_stops(8), "ab\tc\t"), Position::new(0, 16));
+    assert_eq!(end(ColumnPolicy::bytes().tab_stops(4), "é\t"), Position::new(0, 4));
+
+    // Literals and lifted parsers count the same way as the character matchers
+    let mut position = Position::with_policy(ColumnPolicy::bytes());
+    position.advance_over("x\n🦀");
+    let lifted = "x\n🦀".make_literal_matcher("Expected text").lift::<Position>();
+    let (rest, _) = lifted.parse("x\n🦀".with_state(Position::with_policy(ColumnPolicy::bytes()))).unwrap();
+    assert_eq!((rest.state, position), (Position::new(1, 4), Position::new(1, 4)));
+    assert_eq!(rest.state.policy(), ColumnPolicy::bytes());
+
+    // Offsets inside a character or past the end are clamped instead of panicking
+    assert_eq!(Position::from_offset("a\n日本", 4), Position::new(1, 0));
+    assert_eq!(Position::from_offset("a\n日本", 6), Position::new(1, 1));
+    assert_eq!(Position::from_offset("ab", 10), Position::new(0, 2));
+
+    #[cfg(feature = "unicode-segmentation")]
+    {
+        // A combining accent, a joined family emoji and each flag are one cluster
+        let text = "e\u{301}👨\u{200d}👩\u{200d}👧🇩🇪🇫🇷x";
+        assert_eq!(end(ColumnPolicy::graphemes(), text), Position::new(0, 5));
+        assert_eq!(end(ColumnPolicy::chars(), text), Position::new(0, 12));
+        let literal = text.make_literal_matcher("Expected text").lift::<Position>();
+        let (rest, _) = literal.parse(text.with_state(Position::with_policy(ColumnPolicy::graphemes()))).unwrap();
+        assert_eq!(rest.state.column, 5);
+    }
+}
+
+/// Test that shared and boxed parsers delegate to the parser they point to
+#[test]
+fn test_parsers_behind_pointers() {
+    use alloc::rc::Rc;
+
+    // One `Rc` shared by two chains, without rebuilding the parser
+    let word = Rc::new("ab".make_literal_matcher("Expected ab"));
+    let twice = word.clone().seq(word.clone());
+    let listed = word.clone().sep_by(",".make_literal_matcher("Expected ab"));
+    assert_eq!(twice.parse("abab!"), Ok(("!", ("ab", "ab"))));
+    assert_eq!(listed.parse("ab,ab,x"), Ok((",x", vec!["ab", "ab"])));
+    assert_eq!(word.parse("ba"), Err(("ba", "Expected ab")));
+
+    // Trait objects can be handed to combinators directly
+    let boxed: Box<dyn Parser<&str, char, &str>> = Box::new('x'.make_character_matcher("Expected x"));
+    assert_eq!(boxed.many().parse("xxy"), Ok(("y", vec!['x', 'x'])));
+    let digit = '1'.make_character_matcher("Expected 1");
+    let borrowed: &dyn Parser<&str, char, &str> = &digit;
+    assert_eq!(borrowed.maybe().parse("2"), Ok(("2", None)));
+
+    #[cfg(feature = "std")]
+    {
+        use alloc::sync::Arc;
+
+        let shared: Arc<SyncParser<&'static str, &'static str, &'static str>> =
+            Arc::new(Box::new("go".make_literal_matcher("Expected go")));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let parser = shared.clone();
+                std::thread::spawn(move || parser.many().parse("gogo!"))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Ok(("!", vec!["go", "go"])));
+        }
+    }
+}
+
+/// Test that clones of a shared parser use the same parser
+#[test]
+fn test_shared_parser_clones() {
+    use core::cell::Cell;
+
+    let calls = Cell::new(0);
+    let counted = |input: &'static str| {
+        calls.set(calls.get() + 1);
+        'a'.make_character_matcher("Expected a").parse(input)
+    };
+    let shared = counted.shared();
+    let pair = shared.clone().seq(shared.clone());
+    let run = shared.clone().many();
+
+    assert_eq!(pair.parse("aa"), Ok(("", ('a', 'a'))));
+    assert_eq!(run.parse("aab"), Ok(("b", vec!['a', 'a'])));
+    assert_eq!(shared.parse("b"), Err(("b", "Expected a")));
+    assert_eq!(calls.get(), 6);
+
+    let sync = 'z'.make_character_matcher("Expected z").shared_sync();
+    assert_eq!(sync.clone().maybe().parse("zz"), Ok(("z", Some('z'))));
+}
+
+/// Test that inspecting taps see outputs and errors without changing them
+#[test]
+fn test_inspect_taps() {
+    use core::cell::{Cell, RefCell};
+
+    let digits = RefCell::new(Vec::new());
+    let failures = Cell::new(0);
+    let digit = '1'.make_character_matcher("Expected 1")
+        .alt('2'.make_character_matcher("Expected 2"))
+        .map(|d| d.fold())
+        .inspect(|d| digits.borrow_mut().push(*d))
+        .inspect_err(|err| {
+            assert_eq!(*err, ("Expected 1", "Expected 2"));
+            failures.set(failures.get() + 1);
+        });
+
+    assert_eq!(digit.by_ref().many().parse("212x"), Ok(("x", vec!['2', '1', '2'])));
+    assert_eq!(digit.parse("x"), Err(("x", ("Expected 1", "Expected 2"))));
+    assert_eq!(*digits.borrow(), vec!['2', '1', '2']);
+    assert_eq!(failures.get(), 2);
+}
+
+/// Test that deep nesting fails with an error instead of overflowing the stack
+#[test]
+fn test_depth_limit_and_long_right_chains() {
+    let nested = recursive(|nested| {
+        let level = move |input: &'static str| match '['.make_character_matcher("Expected [").parse(input) {
+            Ok((rest, _)) => {
+                let (rest, items) = nested.parse(rest)?;
+                let (rest, _) = ']'.make_character_matcher("Expected ]").parse(rest)?;
+                Ok((rest, items + 1))
+            }
+            Err(_) => Ok((input, 0)),
+        };
+        Box::new(level.with_depth_limit(3, "Too deep"))
+    });
+    assert_eq!(nested.parse("[[]]"), Ok(("", 2)));
+    assert_eq!(nested.parse("[[[x"), Err(("x", "Too deep")));
+    // The count is released after failures, so the parser stays usable
+    assert_eq!(nested.parse("[[]"), Err(("", "Expected ]")));
+    assert_eq!(nested.parse("[[]]"), Ok(("", 2)));
+    let deep: &'static str = "[".repeat(100_000).leak();
+    assert_eq!(nested.parse(deep), Err((&deep[3..], "Too deep")));
+
+    // A right-associative chain does not recurse per operator
+    let long = format!("{}1+", "1+".repeat(200_000));
+    let one = '1'.make_character_matcher("Expected 1").map(|_| 1u64);
+    let plus = '+'.make_character_matcher("Expected +")
+        .map(|_| Box::new(|a: u64, b: u64| a + b) as Box<dyn Fn(u64, u64) -> u64>);
+    let sum = one.chainr1(plus);
+    assert_eq!(sum.parse(long.as_str()), Ok(("+", 200_001)));
+}
+
+/// Test infix chains with operator values of their own
+#[test]
+fn test_infix_chains_with_operator_values() {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Op { Minus, Power }
+
+    let digit = '2'.make_character_matcher("Expected 2").value(2i64)
+        .alt('3'.make_character_matcher("Expected 3").value(3))
+        .map(|n| n.fold())
+        .map_err(|_| "Expected digit");
+    let minus = '-'.make_character_matcher(()).value(Op::Minus);
+    let power = "**".make_literal_matcher(0u8).value(Op::Power);
+    let apply = |left: i64, op, right: i64| match op {
+        Op::Minus => left - right,
+        Op::Power => left.pow(right as u32),
+    };
+
+    let difference = digit.by_ref().infixl(minus, apply);
+    let tower = digit.by_ref().infixr(power, apply);
+    assert_eq!(difference.parse("3-2-3"), Ok(("", -2)));
+    assert_eq!(tower.parse("2**3**2"), Ok(("", 512)));
+    assert_eq!(tower.parse("2"), Ok(("", 2)));
+    assert_eq!(tower.parse("x"), Err(("x", "Expected digit")));
+
+    // A dangling operator is left unconsumed
+    assert_eq!(difference.parse("3-2-x"), Ok(("-x", 1)));
+    assert_eq!(tower.parse("2**3**"), Ok(("**", 8)));
+}
+
+/// Test predicate matchers across input types
+#[test]
+fn test_predicate_matchers() {
+    let not_space = <&str as Parsable<&str>>::make_predicate_matcher(|c| !c.is_whitespace(), "Expected word");
+    assert_eq!(not_space.by_ref().many().parse("héllo world"), Ok((" world", vec!['h', 'é', 'l', 'l', 'o'])));
+    assert_eq!(not_space.parse(" x"), Err((" x", "Expected word")));
+    assert_eq!(not_space.parse(""), Err(("", "Expected word")));
+
+    let even = <&[u8] as Parsable<()>>::make_predicate_matcher(|b| **b % 2 == 0, ());
+    assert_eq!(even.parse(&[4, 5]), Ok((&[5][..], &4)));
+    assert_eq!(even.parse(&[5, 4]), Err((&[5, 4][..], ())));
+
+    // A rejected item leaves the input and the state where they were
+    let letter = <StateCarrier<Position, &str> as Parsable<&str>>::make_predicate_matcher(
+        |c| c.is_alphabetic(),
+        "Expected letter",
+    );
+    let (rest, c) = letter.parse("a\n1".with_state(Position::default())).unwrap();
+    assert_eq!((rest.input, rest.state.column, c), ("\n1", 1, 'a'));
+    let (failed, err) = letter.parse(rest).unwrap_err();
+    assert_eq!((failed.input, failed.state, err), (rest.input, rest.state, "Expected letter"));
+
+    let counted = <StateCarrier<Offset, &[u8]> as Parsable<()>>::make_predicate_matcher(|b| **b > 0, ());
+    let (rest, _) = counted.parse((&[1u8, 0][..]).with_state(Offset::default())).unwrap();
+    assert_eq!(counted.parse(rest).unwrap_err().0.state, rest.state);
+}
+
+/// Test seq_keep! dropping unmarked outputs
+#[test]
+fn test_seq_keep() {
+    let lit = |s: &'static str| s.make_literal_matcher("Expected literal");
+    let digit = crate::parsers::make_char_predicate_matcher(|c| c.is_ascii_digit(), "Expected digit");
+
+    let call = crate::seq_keep!(
+        keep(lit("f")),
+        lit("("),
+        keep(digit.by_ref()),
+        lit(","),
+        keep(digit.by_ref()),
+        lit(","),
+        keep(digit.by_ref().many()),
+        lit(")"),
+    );
+    assert_eq!(call.parse("f(1,2,34);"), Ok((";", ("f", '1', '2', vec!['3', '4']))));
+    assert_eq!(call.parse("f(1,x"), Err(("x", Either8::_5("Expected digit"))));
+
+    let unit = crate::seq_keep!(lit("<"), lit(">"));
+    assert_eq!(unit.parse("<>"), Ok(("", ())));
+    let first = crate::seq_keep!(keep(lit("a")), lit(";"));
+    assert_eq!(first.parse("a;b"), Ok(("b", "a")));
+}
+
+/// Test optional parsers with fallback values
+#[test]
+fn test_or_default_and_or_value() {
+    let digits = crate::parsers::make_char_predicate_matcher(|c| c.is_ascii_digit(), "Expected digit")
+        .many()
+        .non_empty("Expected digits")
+        .map(|ds| ds.into_iter().collect::<String>());
+    let port = ':'.make_character_matcher("Expected :")
+        .seq(digits)
+        .map(|(_, ds)| ds)
+        .or_value(String::from("80"));
+    let label = "#".make_literal_matcher("Expected #").map(String::from).or_default();
+
+    assert_eq!(port.parse(":8080/"), Ok(("/", String::from("8080"))));
+    assert_eq!(port.parse("/"), Ok(("/", String::from("80"))));
+    // A partial match is rewound before falling back
+    assert_eq!(port.parse(":x"), Ok((":x", String::from("80"))));
+    assert_eq!(label.parse("#!"), Ok(("!", String::from("#"))));
+    assert_eq!(label.parse("!"), Ok(("!", String::new())));
+
+    let stateful = "a".with_state(Offset::default()).make_literal_matcher("Expected a").map(|a| a.input).or_default();
+    let (rest, out) = stateful.parse("b".with_state(Offset::default())).unwrap();
+    assert_eq!((rest.state, out), (Offset(0), ""));
+}
+
+/// Test choice policies preferring the first, the last or the longest alternative
+#[test]
+fn test_alt_policies() {
+    let name = |n: &'static str| n.make_literal_matcher("Expected name");
+    let keyword = "if".make_literal_matcher("Expected keyword").value("keyword");
+    let ident = crate::parsers::make_char_predicate_matcher(|c| c.is_alphanumeric(), "Expected ident").many().non_empty("Expected ident").map(|_| "ident");
+
+    assert_eq!(keyword.by_ref().alt_first(ident.by_ref()).parse("if"), Ok(("", Either::Left("keyword"))));
+    assert_eq!(keyword.by_ref().alt_last(ident.by_ref()).parse("if"), Ok(("", Either::Right("ident"))));
+    assert_eq!(keyword.by_ref().alt_last(ident.by_ref()).parse("if+"), Ok(("+", Either::Right("ident"))));
+    assert_eq!(keyword.by_ref().alt_longest(ident.by_ref()).parse("iffy"), Ok(("", Either::Right("ident"))));
+    assert_eq!(keyword.by_ref().alt_last(name("x")).parse("if"), Ok(("", Either::Left("keyword"))));
+
+    let tuple = (name("a"), name("ab"), name("a"));
+    assert_eq!(tuple.alt_first().parse("ab"), Ok(("b", Either3::Left("a"))));
+    let tuple = (name("a"), name("ab"), name("a"));
+    assert_eq!(tuple.alt_last().parse("ab"), Ok(("b", Either3::Right("a"))));
+    let tuple = (name("a"), name("ab"), name("b"));
+    assert_eq!(tuple.alt_last().parse("ab"), Ok(("", Either3::Middle("ab"))));
+
+    // A failing alternative does not leak consumed input or state into the next one
+    let ab = "ab".with_state(Offset::default()).make_literal_matcher("Expected ab");
+    let a = "a".with_state(Offset::default()).make_literal_matcher("Expected a");
+    let choice = (a, ab).alt_last();
+    let (rest, out) = choice.parse("ac".with_state(Offset::default())).unwrap();
+    assert_eq!((rest.input, rest.state, out.fold().input), ("c", Offset(1), "a"));
+    let (rest, errs) = choice.parse("x".with_state(Offset::default())).unwrap_err();
+    assert_eq!((rest.input, errs), ("x", ("Expected a", "Expected ab")));
+}
+
+/// Test Either conversions to and from Result
+#[test]
+fn test_either_result_interop() {
+    let number = "x".make_literal_matcher("Expected x")
+        .alt(crate::parsers::make_char_predicate_matcher(|c| c.is_ascii_digit(), "Expected digit"));
+    let (_, out) = number.parse("7").unwrap();
+    assert_eq!(out.as_ref().map_right(|c| c.is_ascii_digit()), Either::Right(true));
+    assert_eq!(out.into_result(), Ok('7'));
+
+    let mut either: Either<i32, &str> = Either::from(Err::<&str, i32>(3));
+    if let Either::Left(n) = either.as_mut() {
+        *n += 1;
+    }
+    assert_eq!(either.map_left(|n| n * 2), Either::Left(8));
+    assert_eq!(Result::from(Either::<i32, &str>::Right("ok")), Ok("ok"));
+    assert_eq!(Either::<i32, &str>::from_result(Ok("ok")).into_result(), Ok("ok"));
+}
+
+/// Test conversions between Either and the either crate
+#[cfg(feature = "either")]
+#[test]
+fn test_either_crate_interop() {
+    let ours: Either<u8, &str> = either::Either::Left(1).into();
+    assert_eq!(ours, Either::Left(1));
+    let theirs: either::Either<u8, &str> = Either::Right("r").into();
+    assert_eq!(theirs, either::Either::Right("r"));
+}
+
+/// Test labelled errors and rule traces
+#[test]
+fn test_label_and_context() {
+    let digit = || '1'.make_character_matcher("Expected 1").label("digit");
+    let pair = digit()
+        .preceded_by('('.make_character_matcher(Labelled::new("Expected (", "open")))
+        .map_err(Foldable::fold)
+        .context("pair");
+
+    assert_eq!(pair.parse("(1"), Ok(("", '1')));
+
+    let (rest, err) = pair.parse("(2").unwrap_err();
+    assert_eq!(rest, "2");
+    assert_eq!(err.error, "Expected 1");
+    assert_eq!(err.trace, vec!["digit", "pair"]);
+
+    let (_, err) = pair.parse("1").unwrap_err();
+    assert_eq!(err.label(), "open");
+    assert_eq!(err.to_string(), "Expected (\n  while parsing open\n  while parsing pair");
+}
+
+/// Test pattern library combinators
+#[test]
+fn test_patterns() {
+    let x = || 'x'.make_character_matcher("Expected x");
+    let y = || 'y'.make_character_matcher("Expected y");
+
+    let signed = x().optional_prefixed("+-".make_literal_matcher("Expected +-"));
+    assert_eq!(signed.parse("+-x"), Ok(("", (Some("+-"), 'x'))));
+    assert_eq!(signed.parse("x"), Ok(("", (None, 'x'))));
+    assert_eq!(signed.parse("+x"), Err(("+x", "Expected x")));
+
+    let nested = x().wrapped_or_bare('['.make_character_matcher("Expected ["), ']'.make_character_matcher("Expected ]"));
+    assert_eq!(nested.parse("[x]!"), Ok(("!", 'x')));
+    assert_eq!(nested.parse("x]"), Ok(("]", 'x')));
+    assert_eq!(nested.parse("[y]"), Err(("y]", Either::Left("Expected x"))));
+
+    let both = x().either_order(y());
+    assert_eq!(both.parse("yx"), Ok(("", ('x', 'y'))));
+    assert_eq!(
+        both.parse("xx"),
+        Err(("xx", (Either::Right("Expected y"), Either::Left("Expected y"))))
+    );
+}
+
 /// Test skip parser
 #[test]
 fn test_skip() {
@@ -164,6 +1053,25 @@ fn test_skip() {
     assert_eq!(parser.parse("a"), Err(("", "No b")));
 }
 
+/// Test operator sugar on the `P` wrapper
+#[test]
+fn test_operator_sugar() {
+    let a = || P("a".make_literal_matcher("No a"));
+    let b = || P("b".make_literal_matcher("No b"));
+
+    assert_eq!((a() >> b()).parse("ab"), Ok(("", ("a", "b"))));
+    assert_eq!((a() >> b()).parse("ac"), Err(("c", Either::Right("No b"))));
+    assert_eq!((a() | b()).parse("b"), Ok(("", Either::Right("b"))));
+    assert_eq!((a() | b()).parse("c"), Err(("c", ("No a", "No b"))));
+    assert_eq!((a() << b()).parse("ab"), Ok(("", "a")));
+    assert_eq!((a() * b()).parse("ab"), Ok(("", "b")));
+    assert_eq!((a() * b()).parse("b"), Err(("b", Either::Left("No a"))));
+
+    let nested = a() >> b() >> a();
+    assert_eq!(nested.parse("aba"), Ok(("", (("a", "b"), "a"))));
+    assert_eq!(nested.into_inner().parse("abb"), Err(("b", Either::Right("No a"))));
+}
+
 /// Test validate parser
 #[test]
 fn test_validate() {
@@ -182,6 +1090,61 @@ fn test_validate() {
     assert_eq!(even_num.parse("3"), Err(("3", "Odd number")));
 }
 
+/// Test validate_map parser on stateful input
+#[test]
+fn test_validate_map() {
+    let number = '1'.make_character_matcher("Expected digit")
+        .alt('2'.make_character_matcher("Expected digit"))
+        .map(|c| c.fold())
+        .map_err(|_| "Expected digit")
+        .many()
+        .validate_map(|ds| ds.into_iter().collect::<String>().parse::<u8>().ok().filter(|n| *n > 9), "Expected two digits");
+
+    let input = "x\n212;".with_state(Position::default());
+    let (start, _) = 'x'.make_character_matcher("Expected x").parse(input).unwrap();
+    let (start, _) = '\n'.make_character_matcher("Expected newline").parse(start).unwrap();
+    let (rest, n) = number.parse(start).unwrap();
+    assert_eq!((rest.input, rest.state.column, n), (";", 3, 212));
+
+    // The failure is reported where the number started, with the state from before it
+    let (failed, err) = number.parse("2;".with_state(start.state)).unwrap_err();
+    assert_eq!((failed.input, failed.state, err), ("2;", start.state, "Expected two digits"));
+}
+
+/// Test errors located relative to where the parser started
+#[test]
+fn test_located_errors() {
+    let header = ("ab\n".make_literal_matcher("Expected ab"), "cd".make_literal_matcher("Expected cd"))
+        .seq()
+        .map_err(|err| err.fold())
+        .located();
+    assert_eq!(header.parse("ab\ncd!"), Ok(("!", ("ab\n", "cd"))));
+    let (rest, err) = header.parse("ab\nce").unwrap_err();
+    assert_eq!((rest, err.offset, err.position), ("ce", 3, Some(Position::new(1, 0))));
+    let (_, err) = header.parse("x").unwrap_err();
+    assert_eq!(err, ErrorAt { error: "Expected ab", offset: 0, position: Some(Position::new(0, 0)) });
+
+    // Offsets are counted from where the located parser started, not the whole input
+    let after_prefix = ("x".make_literal_matcher(()), header.map_err(|err| err.offset)).seq();
+    assert_eq!(after_prefix.parse("xab\nz").unwrap_err().1, Either::Right(3));
+
+    let bytes = (<&[u8] as Parsable<()>>::make_item_matcher(&1, ()), <&[u8] as Parsable<()>>::make_item_matcher(&2, ()))
+        .seq()
+        .map_err(|err| err.fold())
+        .located();
+    let (_, err) = bytes.parse(&[1, 3]).unwrap_err();
+    assert_eq!((err.offset, err.position), (1, None));
+
+    let counted = (
+        "a".with_state(Offset::default()).make_literal_matcher("Expected a").many(),
+        "b".with_state(Offset::default()).make_literal_matcher("Expected b"),
+    )
+        .seq()
+        .map_err(|err| err.fold());
+    let (rest, err) = counted.located().parse("aac".with_state(Offset::default())).unwrap_err();
+    assert_eq!((rest.input, rest.state, err.offset), ("c", Offset(2), 2));
+}
+
 /// Test bind parser
 #[test]
 fn test_bind_output() {
@@ -291,8 +1254,8 @@ fn test_general_bind() {
     assert_eq!(
         r1,
         Ok((
-            StateCarrier::new(Position { column: 9, line: 0 }, ""),
-            StateCarrier::new(Position { column: 1, line: 1 }, "haha")
+            StateCarrier::new(Position::new(0, 9), ""),
+            StateCarrier::new(Position::new(1, 1), "haha")
         ))
     )
 }
@@ -327,6 +1290,34 @@ fn test_stateful_parser() {
     }
 }
 
+/// Test state-aware recovery
+#[test]
+fn test_recover_with_state() {
+    let counted = |c: char| {
+        c.make_character_matcher("Unexpected char").with_state_transition(
+            |mut state: Offset, input, output, _orig| {
+                state.increment(1);
+                (state, input, output)
+            },
+            |state, input, error, _orig| (state, input, error),
+        )
+    };
+
+    // The failed branch advances the state to 1 before failing on 'c'
+    let ab = counted('a')
+        .seq(counted('b'))
+        .map_err(|_| "Expected ab")
+        .map(|(a, b)| format!("{}{}", a, b));
+    let parser = (move |input| ab.parse(input)).recover_with_state(|state: Offset, err| {
+            assert_eq!((state.value(), err), (1, "Expected ab"));
+            counted('a').map(|a| a.to_string()).map_err(|_| "Expected a")
+        });
+
+    let (rest, output) = parser.parse("ac".with_state(Offset(0))).unwrap();
+    assert_eq!(output, "a");
+    assert_eq!((rest.state.value(), rest.input), (1, "c"));
+}
+
 #[test]
 fn test_state_capture() {
     // Test get_current_state
@@ -419,7 +1410,7 @@ fn test_general_bind_2() {
 
     // Test general_bind with success case
     let bind_parser = digit_parser.general_bind(
-        |state, digit| {
+        |_state, digit| {
             // On success, check if the digit is '1' and return a new parser
             if digit == '1' {
                 "Y".with_state(Offset(0)).make_literal_matcher("No Y")
@@ -516,6 +1507,34 @@ fn test_chainl1() {
     assert_eq!(parser.parse("1+1+1"), Ok(("", 3)));
 }
 
+/// Test fold_many and fold_many1 parsers
+#[test]
+fn test_fold_many() {
+    let a = || "a".make_literal_matcher("No a");
+
+    let count = a().fold_many(0usize, |n, _| n + 1);
+    assert_eq!(count.parse("aaab"), Ok(("b", 3)));
+    assert_eq!(count.parse("b"), Ok(("b", 0)));
+    // The accumulator starts fresh on every parse
+    assert_eq!(count.parse("ab"), Ok(("b", 1)));
+
+    let joined = a().fold_many1(String::new(), |s, x| s + x, "Need an a");
+    assert_eq!(joined.parse("aa"), Ok(("", "aa".to_string())));
+    assert_eq!(joined.parse(""), Err(("", "Need an a")));
+}
+
+/// Test that repetition terminates on parsers that consume nothing
+#[test]
+fn test_many_zero_width() {
+    let empty = || "".make_literal_matcher("Expected nothing");
+
+    assert_eq!(empty().many().parse("abc"), Ok(("abc", vec![""])));
+    assert_eq!(empty().fold_many(0, |n, _| n + 1).parse("abc"), Ok(("abc", 1)));
+
+    let optional_a = "a".make_literal_matcher("No a").maybe().many();
+    assert_eq!(optional_a.parse("aab"), Ok(("b", vec![Some("a"), Some("a"), None])));
+}
+
 /// Test recover parser
 #[test]
 fn test_recover() {
@@ -545,6 +1564,7 @@ fn test_literal_sequence() {
 
 /// Test function application
 #[test]
+#[allow(clippy::extra_unused_lifetimes)]
 fn test_fapply<'a>() {
     let p = "hello "
         .make_literal_matcher("hello failed")
@@ -562,6 +1582,7 @@ fn test_fapply<'a>() {
 
 /// Test at most N parser
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn test_at_most_n_parser() {
     let parser = (
         'a'.make_character_matcher("char failed"),
@@ -580,205 +1601,23 @@ fn test_at_most_n_parser() {
     }
 }
 
-/// JSON value definition for testing JSON parser
-#[derive(PartialEq, Debug, Clone)]
-enum JsonValue {
-    Null,
-    Bool(bool),
-    Number(f64),
-    String(String),
-    Array(Vec<JsonValue>),
-    Object(Vec<(String, JsonValue)>),
-}
-
-/// JSON error definition for testing JSON parser
-#[derive(Debug, Clone, PartialEq)]
-enum JsonError {
-    ExpectedValue,
-    ExpectedBool,
-    ExpectedNumber,
-    ExpectedString,
-    ExpectedArray,
-    ExpectedObject,
-    ExpectedColon,
-    ExpectedComma,
-    ExpectedEndOfInput,
-    NoMatch,
-}
-
-/// A simple JSON parser as an example of a complete parser
-fn json_parser<'a>() -> impl Parser<&'a str, JsonValue, JsonError> {
-    fn value_parser<'a>() -> impl Parser<&'a str, JsonValue, JsonError> {
-        |input| {
-            (
-                null_parser(),
-                bool_parser(),
-                number_parser(),
-                string_parser(),
-                array_parser(),
-                object_parser(),
-            )
-                .alt()
-                .map(|x| match x {
-                    Either6::_1(x)
-                    | Either6::_2(x)
-                    | Either6::_3(x)
-                    | Either6::_4(x)
-                    | Either6::_5(x)
-                    | Either6::_6(x) => x,
-                })
-                .map_err(|_| JsonError::NoMatch)
-                .parse(input)
-        }
-    }
-
-    fn null_parser<'a>() -> impl Parser<&'a str, JsonValue, JsonError> {
-        "null"
-            .make_literal_matcher(JsonError::ExpectedValue)
-            .map(|_| JsonValue::Null)
-    }
-
-    fn bool_parser<'a>() -> impl Parser<&'a str, JsonValue, JsonError> {
-        "true"
-            .make_literal_matcher(JsonError::ExpectedBool)
-            .alt("false".make_literal_matcher(JsonError::ExpectedBool))
-            .map(|b| match b {
-                Either::Left("true") => JsonValue::Bool(true),
-                Either::Right("false") => JsonValue::Bool(false),
-                _ => unreachable!(),
-            })
-            .map_err(|(e1, _)| e1)
-    }
-
-    fn number_parser<'a>() -> impl Parser<&'a str, JsonValue, JsonError> {
-        <&str as Parsable<JsonError>>::make_anything_matcher(JsonError::ExpectedNumber)
-            .many()
-            .map(|digits| {
-                let s: String = digits.into_iter().collect();
-                s
-            })
-            .validate(
-                |s: &String| s.parse::<f32>().is_ok(),
-                JsonError::ExpectedNumber,
-            )
-            .map(|s| JsonValue::Number(s.parse::<f32>().unwrap() as f64))
-    }
-
-    fn string_parser<'a>() -> impl Parser<&'a str, JsonValue, JsonError> {
-        (
-            '"'.make_character_matcher(JsonError::ExpectedString),
-            <&'a str as Parsable<JsonError>>::make_anything_matcher(JsonError::ExpectedString)
-                .validate(|c| c != &'"', JsonError::ExpectedString)
-                .many()
-                .map(|x| JsonValue::String(x.into_iter().collect::<String>())),
-            '"'.make_character_matcher(JsonError::ExpectedString),
-        )
-            .seq()
-            .map(|(_, content, _)| content)
-            .map_err(|x| match x {
-                Either3::Left(e) | Either3::Middle(e) | Either3::Right(e) => e,
-            })
-    }
-
-    fn bare_string_parser<'a>() -> impl Parser<&'a str, String, JsonError> {
-        (
-            '"'.make_character_matcher(JsonError::ExpectedString),
-            <&'a str as Parsable<JsonError>>::make_anything_matcher(JsonError::ExpectedString)
-                .validate(|c| c != &'"', JsonError::ExpectedString)
-                .many()
-                .map(|x| x.into_iter().collect::<String>()),
-            '"'.make_character_matcher(JsonError::ExpectedString),
-        )
-            .seq()
-            .map(|(_, content, _)| content)
-            .map_err(|x| match x {
-                Either3::Left(e) | Either3::Middle(e) | Either3::Right(e) => e,
-            })
-    }
-
-    fn array_parser<'a>() -> impl Parser<&'a str, JsonValue, JsonError> {
-        (
-            '['.make_character_matcher(JsonError::ExpectedArray),
-            value_parser()
-                .seq(
-                    ','.make_character_matcher(JsonError::ExpectedComma)
-                        .seq(value_parser())
-                        .many(),
-                )
-                .maybe(),
-            ']'.make_character_matcher(JsonError::ExpectedArray),
-        )
-            .seq()
-            .map(|(_, content, _)| {
-                let mut result = Vec::new();
-                if let Some((first, rest)) = content {
-                    result.push(first);
-                    for (_, item) in rest {
-                        result.push(item);
-                    }
-                }
-                JsonValue::Array(result)
-            })
-            .map_err(|x| match x {
-                Either3::Left(e) => e,
-                Either3::Middle(Either::Left(e)) => e,
-                Either3::Middle(Either::Right(Either::Left(e))) => e,
-                Either3::Middle(Either::Right(Either::Right(e))) => e,
-                Either3::Right(e) => e,
-            })
-    }
-
-    fn key_value_pair_parser<'a>() -> impl Parser<&'a str, (String, JsonValue), JsonError> {
-        (
-            bare_string_parser(),
-            ':'.make_character_matcher(JsonError::ExpectedColon),
-            value_parser(),
-        )
-            .seq()
-            .map(|(a, _, b)| (a, b))
-            .map_err(|err| match err {
-                Either3::Left(e) | Either3::Middle(e) | Either3::Right(e) => e,
-            })
-    }
+/// Test repetition with non-Copy outputs
+#[test]
+fn test_repetition_non_copy_output() {
+    let word = || "ab".make_literal_matcher("No ab").map(String::from);
 
-    fn object_parser<'a>() -> impl Parser<&'a str, JsonValue, JsonError> {
-        (
-            '{'.make_character_matcher(JsonError::ExpectedObject),
-            key_value_pair_parser().seq(
-                ','.make_character_matcher(JsonError::ExpectedComma)
-                    .seq(key_value_pair_parser())
-                    .map(|(_, x)| x)
-                    .map_err(|x| match x {
-                        Either::Left(x) | Either::Right(x) => x,
-                    })
-                    .many()
-                    .maybe(),
-            ),
-            '}'.make_character_matcher(JsonError::ExpectedObject),
-        )
-            .seq()
-            .map(|(_, (content, maybe_rest), _)| {
-                let mut result = vec![content];
-                if let Some(x) = maybe_rest {
-                    result.extend(x)
-                }
-                JsonValue::Object(result)
-            })
-            .map_err(|x| match x {
-                Either3::Left(e)
-                | Either3::Middle(Either::Left(e))
-                | Either3::Middle(Either::Right(e))
-                | Either3::Right(e) => e,
-            })
-    }
+    let exactly = word().exactly_n::<2>();
+    assert_eq!(exactly.parse("ababx"), Ok(("x", Box::new(["ab".to_string(), "ab".to_string()]))));
+    assert_eq!(exactly.parse("abx"), Err(("x", RepetitionError { expected: 2, found: 1, error: "No ab" })));
 
-    value_parser()
+    let at_most = word().at_most_n::<3>();
+    assert_eq!(at_most.parse("abx"), Ok(("x", Box::new([Some("ab".to_string()), None, None]))));
 }
 
 /// Test JSON parser - null
 #[test]
 fn test_json_null() {
-    let parser = json_parser();
+    let parser = json_value();
     let ret = parser.parse("null");
     assert_eq!(ret, Ok(("", JsonValue::Null)));
 }
@@ -786,7 +1625,7 @@ fn test_json_null() {
 /// Test JSON parser - true
 #[test]
 fn test_json_true() {
-    let parser = json_parser();
+    let parser = json_value();
     let ret = parser.parse("true");
     assert_eq!(ret, Ok(("", JsonValue::Bool(true))));
 }
@@ -794,7 +1633,7 @@ fn test_json_true() {
 /// Test JSON parser - false
 #[test]
 fn test_json_false() {
-    let parser = json_parser();
+    let parser = json_value();
     let ret = parser.parse("false");
     assert_eq!(ret, Ok(("", JsonValue::Bool(false))));
 }
@@ -802,7 +1641,7 @@ fn test_json_false() {
 /// Test JSON parser - number
 #[test]
 fn test_json_num() {
-    let parser = json_parser();
+    let parser = json_value();
     let ret = parser.parse("3.0");
     assert_eq!(ret, Ok(("", JsonValue::Number(3.0))));
 }
@@ -810,7 +1649,7 @@ fn test_json_num() {
 /// Test JSON parser - string
 #[test]
 fn test_json_string() {
-    let parser = json_parser();
+    let parser = json_value();
     let ret = parser.parse("\"Hello\"");
     assert_eq!(ret, Ok(("", JsonValue::String("Hello".to_owned()))));
 }
@@ -818,7 +1657,7 @@ fn test_json_string() {
 /// Test JSON parser - array
 #[test]
 fn test_json_array() {
-    let parser = json_parser();
+    let parser = json_value();
     let ret = parser.parse("[true,null]");
     assert_eq!(
         ret,
@@ -832,7 +1671,7 @@ fn test_json_array() {
 /// Test JSON parser - object
 #[test]
 fn test_json_object() {
-    let parser = json_parser();
+    let parser = json_value();
     let ret = parser.parse("{\"hmm\":null,\"true\":false,\"self\":{\"hmm\":null,\"true\":false}}");
     assert_eq!(
         ret,
@@ -882,6 +1721,7 @@ mod expr_parser {
     }
 
     /// Parse error
+    #[allow(dead_code)]
     #[derive(Debug, Clone, PartialEq)]
     pub enum ParseError {
         /// Expected a number
@@ -900,17 +1740,6 @@ mod expr_parser {
         EndOfInputExpected,
     }
 
-    /// Checks if input is empty
-    fn end_of_input<'a>() -> impl Parser<&'a str, (), ParseError> {
-        |input: &'a str| {
-            if input.is_empty() {
-                Ok(("", ()))
-            } else {
-                Err((input, ParseError::EndOfInputExpected))
-            }
-        }
-    }
-
     /// Parses a number
     fn number_parser<'a>() -> impl Parser<&'a str, Expr, ParseError> {
         (
@@ -1039,12 +1868,7 @@ mod expr_parser {
 
     /// Parses a complete expression, checking for end of input
     fn expr_parser<'a>() -> impl Parser<&'a str, Expr, ParseError> {
-        expr_parser_without_end()
-            .seq(end_of_input())
-            .map(|(expr, _)| expr)
-            .map_err(|err| match err {
-                Either::Left(e) | Either::Right(e) => e,
-            })
+        expr_parser_without_end().all_consuming(ParseError::EndOfInputExpected)
     }
 
     /// Test for expression parser error handling
@@ -1052,7 +1876,8 @@ mod expr_parser {
     fn test_error_handling() {
         let parser = expr_parser();
 
-        assert_eq!(parser.parse("(3+4"), Err(("", ParseError::ExpectedNumber)));
+        // Every alternative of the atom restarts at the opening parenthesis
+        assert_eq!(parser.parse("(3+4"), Err(("(3+4", ParseError::ExpectedNumber)));
 
         assert_eq!(
             parser.parse("3#4"),
@@ -1167,6 +1992,7 @@ fn test_position_tracking() {
 }
 
 #[test]
+#[allow(clippy::extra_unused_lifetimes, clippy::type_complexity, clippy::while_let_loop)]
 fn test_indentation_aware_parsing<'a>() {
     // Create basic parsers for spaces and newlines
     let space = " ".make_literal_matcher("Expected space");
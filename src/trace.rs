@@ -0,0 +1,203 @@
+//! # Parse Tracing
+//!
+//! This module prints a readable trace of a parse for debugging grammars. Parsers wrapped
+//! with `traced(name)` report when they are entered and whether they succeeded or failed,
+//! indented by nesting depth and showing a prefix of the input they saw.
+//!
+//! Tracing is off unless a hook is installed with `set_trace_hook` or `trace_to_stderr`.
+//! With the `trace` feature enabled, traces go to stderr by default. Hooks are per thread.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::trace::*;
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//!
+//! let lines = Rc::new(RefCell::new(Vec::new()));
+//! let sink = lines.clone();
+//! set_trace_hook(move |event: &TraceEvent| sink.borrow_mut().push(event.to_string()));
+//!
+//! let digit = '1'.make_character_matcher("Expected 1").traced("digit");
+//! let number = digit.many().traced("number");
+//! number.parse("11x").unwrap();
+//! clear_trace_hook();
+//!
+//! assert_eq!(lines.borrow()[0], r#"number <- "11x""#);
+//! assert_eq!(lines.borrow()[1], r#"  digit <- "11x""#);
+//! assert_eq!(lines.borrow()[2], r#"  digit ok, rest "1x""#);
+//! assert_eq!(lines.borrow().last().unwrap(), r#"number ok, rest "x""#);
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Debug, Display, Formatter};
+
+use crate::core::{Parsable, Parser, ParserOutput};
+
+/// Number of characters of the input shown in trace events.
+pub const TRACE_PREFIX_LEN: usize = 24;
+
+/// What happened to a traced parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceKind {
+    /// The parser was entered
+    Enter,
+    /// The parser succeeded
+    Success,
+    /// The parser failed
+    Failure,
+}
+
+/// A single step of a parse trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEvent {
+    /// Name given to the parser with `traced`
+    pub name: &'static str,
+    /// Number of enclosing traced parsers
+    pub depth: usize,
+    pub kind: TraceKind,
+    /// Prefix of the input on entry, or of the rest after success or failure
+    pub input: String,
+}
+
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let indent = "  ".repeat(self.depth);
+        match self.kind {
+            TraceKind::Enter => write!(f, "{}{} <- {}", indent, self.name, self.input),
+            TraceKind::Success => write!(f, "{}{} ok, rest {}", indent, self.name, self.input),
+            TraceKind::Failure => write!(f, "{}{} failed at {}", indent, self.name, self.input),
+        }
+    }
+}
+
+type TraceHook = Box<dyn Fn(&TraceEvent)>;
+
+thread_local! {
+    static HOOK: RefCell<Option<TraceHook>> = RefCell::new(default_hook());
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+#[cfg(feature = "trace")]
+fn default_hook() -> Option<TraceHook> {
+    Some(Box::new(|event: &TraceEvent| eprintln!("{}", event)))
+}
+
+#[cfg(not(feature = "trace"))]
+fn default_hook() -> Option<TraceHook> {
+    None
+}
+
+/// Calls `hook` for every trace event on this thread.
+pub fn set_trace_hook(hook: impl Fn(&TraceEvent) + 'static) {
+    HOOK.with(|h| *h.borrow_mut() = Some(Box::new(hook)));
+}
+
+/// Prints every trace event on this thread to stderr.
+pub fn trace_to_stderr() {
+    set_trace_hook(|event| eprintln!("{}", event));
+}
+
+/// Turns tracing off on this thread.
+pub fn clear_trace_hook() {
+    HOOK.with(|h| *h.borrow_mut() = None);
+}
+
+fn tracing_enabled() -> bool {
+    HOOK.with(|h| h.borrow().is_some())
+}
+
+fn emit(name: &'static str, kind: TraceKind, input: &impl Debug) {
+    let depth = DEPTH.with(Cell::get);
+    let shown = format!("{:?}", input);
+    let input = match shown.char_indices().nth(TRACE_PREFIX_LEN) {
+        Some((end, _)) => format!("{}...", shown.get(..end).unwrap_or_default()),
+        None => shown,
+    };
+    let event = TraceEvent { name, depth, kind, input };
+    // The hook is taken out while it runs, so it may parse traced input itself
+    let hook = HOOK.with(|h| h.borrow_mut().take());
+    if let Some(hook) = hook {
+        hook(&event);
+        HOOK.with(|h| {
+            h.borrow_mut().get_or_insert(hook);
+        });
+    }
+}
+
+/// Extension trait adding tracing to parsers.
+pub trait TraceParser<Input, Output, Error>: Parser<Input, Output, Error>
+where
+    Input: Parsable<Error> + Debug,
+    Output: ParserOutput,
+    Error: Clone,
+{
+    /// Reports entering and leaving this parser under `name` while tracing is enabled.
+    /// Without a trace hook the parser runs unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    /// use friss::trace::*;
+    ///
+    /// let keyword = "let".make_literal_matcher("Expected let").traced("keyword");
+    /// assert_eq!(keyword.parse("let x"), Ok((" x", "let")));
+    /// ```
+    fn traced(self, name: &'static str) -> impl Parser<Input, Output, Error>
+    where
+        Self: Sized,
+    {
+        move |input: Input| {
+            if !tracing_enabled() {
+                return self.parse(input);
+            }
+            emit(name, TraceKind::Enter, &input);
+            DEPTH.with(|d| d.set(d.get() + 1));
+            let result = self.parse(input);
+            DEPTH.with(|d| d.set(d.get() - 1));
+            match &result {
+                Ok((rest, _)) => emit(name, TraceKind::Success, rest),
+                Err((rest, _)) => emit(name, TraceKind::Failure, rest),
+            }
+            result
+        }
+    }
+}
+
+impl<Input, Output, Error, P> TraceParser<Input, Output, Error> for P
+where
+    Input: Parsable<Error> + Debug,
+    Output: ParserOutput,
+    Error: Clone,
+    P: Parser<Input, Output, Error>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_trace_failure_and_truncation() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let sink = events.clone();
+        set_trace_hook(move |event: &TraceEvent| sink.borrow_mut().push(event.clone()));
+
+        let long = "b".repeat(40);
+        let parser = 'a'.make_character_matcher("Expected a").traced("a");
+        assert!(parser.parse(long.as_str()).is_err());
+        clear_trace_hook();
+        assert!(parser.parse("a").is_ok());
+
+        let events = events.borrow();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, TraceKind::Enter);
+        assert_eq!(events[0].input, format!("\"{}...", "b".repeat(TRACE_PREFIX_LEN - 1)));
+        assert_eq!(events[1].kind, TraceKind::Failure);
+        assert_eq!(events[1].depth, 0);
+    }
+}
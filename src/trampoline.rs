@@ -0,0 +1,324 @@
+//! # Iterative Evaluation
+//!
+//! Recursive grammars built from combinators recurse on the thread's stack, one or more
+//! frames per level of nesting, so legitimately deep documents overflow it unless the stack
+//! is enlarged. `Parser::with_depth_limit` turns this into an error; this module parses them
+//! instead. A `Trampoline` evaluates a grammar of rules with an explicit work stack on the
+//! heap, so nesting is bounded by memory rather than by the thread's stack.
+//!
+//! The grammar is built from terms, which the trampoline interprets:
+//!
+//! - `leaf(parser)` runs an ordinary parser, which should not recurse into the grammar
+//! - `rule(id)` refers to a rule declared with `Trampoline::declare`, including the rule
+//!   being defined
+//! - `seq(parts, combine)` matches all parts in order and combines their outputs
+//! - `alt(choices)` tries each choice in turn from the same input
+//! - `many(term, combine)` matches a term zero or more times and combines the outputs
+//!
+//! All terms share one output type, usually an enum or a syntax tree. If every tried choice
+//! of an `alt` fails, the error of the last one is returned. The first declared rule is the
+//! start rule of the `Parser` implementation; `parse_rule` starts at any rule.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::trampoline::*;
+//!
+//! // value := "[" value* "]" | digit, counting the digits
+//! let mut grammar = Trampoline::new("Undefined rule");
+//! let value = grammar.declare();
+//! grammar.define(value, alt([
+//!     seq(
+//!         [
+//!             leaf('['.make_character_matcher("Expected [").map(|_| 0)),
+//!             many(rule(value), |counts| counts.into_iter().sum()),
+//!             leaf(']'.make_character_matcher("Expected ]").map(|_| 0)),
+//!         ],
+//!         |counts| counts.into_iter().sum(),
+//!     ),
+//!     leaf(make_char_predicate_matcher(|c| c.is_ascii_digit(), "Expected digit").map(|_| 1)),
+//! ]));
+//!
+//! assert_eq!(grammar.parse("[1[2[]3]]"), Ok(("", 3)));
+//! assert_eq!(grammar.parse("[1x]"), Err(("[1x]", "Expected digit")));
+//!
+//! // Nesting far deeper than the stack allows
+//! let deep: &'static str = format!("{}7{}", "[".repeat(1_000_000), "]".repeat(1_000_000)).leak();
+//! assert_eq!(grammar.parse(deep), Ok(("", 1)));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::core::{Checkpoint, Parsable, Parser};
+
+/// A rule of a `Trampoline`, returned by `Trampoline::declare`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RuleId(usize);
+
+type Combine<'a, O> = Box<dyn Fn(Vec<O>) -> O + 'a>;
+
+/// A part of a rule's definition, built with `leaf`, `rule`, `seq`, `alt` and `many`.
+pub struct Term<'a, I, O, E> {
+    kind: TermKind<'a, I, O, E>,
+}
+
+enum TermKind<'a, I, O, E> {
+    Leaf(Box<dyn Parser<I, O, E> + 'a>),
+    Rule(RuleId),
+    Seq(Vec<Term<'a, I, O, E>>, Combine<'a, O>),
+    Alt(Vec<Term<'a, I, O, E>>),
+    Many(Box<Term<'a, I, O, E>>, Combine<'a, O>),
+}
+
+/// Runs `parser`.
+pub fn leaf<'a, I, O, E>(parser: impl Parser<I, O, E> + 'a) -> Term<'a, I, O, E>
+where
+    I: Parsable<E>,
+    E: Clone,
+{
+    Term { kind: TermKind::Leaf(Box::new(parser)) }
+}
+
+/// Runs the rule `id`.
+pub fn rule<'a, I, O, E>(id: RuleId) -> Term<'a, I, O, E> {
+    Term { kind: TermKind::Rule(id) }
+}
+
+/// Matches all `parts` in order and combines their outputs with `combine`.
+pub fn seq<'a, I, O, E>(
+    parts: impl IntoIterator<Item = Term<'a, I, O, E>>,
+    combine: impl Fn(Vec<O>) -> O + 'a,
+) -> Term<'a, I, O, E> {
+    Term { kind: TermKind::Seq(parts.into_iter().collect(), Box::new(combine)) }
+}
+
+/// Returns the output of the first of `choices` that matches. Without choices it fails with
+/// the trampoline's error.
+pub fn alt<'a, I, O, E>(choices: impl IntoIterator<Item = Term<'a, I, O, E>>) -> Term<'a, I, O, E> {
+    Term { kind: TermKind::Alt(choices.into_iter().collect()) }
+}
+
+/// Matches `term` zero or more times and combines the outputs with `combine`. Like
+/// `Parser::many`, it stops after a match that consumed nothing.
+pub fn many<'a, I, O, E>(term: Term<'a, I, O, E>, combine: impl Fn(Vec<O>) -> O + 'a) -> Term<'a, I, O, E> {
+    Term { kind: TermKind::Many(Box::new(term), Box::new(combine)) }
+}
+
+/// A grammar evaluated with an explicit work stack, see the module documentation.
+pub struct Trampoline<'a, I, O, E> {
+    rules: Vec<Option<Term<'a, I, O, E>>>,
+    err: E,
+}
+
+/// Work left over while a term runs: the rest of a sequence, the remaining choices, or the
+/// next repetition.
+enum Frame<'t, 'a, I: Checkpoint, O, E> {
+    Seq {
+        parts: &'t [Term<'a, I, O, E>],
+        outputs: Vec<O>,
+        combine: &'t Combine<'a, O>,
+    },
+    Alt {
+        choices: &'t [Term<'a, I, O, E>],
+        checkpoint: I::Checkpoint,
+    },
+    Many {
+        term: &'t Term<'a, I, O, E>,
+        outputs: Vec<O>,
+        combine: &'t Combine<'a, O>,
+        start: I,
+        checkpoint: I::Checkpoint,
+    },
+}
+
+enum Step<'t, 'a, I, O, E> {
+    Enter(&'t Term<'a, I, O, E>, I),
+    Return(Result<(I, O), (I, E)>),
+}
+
+impl<'a, I, O, E> Trampoline<'a, I, O, E>
+where
+    I: Parsable<E> + PartialEq,
+    E: Clone,
+{
+    /// Creates an empty grammar. Running a declared rule that was never defined fails
+    /// with `err`.
+    pub fn new(err: E) -> Self {
+        Trampoline { rules: Vec::new(), err }
+    }
+
+    /// Declares a rule, to be defined with `define`.
+    pub fn declare(&mut self) -> RuleId {
+        self.rules.push(None);
+        RuleId(self.rules.len() - 1)
+    }
+
+    /// Defines the rule `id` as `term`, replacing an earlier definition.
+    pub fn define(&mut self, id: RuleId, term: Term<'a, I, O, E>) {
+        if let Some(rule) = self.rules.get_mut(id.0) {
+            *rule = Some(term);
+        }
+    }
+
+    /// Parses `input` starting at the rule `id`.
+    pub fn parse_rule(&self, id: RuleId, input: I) -> Result<(I, O), (I, E)> {
+        let mut stack: Vec<Frame<'_, 'a, I, O, E>> = Vec::new();
+        let mut step = match self.rules.get(id.0) {
+            Some(Some(term)) => Step::Enter(term, input),
+            _ => Step::Return(Err((input, self.err.clone()))),
+        };
+        loop {
+            step = match step {
+                Step::Enter(term, input) => self.enter(term, input, &mut stack),
+                Step::Return(result) => match stack.pop() {
+                    Some(frame) => Self::resume(frame, result, &mut stack),
+                    None => return result,
+                },
+            };
+        }
+    }
+
+    /// Starts running `term`, returning the term to run next or, for a term that runs no
+    /// others, its result.
+    fn enter<'t>(
+        &'t self,
+        term: &'t Term<'a, I, O, E>,
+        input: I,
+        stack: &mut Vec<Frame<'t, 'a, I, O, E>>,
+    ) -> Step<'t, 'a, I, O, E> {
+        match &term.kind {
+            TermKind::Leaf(parser) => Step::Return(parser.parse(input)),
+            TermKind::Rule(id) => match self.rules.get(id.0) {
+                Some(Some(term)) => Step::Enter(term, input),
+                _ => Step::Return(Err((input, self.err.clone()))),
+            },
+            TermKind::Seq(parts, combine) => match parts.split_first() {
+                Some((first, parts)) => {
+                    stack.push(Frame::Seq { parts, outputs: Vec::new(), combine });
+                    Step::Enter(first, input)
+                }
+                None => Step::Return(Ok((input, combine(Vec::new())))),
+            },
+            TermKind::Alt(choices) => match choices.split_first() {
+                Some((first, choices)) => {
+                    stack.push(Frame::Alt { choices, checkpoint: input.checkpoint() });
+                    Step::Enter(first, input)
+                }
+                None => Step::Return(Err((input, self.err.clone()))),
+            },
+            TermKind::Many(term, combine) => {
+                stack.push(Frame::Many {
+                    term,
+                    outputs: Vec::new(),
+                    combine,
+                    checkpoint: input.checkpoint(),
+                    start: input.clone(),
+                });
+                Step::Enter(term, input)
+            }
+        }
+    }
+
+    /// Continues the work of `frame` with the result of the term it was waiting for.
+    fn resume<'t>(
+        frame: Frame<'t, 'a, I, O, E>,
+        result: Result<(I, O), (I, E)>,
+        stack: &mut Vec<Frame<'t, 'a, I, O, E>>,
+    ) -> Step<'t, 'a, I, O, E> {
+        match (frame, result) {
+            (Frame::Seq { parts, mut outputs, combine }, Ok((rest, output))) => {
+                outputs.push(output);
+                match parts.split_first() {
+                    Some((next, parts)) => {
+                        stack.push(Frame::Seq { parts, outputs, combine });
+                        Step::Enter(next, rest)
+                    }
+                    None => Step::Return(Ok((rest, combine(outputs)))),
+                }
+            }
+            (Frame::Alt { choices, checkpoint }, Err(err)) => match choices.split_first() {
+                Some((next, choices)) => {
+                    let input = I::rewind(checkpoint);
+                    stack.push(Frame::Alt { choices, checkpoint: input.checkpoint() });
+                    Step::Enter(next, input)
+                }
+                None => Step::Return(Err(err)),
+            },
+            (Frame::Many { term, mut outputs, combine, start, .. }, Ok((rest, output))) => {
+                outputs.push(output);
+                // A match that consumed nothing would repeat forever
+                if rest == start {
+                    return Step::Return(Ok((rest, combine(outputs))));
+                }
+                stack.push(Frame::Many {
+                    term,
+                    outputs,
+                    combine,
+                    checkpoint: rest.checkpoint(),
+                    start: rest.clone(),
+                });
+                Step::Enter(term, rest)
+            }
+            (Frame::Many { outputs, combine, checkpoint, .. }, Err(_)) => {
+                Step::Return(Ok((I::rewind(checkpoint), combine(outputs))))
+            }
+            (Frame::Seq { .. }, Err(err)) => Step::Return(Err(err)),
+            (Frame::Alt { .. }, Ok(result)) => Step::Return(Ok(result)),
+        }
+    }
+}
+
+impl<I, O, E> Parser<I, O, E> for Trampoline<'_, I, O, E>
+where
+    I: Parsable<E> + PartialEq,
+    E: Clone,
+{
+    fn parse(&self, input: I) -> Result<(I, O), (I, E)> {
+        self.parse_rule(RuleId(0), input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ParsableItem;
+
+    #[test]
+    fn test_trampoline_rules_and_backtracking() {
+        let lit = |c: char| leaf(ParsableItem::<&str, _>::make_character_matcher(c, "Unexpected").map(move |_| c.to_string()));
+        let concat = |parts: Vec<String>| parts.concat();
+
+        // list := item ("," item)* ; item := "a" "b" | "a" | "(" list ")"
+        let mut grammar = Trampoline::new("Undefined");
+        let list = grammar.declare();
+        let item = grammar.declare();
+        let undefined = grammar.declare();
+        grammar.define(list, seq([rule(item), many(seq([lit(','), rule(item)], concat), concat)], concat));
+        grammar.define(
+            item,
+            alt([
+                seq([lit('a'), lit('b')], concat),
+                lit('a'),
+                seq([lit('('), rule(list), lit(')')], concat),
+            ]),
+        );
+
+        // A failed choice and a failed repetition are rewound
+        assert_eq!(grammar.parse("a,ab,(a,a)"), Ok(("", "a,ab,(a,a)".to_string())));
+        assert_eq!(grammar.parse("ab,(a;"), Ok((",(a;", "ab".to_string())));
+        assert_eq!(grammar.parse_rule(item, "(a;"), Err((";", "Unexpected")));
+        assert_eq!(grammar.parse("a,;"), Ok((",;", "a".to_string())));
+        assert_eq!(grammar.parse_rule(item, "(a)b"), Ok(("b", "(a)".to_string())));
+        assert_eq!(grammar.parse_rule(undefined, "a"), Err(("a", "Undefined")));
+
+        // Repeating something that matches nothing stops
+        let mut empty = Trampoline::new("Undefined");
+        let start = empty.declare();
+        empty.define(start, many(alt([]), concat));
+        assert_eq!(empty.parse("x"), Ok(("x", String::new())));
+        empty.define(start, many(seq([], concat), concat));
+        assert_eq!(empty.parse("x"), Ok(("x", String::new())));
+    }
+}
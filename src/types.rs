@@ -3,6 +3,7 @@
 //! This module defines various type utilities used throughout the parser library,
 //! including Either types, natural number types, and type traits for sum and product types.
 
+use alloc::{vec, vec::Vec};
 use core::fmt::Debug;
 
 /// A sum type representing one of two possible values.
@@ -14,6 +15,100 @@ pub enum Either<A, B> {
     Right(B),
 }
 
+impl<A, B> Either<A, B> {
+    /// Converts to a `Result`, with `Right` as `Ok` and `Left` as `Err`, following the
+    /// `either` crate.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use friss::*;
+    ///
+    /// let parsed: Either<&str, u32> = Either::Right(7);
+    /// assert_eq!(parsed.into_result(), Ok(7));
+    /// assert_eq!(Either::from_result("x".parse::<u32>().map_err(|_| "bad")), Either::Left("bad"));
+    /// ```
+    pub fn into_result(self) -> Result<B, A> {
+        match self {
+            Either::Left(a) => Err(a),
+            Either::Right(b) => Ok(b),
+        }
+    }
+
+    /// Converts from a `Result`, with `Ok` as `Right` and `Err` as `Left`.
+    pub fn from_result(result: Result<B, A>) -> Self {
+        match result {
+            Ok(b) => Either::Right(b),
+            Err(a) => Either::Left(a),
+        }
+    }
+
+    /// Borrows the content, as `Option::as_ref` does.
+    pub fn as_ref(&self) -> Either<&A, &B> {
+        match self {
+            Either::Left(a) => Either::Left(a),
+            Either::Right(b) => Either::Right(b),
+        }
+    }
+
+    /// Borrows the content mutably.
+    pub fn as_mut(&mut self) -> Either<&mut A, &mut B> {
+        match self {
+            Either::Left(a) => Either::Left(a),
+            Either::Right(b) => Either::Right(b),
+        }
+    }
+
+    /// Maps the `Left` content, the same as `map_0`.
+    pub fn map_left<C>(self, f: impl FnOnce(A) -> C) -> Either<C, B> {
+        match self {
+            Either::Left(a) => Either::Left(f(a)),
+            Either::Right(b) => Either::Right(b),
+        }
+    }
+
+    /// Maps the `Right` content, the same as `map_1`.
+    pub fn map_right<C>(self, f: impl FnOnce(B) -> C) -> Either<A, C> {
+        match self {
+            Either::Left(a) => Either::Left(a),
+            Either::Right(b) => Either::Right(f(b)),
+        }
+    }
+}
+
+impl<A, B> From<Result<B, A>> for Either<A, B> {
+    fn from(result: Result<B, A>) -> Self {
+        Either::from_result(result)
+    }
+}
+
+impl<A, B> From<Either<A, B>> for Result<B, A> {
+    fn from(either: Either<A, B>) -> Self {
+        either.into_result()
+    }
+}
+
+// Conversions to and from the `either` crate, whose variants match ours one to one
+#[cfg(feature = "either")]
+impl<A, B> From<either::Either<A, B>> for Either<A, B> {
+    fn from(either: either::Either<A, B>) -> Self {
+        match either {
+            either::Either::Left(a) => Either::Left(a),
+            either::Either::Right(b) => Either::Right(b),
+        }
+    }
+}
+
+#[cfg(feature = "either")]
+impl<A, B> From<Either<A, B>> for either::Either<A, B> {
+    fn from(either: Either<A, B>) -> Self {
+        match either {
+            Either::Left(a) => either::Either::Left(a),
+            Either::Right(b) => either::Either::Right(b),
+        }
+    }
+}
+
 
 
 // Macro to define Either types
@@ -41,11 +136,23 @@ define_either! {
     Either10(_1, _2, _3, _4, _5, _6, _7, _8, _9, _10)
 }
 
+// Either11 to Either16 for grammars with more than ten alternatives
+#[cfg(feature = "wide-tuples")]
+define_either! {
+    Either11(_1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11),
+    Either12(_1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12),
+    Either13(_1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13),
+    Either14(_1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13, _14),
+    Either15(_1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13, _14, _15),
+    Either16(_1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13, _14, _15, _16)
+}
+
 // Macro to implement map method for Either types
 macro_rules! impl_either_map {
     ($either:ident, $($variant:ident => $type1:ident => $type2:ident =>  $fun:ident),+) => {
         impl<$($type1),+> $either<$($type1),+>
         {
+            #[allow(clippy::too_many_arguments)]
             pub fn m_map<$($type2,)+ >(
                 self,
                 $($fun: impl FnOnce($type1) -> $type2,)+
@@ -55,6 +162,19 @@ macro_rules! impl_either_map {
                     $($either::$variant(a) => $either::$variant($fun(a)),)+
                 }
             }
+
+            /// Folds the value into a single result, applying the function matching the
+            /// variant. Unlike `Foldable::fold`, the variants may have different types.
+            #[allow(clippy::too_many_arguments)]
+            pub fn m_fold<R>(
+                self,
+                $($fun: impl FnOnce($type1) -> R,)+
+            ) -> R
+            {
+                match self {
+                    $($either::$variant(a) => $fun(a),)+
+                }
+            }
         }
     };
 }
@@ -73,6 +193,18 @@ impl_either_map!(Either7, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 =
 impl_either_map!(Either8, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 => B3 => f3, _4 => A4 => B4 => f4, _5 => A5 => B5 => f5, _6 => A6 => B6 => f6, _7 => A7 => B7 => f7, _8 => A8 => B8 => f8);
 impl_either_map!(Either9, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 => B3 => f3, _4 => A4 => B4 => f4, _5 => A5 => B5 => f5, _6 => A6 => B6 => f6, _7 => A7 => B7 => f7, _8 => A8 => B8 => f8, _9 => A9 => B9 => f9);
 impl_either_map!(Either10, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 => B3 => f3, _4 => A4 => B4 => f4, _5 => A5 => B5 => f5, _6 => A6 => B6 => f6, _7 => A7 => B7 => f7, _8 => A8 => B8 => f8, _9 => A9 => B9 => f9, _10 => A10 => B10 => f10);
+#[cfg(feature = "wide-tuples")]
+impl_either_map!(Either11, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 => B3 => f3, _4 => A4 => B4 => f4, _5 => A5 => B5 => f5, _6 => A6 => B6 => f6, _7 => A7 => B7 => f7, _8 => A8 => B8 => f8, _9 => A9 => B9 => f9, _10 => A10 => B10 => f10, _11 => A11 => B11 => f11);
+#[cfg(feature = "wide-tuples")]
+impl_either_map!(Either12, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 => B3 => f3, _4 => A4 => B4 => f4, _5 => A5 => B5 => f5, _6 => A6 => B6 => f6, _7 => A7 => B7 => f7, _8 => A8 => B8 => f8, _9 => A9 => B9 => f9, _10 => A10 => B10 => f10, _11 => A11 => B11 => f11, _12 => A12 => B12 => f12);
+#[cfg(feature = "wide-tuples")]
+impl_either_map!(Either13, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 => B3 => f3, _4 => A4 => B4 => f4, _5 => A5 => B5 => f5, _6 => A6 => B6 => f6, _7 => A7 => B7 => f7, _8 => A8 => B8 => f8, _9 => A9 => B9 => f9, _10 => A10 => B10 => f10, _11 => A11 => B11 => f11, _12 => A12 => B12 => f12, _13 => A13 => B13 => f13);
+#[cfg(feature = "wide-tuples")]
+impl_either_map!(Either14, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 => B3 => f3, _4 => A4 => B4 => f4, _5 => A5 => B5 => f5, _6 => A6 => B6 => f6, _7 => A7 => B7 => f7, _8 => A8 => B8 => f8, _9 => A9 => B9 => f9, _10 => A10 => B10 => f10, _11 => A11 => B11 => f11, _12 => A12 => B12 => f12, _13 => A13 => B13 => f13, _14 => A14 => B14 => f14);
+#[cfg(feature = "wide-tuples")]
+impl_either_map!(Either15, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 => B3 => f3, _4 => A4 => B4 => f4, _5 => A5 => B5 => f5, _6 => A6 => B6 => f6, _7 => A7 => B7 => f7, _8 => A8 => B8 => f8, _9 => A9 => B9 => f9, _10 => A10 => B10 => f10, _11 => A11 => B11 => f11, _12 => A12 => B12 => f12, _13 => A13 => B13 => f13, _14 => A14 => B14 => f14, _15 => A15 => B15 => f15);
+#[cfg(feature = "wide-tuples")]
+impl_either_map!(Either16, _1 => A1 => B1 => f1, _2 => A2 => B2 => f2, _3 => A3 => B3 => f3, _4 => A4 => B4 => f4, _5 => A5 => B5 => f5, _6 => A6 => B6 => f6, _7 => A7 => B7 => f7, _8 => A8 => B8 => f8, _9 => A9 => B9 => f9, _10 => A10 => B10 => f10, _11 => A11 => B11 => f11, _12 => A12 => B12 => f12, _13 => A13 => B13 => f13, _14 => A14 => B14 => f14, _15 => A15 => B15 => f15, _16 => A16 => B16 => f16);
 
 
 
@@ -167,15 +299,335 @@ impl_map_n!(map_7 for Either10 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5,
 impl_map_n!(map_8 for Either10 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8] : A9 = _9 : [A10 = _10]);
 impl_map_n!(map_9 for Either10 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9] : A10 = _10 : []);
 
+// Either11 - map_0 through map_10
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_0 for Either11 => [] : A1 = _1 : [A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_1 for Either11 => [A1 = _1] : A2 = _2 : [A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_2 for Either11 => [A1 = _1, A2 = _2] : A3 = _3 : [A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_3 for Either11 => [A1 = _1, A2 = _2, A3 = _3] : A4 = _4 : [A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_4 for Either11 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4] : A5 = _5 : [A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_5 for Either11 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5] : A6 = _6 : [A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_6 for Either11 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6] : A7 = _7 : [A8 = _8, A9 = _9, A10 = _10, A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_7 for Either11 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7] : A8 = _8 : [A9 = _9, A10 = _10, A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_8 for Either11 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8] : A9 = _9 : [A10 = _10, A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_9 for Either11 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9] : A10 = _10 : [A11 = _11]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_10 for Either11 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10] : A11 = _11 : []);
+
+// Either12 - map_0 through map_11
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_0 for Either12 => [] : A1 = _1 : [A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_1 for Either12 => [A1 = _1] : A2 = _2 : [A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_2 for Either12 => [A1 = _1, A2 = _2] : A3 = _3 : [A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_3 for Either12 => [A1 = _1, A2 = _2, A3 = _3] : A4 = _4 : [A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_4 for Either12 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4] : A5 = _5 : [A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_5 for Either12 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5] : A6 = _6 : [A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_6 for Either12 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6] : A7 = _7 : [A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_7 for Either12 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7] : A8 = _8 : [A9 = _9, A10 = _10, A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_8 for Either12 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8] : A9 = _9 : [A10 = _10, A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_9 for Either12 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9] : A10 = _10 : [A11 = _11, A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_10 for Either12 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10] : A11 = _11 : [A12 = _12]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_11 for Either12 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11] : A12 = _12 : []);
+
+// Either13 - map_0 through map_12
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_0 for Either13 => [] : A1 = _1 : [A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_1 for Either13 => [A1 = _1] : A2 = _2 : [A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_2 for Either13 => [A1 = _1, A2 = _2] : A3 = _3 : [A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_3 for Either13 => [A1 = _1, A2 = _2, A3 = _3] : A4 = _4 : [A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_4 for Either13 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4] : A5 = _5 : [A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_5 for Either13 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5] : A6 = _6 : [A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_6 for Either13 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6] : A7 = _7 : [A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_7 for Either13 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7] : A8 = _8 : [A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_8 for Either13 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8] : A9 = _9 : [A10 = _10, A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_9 for Either13 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9] : A10 = _10 : [A11 = _11, A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_10 for Either13 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10] : A11 = _11 : [A12 = _12, A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_11 for Either13 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11] : A12 = _12 : [A13 = _13]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_12 for Either13 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12] : A13 = _13 : []);
+
+// Either14 - map_0 through map_13
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_0 for Either14 => [] : A1 = _1 : [A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_1 for Either14 => [A1 = _1] : A2 = _2 : [A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_2 for Either14 => [A1 = _1, A2 = _2] : A3 = _3 : [A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_3 for Either14 => [A1 = _1, A2 = _2, A3 = _3] : A4 = _4 : [A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_4 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4] : A5 = _5 : [A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_5 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5] : A6 = _6 : [A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_6 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6] : A7 = _7 : [A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_7 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7] : A8 = _8 : [A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_8 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8] : A9 = _9 : [A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_9 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9] : A10 = _10 : [A11 = _11, A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_10 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10] : A11 = _11 : [A12 = _12, A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_11 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11] : A12 = _12 : [A13 = _13, A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_12 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12] : A13 = _13 : [A14 = _14]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_13 for Either14 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13] : A14 = _14 : []);
+
+// Either15 - map_0 through map_14
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_0 for Either15 => [] : A1 = _1 : [A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_1 for Either15 => [A1 = _1] : A2 = _2 : [A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_2 for Either15 => [A1 = _1, A2 = _2] : A3 = _3 : [A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_3 for Either15 => [A1 = _1, A2 = _2, A3 = _3] : A4 = _4 : [A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_4 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4] : A5 = _5 : [A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_5 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5] : A6 = _6 : [A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_6 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6] : A7 = _7 : [A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_7 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7] : A8 = _8 : [A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_8 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8] : A9 = _9 : [A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_9 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9] : A10 = _10 : [A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_10 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10] : A11 = _11 : [A12 = _12, A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_11 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11] : A12 = _12 : [A13 = _13, A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_12 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12] : A13 = _13 : [A14 = _14, A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_13 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13] : A14 = _14 : [A15 = _15]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_14 for Either15 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14] : A15 = _15 : []);
+
+// Either16 - map_0 through map_15
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_0 for Either16 => [] : A1 = _1 : [A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_1 for Either16 => [A1 = _1] : A2 = _2 : [A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_2 for Either16 => [A1 = _1, A2 = _2] : A3 = _3 : [A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_3 for Either16 => [A1 = _1, A2 = _2, A3 = _3] : A4 = _4 : [A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_4 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4] : A5 = _5 : [A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_5 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5] : A6 = _6 : [A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_6 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6] : A7 = _7 : [A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_7 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7] : A8 = _8 : [A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_8 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8] : A9 = _9 : [A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_9 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9] : A10 = _10 : [A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_10 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10] : A11 = _11 : [A12 = _12, A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_11 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11] : A12 = _12 : [A13 = _13, A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_12 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12] : A13 = _13 : [A14 = _14, A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_13 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13] : A14 = _14 : [A15 = _15, A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_14 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14] : A15 = _15 : [A16 = _16]);
+#[cfg(feature = "wide-tuples")]
+impl_map_n!(map_15 for Either16 => [A1 = _1, A2 = _2, A3 = _3, A4 = _4, A5 = _5, A6 = _6, A7 = _7, A8 = _8, A9 = _9, A10 = _10, A11 = _11, A12 = _12, A13 = _13, A14 = _14, A15 = _15] : A16 = _16 : []);
+
+// Macro to implement widening conversions into the next larger Either type
+macro_rules! impl_either_widen {
+    ($from:ident => $to:ident, $($param:ident: $from_variant:ident => $to_variant:ident),+; $new:ident) => {
+        impl<$($param,)+ $new> From<$from<$($param),+>> for $to<$($param,)+ $new> {
+            fn from(either: $from<$($param),+>) -> Self {
+                match either {
+                    $($from::$from_variant(a) => $to::$to_variant(a),)+
+                }
+            }
+        }
+    };
+}
 
+impl_either_widen!(Either => Either3, A1: Left => Left, A2: Right => Middle; A3);
+impl_either_widen!(Either3 => Either4, A1: Left => _1, A2: Middle => _2, A3: Right => _3; A4);
+impl_either_widen!(Either4 => Either5, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4; A5);
+impl_either_widen!(Either5 => Either6, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5; A6);
+impl_either_widen!(Either6 => Either7, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6; A7);
+impl_either_widen!(Either7 => Either8, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6, A7: _7 => _7; A8);
+impl_either_widen!(Either8 => Either9, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6, A7: _7 => _7, A8: _8 => _8; A9);
+impl_either_widen!(Either9 => Either10, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6, A7: _7 => _7, A8: _8 => _8, A9: _9 => _9; A10);
+#[cfg(feature = "wide-tuples")]
+impl_either_widen!(Either10 => Either11, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6, A7: _7 => _7, A8: _8 => _8, A9: _9 => _9, A10: _10 => _10; A11);
+#[cfg(feature = "wide-tuples")]
+impl_either_widen!(Either11 => Either12, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6, A7: _7 => _7, A8: _8 => _8, A9: _9 => _9, A10: _10 => _10, A11: _11 => _11; A12);
+#[cfg(feature = "wide-tuples")]
+impl_either_widen!(Either12 => Either13, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6, A7: _7 => _7, A8: _8 => _8, A9: _9 => _9, A10: _10 => _10, A11: _11 => _11, A12: _12 => _12; A13);
+#[cfg(feature = "wide-tuples")]
+impl_either_widen!(Either13 => Either14, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6, A7: _7 => _7, A8: _8 => _8, A9: _9 => _9, A10: _10 => _10, A11: _11 => _11, A12: _12 => _12, A13: _13 => _13; A14);
+#[cfg(feature = "wide-tuples")]
+impl_either_widen!(Either14 => Either15, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6, A7: _7 => _7, A8: _8 => _8, A9: _9 => _9, A10: _10 => _10, A11: _11 => _11, A12: _12 => _12, A13: _13 => _13, A14: _14 => _14; A15);
+#[cfg(feature = "wide-tuples")]
+impl_either_widen!(Either15 => Either16, A1: _1 => _1, A2: _2 => _2, A3: _3 => _3, A4: _4 => _4, A5: _5 => _5, A6: _6 => _6, A7: _7 => _7, A8: _8 => _8, A9: _9 => _9, A10: _10 => _10, A11: _11 => _11, A12: _12 => _12, A13: _13 => _13, A14: _14 => _14, A15: _15 => _15; A16);
+
+/// Collapses an Either value by grouping variants that share a type.
+///
+/// Each arm lists one or more variants separated by `|` and the function applied to their
+/// content, so the variants of one arm must have the same type. All arms return the same
+/// type.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+///
+/// let describe = |value: Either4<i64, &str, i64, &str>| {
+///     collapse!(value, Either4 {
+///         _1 | _3 => |n: i64| n.to_string(),
+///         _2 | _4 => |s: &str| s.to_uppercase(),
+///     })
+/// };
+///
+/// assert_eq!(describe(Either4::_3(7)), "7");
+/// assert_eq!(describe(Either4::_4("ok")), "OK");
+/// ```
+#[macro_export]
+macro_rules! collapse {
+    ($value:expr, $either:ident { $($($variant:ident)|+ => $fun:expr),+ $(,)? }) => {
+        match $value {
+            $($($crate::$either::$variant(a))|+ => ($fun)(a),)+
+        }
+    };
+}
 
+/// Flattens the nested errors built up by chaining binary combinators into one flat type.
+///
+/// `N` is the number of errors in the flat type. Chained `seq` calls produce left-nested
+/// Eithers such as `Either<Either<A, B>, C>`, which normalize to `Either3<A, B, C>`, and
+/// chained `alt` calls produce left-nested tuples such as `((A, B), C)`, which normalize to
+/// `(A, B, C)`. Implemented for up to 10 errors.
+///
+/// A nested value can be flattened to several depths, so `N` usually has to be given.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+///
+/// let err: Either<Either<Either<i32, &str>, char>, bool> = Either::Left(Either::Right('x'));
+/// assert_eq!(ErrorFold::<4>::normalize(err), Either4::_3('x'));
+///
+/// let errs = (((1, "two"), '3'), false);
+/// assert_eq!(ErrorFold::<4>::normalize(errs), (1, "two", '3', false));
+/// ```
+pub trait ErrorFold<const N: usize> {
+    /// The flat error type.
+    type Flat;
+
+    /// Flattens the error.
+    fn normalize(self) -> Self::Flat;
+}
 
+impl<A1, A2> ErrorFold<2> for Either<A1, A2> {
+    type Flat = Either<A1, A2>;
 
+    fn normalize(self) -> Self::Flat {
+        self
+    }
+}
 
+impl<A1, A2> ErrorFold<2> for (A1, A2) {
+    type Flat = (A1, A2);
 
+    fn normalize(self) -> Self::Flat {
+        self
+    }
+}
 
+// Builds the left-nested type produced by chaining binary combinators
+macro_rules! left_nested_either {
+    ($first:ty, $second:ty $(, $rest:ty)*) => { left_nested_either!(@acc Either<$first, $second> $(, $rest)*) };
+    (@acc $acc:ty) => { $acc };
+    (@acc $acc:ty, $next:ty $(, $rest:ty)*) => { left_nested_either!(@acc Either<$acc, $next> $(, $rest)*) };
+}
 
+macro_rules! left_nested_tuple {
+    ($first:ty, $second:ty $(, $rest:ty)*) => { left_nested_tuple!(@acc ($first, $second) $(, $rest)*) };
+    (@acc $acc:ty) => { $acc };
+    (@acc $acc:ty, $next:ty $(, $rest:ty)*) => { left_nested_tuple!(@acc ($acc, $next) $(, $rest)*) };
+}
+
+// Flattens the left side one level shallower, then widens it with the last error
+macro_rules! impl_error_fold {
+    ($n:literal: $either:ident; $($param:ident $value:ident),+; $last:ident $variant:ident) => {
+        impl<$($param,)+ $last> ErrorFold<$n> for Either<left_nested_either!($($param),+), $last> {
+            type Flat = $either<$($param,)+ $last>;
+
+            fn normalize(self) -> Self::Flat {
+                match self {
+                    Either::Left(rest) => ErrorFold::<{ $n - 1 }>::normalize(rest).into(),
+                    Either::Right(last) => $either::$variant(last),
+                }
+            }
+        }
+
+        impl<$($param,)+ $last> ErrorFold<$n> for (left_nested_tuple!($($param),+), $last) {
+            type Flat = ($($param,)+ $last);
+
+            fn normalize(self) -> Self::Flat {
+                let (rest, last) = self;
+                let ($($value,)+) = ErrorFold::<{ $n - 1 }>::normalize(rest);
+                ($($value,)+ last)
+            }
+        }
+    };
+}
 
+impl_error_fold!(3: Either3; A1 a1, A2 a2; A3 Right);
+impl_error_fold!(4: Either4; A1 a1, A2 a2, A3 a3; A4 _4);
+impl_error_fold!(5: Either5; A1 a1, A2 a2, A3 a3, A4 a4; A5 _5);
+impl_error_fold!(6: Either6; A1 a1, A2 a2, A3 a3, A4 a4, A5 a5; A6 _6);
+impl_error_fold!(7: Either7; A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6; A7 _7);
+impl_error_fold!(8: Either8; A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7; A8 _8);
+impl_error_fold!(9: Either9; A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8; A9 _9);
+impl_error_fold!(10: Either10; A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8, A9 a9; A10 _10);
 
 /// Trait for types that can be folded to a common result type.
 pub trait Foldable {
@@ -220,6 +672,18 @@ impl_either_foldable!(Either7, _1, _2, _3, _4, _5, _6, _7);
 impl_either_foldable!(Either8, _1, _2, _3, _4, _5, _6, _7, _8);
 impl_either_foldable!(Either9, _1, _2, _3, _4, _5, _6, _7, _8, _9);
 impl_either_foldable!(Either10, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10);
+#[cfg(feature = "wide-tuples")]
+impl_either_foldable!(Either11, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11);
+#[cfg(feature = "wide-tuples")]
+impl_either_foldable!(Either12, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12);
+#[cfg(feature = "wide-tuples")]
+impl_either_foldable!(Either13, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13);
+#[cfg(feature = "wide-tuples")]
+impl_either_foldable!(Either14, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13, _14);
+#[cfg(feature = "wide-tuples")]
+impl_either_foldable!(Either15, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13, _14, _15);
+#[cfg(feature = "wide-tuples")]
+impl_either_foldable!(Either16, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13, _14, _15, _16);
 
 
 
@@ -323,6 +787,18 @@ impl_all_multi_foldables!(Either7, _1, _2, _3, _4, _5, _6, _7);
 impl_all_multi_foldables!(Either8, _1, _2, _3, _4, _5, _6, _7, _8);
 impl_all_multi_foldables!(Either9, _1, _2, _3, _4, _5, _6, _7, _8, _9);
 impl_all_multi_foldables!(Either10, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10);
+#[cfg(feature = "wide-tuples")]
+impl_all_multi_foldables!(Either11, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11);
+#[cfg(feature = "wide-tuples")]
+impl_all_multi_foldables!(Either12, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12);
+#[cfg(feature = "wide-tuples")]
+impl_all_multi_foldables!(Either13, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13);
+#[cfg(feature = "wide-tuples")]
+impl_all_multi_foldables!(Either14, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13, _14);
+#[cfg(feature = "wide-tuples")]
+impl_all_multi_foldables!(Either15, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13, _14, _15);
+#[cfg(feature = "wide-tuples")]
+impl_all_multi_foldables!(Either16, _1, _2, _3, _4, _5, _6, _7, _8, _9, _10, _11, _12, _13, _14, _15, _16);
 
 
 
@@ -439,7 +915,7 @@ pub trait MultiFoldable5 {
 
 
 pub struct Zero;
-pub struct S<N>(std::marker::PhantomData<N>);
+pub struct S<N>(core::marker::PhantomData<N>);
 
 
 
@@ -478,9 +954,249 @@ impl_sum_type_for_either!(
     Either10<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10>
 );
 
+#[cfg(feature = "wide-tuples")]
+impl_sum_type_for_either!(
+    Either11<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11>,
+    Either12<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12>,
+    Either13<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13>,
+    Either14<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14>,
+    Either15<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15>,
+    Either16<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16>
+);
+
 impl SumType for () {}
 impl<T1> SumType for (T1,) {}
 impl SumAndProdType for () {}
 impl<T1> SumAndProdType for (T1,) {}
 impl ProdType for () {}
 impl<T1> ProdType for (T1,) {}
+
+/// An error annotated with the names of the rules that were being parsed when it occurred.
+///
+/// The trace is ordered from the innermost to the outermost rule.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Labelled<E> {
+    /// The original error.
+    pub error: E,
+    /// Names of the enclosing rules, innermost first.
+    pub trace: Vec<&'static str>,
+}
+
+impl<E> Labelled<E> {
+    /// Creates an error labelled with a single rule name.
+    pub fn new(error: E, label: &'static str) -> Self {
+        Labelled {
+            error,
+            trace: vec![label],
+        }
+    }
+
+    /// Returns the innermost rule name.
+    pub fn label(&self) -> &'static str {
+        self.trace.first().copied().unwrap_or_default()
+    }
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for Labelled<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.error)?;
+        for label in &self.trace {
+            write!(f, "\n  while parsing {}", label)?;
+        }
+        Ok(())
+    }
+}
+
+/// The error of an alternation in which every branch failed, reporting the branch that got
+/// furthest into the input.
+///
+/// `error` is the error of that branch, as the Either variant of the branch. `notes` holds
+/// the errors of all branches in declaration order, each paired with the number of input
+/// items left where the branch failed.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Farthest<E, Notes> {
+    /// Error of the branch that got furthest.
+    pub error: E,
+    /// Number of input items left where that branch failed.
+    pub remaining: usize,
+    /// Errors of all branches with their remaining input.
+    pub notes: Notes,
+}
+
+impl<E: core::fmt::Display, Notes> core::fmt::Display for Farthest<E, Notes> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+/// Errors that can record the rules enclosing them.
+pub trait LabelStack {
+    /// Records that the error occurred inside the rule `label`.
+    fn push_label(&mut self, label: &'static str);
+}
+
+impl<E> LabelStack for Labelled<E> {
+    fn push_label(&mut self, label: &'static str) {
+        self.trace.push(label);
+    }
+}
+
+/// Inputs whose failure location can be reported, used by `Parser::parse_complete`.
+pub trait Locate: Clone {
+    /// Returns the number of items left in the input.
+    fn remaining(&self) -> usize;
+
+    /// Returns the line and column of this input within `original`, if the input is text.
+    fn position_in(&self, _original: &Self) -> Option<crate::parsers::Position> {
+        None
+    }
+}
+
+impl Locate for &str {
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+
+    fn position_in(&self, original: &Self) -> Option<crate::parsers::Position> {
+        let offset = original.len().saturating_sub(self.len());
+        Some(crate::parsers::Position::from_offset(original, offset))
+    }
+}
+
+impl<T> Locate for &[T] {
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<S: Clone, I: Locate> Locate for crate::state::StateCarrier<S, I> {
+    fn remaining(&self) -> usize {
+        self.input.remaining()
+    }
+
+    fn position_in(&self, original: &Self) -> Option<crate::parsers::Position> {
+        self.input.position_in(&original.input)
+    }
+}
+
+/// What went wrong in `Parser::parse_complete`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrissErrorKind<E> {
+    /// The parser failed with this error
+    Parse(E),
+    /// The parser succeeded but did not consume the whole input
+    TrailingInput,
+}
+
+/// An error of `Parser::parse_complete`, located in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrissError<E> {
+    /// Whether the parser failed or left input behind
+    pub kind: FrissErrorKind<E>,
+    /// Number of items consumed before the failure
+    pub offset: usize,
+    /// Line and column of the failure, for text input
+    pub position: Option<crate::parsers::Position>,
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for FrissError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{}: ", position)?,
+            None => write!(f, "{}: ", self.offset)?,
+        }
+        match &self.kind {
+            FrissErrorKind::Parse(e) => write!(f, "{}", e),
+            FrissErrorKind::TrailingInput => write!(f, "unexpected trailing input"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> core::error::Error for FrissError<E> {}
+
+/// An error of `Parser::exactly_n`: the repetition stopped after `found` of the `expected`
+/// matches, failing with `error`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepetitionError<E> {
+    /// The number of matches the repetition needed
+    pub expected: usize,
+    /// The number of matches before the failure
+    pub found: usize,
+    /// The error of the failed attempt
+    pub error: E,
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for RepetitionError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "expected {} repetitions, found {}: {}", self.expected, self.found, self.error)
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> core::error::Error for RepetitionError<E> {}
+
+/// An error of `Parser::exactly_n_partial` and `Parser::at_least_n_partial`, keeping the
+/// items parsed before the repetition stopped short of `expected` matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialRepetition<O, E> {
+    /// The number of matches the repetition needed
+    pub expected: usize,
+    /// The items parsed before the failure
+    pub items: Vec<O>,
+    /// The error of the failed attempt
+    pub error: E,
+}
+
+impl<O, E> PartialRepetition<O, E> {
+    /// Returns the index of the failed attempt, which is the number of items parsed.
+    pub fn index(&self) -> usize {
+        self.items.len()
+    }
+}
+
+impl<O, E: core::fmt::Display> core::fmt::Display for PartialRepetition<O, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "expected {} repetitions, found {}: {}", self.expected, self.index(), self.error)
+    }
+}
+
+impl<O: core::fmt::Debug, E: core::fmt::Debug + core::fmt::Display> core::error::Error for PartialRepetition<O, E> {}
+
+/// An error of `Parser::located`, with how far into the input it was reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorAt<E> {
+    pub error: E,
+    /// Number of items consumed before the failure, counted from where the located parser
+    /// started
+    pub offset: usize,
+    /// Line and column of the failure relative to the same start, for text input
+    pub position: Option<crate::parsers::Position>,
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for ErrorAt<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{}: {}", position, self.error),
+            None => write!(f, "{}: {}", self.offset, self.error),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> core::error::Error for ErrorAt<E> {}
+
+/// A panic caught by `Parser::parse_catch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Panicked {
+    /// The panic message, if it was a string
+    pub message: Option<alloc::string::String>,
+}
+
+impl core::fmt::Display for Panicked {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "parser panicked: {}", message),
+            None => write!(f, "parser panicked"),
+        }
+    }
+}
+
+impl core::error::Error for Panicked {}
@@ -0,0 +1,145 @@
+//! # Unicode Character Classes
+//!
+//! This module provides character class parsers for `&str` input that work on whole
+//! characters rather than bytes, and a ready-made `identifier` parser following Unicode
+//! Standard Annex #31 as used by Rust, Python and JavaScript lexers.
+//!
+//! With the `unicode` feature the `XID_Start` and `XID_Continue` properties come from the
+//! `unicode-ident` tables. Without it they are approximated with `char::is_alphabetic` and
+//! `char::is_alphanumeric`, which accepts a few characters the standard excludes.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::unicode::*;
+//!
+//! let name = identifier("Expected identifier");
+//!
+//! assert_eq!(name.parse("größe = 1"), Ok((" = 1", "größe")));
+//! assert_eq!(name.parse("_tmp1+"), Ok(("+", "_tmp1")));
+//! assert_eq!(name.parse("1abc"), Err(("1abc", "Expected identifier")));
+//! ```
+
+use crate::core::Parser;
+use crate::parsers::make_char_predicate_matcher;
+
+/// Returns true if `c` may start an identifier.
+pub fn is_xid_start(c: char) -> bool {
+    #[cfg(feature = "unicode")]
+    {
+        unicode_ident::is_xid_start(c)
+    }
+    #[cfg(not(feature = "unicode"))]
+    {
+        c.is_alphabetic()
+    }
+}
+
+/// Returns true if `c` may continue an identifier.
+pub fn is_xid_continue(c: char) -> bool {
+    #[cfg(feature = "unicode")]
+    {
+        unicode_ident::is_xid_continue(c)
+    }
+    #[cfg(not(feature = "unicode"))]
+    {
+        c.is_alphanumeric() || c == '_'
+    }
+}
+
+/// Matches one alphabetic character, such as `a`, `ß` or `λ`.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::unicode::*;
+///
+/// let letter = unicode_alphabetic("Expected letter");
+/// assert_eq!(letter.parse("λx"), Ok(("x", 'λ')));
+/// assert_eq!(letter.parse("1"), Err(("1", "Expected letter")));
+/// ```
+pub fn unicode_alphabetic<'a, Error: Clone>(err: Error) -> impl Parser<&'a str, char, Error> {
+    make_char_predicate_matcher(char::is_alphabetic, err)
+}
+
+/// Matches one numeric character, such as `7`, `٣` or `½`.
+pub fn unicode_numeric<'a, Error: Clone>(err: Error) -> impl Parser<&'a str, char, Error> {
+    make_char_predicate_matcher(char::is_numeric, err)
+}
+
+/// Matches one alphabetic or numeric character.
+pub fn unicode_alphanumeric<'a, Error: Clone>(err: Error) -> impl Parser<&'a str, char, Error> {
+    make_char_predicate_matcher(char::is_alphanumeric, err)
+}
+
+/// Matches one character that may start an identifier.
+pub fn xid_start<'a, Error: Clone>(err: Error) -> impl Parser<&'a str, char, Error> {
+    make_char_predicate_matcher(is_xid_start, err)
+}
+
+/// Matches one character that may continue an identifier.
+pub fn xid_continue<'a, Error: Clone>(err: Error) -> impl Parser<&'a str, char, Error> {
+    make_char_predicate_matcher(is_xid_continue, err)
+}
+
+/// Matches an identifier: an `XID_Start` character or `_`, followed by any number of
+/// `XID_Continue` characters. A lone `_` is accepted.
+pub fn identifier<'a, Error: Clone>(err: Error) -> impl Parser<&'a str, &'a str, Error> {
+    identifier_with(|c| c == '_' || is_xid_start(c), is_xid_continue, err)
+}
+
+/// Matches an identifier with custom character classes: one character satisfying `start`,
+/// followed by any number of characters satisfying `cont`.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::unicode::*;
+///
+/// let lisp = identifier_with(char::is_alphabetic, |c| c.is_alphanumeric() || c == '-', "Expected symbol");
+/// assert_eq!(lisp.parse("list-ref x"), Ok((" x", "list-ref")));
+/// assert_eq!(lisp.parse("-x"), Err(("-x", "Expected symbol")));
+/// ```
+pub fn identifier_with<'a, Error: Clone>(
+    start: impl Fn(char) -> bool,
+    cont: impl Fn(char) -> bool,
+    err: Error,
+) -> impl Parser<&'a str, &'a str, Error> {
+    move |input: &'a str| {
+        let mut chars = input.char_indices();
+        match chars.next() {
+            Some((_, c)) if start(c) => {}
+            _ => return Err((input, err.clone())),
+        }
+        let end = chars
+            .find(|&(_, c)| !cont(c))
+            .map_or(input.len(), |(i, _)| i);
+        let (ident, rest) = input.split_at(end);
+        Ok((rest, ident))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_character_classes() {
+        assert_eq!(unicode_numeric("Expected digit").parse("٣x"), Ok(("x", '٣')));
+        assert_eq!(unicode_alphanumeric("Expected word").parse("ñ"), Ok(("", 'ñ')));
+        assert_eq!(unicode_alphanumeric("Expected word").parse(""), Err(("", "Expected word")));
+        assert_eq!(xid_start("Expected start").parse("_a"), Err(("_a", "Expected start")));
+        assert_eq!(xid_continue("Expected continue").parse("_a"), Ok(("a", '_')));
+
+        let name = identifier("Expected identifier");
+        assert_eq!(name.parse("_"), Ok(("", "_")));
+        assert_eq!(name.parse("变量 = 2"), Ok((" = 2", "变量")));
+        assert_eq!(name.parse("-x"), Err(("-x", "Expected identifier")));
+        // U+00B7 MIDDLE DOT continues identifiers according to the Unicode tables
+        #[cfg(feature = "unicode")]
+        assert_eq!(name.parse("l·l"), Ok(("", "l·l")));
+    }
+}
@@ -0,0 +1,310 @@
+//! # Invertible Syntax Descriptions
+//!
+//! Editing tools need to parse a document, change it and print it back. This module builds
+//! the parser and the printer from a single description, so both stay in sync. Every
+//! combinator implements `Parser<&str, _, _>` for parsing and `Unparse` for printing:
+//!
+//! - `literal(text, err)` matches and prints fixed text, with `()` as its value
+//! - `chars_while(predicate, err)` matches and prints a non-empty run of characters
+//! - `seq(a, b)` and `alt(a, b)` mirror `Parser::seq` and `Parser::alt`
+//! - `many(a)` mirrors `Parser::many`
+//! - `iso_map(a, iso, err)` converts values with a partial isomorphism `Iso`
+//!
+//! The outputs and errors of the parsers are those of the corresponding `Parser` methods.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::unparse::*;
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! struct Setting { key: String, value: String }
+//!
+//! let word = || chars_while(|c: char| c.is_alphanumeric(), "Expected word");
+//! let setting = iso_map(
+//!     seq(seq(word(), literal(" = ", "Expected =")), seq(word(), literal("\n", "Expected newline"))),
+//!     Iso::new(
+//!         |((key, ()), (value, ()))| Some(Setting { key, value }),
+//!         |s: &Setting| Some(((s.key.clone(), ()), (s.value.clone(), ()))),
+//!     ),
+//!     Either::Left(Either::Left("Invalid setting")),
+//! );
+//! let document = many(setting);
+//!
+//! let (_, mut settings) = document.parse("name = friss\nmode = fast\n").unwrap();
+//! settings[1].value = "slow".to_string();
+//!
+//! assert_eq!(document.print(&settings), Some("name = friss\nmode = slow\n".to_string()));
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::core::{Parsable, Parser};
+use crate::parsers::consumed;
+use crate::types::Either;
+
+/// Printing half of an invertible syntax description.
+pub trait Unparse<Value> {
+    /// Appends the text of `value` to `out`. Returns false if `value` cannot be printed, in
+    /// which case `out` may hold a partial output.
+    fn unparse(&self, value: &Value, out: &mut String) -> bool;
+
+    /// Returns the text of `value`, or `None` if it cannot be printed.
+    fn print(&self, value: &Value) -> Option<String> {
+        let mut out = String::new();
+        self.unparse(value, &mut out).then_some(out)
+    }
+}
+
+/// A partial isomorphism between `A` and `B`: `apply` converts parsed values, `unapply`
+/// converts them back for printing. Either direction may reject a value.
+pub struct Iso<A, B, F, G> {
+    apply: F,
+    unapply: G,
+    _phantom: PhantomData<fn(A) -> B>,
+}
+
+impl<A, B, F, G> Iso<A, B, F, G>
+where
+    F: Fn(A) -> Option<B>,
+    G: Fn(&B) -> Option<A>,
+{
+    pub fn new(apply: F, unapply: G) -> Self {
+        Iso {
+            apply,
+            unapply,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Syntax produced by `literal`.
+pub struct Literal<'s, E> {
+    text: &'s str,
+    err: E,
+}
+
+/// Syntax produced by `chars_while`.
+pub struct CharsWhile<F, E> {
+    predicate: F,
+    err: E,
+}
+
+/// Syntax produced by `seq`.
+pub struct Seq<A, B>(A, B);
+
+/// Syntax produced by `alt`.
+pub struct Alt<A, B>(A, B);
+
+/// Syntax produced by `many`.
+pub struct Many<A>(A);
+
+/// Syntax produced by `iso_map`.
+pub struct IsoMap<A, I, E> {
+    syntax: A,
+    iso: I,
+    err: E,
+}
+
+/// Matches and prints `text`.
+pub fn literal<E: Clone>(text: &str, err: E) -> Literal<'_, E> {
+    Literal { text, err }
+}
+
+/// Matches a non-empty run of characters satisfying `predicate`, and prints strings made
+/// only of such characters.
+pub fn chars_while<F, E>(predicate: F, err: E) -> CharsWhile<F, E>
+where
+    F: Fn(char) -> bool,
+    E: Clone,
+{
+    CharsWhile { predicate, err }
+}
+
+/// Sequences two syntaxes; the value is the pair of their values.
+pub fn seq<A, B>(a: A, b: B) -> Seq<A, B> {
+    Seq(a, b)
+}
+
+/// Tries `a`, then `b`; the value tells which one matched.
+pub fn alt<A, B>(a: A, b: B) -> Alt<A, B> {
+    Alt(a, b)
+}
+
+/// Repeats a syntax zero or more times.
+pub fn many<A>(a: A) -> Many<A> {
+    Many(a)
+}
+
+/// Converts the values of `syntax` with `iso`. Parsing fails with `err` if `iso` rejects a
+/// parsed value.
+pub fn iso_map<A, I, E: Clone>(syntax: A, iso: I, err: E) -> IsoMap<A, I, E> {
+    IsoMap { syntax, iso, err }
+}
+
+impl<'a, E: Clone> Parser<&'a str, (), E> for Literal<'_, E> {
+    fn parse(&self, input: &'a str) -> Result<(&'a str, ()), (&'a str, E)> {
+        match input.strip_prefix(self.text) {
+            Some(rest) => Ok((rest, ())),
+            None => Err((input, self.err.clone())),
+        }
+    }
+}
+
+impl<E> Unparse<()> for Literal<'_, E> {
+    fn unparse(&self, _value: &(), out: &mut String) -> bool {
+        out.push_str(self.text);
+        true
+    }
+}
+
+impl<'a, F, E> Parser<&'a str, String, E> for CharsWhile<F, E>
+where
+    F: Fn(char) -> bool,
+    E: Clone,
+{
+    fn parse(&self, input: &'a str) -> Result<(&'a str, String), (&'a str, E)> {
+        let rest = input.trim_start_matches(&self.predicate);
+        if rest.len() == input.len() {
+            return Err((input, self.err.clone()));
+        }
+        Ok((rest, String::from(consumed(input, rest))))
+    }
+}
+
+impl<F, E> Unparse<String> for CharsWhile<F, E>
+where
+    F: Fn(char) -> bool,
+{
+    fn unparse(&self, value: &String, out: &mut String) -> bool {
+        if value.is_empty() || !value.chars().all(&self.predicate) {
+            return false;
+        }
+        out.push_str(value);
+        true
+    }
+}
+
+impl<'a, A, B, OutA, OutB, ErrA, ErrB> Parser<&'a str, (OutA, OutB), Either<ErrA, ErrB>> for Seq<A, B>
+where
+    A: Parser<&'a str, OutA, ErrA>,
+    B: Parser<&'a str, OutB, ErrB>,
+    ErrA: Clone,
+    ErrB: Clone,
+{
+    fn parse(&self, input: &'a str) -> Result<(&'a str, (OutA, OutB)), (&'a str, Either<ErrA, ErrB>)> {
+        (|i| self.0.parse(i)).seq(|i| self.1.parse(i)).parse(input)
+    }
+}
+
+impl<A, B, VA, VB> Unparse<(VA, VB)> for Seq<A, B>
+where
+    A: Unparse<VA>,
+    B: Unparse<VB>,
+{
+    fn unparse(&self, (a, b): &(VA, VB), out: &mut String) -> bool {
+        self.0.unparse(a, out) && self.1.unparse(b, out)
+    }
+}
+
+impl<'a, A, B, OutA, OutB, ErrA, ErrB> Parser<&'a str, Either<OutA, OutB>, (ErrA, ErrB)> for Alt<A, B>
+where
+    A: Parser<&'a str, OutA, ErrA>,
+    B: Parser<&'a str, OutB, ErrB>,
+    ErrA: Clone,
+    ErrB: Clone,
+{
+    fn parse(&self, input: &'a str) -> Result<(&'a str, Either<OutA, OutB>), (&'a str, (ErrA, ErrB))> {
+        (|i| self.0.parse(i)).alt(|i| self.1.parse(i)).parse(input)
+    }
+}
+
+impl<A, B, VA, VB> Unparse<Either<VA, VB>> for Alt<A, B>
+where
+    A: Unparse<VA>,
+    B: Unparse<VB>,
+{
+    fn unparse(&self, value: &Either<VA, VB>, out: &mut String) -> bool {
+        match value {
+            Either::Left(a) => self.0.unparse(a, out),
+            Either::Right(b) => self.1.unparse(b, out),
+        }
+    }
+}
+
+impl<'a, A, Out, Err> Parser<&'a str, Vec<Out>, Err> for Many<A>
+where
+    A: Parser<&'a str, Out, Err>,
+    Err: Clone,
+{
+    fn parse(&self, input: &'a str) -> Result<(&'a str, Vec<Out>), (&'a str, Err)> {
+        (|i| self.0.parse(i)).many().parse(input)
+    }
+}
+
+impl<A, V> Unparse<Vec<V>> for Many<A>
+where
+    A: Unparse<V>,
+{
+    fn unparse(&self, values: &Vec<V>, out: &mut String) -> bool {
+        values.iter().all(|value| self.0.unparse(value, out))
+    }
+}
+
+impl<'a, S, F, G, VA, VB, Err> Parser<&'a str, VB, Err> for IsoMap<S, Iso<VA, VB, F, G>, Err>
+where
+    S: Parser<&'a str, VA, Err>,
+    F: Fn(VA) -> Option<VB>,
+    Err: Clone,
+    &'a str: Parsable<Err>,
+{
+    fn parse(&self, input: &'a str) -> Result<(&'a str, VB), (&'a str, Err)> {
+        let (rest, value) = self.syntax.parse(input)?;
+        match (self.iso.apply)(value) {
+            Some(value) => Ok((rest, value)),
+            None => Err((input, self.err.clone())),
+        }
+    }
+}
+
+impl<S, F, G, VA, VB, Err> Unparse<VB> for IsoMap<S, Iso<VA, VB, F, G>, Err>
+where
+    S: Unparse<VA>,
+    G: Fn(&VB) -> Option<VA>,
+{
+    fn unparse(&self, value: &VB, out: &mut String) -> bool {
+        (self.iso.unapply)(value).is_some_and(|value| self.syntax.unparse(&value, out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_alt_and_rejected_values() {
+        let flag = alt(literal("on", "Expected on"), literal("off", "Expected off"));
+        let digits = iso_map(
+            chars_while(|c: char| c.is_ascii_digit(), "Expected digits"),
+            Iso::new(|s: String| s.parse::<u8>().ok(), |n: &u8| Some(n.to_string())),
+            "Number too large",
+        );
+        let entry = seq(flag, seq(literal(":", "Expected :"), digits));
+
+        let (rest, value) = entry.parse("off:42;").unwrap();
+        assert_eq!(rest, ";");
+        assert_eq!(value, (Either::Right(()), ((), 42)));
+        assert_eq!(entry.print(&value), Some(String::from("off:42")));
+        assert_eq!(entry.print(&(Either::Left(()), ((), 7))), Some(String::from("on:7")));
+
+        assert_eq!(entry.parse("on:300"), Err(("300", Either::Right(Either::Right("Number too large")))));
+        assert_eq!(entry.parse("maybe"), Err(("maybe", Either::Left(("Expected on", "Expected off")))));
+
+        let word = chars_while(|c: char| c.is_alphabetic(), "Expected word");
+        assert_eq!(word.print(&String::from("abc1")), None);
+        assert_eq!(many(word).print(&Vec::new()), Some(String::new()));
+    }
+}
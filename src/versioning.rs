@@ -0,0 +1,271 @@
+//! # Versioned Grammars
+//!
+//! This module lets one grammar handle several versions of an evolving format. The
+//! negotiated `Version` is carried in the parser state, and `when_version`, `since` and
+//! `until` enable or disable branches depending on it. When a disabled branch would have
+//! matched, the error names the versions that accept the input.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use friss::*;
+//! use friss::parsers::WithState;
+//! use friss::versioning::*;
+//!
+//! let arrow = || "=>".with_state(Version::new(1, 0)).make_literal_matcher("Expected =>");
+//! let lambda = since(Version::new(2, 0), arrow());
+//!
+//! let (_, out) = lambda.parse("=>".with_state(Version::new(2, 1))).unwrap();
+//! assert_eq!(out.input, "=>");
+//!
+//! let (_, err) = lambda.parse("=>".with_state(Version::new(1, 4))).unwrap_err();
+//! assert_eq!(err.to_string(), "input requires version >= 2.0, but version 1.4 is in use");
+//! ```
+
+use alloc::format;
+use core::fmt::{self, Display, Formatter};
+use core::ops::{Bound, RangeBounds};
+
+use crate::core::{Parsable, Parser};
+use crate::state::{StateCarrier, StatefulResult};
+
+/// A format version, ordered by `major` and then `minor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Version {
+    /// The major version, for incompatible changes
+    pub major: u32,
+    /// The minor version, for compatible additions
+    pub minor: u32,
+}
+
+impl Version {
+    /// Creates the version `major.minor`.
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Version { major, minor }
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// A range of versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VersionRange {
+    /// The lowest version in the range
+    pub start: Bound<Version>,
+    /// The highest version in the range
+    pub end: Bound<Version>,
+}
+
+impl VersionRange {
+    /// Creates a version range from any range expression, such as `v1..v2` or `v1..`.
+    pub fn new(range: impl RangeBounds<Version>) -> Self {
+        VersionRange {
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    /// Returns true if `version` lies in the range.
+    pub fn contains(&self, version: &Version) -> bool {
+        (self.start, self.end).contains(version)
+    }
+}
+
+impl Display for VersionRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let start = match self.start {
+            Bound::Included(v) => Some(format!(">= {}", v)),
+            Bound::Excluded(v) => Some(format!("> {}", v)),
+            Bound::Unbounded => None,
+        };
+        let end = match self.end {
+            Bound::Included(v) => Some(format!("<= {}", v)),
+            Bound::Excluded(v) => Some(format!("< {}", v)),
+            Bound::Unbounded => None,
+        };
+        match (start, end) {
+            (Some(start), Some(end)) => write!(f, "{}, {}", start, end),
+            (Some(bound), None) | (None, Some(bound)) => write!(f, "{}", bound),
+            (None, None) => write!(f, "any"),
+        }
+    }
+}
+
+/// Errors of version-gated parsers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionError<E> {
+    /// The gated parser failed
+    Parse(E),
+    /// The gated parser matches, but only in versions of `accepted`
+    Unsupported {
+        /// The version in use
+        version: Version,
+        /// The versions that accept the input
+        accepted: VersionRange,
+    },
+}
+
+impl<E: Display> Display for VersionError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionError::Parse(e) => write!(f, "{}", e),
+            VersionError::Unsupported { version, accepted } => write!(
+                f,
+                "input requires version {}, but version {} is in use",
+                accepted, version
+            ),
+        }
+    }
+}
+
+/// Enables `parser` only when the version in the state lies in `range`.
+///
+/// Outside the range the parser is still tried so that the error can tell whether the input
+/// is merely unsupported in this version; the input is never consumed in that case.
+///
+/// ## Example
+///
+/// ```rust
+/// use friss::*;
+/// use friss::parsers::WithState;
+/// use friss::versioning::*;
+///
+/// let v = Version::new;
+/// let legacy = when_version(v(1, 0)..v(2, 0), "<>".with_state(v(1, 0)).make_literal_matcher("Expected <>"));
+///
+/// assert!(legacy.parse("<>".with_state(v(1, 5))).is_ok());
+/// let (_, err) = legacy.parse("<>".with_state(v(2, 0))).unwrap_err();
+/// assert_eq!(err, VersionError::Unsupported { version: v(2, 0), accepted: VersionRange::new(v(1, 0)..v(2, 0)) });
+/// ```
+pub fn when_version<S, O, E>(
+    range: impl RangeBounds<Version>,
+    parser: impl Parser<StateCarrier<Version, S>, O, E>,
+) -> impl Parser<StateCarrier<Version, S>, O, VersionError<E>>
+where
+    S: Clone,
+    E: Clone,
+    StateCarrier<Version, S>: Parsable<E> + Parsable<VersionError<E>>,
+{
+    let range = VersionRange::new(range);
+    move |input: StateCarrier<Version, S>| {
+        let version = input.state;
+        match parser.parse(input.clone()) {
+            Ok(ok) if range.contains(&version) => Ok(ok),
+            Ok(_) => Err((input, VersionError::Unsupported { version, accepted: range })),
+            Err((rest, e)) => Err((rest, VersionError::Parse(e))),
+        }
+    }
+}
+
+/// Enables `parser` from `version` on.
+pub fn since<S, O, E>(
+    version: Version,
+    parser: impl Parser<StateCarrier<Version, S>, O, E>,
+) -> impl Parser<StateCarrier<Version, S>, O, VersionError<E>>
+where
+    S: Clone,
+    E: Clone,
+    StateCarrier<Version, S>: Parsable<E> + Parsable<VersionError<E>>,
+{
+    when_version(version.., parser)
+}
+
+/// Enables `parser` for versions before `version`.
+pub fn until<S, O, E>(
+    version: Version,
+    parser: impl Parser<StateCarrier<Version, S>, O, E>,
+) -> impl Parser<StateCarrier<Version, S>, O, VersionError<E>>
+where
+    S: Clone,
+    E: Clone,
+    StateCarrier<Version, S>: Parsable<E> + Parsable<VersionError<E>>,
+{
+    when_version(..version, parser)
+}
+
+/// The version stays unchanged while parsing, so all matchers delegate to the inner input.
+impl<S, Error> Parsable<Error> for StateCarrier<Version, S>
+where
+    S: Parsable<Error>,
+    Error: Clone,
+    S::Item: Clone,
+{
+    type Item = S::Item;
+
+    fn make_literal_matcher(self, err: Error) -> impl Parser<Self, Self, Error> {
+        let literal = self.input.make_literal_matcher(err);
+        move |input: StateCarrier<Version, S>| {
+            let state = input.state;
+            literal
+                .parse(input.input)
+                .map(|(rest, ret)| (StateCarrier::new(state, rest), StateCarrier::new(state, ret)))
+                .map_err(|(rest, err)| (StateCarrier::new(state, rest), err))
+        }
+    }
+
+    fn make_anything_matcher(err: Error) -> impl Parser<Self, Self::Item, Error> {
+        let anything = S::make_anything_matcher(err);
+        move |input: StateCarrier<Version, S>| delegate(&anything, input)
+    }
+
+    fn make_item_matcher(character: Self::Item, err: Error) -> impl Parser<Self, Self::Item, Error> {
+        let item = S::make_item_matcher(character, err);
+        move |input: StateCarrier<Version, S>| delegate(&item, input)
+    }
+
+    fn make_empty_matcher(err: Error) -> impl Parser<Self, (), Error> {
+        let empty = S::make_empty_matcher(err);
+        move |input: StateCarrier<Version, S>| delegate(&empty, input)
+    }
+}
+
+fn delegate<S, O, E>(
+    parser: &impl Parser<S, O, E>,
+    input: StateCarrier<Version, S>,
+) -> StatefulResult<Version, S, O, E>
+where
+    S: Parsable<E>,
+    E: Clone,
+{
+    let state = input.state;
+    parser
+        .parse(input.input)
+        .map(|(rest, ret)| (StateCarrier::new(state, rest), ret))
+        .map_err(|(rest, err)| (StateCarrier::new(state, rest), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::WithState;
+    use crate::*;
+
+    #[test]
+    fn test_version_gated_alternatives() {
+        let v = Version::new;
+        let lit = |s: &'static str| s.with_state(v(0, 0)).make_literal_matcher("Expected keyword");
+
+        // "fn" in version 1, "func" from version 2 on
+        let keyword = |input: StateCarrier<Version, &'static str>| {
+            since(v(2, 0), lit("func"))
+                .parse(input)
+                .or_else(|_| until(v(2, 0), lit("fn")).parse(input))
+        };
+
+        let out = |version, text: &'static str| keyword.parse(text.with_state(version)).map(|(_, out)| out.input);
+        assert_eq!(out(v(1, 0), "fn"), Ok("fn"));
+        assert_eq!(out(v(2, 3), "func"), Ok("func"));
+
+        let (rest, err) = keyword.parse("fn".with_state(v(2, 0))).unwrap_err();
+        assert_eq!(rest.input, "fn");
+        assert_eq!(err.to_string(), "input requires version < 2.0, but version 2.0 is in use");
+        assert_eq!(
+            keyword.parse("x".with_state(v(1, 0))).unwrap_err().1,
+            VersionError::Parse("Expected keyword")
+        );
+    }
+}